@@ -0,0 +1,40 @@
+//! Prints the resolved manifest, sizes and dependencies of some assets, e.g.
+//! to diff the asset output of two branches in CI:
+//!
+//! ```text
+//! cargo run --example cli --features cli > assets.txt
+//! cargo run --example cli --features cli -- manifest sizes
+//! ```
+//!
+//! In a real application, this would be a small binary next to the server
+//! that shares the asset configuration with it.
+
+use std::process::ExitCode;
+
+use reinda::{cli::AssetPipeline, Builder};
+
+
+const EMBEDS: reinda::Embeds = reinda::embed! {
+    base_path: "examples/assets",
+    files: ["index.html", "style.css", "fonts/*.woff2"],
+};
+
+struct App;
+
+impl AssetPipeline for App {
+    fn configure<'a>(&'a self, builder: &mut Builder<'a>) {
+        let font_paths = builder.add_embedded("static/fonts/", &EMBEDS["fonts/*.woff2"])
+            .with_hash()
+            .http_paths();
+        builder.add_embedded("static/style.css", &EMBEDS["style.css"])
+            .with_hash()
+            .with_path_fixup(font_paths.into_iter().map(|p| p.into_owned()));
+        builder.add_embedded("index.html", &EMBEDS["index.html"])
+            .with_path_fixup(["static/style.css"]);
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    reinda::cli::run(&App).await
+}
@@ -0,0 +1,32 @@
+//! Writes the path manifest of some assets as JSON to stdout, to be consumed
+//! by a server-side rendering layer in another language. Run in prod mode,
+//! as only then paths are hashed:
+//!
+//! ```text
+//! cargo run --release --example manifest --features manifest > manifest.json
+//! ```
+//!
+//! In a real application, this would be a flag of the server binary itself,
+//! so that the manifest is built from exactly the same assets.
+
+use reinda::Assets;
+
+
+const EMBEDS: reinda::Embeds = reinda::embed! {
+    base_path: "examples/assets",
+    files: ["index.html", "style.css", "fonts/*.woff2"],
+};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = Assets::builder();
+    builder.add_embedded("index.html", &EMBEDS["index.html"]);
+    builder.add_embedded("static/style.css", &EMBEDS["style.css"]).with_hash();
+    builder.add_embedded("static/fonts/", &EMBEDS["fonts/*.woff2"]).with_hash();
+
+    let assets = builder.build().await?;
+    assets.manifest().write_json(std::io::stdout())?;
+    println!();
+
+    Ok(())
+}
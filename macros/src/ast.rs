@@ -8,8 +8,45 @@ pub(crate) struct Input {
     pub(crate) base_path: Option<String>,
     pub(crate) compression_threshold: Option<f32>,
     pub(crate) compression_quality: Option<u8>,
+    pub(crate) compression: Option<Compression>,
     pub(crate) print_stats: Option<bool>,
+    pub(crate) brotli_dictionary: Option<bool>,
     pub(crate) files: Vec<(String, Span)>,
+    pub(crate) urls: Vec<UrlEntry>,
+}
+
+/// Algorithm used to compress embedded files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(dev_mode, allow(dead_code))]
+pub(crate) enum Compression {
+    None,
+    Brotli,
+    Zstd,
+}
+
+impl Compression {
+    /// Brotli if the `compress` feature is enabled, otherwise zstd if
+    /// `compress-zstd` is enabled.
+    fn default_for_features() -> Self {
+        if cfg!(feature = "compress") {
+            Compression::Brotli
+        } else if cfg!(feature = "compress-zstd") {
+            Compression::Zstd
+        } else {
+            Compression::None
+        }
+    }
+}
+
+/// An entry of the `urls` field: a file that is downloaded at compile time.
+#[derive(Debug)]
+#[cfg_attr(not(feature = "urls"), allow(dead_code))]
+pub(crate) struct UrlEntry {
+    pub(crate) path: String,
+    pub(crate) url: String,
+    /// Lowercase hex encoded SHA-256 digest.
+    pub(crate) sha256: String,
+    pub(crate) span: Span,
 }
 
 impl Input {
@@ -18,8 +55,11 @@ impl Input {
             base_path: self.base_path,
             compression_threshold: self.compression_threshold.unwrap_or(0.85),
             compression_quality: self.compression_quality.unwrap_or(9),
+            compression: self.compression.unwrap_or_else(Compression::default_for_features),
             print_stats: self.print_stats.unwrap_or(false),
+            brotli_dictionary: self.brotli_dictionary.unwrap_or(false),
             files: self.files,
+            urls: self.urls,
         }
     }
 }
@@ -30,6 +70,11 @@ pub(crate) struct EmbedConfig {
     pub(crate) compression_threshold: f32,
     #[allow(dead_code)]
     pub(crate) compression_quality: u8,
+    #[allow(dead_code)]
+    pub(crate) compression: Compression,
     pub(crate) print_stats: bool,
+    pub(crate) brotli_dictionary: bool,
     pub(crate) files: Vec<(String, Span)>,
+    #[cfg_attr(not(feature = "urls"), allow(dead_code))]
+    pub(crate) urls: Vec<UrlEntry>,
 }
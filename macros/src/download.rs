@@ -0,0 +1,140 @@
+//! Downloading the files listed in the `urls` field at compile time.
+//!
+//! Downloaded files are stored in a content-addressed cache directory (named
+//! by their SHA-256 digest), so that every file is only downloaded once and
+//! builds work offline once the cache is populated. The cache directory is
+//! `$REINDA_URL_CACHE` or, if unset, `reinda-url-cache` in the system's
+//! temporary directory.
+
+use std::{fs, io::Read, path::{Path, PathBuf}, time::Duration};
+use sha2::{Digest, Sha256};
+
+use crate::{ast::UrlEntry, err, Error};
+
+
+/// Files larger than this are rejected.
+const MAX_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Makes sure the file described by `entry` is in the cache and returns its
+/// path.
+pub(crate) fn fetch(entry: &UrlEntry) -> Result<PathBuf, Error> {
+    let dir = std::env::var_os("REINDA_URL_CACHE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::temp_dir().join("reinda-url-cache"));
+    fetch_into(entry, &dir)
+}
+
+fn fetch_into(entry: &UrlEntry, dir: &Path) -> Result<PathBuf, Error> {
+    let path = dir.join(&entry.sha256);
+    if matches!(fs::read(&path), Ok(data) if hex_digest(&data) == entry.sha256) {
+        return Ok(path);
+    }
+
+    let url = &entry.url;
+    let data = download(url)
+        .map_err(|e| err!(@entry.span, "failed to download '{url}': {e}"))?;
+    let actual = hex_digest(&data);
+    if actual != entry.sha256 {
+        return Err(err!(
+            @entry.span,
+            "checksum mismatch for '{url}': expected {}, got {actual}",
+            entry.sha256,
+        ));
+    }
+
+    // Write to a temporary file first, so that concurrent builds never see a
+    // partially written file.
+    let io_err = |e| err!(@entry.span, "failed to write to URL cache '{}': {e}", dir.display());
+    fs::create_dir_all(dir).map_err(io_err)?;
+    let tmp = dir.join(format!("{}.{}.tmp", entry.sha256, std::process::id()));
+    fs::write(&tmp, &data).map_err(io_err)?;
+    fs::rename(&tmp, &path).map_err(io_err)?;
+
+    Ok(path)
+}
+
+fn download(url: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let response = ureq::get(url).timeout(Duration::from_secs(60)).call()?;
+    let mut data = Vec::new();
+    response.into_reader().take(MAX_SIZE + 1).read_to_end(&mut data)?;
+    if data.len() as u64 > MAX_SIZE {
+        return Err(format!("file larger than {MAX_SIZE} bytes").into());
+    }
+    Ok(data)
+}
+
+fn hex_digest(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{b:02x}")).collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::{io::{BufRead, BufReader, Write}, net::TcpListener};
+    use proc_macro2::Span;
+    use super::*;
+
+    /// Serves `body` for `n` requests on a random local port.
+    fn serve(body: &'static [u8], n: usize) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(n) {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(&stream);
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+                write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", body.len())
+                    .unwrap();
+                stream.write_all(body).unwrap();
+            }
+        });
+        format!("http://{addr}/file.js")
+    }
+
+    fn entry(url: String, sha256: &str) -> UrlEntry {
+        UrlEntry {
+            path: "vendor/file.js".into(),
+            url,
+            sha256: sha256.into(),
+            span: Span::call_site(),
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("reinda-macros-test-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    const HELLO: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    #[test]
+    fn download_and_cache() {
+        let dir = temp_dir("cache");
+        let url = serve(b"hello", 1);
+
+        let path = fetch_into(&entry(url.clone(), HELLO), &dir).map_err(|e| e.msg).unwrap();
+        assert_eq!(path, dir.join(HELLO));
+        assert_eq!(fs::read(&path).unwrap(), b"hello");
+
+        // The server only answers once, so this has to be served from cache.
+        let path = fetch_into(&entry(url, HELLO), &dir).map_err(|e| e.msg).unwrap();
+        assert_eq!(fs::read(path).unwrap(), b"hello");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn checksum_mismatch() {
+        let dir = temp_dir("mismatch");
+        let url = serve(b"hello\n", 1);
+
+        let msg = fetch_into(&entry(url, HELLO), &dir).map(|_| ()).unwrap_err().msg;
+        assert!(msg.contains("checksum mismatch"), "{}", msg);
+        assert!(!dir.join(HELLO).exists());
+    }
+}
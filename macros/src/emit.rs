@@ -1,10 +1,13 @@
-use std::path::{Path, PathBuf};
+use std::{collections::HashMap, path::{Path, PathBuf}};
 use glob::glob;
 
 use proc_macro2::{Span, TokenStream};
 use quote::quote;
 
-use crate::{err, EmbedConfig, Error, Input};
+use crate::{ast::Compression, err, EmbedConfig, Error, Input};
+
+#[cfg(prod_mode)]
+mod dictionary;
 
 
 
@@ -28,24 +31,16 @@ pub(crate) fn emit(input: Input) -> Result<TokenStream, Error> {
     let escaped_base = glob::Pattern::escape(&base_str);
     let escaped_base = Path::new(&escaped_base);
 
-    let mut stats = Stats::default();
-    let mut entries = Vec::new();
+    // Resolve all paths first, as the Brotli dictionary is built from all
+    // files.
+    let mut resolved = Vec::new();
     for (path, span) in &config.files {
         let utf8_err = || err!(@span, "path is not valid UTF-8");
 
         match Globness::check(path) {
             Globness::NotGlob(unescaped) => {
                 let full_path = base.join(&unescaped).to_str().ok_or_else(utf8_err)?.to_owned();
-                let embed_tokens = embed(&unescaped, span, &full_path, &config, &mut stats)?;
-
-                entries.push(quote! {
-                    reinda::EmbeddedEntry::Single(
-                        reinda::EmbeddedFile {
-                            #embed_tokens
-                            path: #unescaped,
-                        }
-                    )
-                });
+                resolved.push(Resolved::Single { path: unescaped, span, full_path });
             }
 
             Globness::Glob => {
@@ -62,13 +57,54 @@ pub(crate) fn emit(input: Input) -> Result<TokenStream, Error> {
                     let short_path = file_path.strip_prefix(&base)
                         .unwrap_or(&file_path)
                         .to_str()
-                        .ok_or_else(utf8_err)?;
-                    let file_path = file_path.to_str().ok_or_else(utf8_err)?;
+                        .ok_or_else(utf8_err)?
+                        .to_owned();
+                    let file_path = file_path.to_str().ok_or_else(utf8_err)?.to_owned();
+                    files.push((short_path, file_path));
+                }
+
+                resolved.push(Resolved::Glob { pattern: path, span, files });
+            }
+        }
+    }
+
+    if config.brotli_dictionary && config.compression != Compression::Brotli {
+        return Err(err!("`brotli_dictionary` requires Brotli compression"));
+    }
+    #[cfg(prod_mode)]
+    let dictionary = match config.brotli_dictionary {
+        #[cfg(feature = "compress")]
+        true => dictionary::build(&resolved, &config)?,
+        _ => None,
+    };
+    #[cfg(dev_mode)]
+    let dictionary: Option<Dictionary> = None;
+
+    let mut stats = Stats::default();
+    let mut dedup = Dedup::new(&resolved);
+    let mut entries = Vec::new();
+    for entry in &resolved {
+        match entry {
+            Resolved::Single { path, span, full_path } => {
+                let embed_tokens = dedup.embed(path, span, full_path, &config, &dictionary, &mut stats)?;
+
+                entries.push(quote! {
+                    reinda::EmbeddedEntry::Single(
+                        reinda::EmbeddedFile {
+                            #embed_tokens
+                            path: #path,
+                        }
+                    )
+                });
+            }
 
+            Resolved::Glob { pattern, span, files } => {
+                let mut file_tokens = Vec::new();
+                for (short_path, file_path) in files {
                     // Load file the current build mode says so.
-                    let embed_tokens = embed(short_path, span, file_path, &config, &mut stats)?;
+                    let embed_tokens = dedup.embed(short_path, span, file_path, &config, &dictionary, &mut stats)?;
 
-                    files.push(quote! {
+                    file_tokens.push(quote! {
                         reinda::EmbeddedFile {
                             #embed_tokens
                             path: #short_path,
@@ -86,15 +122,39 @@ pub(crate) fn emit(input: Input) -> Result<TokenStream, Error> {
 
                 entries.push(quote! {
                     reinda::EmbeddedEntry::Glob(reinda::EmbeddedGlob {
-                        pattern: #path,
+                        pattern: #pattern,
                         #base_path_tokens
-                        files: &[ #(#files ,)* ],
+                        files: &[ #(#file_tokens ,)* ],
                     })
                 });
             }
         }
     }
 
+    // Downloaded files are embedded like local files, just from the cache.
+    #[cfg(feature = "urls")]
+    for entry in &config.urls {
+        let cached = crate::download::fetch(entry)?;
+        let full_path = cached.to_str()
+            .ok_or_else(|| err!(@entry.span, "URL cache path is not valid UTF-8"))?;
+        let path = &entry.path;
+        let embed_tokens = dedup.embed(path, &entry.span, full_path, &config, &dictionary, &mut stats)?;
+
+        entries.push(quote! {
+            reinda::EmbeddedEntry::Single(
+                reinda::EmbeddedFile {
+                    #embed_tokens
+                    path: #path,
+                }
+            )
+        });
+    }
+
+    #[cfg(prod_mode)]
+    if let Some(dictionary) = &dictionary {
+        stats.compressed_size += dictionary.data.len();
+    }
+
     if config.print_stats {
         #[cfg(prod_mode)]
         println!(
@@ -112,9 +172,25 @@ pub(crate) fn emit(input: Input) -> Result<TokenStream, Error> {
 
 
 
+    // The dictionary is stored only once and referenced by all files
+    // compressed with it.
+    let dictionary_tokens = match &dictionary {
+        #[cfg(prod_mode)]
+        Some(dictionary) => {
+            let lit = proc_macro2::Literal::byte_string(&dictionary.data);
+            quote! { const REINDA_BROTLI_DICTIONARY: &[u8] = #lit; }
+        }
+        _ => quote! {},
+    };
+    let shared_tokens = &dedup.consts;
+
     Ok(quote! {
-        reinda::Embeds {
-            entries: &[ #(#entries ,)* ],
+        {
+            #dictionary_tokens
+            #(#shared_tokens)*
+            reinda::Embeds {
+                entries: &[ #(#entries ,)* ],
+            }
         }
     })
 }
@@ -158,6 +234,113 @@ impl Globness {
 }
 
 
+/// An entry of `files` with all paths resolved.
+enum Resolved<'a> {
+    Single {
+        path: String,
+        span: &'a Span,
+        full_path: String,
+    },
+    Glob {
+        pattern: &'a str,
+        span: &'a Span,
+        /// Path relative to the base path and full path of all matching files.
+        files: Vec<(String, String)>,
+    },
+}
+
+impl Resolved<'_> {
+    /// The path or pattern as specified in the macro.
+    fn label(&self) -> &str {
+        match self {
+            Resolved::Single { path, .. } => path,
+            Resolved::Glob { pattern, .. } => pattern,
+        }
+    }
+
+    fn full_paths(&self) -> Vec<&str> {
+        match self {
+            Resolved::Single { full_path, .. } => vec![full_path],
+            Resolved::Glob { files, .. } => files.iter().map(|(_, full_path)| &**full_path).collect(),
+        }
+    }
+}
+
+/// Makes sure files matched by several entries are only stored once in the
+/// executable.
+struct Dedup {
+    /// Files matched by more than one entry, by full path.
+    shared: HashMap<String, Option<TokenStream>>,
+
+    /// `const` items holding the content of shared files.
+    consts: Vec<TokenStream>,
+}
+
+impl Dedup {
+    /// Finds all files matched by several entries and prints a warning for
+    /// each of them.
+    fn new(resolved: &[Resolved<'_>]) -> Self {
+        let mut first_entry = HashMap::new();
+        let mut shared = HashMap::new();
+        for entry in resolved {
+            for full_path in entry.full_paths() {
+                match first_entry.get(full_path) {
+                    None => { first_entry.insert(full_path, entry.label()); }
+                    Some(first) => {
+                        eprintln!(
+                            "[reinda] warning: '{full_path}' is embedded by both '{first}' and \
+                                '{}'. It is only stored once, but served twice if both \
+                                entries are added to the builder.",
+                            entry.label(),
+                        );
+                        shared.insert(full_path.to_owned(), None);
+                    }
+                }
+            }
+        }
+
+        Self { shared, consts: vec![] }
+    }
+
+    /// Like [`embed`], but for shared files, the content is only embedded on
+    /// the first call and referenced by all others.
+    fn embed(
+        &mut self,
+        path: &str,
+        span: &Span,
+        full_path: &str,
+        config: &EmbedConfig,
+        dictionary: &Option<Dictionary>,
+        stats: &mut Stats,
+    ) -> Result<TokenStream, Error> {
+        if let Some(Some(tokens)) = self.shared.get(full_path) {
+            return Ok(tokens.clone());
+        }
+
+        let (mut content, fields) = embed(path, span, full_path, config, dictionary, stats)?;
+        let is_shared = self.shared.contains_key(full_path);
+        if is_shared && !content.is_empty() {
+            let ident = quote::format_ident!("REINDA_SHARED_{}", self.consts.len());
+            self.consts.push(quote! { const #ident: &[u8] = #content; });
+            content = quote! { #ident };
+        }
+
+        let tokens = match content.is_empty() {
+            true => fields,
+            false => quote! { content: #content, #fields },
+        };
+        if is_shared {
+            self.shared.insert(full_path.to_owned(), Some(tokens.clone()));
+        }
+        Ok(tokens)
+    }
+}
+
+#[cfg(dev_mode)]
+type Dictionary = std::convert::Infallible;
+#[cfg(prod_mode)]
+use dictionary::Dictionary;
+
 #[derive(Default)]
 #[allow(dead_code)]
 struct Stats {
@@ -167,17 +350,20 @@ struct Stats {
     embedded_compressed: u32,
 }
 
+/// Returns the expression for the `content` field (empty in dev mode) and
+/// the tokens for all other fields of `EmbeddedFile` except `path`.
 #[cfg(dev_mode)]
 fn embed(
     _: &str,
     _: &Span,
     full_path: &str,
     _: &EmbedConfig,
+    _: &Option<Dictionary>,
     _: &mut Stats,
-) -> Result<TokenStream, Error> {
-    Ok(quote! {
+) -> Result<(TokenStream, TokenStream), Error> {
+    Ok((quote! {}, quote! {
         full_path: #full_path,
-    })
+    }))
 }
 
 #[cfg(prod_mode)]
@@ -186,50 +372,46 @@ fn embed(
     span: &Span,
     full_path: &str,
     config: &EmbedConfig,
+    dictionary: &Option<Dictionary>,
     stats: &mut Stats,
-) -> Result<TokenStream, Error> {
+) -> Result<(TokenStream, TokenStream), Error> {
     // Read the full file.
     let data = std::fs::read(&full_path)
         .map_err(|e| err!(@span, "could not read '{full_path}': {e}"))?;
     stats.uncompressed_size += data.len();
 
-    // Compress.
-    let use_compressed_data: Option<Vec<u8>>;
-    #[cfg(feature = "compress")]
-    {
-        let compression_threshold = config.compression_threshold;
-        let compression_quality = config.compression_quality;
-
-        let before = std::time::Instant::now();
-        let mut compressed = Vec::new();
-        brotli::BrotliCompress(&mut &*data, &mut compressed, &brotli::enc::BrotliEncoderParams {
-            quality: compression_quality.into(),
-            ..Default::default()
-        }).expect("unexpected error while compressing");
-        let compress_duration = before.elapsed();
-
-        let compression_ratio = compressed.len() as f32 / data.len() as f32;
-        let use_compression = compression_ratio < compression_threshold;
-        if config.print_stats {
-            println!(
-                "[reinda] '{path}': compression ratio {:.1}% (original {}, compressed {}) \
-                    => using {} (compression took {:.2?})",
-                compression_ratio * 100.0,
-                ByteSize(data.len()),
-                ByteSize(compressed.len()),
-                if use_compression { "compressed" } else { "original" },
-                compress_duration,
-            );
+    // Compress. Files for which the dictionary helps were already compressed
+    // while building it.
+    let with_dictionary = dictionary.as_ref().and_then(|d| d.compressed.get(full_path));
+    let compressed = match with_dictionary {
+        Some((compressed, duration)) => Some((compressed.clone(), *duration)),
+        None => compress(&data, config),
+    };
+    let use_compressed_data = match compressed {
+        Some((compressed, compress_duration)) => {
+            let compression_ratio = compressed.len() as f32 / data.len() as f32;
+            let use_compression = compression_ratio < config.compression_threshold;
+            if config.print_stats {
+                println!(
+                    "[reinda] '{path}': compression ratio {:.1}% (original {}, compressed {}{}) \
+                        => using {} (compression took {:.2?})",
+                    compression_ratio * 100.0,
+                    ByteSize(data.len()),
+                    ByteSize(compressed.len()),
+                    if with_dictionary.is_some() { " with dictionary" } else { "" },
+                    if use_compression { "compressed" } else { "original" },
+                    compress_duration,
+                );
+            }
+            if use_compression { Some(compressed) } else { None }
         }
-        use_compressed_data = if use_compression { Some(compressed) } else { None };
-    }
-    #[cfg(not(feature = "compress"))]
-    {
-        use_compressed_data = None;
-        if config.print_stats {
-            println!("[reinda] '{path}': {}", ByteSize(data.len()));
+        None => {
+            if config.print_stats {
+                println!("[reinda] '{path}': {}", ByteSize(data.len()));
+            }
+            None
         }
-    }
+    };
 
 
     let content = if let Some(compressed) = &use_compressed_data {
@@ -254,11 +436,66 @@ fn embed(
     };
 
 
-    let compressed = use_compressed_data.is_some();
-    Ok(quote! {
-        content: #content,
-        compressed: #compressed,
-    })
+    let compression = match (use_compressed_data.is_some(), config.compression) {
+        (false, _) | (true, Compression::None) => quote! { None },
+        (true, Compression::Brotli) if with_dictionary.is_some() => {
+            quote! { BrotliDictionary(REINDA_BROTLI_DICTIONARY) }
+        }
+        (true, Compression::Brotli) => quote! { Brotli },
+        (true, Compression::Zstd) => quote! { Zstd },
+    };
+    let modified = match modified(full_path) {
+        Some(secs) => quote! { Some(#secs) },
+        None => quote! { None },
+    };
+    Ok((content, quote! {
+        compression: reinda::EmbeddedCompression::#compression,
+        modified: #modified,
+    }))
+}
+
+/// Returns the modification time of the file in seconds since the UNIX epoch.
+/// For reproducible builds, it's clamped to `SOURCE_DATE_EPOCH` if that's set.
+#[cfg(prod_mode)]
+fn modified(full_path: &str) -> Option<u64> {
+    let mtime = std::fs::metadata(full_path).ok()?.modified().ok()?;
+    let secs = mtime.duration_since(std::time::UNIX_EPOCH).ok()?.as_secs();
+    let source_date_epoch = std::env::var("SOURCE_DATE_EPOCH").ok()
+        .and_then(|v| v.parse::<u64>().ok());
+    Some(source_date_epoch.map_or(secs, |epoch| secs.min(epoch)))
+}
+
+/// Compresses `data` with the configured algorithm, returning the compressed
+/// data and how long it took. Returns `None` if compression is disabled.
+#[cfg(prod_mode)]
+#[cfg_attr(not(any(feature = "compress", feature = "compress-zstd")), allow(unused_variables))]
+fn compress(data: &[u8], config: &EmbedConfig) -> Option<(Vec<u8>, std::time::Duration)> {
+    #[cfg_attr(not(any(feature = "compress", feature = "compress-zstd")), allow(unused_variables))]
+    let before = std::time::Instant::now();
+    match config.compression {
+        Compression::None => None,
+
+        #[cfg(feature = "compress")]
+        Compression::Brotli => {
+            let mut compressed = Vec::new();
+            brotli::BrotliCompress(&mut &*data, &mut compressed, &brotli::enc::BrotliEncoderParams {
+                quality: config.compression_quality.into(),
+                ..Default::default()
+            }).expect("unexpected error while compressing");
+            Some((compressed, before.elapsed()))
+        }
+
+        #[cfg(feature = "compress-zstd")]
+        Compression::Zstd => {
+            let compressed = zstd::bulk::compress(data, config.compression_quality.into())
+                .expect("unexpected error while compressing");
+            Some((compressed, before.elapsed()))
+        }
+
+        // Rejected while parsing.
+        #[allow(unreachable_patterns)]
+        _ => unreachable!(),
+    }
 }
 
 #[cfg(prod_mode)]
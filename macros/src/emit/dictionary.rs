@@ -0,0 +1,113 @@
+//! Building a custom Brotli dictionary shared by all embedded files, see the
+//! `brotli_dictionary` field of `embed!`.
+
+use std::{collections::HashMap, time::Duration};
+
+#[cfg(feature = "compress")]
+use super::{compress, ByteSize, Resolved};
+#[cfg(feature = "compress")]
+use crate::{err, EmbedConfig, Error};
+
+
+/// Upper bound for the size of the dictionary.
+#[cfg(feature = "compress")]
+const MAX_SIZE: usize = 64 * 1024;
+
+/// Upper bound for how many bytes of each file are added to the dictionary.
+#[cfg(feature = "compress")]
+const MAX_SAMPLE_SIZE: usize = 4 * 1024;
+
+#[cfg_attr(not(feature = "compress"), allow(dead_code))]
+pub(super) struct Dictionary {
+    /// The dictionary itself, compressed with Brotli.
+    pub(super) data: Vec<u8>,
+
+    /// Compressed content and how long compression took for all files (by
+    /// full path) for which using the dictionary results in a smaller size.
+    pub(super) compressed: HashMap<String, (Vec<u8>, Duration)>,
+}
+
+/// Builds a dictionary from the beginnings of all files, as that's where
+/// similar files usually share the most (e.g. SVG or JSON boilerplate).
+/// Returns `None` if there are too few files or if the dictionary is not
+/// worth it, i.e. if it is (compressed) larger than what it saves.
+#[cfg(feature = "compress")]
+pub(super) fn build(resolved: &[Resolved<'_>], config: &EmbedConfig) -> Result<Option<Dictionary>, Error> {
+    let mut files = Vec::new();
+    for entry in resolved {
+        match entry {
+            Resolved::Single { span, full_path, .. } => files.push((*span, full_path)),
+            Resolved::Glob { span, files: matched, .. } => {
+                files.extend(matched.iter().map(|(_, full_path)| (*span, full_path)));
+            }
+        }
+    }
+    if files.len() < 2 {
+        return Ok(None);
+    }
+
+    let mut contents = Vec::new();
+    for (span, full_path) in files {
+        let data = std::fs::read(full_path)
+            .map_err(|e| err!(@span, "could not read '{full_path}': {e}"))?;
+        contents.push((full_path, data));
+    }
+
+    let sample_size = (MAX_SIZE / contents.len()).min(MAX_SAMPLE_SIZE);
+    let mut data = Vec::new();
+    for (_, content) in &contents {
+        data.extend_from_slice(&content[..content.len().min(sample_size)]);
+    }
+
+    let mut compressed = HashMap::new();
+    let mut saved = 0;
+    for (full_path, content) in &contents {
+        let Some((without, _)) = compress(content, config) else {
+            continue;
+        };
+        let before = std::time::Instant::now();
+        let with = compress_with(content, &data, config);
+        if with.len() < without.len() {
+            saved += without.len() - with.len();
+            compressed.insert(full_path.to_string(), (with, before.elapsed()));
+        }
+    }
+
+    // The dictionary itself is stored compressed as well.
+    let (data, _) = compress(&data, config).expect("Brotli compression is enabled");
+    if config.print_stats {
+        println!(
+            "[reinda] Brotli dictionary of {} (compressed) saves {} => {}",
+            ByteSize(data.len()),
+            ByteSize(saved),
+            if saved > data.len() { "using it" } else { "not using it" },
+        );
+    }
+
+    if saved <= data.len() {
+        return Ok(None);
+    }
+    Ok(Some(Dictionary { data, compressed }))
+}
+
+/// Compresses `content` with Brotli using the custom dictionary `dict`.
+#[cfg(feature = "compress")]
+fn compress_with(content: &[u8], dict: &[u8], config: &EmbedConfig) -> Vec<u8> {
+    let mut compressed = Vec::new();
+    let params = brotli::enc::BrotliEncoderParams {
+        quality: config.compression_quality.into(),
+        ..Default::default()
+    };
+    brotli::BrotliCompressCustomIoCustomDict(
+        &mut brotli::IoReaderWrapper(&mut &*content),
+        &mut brotli::IoWriterWrapper(&mut compressed),
+        &mut [0; 4096],
+        &mut [0; 4096],
+        &params,
+        brotli::enc::StandardAlloc::default(),
+        &mut |_, _, _, _| (),
+        dict,
+        std::io::Error::from(std::io::ErrorKind::UnexpectedEof),
+    ).expect("unexpected error while compressing");
+    compressed
+}
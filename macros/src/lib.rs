@@ -5,6 +5,8 @@ use self::{
     ast::{EmbedConfig, Input},
 };
 
+#[cfg(feature = "urls")]
+mod download;
 mod emit;
 mod err;
 mod ast;
@@ -1,15 +1,18 @@
 use std::{convert::TryFrom, iter::Peekable};
 use proc_macro2::{token_stream::IntoIter, Delimiter, TokenStream, TokenTree};
 
-use crate::{err::{err, Error}, ast::Input};
+use crate::{err::{err, Error}, ast::{Compression, Input, UrlEntry}};
 
 
 pub(crate) fn parse(tokens: TokenStream) -> Result<Input, Error> {
     let mut base_path = None;
     let mut files = None;
+    let mut urls = None;
     let mut compression_threshold = None;
     let mut compression_quality = None;
+    let mut compression = None;
     let mut print_stats = None;
+    let mut brotli_dictionary = None;
 
     let mut it = tokens.into_iter().peekable();
 
@@ -36,6 +39,10 @@ pub(crate) fn parse(tokens: TokenStream) -> Result<Input, Error> {
                 print_stats = Some(parse_lit::<litrs::BoolLit>(&mut it)?.value());
             }
 
+            "brotli_dictionary" => {
+                brotli_dictionary = Some(parse_lit::<litrs::BoolLit>(&mut it)?.value());
+            }
+
             "compression_threshold" => {
                 let lit = parse_lit::<litrs::FloatLit<String>>(&mut it)?;
                 let value = lit.number_part().parse()
@@ -50,6 +57,22 @@ pub(crate) fn parse(tokens: TokenStream) -> Result<Input, Error> {
                 compression_quality = Some(value);
             }
 
+            "compression" => {
+                let span = it.peek().map(|t| t.span());
+                let value = match parse_string_lit(&mut it)?.as_str() {
+                    "none" => Compression::None,
+                    "brotli" if cfg!(feature = "compress") => Compression::Brotli,
+                    "zstd" if cfg!(feature = "compress-zstd") => Compression::Zstd,
+                    "brotli" => return Err(err!(@span.unwrap(),
+                        "Brotli compression requires the `compress` feature of reinda")),
+                    "zstd" => return Err(err!(@span.unwrap(),
+                        "zstd compression requires the `compress-zstd` feature of reinda")),
+                    other => return Err(err!(@span.unwrap(),
+                        "unknown compression '{other}', expected 'brotli', 'zstd' or 'none'")),
+                };
+                compression = Some(value);
+            }
+
             "files" => {
                 let inner = match it.next().ok_or_else(unexpected_end_of_input)? {
                     TokenTree::Group(g) if g.delimiter() == Delimiter::Bracket => g.stream(),
@@ -68,6 +91,29 @@ pub(crate) fn parse(tokens: TokenStream) -> Result<Input, Error> {
                 files = Some(values);
             }
 
+            "urls" => {
+                if cfg!(not(feature = "urls")) {
+                    return Err(err!(
+                        @field_name.span(),
+                        "the `urls` field requires the `embed-urls` feature of reinda",
+                    ));
+                }
+
+                let inner = match it.next().ok_or_else(unexpected_end_of_input)? {
+                    TokenTree::Group(g) if g.delimiter() == Delimiter::Bracket => g.stream(),
+                    other => return Err(err!(@other.span(), "expected array `[...]`")),
+                };
+
+                let mut inner_it = inner.into_iter().peekable();
+                let mut values = vec![];
+                while inner_it.peek().is_some() {
+                    values.push(parse_url_entry(&mut inner_it)?);
+                    eat_comma_sep(&mut inner_it)?;
+                }
+
+                urls = Some(values);
+            }
+
             other => return Err(err!(@field_name.span(), "unknown field name '{other}'")),
         }
 
@@ -77,12 +123,49 @@ pub(crate) fn parse(tokens: TokenStream) -> Result<Input, Error> {
     Ok(Input {
         base_path,
         print_stats,
+        brotli_dictionary,
         compression_threshold,
         compression_quality,
-        files: files.ok_or_else(|| err!("missing field 'files' in input"))?,
+        compression,
+        files: match (files, &urls) {
+            (Some(files), _) => files,
+            (None, Some(_)) => vec![],
+            (None, None) => return Err(err!("missing field 'files' in input")),
+        },
+        urls: urls.unwrap_or_default(),
     })
 }
 
+/// Parses `("path", "url", "sha256")`.
+fn parse_url_entry(it: &mut ParseIter) -> Result<UrlEntry, Error> {
+    let group = match it.next().ok_or_else(unexpected_end_of_input)? {
+        TokenTree::Group(g) if g.delimiter() == Delimiter::Parenthesis => g,
+        other => return Err(err!(
+            @other.span(),
+            "expected tuple `(\"path\", \"url\", \"sha256\")`",
+        )),
+    };
+    let span = group.span();
+
+    let mut inner = group.stream().into_iter().peekable();
+    let path = parse_string_lit(&mut inner)?;
+    eat_comma(&mut inner)?;
+    let url = parse_string_lit(&mut inner)?;
+    eat_comma(&mut inner)?;
+    let sha_span = inner.peek().map(|t| t.span()).unwrap_or(span);
+    let sha256 = parse_string_lit(&mut inner)?.to_ascii_lowercase();
+    eat_comma_sep(&mut inner)?;
+    if let Some(other) = inner.next() {
+        return Err(err!(@other.span(), "unexpected fourth tuple element"));
+    }
+
+    if sha256.len() != 64 || !sha256.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(err!(@sha_span, "expected SHA-256 digest as 64 hex digits"));
+    }
+
+    Ok(UrlEntry { path, url, sha256, span })
+}
+
 fn unexpected_end_of_input() -> Error {
     err!("unexpected end of input")
 }
@@ -97,6 +180,14 @@ fn eat_comma_sep(it: &mut ParseIter) -> Result<(), Error> {
     }
 }
 
+fn eat_comma(it: &mut ParseIter) -> Result<(), Error> {
+    match it.next() {
+        Some(TokenTree::Punct(p)) if p.as_char() == ',' => Ok(()),
+        Some(other) => Err(err!(@other.span(), "expected comma")),
+        None => Err(unexpected_end_of_input()),
+    }
+}
+
 fn parse_string_lit(it: &mut ParseIter) -> Result<String, Error> {
     parse_lit::<litrs::StringLit<String>>(it).map(|l| l.into_value().into_owned())
 }
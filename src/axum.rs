@@ -0,0 +1,170 @@
+//! Integration with `axum`, see [`Assets::into_router`].
+
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    response::{IntoResponse, Response},
+    Router,
+};
+
+use crate::{
+    serve::{conditional, content_type, is_allowed, load_encoded, percent_decode},
+    Asset, Assets, RequestContext,
+};
+
+
+impl Assets {
+    /// Returns an `axum` router serving all assets under their *hashed HTTP
+    /// paths*. Mount it with `Router::nest_service` or `Router::fallback_service`
+    /// to serve it under a prefix or as fallback, respectively.
+    ///
+    /// The router handles `GET` and `HEAD` requests and:
+    /// - sets `Content-Type` (with `charset` for text assets) and, if
+    ///   configured, `Cache-Control`,
+    /// - serves the content encoded according to the `Accept-Encoding`
+    ///   header if possible (see [`Asset::content_encoded`]),
+    /// - serves the best variant according to the `Accept` header (see
+    ///   [`Asset::best_variant`]), unless the guard of that variant denies
+    ///   access,
+    /// - sets `ETag` and `Last-Modified` and answers conditional requests with
+    ///   `304 Not Modified` (see [`Conditional`][crate::Conditional]; requires
+    ///   the crate feature `hash` or `http`),
+    /// - responds with the fallback asset (see
+    ///   [`Builder::set_fallback`][crate::Builder::set_fallback]), the error
+    ///   asset for 404 (see
+    ///   [`Builder::set_error_asset`][crate::Builder::set_error_asset]) or an
+    ///   empty 404 response for unknown paths, in that order of preference,
+    /// - responds with the error asset for 403 or an empty 403 response if
+    ///   the guard of the asset denies access (see
+    ///   [`EntryBuilder::with_guard`][crate::EntryBuilder::with_guard]),
+    /// - responds with [`LoadError::to_html`][crate::LoadError::to_html] and
+    ///   status 500 if loading an asset fails (only possible in dev mode).
+    ///
+    /// ```
+    /// # async fn build() -> Result<reinda::Assets, reinda::BuildError> {
+    /// # reinda::Assets::builder().build().await }
+    /// # async fn run() -> Result<(), reinda::BuildError> {
+    /// let assets = build().await?;
+    /// let app = axum::Router::<()>::new()
+    ///     .nest_service("/assets", assets.into_router());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Method is only available if the crate feature `axum` is enabled.
+    pub fn into_router<S>(self) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        Router::new().fallback(serve).with_state(Arc::new(self))
+    }
+}
+
+async fn serve(State(assets): State<Arc<Assets>>, req: Request) -> Response {
+    if req.method() != Method::GET && req.method() != Method::HEAD {
+        let mut response = StatusCode::METHOD_NOT_ALLOWED.into_response();
+        response.headers_mut().insert(header::ALLOW, HeaderValue::from_static("GET, HEAD"));
+        return response;
+    }
+
+    let head = req.method() == Method::HEAD;
+    let path = percent_decode(req.uri().path().trim_start_matches('/'));
+    let asset = path.as_deref().and_then(|path| Some((path, assets.get_or_fallback(path)?)));
+    match asset {
+        Some((path, asset)) => {
+            let variant = {
+                let context = || {
+                    let headers = req.headers().iter()
+                        .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?)));
+                    RequestContext::new(req.method().as_str(), path, headers)
+                };
+
+                // Variants can have guards of their own. If the best one
+                // denies access, the requested asset is served instead.
+                let accept = req.headers().get(header::ACCEPT).and_then(|v| v.to_str().ok());
+                is_allowed(&asset, context).then(|| match accept {
+                    Some(accept) if asset.has_variants() => Some(asset.best_variant(accept))
+                        .filter(|variant| is_allowed(variant, context))
+                        .unwrap_or_else(|| asset.clone()),
+                    _ => asset.clone(),
+                })
+            };
+            let Some(variant) = variant else {
+                return match assets.error_asset(403) {
+                    Some(asset) => respond(&asset, StatusCode::FORBIDDEN, head, None).await,
+                    None => StatusCode::FORBIDDEN.into_response(),
+                };
+            };
+            let mut response = respond(&variant, StatusCode::OK, head, Some(req.headers())).await;
+            if asset.has_variants() {
+                response.headers_mut().append(header::VARY, HeaderValue::from_static("accept"));
+            }
+            response
+        }
+        None => match assets.error_asset(404) {
+            Some(asset) => respond(&asset, StatusCode::NOT_FOUND, head, None).await,
+            None => StatusCode::NOT_FOUND.into_response(),
+        },
+    }
+}
+
+/// Responds with `asset`. Conditional request headers and `Accept-Encoding`
+/// are only evaluated if `req_headers` is given.
+async fn respond(
+    asset: &Asset,
+    status: StatusCode,
+    head: bool,
+    req_headers: Option<&HeaderMap>,
+) -> Response {
+    let accept_encoding = req_headers
+        .and_then(|headers| headers.get(header::ACCEPT_ENCODING))
+        .and_then(|v| v.to_str().ok());
+    let (content, encoding_headers) = match load_encoded(asset, accept_encoding).await {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            let html = e.to_html();
+            let content_type = HeaderValue::from_static("text/html; charset=utf-8");
+            return (StatusCode::INTERNAL_SERVER_ERROR, [(header::CONTENT_TYPE, content_type)], html)
+                .into_response();
+        }
+    };
+
+    let (not_modified, validators) = match req_headers {
+        Some(req_headers) => conditional(asset, &content, req_headers.iter()
+            .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?)))),
+        None => (false, Vec::new()),
+    };
+
+    let len = content.len();
+    let mut response = match head || not_modified {
+        true => Response::new(Body::empty()),
+        false => {
+            #[cfg(feature = "metrics")]
+            crate::metrics::served(asset, len);
+            Response::new(Body::from(content))
+        }
+    };
+    *response.status_mut() = if not_modified { StatusCode::NOT_MODIFIED } else { status };
+    let headers = response.headers_mut();
+    if !not_modified {
+        if let Ok(value) = HeaderValue::from_str(&content_type(asset)) {
+            headers.insert(header::CONTENT_TYPE, value);
+        }
+        headers.insert(header::CONTENT_LENGTH, len.into());
+    }
+    for (name, value) in encoding_headers {
+        headers.append(name, HeaderValue::from_static(value));
+    }
+    if let Some(value) = asset.cache_control().and_then(|v| HeaderValue::from_str(v).ok()) {
+        headers.insert(header::CACHE_CONTROL, value);
+    }
+    for (name, value) in validators {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            headers.insert(name, value);
+        }
+    }
+    response
+}
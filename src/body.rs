@@ -0,0 +1,152 @@
+//! Response bodies implementing `http_body::Body`, see [`AssetBody`].
+
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use http_body::{Body, Frame, SizeHint};
+use tokio::io::{AsyncRead, ReadBuf};
+
+use crate::{Asset, ContentStream, LoadError};
+
+
+/// Size of the chunks in which streamed contents are read.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The content of an asset as response body implementing `http_body::Body`
+/// with an exact size hint, created via [`Asset::body`] or from `Bytes` or a
+/// [`ContentStream`] (e.g. a slice for a `Range` request). In-memory contents
+/// are sent as a single frame, files streamed in dev mode in chunks.
+///
+/// ```no_run
+/// # async fn foo(asset: reinda::Asset) -> Result<(), reinda::LoadError> {
+/// let response = http::Response::new(asset.body().await?);
+/// # Ok(()) }
+/// ```
+///
+/// Type is only available if the crate feature `http-body` is enabled.
+#[derive(Debug)]
+pub struct AssetBody(Inner);
+
+#[derive(Debug)]
+enum Inner {
+    Bytes(Option<Bytes>),
+    Stream {
+        stream: ContentStream,
+        remaining: u64,
+    },
+}
+
+impl Asset {
+    /// Returns the content of this asset as [`AssetBody`]. Like
+    /// [`Self::content_stream`], files are streamed from the file system in
+    /// dev mode where possible.
+    ///
+    /// Method is only available if the crate feature `http-body` is enabled.
+    pub async fn body(&self) -> Result<AssetBody, LoadError> {
+        self.content_stream().await.map(AssetBody::from)
+    }
+}
+
+impl AssetBody {
+    /// Returns an empty body, e.g. for `HEAD` requests or `304` responses.
+    pub fn empty() -> Self {
+        Self(Inner::Bytes(None))
+    }
+}
+
+impl From<Bytes> for AssetBody {
+    fn from(bytes: Bytes) -> Self {
+        Self(Inner::Bytes(Some(bytes).filter(|b| !b.is_empty())))
+    }
+}
+
+impl From<ContentStream> for AssetBody {
+    fn from(stream: ContentStream) -> Self {
+        match stream.as_bytes() {
+            Some(bytes) => bytes.clone().into(),
+            None => Self(Inner::Stream { remaining: stream.len(), stream }),
+        }
+    }
+}
+
+impl Body for AssetBody {
+    type Data = Bytes;
+    type Error = io::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, io::Error>>> {
+        match &mut self.get_mut().0 {
+            Inner::Bytes(bytes) => Poll::Ready(bytes.take().map(|b| Ok(Frame::data(b)))),
+            Inner::Stream { remaining: 0, .. } => Poll::Ready(None),
+            Inner::Stream { stream, remaining } => {
+                let mut buf = vec![0; CHUNK_SIZE.min(*remaining as usize)];
+                let mut read_buf = ReadBuf::new(&mut buf);
+                match Pin::new(stream).poll_read(cx, &mut read_buf) {
+                    Poll::Pending => Poll::Pending,
+                    Poll::Ready(Err(e)) => Poll::Ready(Some(Err(e))),
+                    Poll::Ready(Ok(())) => {
+                        let n = read_buf.filled().len();
+                        if n == 0 {
+                            let msg = "file is shorter than expected";
+                            return Poll::Ready(Some(Err(io::Error::new(io::ErrorKind::UnexpectedEof, msg))));
+                        }
+                        *remaining -= n as u64;
+                        buf.truncate(n);
+                        Poll::Ready(Some(Ok(Frame::data(buf.into()))))
+                    }
+                }
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        match &self.0 {
+            Inner::Bytes(bytes) => bytes.is_none(),
+            Inner::Stream { remaining, .. } => *remaining == 0,
+        }
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        match &self.0 {
+            Inner::Bytes(bytes) => SizeHint::with_exact(bytes.as_ref().map_or(0, |b| b.len() as u64)),
+            Inner::Stream { remaining, .. } => SizeHint::with_exact(*remaining),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn collect(mut body: AssetBody) -> Vec<Bytes> {
+        let mut frames = Vec::new();
+        while let Some(frame) = std::future::poll_fn(|cx| Pin::new(&mut body).poll_frame(cx)).await {
+            frames.push(frame.unwrap().into_data().unwrap());
+        }
+        assert!(body.is_end_stream());
+        frames
+    }
+
+    #[tokio::test]
+    async fn frames() {
+        let body = AssetBody::from(Bytes::from_static(b"Peter"));
+        assert_eq!(body.size_hint().exact(), Some(5));
+        assert_eq!(collect(body).await, [&b"Peter"[..]]);
+
+        assert!(AssetBody::empty().is_end_stream());
+        assert!(collect(Bytes::new().into()).await.is_empty());
+
+        let path = std::path::Path::new("tests/files/peter.txt");
+        let stream = ContentStream::from_file(path).await.unwrap().slice(6..10).await.unwrap();
+        let body = AssetBody::from(stream);
+        assert_eq!(body.size_hint().exact(), Some(4));
+        assert_eq!(collect(body).await, [&b"und "[..]]);
+    }
+}
@@ -1,14 +1,64 @@
-use std::{borrow::Cow, path::PathBuf, sync::Arc};
+use std::{any::{Any, TypeId}, borrow::Cow, fmt, path::PathBuf, sync::Arc, time::Duration};
 
+use ahash::HashMap;
 use bytes::Bytes;
 
-use crate::{Assets, BuildError, DataSource, EmbeddedEntry, EmbeddedFile, EmbeddedGlob, Modifier, ModifierContext, PathHash, SplitGlob};
+use crate::{
+    cache::{AssetMeta, CachePolicy, CachePolicyFn},
+    checksum::Sha256,
+    source::{CustomSource, InMemory},
+    diagnostics::{Warning, WarningHandler},
+    guard::{Guard, RequestContext, Restriction},
+    AssetSource, Assets, BuildError, DataSource, EmbeddedEntry, EmbeddedFile, EmbeddedGlob, Modifier,
+    GlobMount, ModifierContext, PathHash, SplitGlob,
+};
 
 
+/// Variables set via `Builder::add_variable`.
+pub(crate) type Variables = HashMap<Cow<'static, str>, Cow<'static, str>>;
+
 /// Helper to build [`Assets`].
 #[derive(Debug)]
 pub struct Builder<'a> {
     pub(crate) assets: Vec<EntryBuilder<'a>>,
+    pub(crate) groups: Vec<GroupBuilder<'a>>,
+    pub(crate) cache_policy: Option<CachePolicyFn>,
+    #[cfg(feature = "modifiers")]
+    pub(crate) global_replacements: Vec<(Cow<'static, str>, Cow<'static, str>)>,
+    pub(crate) global_modifiers: Vec<GlobalModifier>,
+    pub(crate) warning_handler: WarningHandler,
+    #[cfg_attr(dev_mode, allow(dead_code))]
+    pub(crate) max_concurrent_reads: usize,
+    pub(crate) allow_duplicate_paths: bool,
+    pub(crate) error_assets: HashMap<u16, Cow<'static, str>>,
+    pub(crate) fallback: Option<Cow<'static, str>>,
+    pub(crate) directory_index: Option<Cow<'static, str>>,
+    pub(crate) path_params: HashMap<Cow<'static, str>, Cow<'static, str>>,
+    pub(crate) variables: Variables,
+    #[cfg_attr(prod_mode, allow(dead_code))]
+    pub(crate) dev_max_stale: Option<Duration>,
+    #[cfg_attr(prod_mode, allow(dead_code))]
+    pub(crate) path_remaps: Vec<(PathBuf, PathBuf)>,
+    #[cfg(feature = "hash")]
+    hash_placement: Option<HashPlacement>,
+    #[cfg(feature = "hash")]
+    #[cfg_attr(dev_mode, allow(dead_code))]
+    pub(crate) hash_salt: Vec<u8>,
+    #[cfg(feature = "hash")]
+    #[cfg_attr(dev_mode, allow(dead_code))]
+    pub(crate) hasher: crate::hash::DynHasher,
+    #[cfg(feature = "hash")]
+    hash_length: Option<usize>,
+    pub(crate) mime_types: crate::mime::CustomTypes,
+    #[cfg(feature = "gzip")]
+    #[cfg_attr(dev_mode, allow(dead_code))]
+    pub(crate) gzip: bool,
+    #[cfg(feature = "compress")]
+    #[cfg_attr(dev_mode, allow(dead_code))]
+    pub(crate) compress_runtime_files: bool,
+    #[cfg(any(feature = "compress", feature = "gzip"))]
+    #[cfg_attr(dev_mode, allow(dead_code))]
+    pub(crate) compression_cache_budget: Option<usize>,
 }
 
 /// Returned by the various `Builder::add_*` functions, allowing you to
@@ -16,9 +66,404 @@ pub struct Builder<'a> {
 #[derive(Debug)]
 pub struct EntryBuilder<'a> {
     pub(crate) kind: EntryBuilderKind<'a>,
+    pub(crate) config: EntryConfig<'a>,
+}
+
+/// Settings of an entry that apply to each of its files. For glob entries,
+/// this is cloned for every matched file.
+#[derive(Debug, Clone)]
+pub(crate) struct EntryConfig<'a> {
     #[cfg_attr(not(feature = "hash"), allow(dead_code))]
     pub(crate) path_hash: PathHash<'a>,
     pub(crate) modifier: Modifier,
+    pub(crate) cache_control: Option<Cow<'static, str>>,
+    /// Set via `EntryBuilder::with_mime`, overriding the detection by path.
+    pub(crate) mime: Option<&'static str>,
+    #[cfg_attr(dev_mode, allow(dead_code))]
+    pub(crate) after: Vec<Cow<'static, str>>,
+    pub(crate) expected_sha256: Option<Sha256>,
+    pub(crate) variants: Vec<Cow<'static, str>>,
+    /// Language tags and *unhashed HTTP paths*.
+    pub(crate) locale_variants: Vec<(Cow<'static, str>, Cow<'static, str>)>,
+    pub(crate) validators: Vec<Validator>,
+    #[cfg_attr(not(feature = "sanitize-svg"), allow(dead_code))]
+    pub(crate) sanitize_svg: bool,
+    #[cfg_attr(any(not(feature = "minify-js"), all(dev_mode, not(feature = "manifest"))), allow(dead_code))]
+    pub(crate) minify_js: bool,
+    #[cfg_attr(any(not(feature = "optimize-svg"), all(dev_mode, not(feature = "manifest"))), allow(dead_code))]
+    pub(crate) optimize_svg: bool,
+    /// Set via `EntryBuilder::with_prepend` and `EntryBuilder::with_append`.
+    pub(crate) prepend: Option<Bytes>,
+    pub(crate) append: Option<Bytes>,
+    pub(crate) extensions: Extensions,
+    pub(crate) guard: Option<Guard>,
+    #[cfg(feature = "metrics")]
+    pub(crate) metrics_tag: Option<Cow<'static, str>>,
+    /// Number of hash bytes in the filename, set via
+    /// `EntryBuilder::with_hash_length` or `Builder::set_hash_length`.
+    #[cfg(feature = "hash")]
+    #[cfg_attr(dev_mode, allow(dead_code))]
+    pub(crate) hash_length: Option<usize>,
+    #[cfg(feature = "modifiers")]
+    #[cfg_attr(all(dev_mode, not(feature = "manifest")), allow(dead_code))]
+    pub(crate) binary_policy: BinaryPolicy,
+    /// Set via `EntryBuilder::with_auto_path_fixup`. The paths of the
+    /// `PathFixup` modifier are then extended while building.
+    #[cfg(feature = "modifiers")]
+    #[cfg_attr(all(dev_mode, not(feature = "manifest")), allow(dead_code))]
+    pub(crate) auto_path_fixup: bool,
+}
+
+/// User data attached via [`EntryBuilder::with_extension`], one value per type.
+#[derive(Clone, Default)]
+pub(crate) struct Extensions(HashMap<TypeId, Arc<dyn Any + Send + Sync>>);
+
+impl Extensions {
+    pub(crate) fn get<T: 'static>(&self) -> Option<&T> {
+        self.0.get(&TypeId::of::<T>()).and_then(|v| v.downcast_ref())
+    }
+}
+
+impl fmt::Debug for Extensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Extensions({})", self.0.len())
+    }
+}
+
+/// A function registered via [`EntryBuilder::validate`].
+#[derive(Clone)]
+pub(crate) struct Validator(Arc<ValidatorFn>);
+
+type ValidatorFn = dyn Send + Sync + Fn(&[u8]) -> Result<(), String>;
+
+impl fmt::Debug for Validator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Validator")
+    }
+}
+
+/// A modifier registered via [`Builder::with_global_modifier`].
+#[derive(Clone)]
+pub(crate) struct GlobalModifier {
+    predicate: Arc<dyn Send + Sync + Fn(&str) -> bool>,
+    pub(crate) f: Arc<dyn Send + Sync + Fn(Bytes, ModifierContext) -> Bytes>,
+}
+
+impl fmt::Debug for GlobalModifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("GlobalModifier")
+    }
+}
+
+impl GlobalModifier {
+    /// Returns whether this modifier is applied to the given asset.
+    pub(crate) fn applies_to(&self, unhashed_http_path: &str) -> bool {
+        (self.predicate)(unhashed_http_path)
+    }
+}
+
+/// A function registered via [`EntryBuilder::filter`].
+#[derive(Clone)]
+pub(crate) struct GlobFilter(Arc<GlobFilterFn>);
+
+type GlobFilterFn = dyn Send + Sync + Fn(&str) -> bool;
+
+impl fmt::Debug for GlobFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("GlobFilter")
+    }
+}
+
+/// A function registered via [`EntryBuilder::map_http_paths`].
+#[derive(Clone)]
+pub(crate) struct PathMapper(Arc<PathMapperFn>);
+
+type PathMapperFn = dyn Send + Sync + Fn(&str) -> String;
+
+impl fmt::Debug for PathMapper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PathMapper")
+    }
+}
+
+impl PathMapper {
+    /// Returns the mapped suffix for the glob suffix `suffix`.
+    pub(crate) fn apply(&self, suffix: &str) -> String {
+        (self.0)(suffix)
+    }
+
+    /// Returns a mapper that first applies `prev` (if any), then `self`.
+    fn after(self, prev: Option<PathMapper>) -> Self {
+        match prev {
+            None => self,
+            Some(prev) => Self(Arc::new(move |suffix| self.apply(&prev.apply(suffix)))),
+        }
+    }
+}
+
+/// Returns whether the file with the given glob suffix passes all `filters`.
+pub(crate) fn passes_filters(filters: &[GlobFilter], suffix: &str) -> bool {
+    filters.iter().all(|filter| (filter.0)(suffix))
+}
+
+/// Adds the content set via `EntryBuilder::with_prepend` and
+/// `EntryBuilder::with_append` to the loaded source `content`.
+pub(crate) fn prepend_append(config: &EntryConfig, content: Bytes) -> Bytes {
+    if config.prepend.is_none() && config.append.is_none() {
+        return content;
+    }
+
+    let prepend = config.prepend.as_deref().unwrap_or_default();
+    let append = config.append.as_deref().unwrap_or_default();
+    [prepend, &content, append].concat().into()
+}
+
+/// Sanitizes `content` if configured and runs all validators, returning the
+/// first error.
+pub(crate) fn sanitize_and_validate(config: &EntryConfig, content: Bytes) -> Result<Bytes, String> {
+    #[cfg(feature = "sanitize-svg")]
+    let content = match config.sanitize_svg {
+        true => crate::svg::sanitize(&content)?.into(),
+        false => content,
+    };
+
+    config.validators.iter().try_for_each(|v| (v.0)(&content))?;
+    Ok(content)
+}
+
+/// Minifies `content` if configured via `EntryBuilder::minify_js` or
+/// `EntryBuilder::optimize_svg`. Only called when building for prod mode.
+#[cfg(all(
+    any(feature = "minify-js", feature = "optimize-svg"),
+    any(prod_mode, feature = "manifest"),
+))]
+pub(crate) fn minify(
+    config: &EntryConfig,
+    unhashed_http_path: &str,
+    content: Bytes,
+) -> Result<Bytes, BuildError> {
+    let err = |msg| BuildError::Minify { http_path: unhashed_http_path.to_owned(), msg };
+
+    #[cfg(feature = "minify-js")]
+    let content = match config.minify_js {
+        true => crate::minify::js(&content).map_err(err)?.into(),
+        false => content,
+    };
+    #[cfg(feature = "optimize-svg")]
+    let content = match config.optimize_svg {
+        true => crate::svg::optimize(&content).map_err(err)?.into(),
+        false => content,
+    };
+    Ok(content)
+}
+
+/// Settings of the prod pipeline shared by all assets, see [`process_asset`].
+#[cfg(any(prod_mode, feature = "manifest"))]
+pub(crate) struct Pipeline<'a> {
+    pub(crate) variables: &'a Variables,
+    pub(crate) global_modifiers: &'a [GlobalModifier],
+    #[cfg(feature = "modifiers")]
+    pub(crate) global_replacements: &'a [(Cow<'static, str>, Cow<'static, str>)],
+    #[cfg(feature = "modifiers")]
+    pub(crate) warning_handler: &'a WarningHandler,
+}
+
+/// Result of [`process_asset`].
+#[cfg(any(prod_mode, feature = "manifest"))]
+pub(crate) struct Processed {
+    pub(crate) content: Bytes,
+    /// See `AssetReport::transformations`.
+    #[cfg(feature = "report")]
+    #[cfg_attr(dev_mode, allow(dead_code))]
+    pub(crate) transformations: Vec<&'static str>,
+}
+
+/// Runs the prod pipeline on the loaded source `raw` of one asset: checks the
+/// checksum, applies prepend/append, the modifier, global modifiers and
+/// replacements, minifies, sanitizes and validates. Used by `Builder::build`
+/// in prod mode and by `Assets::dry_run_prod`. `inner` is called for every
+/// modifier context and has to provide access to all assets processed so
+/// far.
+#[cfg(any(prod_mode, feature = "manifest"))]
+#[cfg_attr(not(feature = "modifiers"), allow(unused_variables))]
+pub(crate) fn process_asset<'a>(
+    pipeline: &Pipeline<'a>,
+    unhashed_http_path: &'a str,
+    config: &'a EntryConfig<'_>,
+    glob_suffix: Option<&'a str>,
+    raw: Bytes,
+    path_map: &crate::hash::PathMap<'_>,
+    inner: impl Fn() -> crate::imp::ModifierContextInner<'a>,
+) -> Result<Processed, BuildError> {
+    let path = unhashed_http_path;
+    crate::checksum::verify(config.expected_sha256, &raw).map_err(|actual| {
+        BuildError::ChecksumMismatch {
+            http_path: path.to_owned(),
+            expected: config.expected_sha256.unwrap().to_string(),
+            actual: actual.to_string(),
+        }
+    })?;
+    let raw = prepend_append(config, raw);
+
+    // Apply modifier
+    let context = |declared_deps, module_prefix| ModifierContext {
+        declared_deps,
+        module_prefix,
+        unhashed_http_path: path,
+        glob_suffix,
+        variables: pipeline.variables,
+        is_prod: true,
+        hash_configured: !matches!(config.path_hash, PathHash::None | PathHash::Stable),
+        inner: inner(),
+    };
+    let content = match &config.modifier {
+        Modifier::None => raw,
+        #[cfg(feature = "modifiers")]
+        Modifier::PathFixup(paths) => {
+            path_fixup(raw, paths, path_map, path, config.binary_policy, pipeline.warning_handler)?
+        }
+        #[cfg(feature = "modifiers")]
+        Modifier::Template(_) => {
+            // All dependencies were checked in `resolve_templates`.
+            let resolve = |dep: &str| Some(path_map.get(dep).unwrap_or(dep).to_owned());
+            let inner = inner();
+            let include = |dep: &str| inner.content_of(dep);
+            render_template(&raw, true, pipeline.variables, resolve, include)
+                .map_err(|msg| BuildError::Template { http_path: path.to_owned(), msg })?
+        }
+        Modifier::Custom { f, deps, module_prefix } => f(raw, context(deps, module_prefix)),
+    };
+    let global_modifiers = pipeline.global_modifiers.iter().filter(|m| m.applies_to(path));
+    #[cfg(feature = "report")]
+    let global_modified = global_modifiers.clone().next().is_some();
+    let content = global_modifiers.fold(content, |content, m| (m.f)(content, context(&[], "")));
+
+    #[cfg(feature = "report")]
+    let unreplaced = content.clone();
+    #[cfg(feature = "modifiers")]
+    let content = apply_global_replacements(pipeline.global_replacements, path, content);
+    #[cfg(feature = "report")]
+    let transformations = crate::report::transformations(
+        config,
+        global_modified,
+        content != unreplaced,
+    );
+
+    #[cfg(any(feature = "minify-js", feature = "optimize-svg"))]
+    let content = minify(config, path, content)?;
+    let content = sanitize_and_validate(config, content)
+        .map_err(|msg| BuildError::Validation { http_path: path.to_owned(), msg })?;
+
+    Ok(Processed {
+        content,
+        #[cfg(feature = "report")]
+        transformations,
+    })
+}
+
+/// Where [`EntryBuilder::with_hash`] inserts the hash into the filename. Only
+/// the last path segment is considered and leading dots of the filename (as
+/// in `.htaccess`) are ignored. If the filename contains no `.`, the hash is
+/// appended after a `-` for all built-in placements.
+///
+/// Set it per entry via [`EntryBuilder::with_hash_placement`] or for all
+/// entries via [`Builder::hash_placement`].
+#[cfg(feature = "hash")]
+#[derive(Clone, Default)]
+#[non_exhaustive]
+pub enum HashPlacement {
+    /// Before the first `.`, followed by another `.`: `jquery.min.js`
+    /// becomes `jquery.sbfNUtVcqxUK.min.js`. This is the default.
+    #[default]
+    AfterFirstDot,
+
+    /// Before the last `.`, i.e. right before the extension: `jquery.min.js`
+    /// becomes `jquery.min.sbfNUtVcqxUK.js`.
+    BeforeExt,
+
+    /// At the very end, after a `-`: `jquery.min.js` becomes
+    /// `jquery.min.js-sbfNUtVcqxUK`.
+    Suffix,
+
+    /// Custom placement, see [`HashPlacement::custom`].
+    Custom(Arc<HashPlacementFn>),
+}
+
+#[cfg(feature = "hash")]
+type HashPlacementFn = dyn Send + Sync + Fn(&str, &str) -> String;
+
+#[cfg(feature = "hash")]
+impl HashPlacement {
+    /// Creates a custom placement. `f` is called with the *unhashed HTTP
+    /// path* and the encoded hash and has to return the *hashed HTTP path*.
+    ///
+    /// ```
+    /// use reinda::HashPlacement;
+    ///
+    /// // `app.js` -> `v-<hash>/app.js`
+    /// let placement = HashPlacement::custom(|path, hash| match path.rsplit_once('/') {
+    ///     Some((dir, file)) => format!("{dir}/v-{hash}/{file}"),
+    ///     None => format!("v-{hash}/{path}"),
+    /// });
+    /// ```
+    pub fn custom(f: impl 'static + Send + Sync + Fn(&str, &str) -> String) -> Self {
+        Self::Custom(Arc::new(f))
+    }
+}
+
+#[cfg(feature = "hash")]
+impl fmt::Debug for HashPlacement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AfterFirstDot => f.write_str("AfterFirstDot"),
+            Self::BeforeExt => f.write_str("BeforeExt"),
+            Self::Suffix => f.write_str("Suffix"),
+            Self::Custom(_) => f.write_str("Custom"),
+        }
+    }
+}
+
+/// What [`EntryBuilder::with_path_fixup`] does with replacements in binary
+/// assets (see [`Asset::is_text`][crate::Asset::is_text]) that would change
+/// the length of the file. Binary formats often store offsets or lengths, so
+/// such replacements likely corrupt the file. Replacements that keep the
+/// length are always performed.
+///
+/// Set it per entry via [`EntryBuilder::with_binary_policy`].
+#[cfg(feature = "modifiers")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum BinaryPolicy {
+    /// Skip those replacements and emit a [`Warning::BinaryFixupSkipped`].
+    /// This is the default.
+    #[default]
+    SkipBinary,
+
+    /// Perform those replacements anyway, e.g. if the heuristic misclassified
+    /// a text file.
+    Force,
+
+    /// Fail with [`BuildError::BinaryFixup`].
+    Error,
+}
+
+/// Returned by [`Builder::group`], allowing you to configure all assets whose
+/// *unhashed HTTP path* matches a selector.
+///
+/// Settings of a group are only used for assets that do not configure that
+/// setting themselves. For example, if an entry has its own modifier, the
+/// group's modifier is not used for it. If multiple groups match an asset,
+/// the group added last takes precedence.
+#[derive(Debug)]
+pub struct GroupBuilder<'a> {
+    pub(crate) selector: glob::Pattern,
+    pub(crate) path_hash: Option<PathHash<'a>>,
+    pub(crate) modifier: Option<Modifier>,
+    pub(crate) cache_control: Option<Cow<'static, str>>,
+    pub(crate) sanitize_svg: bool,
+    pub(crate) minify_js: bool,
+    pub(crate) optimize_svg: bool,
+    pub(crate) guard: Option<Guard>,
 }
 
 #[derive(Debug)]
@@ -29,21 +474,78 @@ pub(crate) enum EntryBuilderKind<'a> {
     },
     Glob {
         http_prefix: Cow<'a, str>,
-        #[cfg_attr(prod_mode, allow(dead_code))]
         glob: SplitGlob,
         files: Vec<GlobFile>,
         #[cfg(dev_mode)]
         base_path: &'static str,
-    }
+        /// Replaces `base_path` joined with the glob prefix, see
+        /// `EntryBuilder::with_dev_path`.
+        #[cfg(dev_mode)]
+        dev_dir: Option<PathBuf>,
+        /// Files are filtered right away, this is only needed for files
+        /// found lazily in dev mode.
+        #[cfg(dev_mode)]
+        filters: Vec<GlobFilter>,
+        /// Like `filters`, paths of `files` are mapped right away.
+        #[cfg(dev_mode)]
+        path_mapper: Option<PathMapper>,
+    },
+    /// A directory walked at runtime, see `Builder::add_dir`.
+    Dir {
+        http_prefix: Cow<'a, str>,
+        fs_path: PathBuf,
+        pattern: glob::Pattern,
+        filters: Vec<GlobFilter>,
+        path_mapper: Option<PathMapper>,
+    },
 }
 
 #[derive(Debug)]
 pub(crate) struct GlobFile {
+    /// Path relative to the glob prefix.
     pub(crate) suffix: &'static str,
+    /// The suffix after applying `EntryBuilder::map_http_paths`, if called.
+    pub(crate) mapped_suffix: Option<String>,
     pub(crate) source: DataSource,
 }
 
 impl<'a> Builder<'a> {
+    pub(crate) fn new() -> Self {
+        Self {
+            assets: vec![],
+            groups: vec![],
+            cache_policy: None,
+            #[cfg(feature = "modifiers")]
+            global_replacements: vec![],
+            global_modifiers: vec![],
+            warning_handler: WarningHandler::default(),
+            max_concurrent_reads: 64,
+            allow_duplicate_paths: false,
+            error_assets: HashMap::default(),
+            fallback: None,
+            directory_index: None,
+            path_params: HashMap::default(),
+            variables: HashMap::default(),
+            dev_max_stale: None,
+            path_remaps: vec![],
+            #[cfg(feature = "hash")]
+            hash_placement: None,
+            #[cfg(feature = "hash")]
+            hash_salt: vec![],
+            #[cfg(feature = "hash")]
+            hasher: Default::default(),
+            #[cfg(feature = "hash")]
+            hash_length: None,
+            mime_types: Default::default(),
+            #[cfg(feature = "gzip")]
+            gzip: false,
+            #[cfg(feature = "compress")]
+            compress_runtime_files: false,
+            #[cfg(any(feature = "compress", feature = "gzip"))]
+            compression_cache_budget: None,
+        }
+    }
+
     /// Adds an asset by *FS path*, to be loaded at runtime (instead of being
     /// embedded into the executable). In prod mode, this is loaded in
     /// `Builder::build`. Mounts it under the given HTTP path.
@@ -52,15 +554,94 @@ impl<'a> Builder<'a> {
         http_path: impl Into<Cow<'a, str>>,
         fs_path: impl Into<PathBuf>,
     ) -> &mut EntryBuilder<'a> {
-        self.assets.push(EntryBuilder {
-            kind: EntryBuilderKind::Single {
-                http_path: http_path.into(),
-                source: DataSource::File(fs_path.into()),
-            },
-            path_hash: PathHash::None,
-            modifier: Modifier::None,
-        });
-        self.assets.last_mut().unwrap()
+        self.push(EntryBuilderKind::Single {
+            http_path: http_path.into(),
+            source: DataSource::File(fs_path.into()),
+        })
+    }
+
+    /// Adds an asset loaded from a custom [`AssetSource`] and mounts it under
+    /// the given HTTP path. Like with [`Self::add_file`], it is loaded in
+    /// `Builder::build` in prod mode and on every load in dev mode.
+    pub fn add_source(
+        &mut self,
+        http_path: impl Into<Cow<'a, str>>,
+        source: impl AssetSource,
+    ) -> &mut EntryBuilder<'a> {
+        self.push(EntryBuilderKind::Single {
+            http_path: http_path.into(),
+            source: DataSource::Custom(CustomSource::new(source)),
+        })
+    }
+
+    /// Adds an asset with the given content, e.g. generated at runtime, and
+    /// mounts it under the given HTTP path. It is treated like any other
+    /// asset, i.e. it can be hashed, modified and referenced by other assets.
+    ///
+    /// ```
+    /// let mut builder = reinda::Assets::builder();
+    /// # #[cfg(feature = "hash")]
+    /// builder.add_bytes("config.json", r#"{ "debug": false }"#).with_hash();
+    /// ```
+    pub fn add_bytes(
+        &mut self,
+        http_path: impl Into<Cow<'a, str>>,
+        content: impl Into<Bytes>,
+    ) -> &mut EntryBuilder<'a> {
+        self.add_source(http_path, InMemory(content.into()))
+    }
+
+    /// Like [`Self::add_bytes`], but for string content.
+    pub fn add_str(
+        &mut self,
+        http_path: impl Into<Cow<'a, str>>,
+        content: impl Into<String>,
+    ) -> &mut EntryBuilder<'a> {
+        self.add_bytes(http_path, content.into())
+    }
+
+    /// Adds an asset downloaded from `url` via HTTP(S) and mounts it under the
+    /// given HTTP path. In prod mode, it is downloaded in `Builder::build`;
+    /// in dev mode, on every load. Shorthand for [`Self::add_source`] with a
+    /// [`UrlSource`][crate::UrlSource] with default settings.
+    ///
+    /// Method is only available if the crate feature `url` is enabled.
+    #[cfg(feature = "url")]
+    pub fn add_url(
+        &mut self,
+        http_path: impl Into<Cow<'a, str>>,
+        url: impl Into<String>,
+    ) -> &mut EntryBuilder<'a> {
+        self.add_source(http_path, crate::UrlSource::new(url))
+    }
+
+    /// Adds an asset that is the deep-merge of the JSON files at the given
+    /// *FS paths*, e.g. a base configuration and an environment specific
+    /// overlay. Files are merged in order: objects are merged recursively,
+    /// while all other values (including arrays) of later files replace
+    /// those of earlier files. Like with [`Self::add_file`], the files are
+    /// loaded and merged in `Builder::build` in prod mode and on every load
+    /// in dev mode. A file that cannot be read or is not valid JSON results
+    /// in an I/O error.
+    ///
+    /// ```
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.add_merged_json("config.json", ["config/base.json", "config/prod.json"]);
+    /// ```
+    ///
+    /// Method is only available if the crate feature `json` is enabled.
+    #[cfg(feature = "json")]
+    pub fn add_merged_json<D, T>(
+        &mut self,
+        http_path: impl Into<Cow<'a, str>>,
+        fs_paths: D,
+    ) -> &mut EntryBuilder<'a>
+    where
+        D: IntoIterator<Item = T>,
+        T: Into<PathBuf>,
+    {
+        let paths = fs_paths.into_iter().map(Into::into).collect();
+        self.add_source(http_path, crate::json::MergedJson { paths })
     }
 
     /// Adds an embedded entry (single file or glob). Just calls
@@ -83,15 +664,10 @@ impl<'a> Builder<'a> {
         http_path: impl Into<Cow<'a, str>>,
         file: &EmbeddedFile,
     ) -> &mut EntryBuilder<'a> {
-        self.assets.push(EntryBuilder {
-            kind: EntryBuilderKind::Single {
-                http_path: http_path.into(),
-                source: file.data_source(),
-            },
-            path_hash: PathHash::None,
-            modifier: Modifier::None,
-        });
-        self.assets.last_mut().unwrap()
+        self.push(EntryBuilderKind::Single {
+            http_path: http_path.into(),
+            source: file.data_source(),
+        })
     }
 
     /// Adds an embedded glob. All files matching this glob are mounted with
@@ -119,130 +695,1865 @@ impl<'a> Builder<'a> {
         glob: &'a EmbeddedGlob,
     ) -> &mut EntryBuilder<'a> {
         let split_glob = SplitGlob::new(glob.pattern);
-        self.assets.push(EntryBuilder {
-            kind: EntryBuilderKind::Glob {
-                http_prefix: http_path.into(),
-                files: glob.files.iter().map(|f| GlobFile {
-                    // This should never be `None`
-                    suffix: f.path.strip_prefix(&split_glob.prefix)
-                        .expect("embedded file path does not start with glob prefix"),
-                    source: f.data_source(),
-                }).collect(),
-                glob: split_glob,
-                #[cfg(dev_mode)]
-                base_path: glob.base_path,
-            },
-            path_hash: PathHash::None,
-            modifier: Modifier::None,
-        });
-        self.assets.last_mut().unwrap()
+        self.push(EntryBuilderKind::Glob {
+            http_prefix: http_path.into(),
+            files: glob.files.iter().map(|f| GlobFile {
+                // This should never be `None`
+                suffix: f.path.strip_prefix(split_glob.prefix)
+                    .expect("embedded file path does not start with glob prefix"),
+                mapped_suffix: None,
+                source: f.data_source(),
+            }).collect(),
+            glob: split_glob,
+            #[cfg(dev_mode)]
+            base_path: glob.base_path,
+            #[cfg(dev_mode)]
+            dev_dir: None,
+            #[cfg(dev_mode)]
+            filters: vec![],
+            #[cfg(dev_mode)]
+            path_mapper: None,
+        })
     }
 
-    /// Builds `Assets` from the configured assets. In prod mode, everything is
-    /// loaded, processed, and assembled into a fast data structure. In dev
-    /// mode, those steps are deferred to later.
-    pub async fn build(self) -> Result<Assets, BuildError> {
-        crate::imp::AssetsInner::build(self).await.map(Assets)
+    /// Adds all files inside the directory at *FS path* `fs_path`
+    /// (recursively), mounted with `http_path` as prefix. For example, with
+    /// `add_dir("static/", "dist")`, the file `dist/css/main.css` is mounted
+    /// as `static/css/main.css`.
+    ///
+    /// Unlike with [`Self::add_embedded_glob`], the directory is walked at
+    /// runtime: in `Builder::build` in prod mode. In dev mode, files are
+    /// looked up lazily whenever a path under `http_path` is requested, so
+    /// files created later are served as well.
+    pub fn add_dir(
+        &mut self,
+        http_path: impl Into<Cow<'a, str>>,
+        fs_path: impl Into<PathBuf>,
+    ) -> &mut EntryBuilder<'a> {
+        self.add_dir_glob(http_path, fs_path, "**")
     }
-}
 
-impl<'a> EntryBuilder<'a> {
-    /// Adds the hash of this asset's content to its HTTP filename (in prod mode).
+    /// Like [`Self::add_dir`], but only mounts files whose path relative to
+    /// `fs_path` matches the glob pattern `glob`.
     ///
-    /// This helps a lot with caching on the web. If you include the content
-    /// hash in the filename then you can set very strong caching headers, like
-    /// `Cache-control :public, max-age=31536000, immutable`, meaning that the
-    /// browser can cache that file for essentially infinitely long time
-    /// without relying on `If-Modified-Since` or `E-Tag` headers.
+    /// ```
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.add_dir_glob("icons/", "assets/icons", "**/*.svg");
+    /// ```
     ///
-    /// In dev mode, hashes are never inserted.
+    /// **Panics** if `glob` is not a valid glob pattern.
+    pub fn add_dir_glob(
+        &mut self,
+        http_path: impl Into<Cow<'a, str>>,
+        fs_path: impl Into<PathBuf>,
+        glob: &str,
+    ) -> &mut EntryBuilder<'a> {
+        let pattern = glob::Pattern::new(glob)
+            .unwrap_or_else(|e| panic!("invalid glob '{}': {}", glob, e));
+        self.push(EntryBuilderKind::Dir {
+            http_prefix: http_path.into(),
+            fs_path: fs_path.into(),
+            pattern,
+            filters: vec![],
+            path_mapper: None,
+        })
+    }
+
+    /// Removes all entries that were added with the given `http_path`, i.e.
+    /// the path passed to `add_*`. For glob entries, that's the prefix under
+    /// which the glob is mounted; single files of glob entries cannot be
+    /// removed. Returns whether any entry was removed.
     ///
-    /// The hash is inserted after the first `.` in the filename and an
-    /// additional `.` is added after the hash. Example: `bundle.js.map`
-    /// becomes `bundle.sbfNUtVcqxUK.js.map`. If there is no `.` in the
-    /// filename, a `-` and then the hash is appended, e.g. `foo-sbfNUtVcqxUK`.
+    /// This is useful to customize a builder pre-populated by another crate.
+    /// Path parameters (see [`Self::set_path_param`]) are not resolved yet,
+    /// so `http_path` has to match the path exactly as it was passed.
+    pub fn remove(&mut self, http_path: &str) -> bool {
+        let len_before = self.assets.len();
+        self.assets.retain(|entry| entry.kind.mount_path() != http_path);
+        self.assets.len() != len_before
+    }
+
+    /// Replaces the source of the single file entry added with the given
+    /// `http_path` by the file at `fs_path`, loaded at runtime like with
+    /// [`Self::add_file`]. All configuration of the entry (like its modifier)
+    /// is kept, except for [`EntryBuilder::expect_sha256`], which is reset.
+    /// Returns the entry for further configuration, or `None` if there is no
+    /// single file entry with that path.
     ///
-    /// Method is only available if the crate feature `hash` is enabled.
-    #[cfg(feature = "hash")]
-    pub fn with_hash(&mut self) -> &mut Self {
-        self.path_hash = PathHash::Auto;
-        self
+    /// Like with [`Self::remove`], `http_path` has to match exactly.
+    pub fn replace(
+        &mut self,
+        http_path: &str,
+        fs_path: impl Into<PathBuf>,
+    ) -> Option<&mut EntryBuilder<'a>> {
+        self.replace_source(http_path, DataSource::File(fs_path.into()))
     }
 
-    // TODO: make public again once its tested.
-    /// Like [`Self::with_hash`], but lets you specify where it insert the hash.
-    #[cfg(feature = "hash")]
-    #[allow(dead_code)]
-    fn with_hash_between(&mut self, prefix: &'a str, suffix: &'a str) -> &mut Self {
-        self.path_hash = PathHash::InBetween { prefix, suffix };
+    /// Like [`Self::replace`], but replaces the source with an embedded file.
+    pub fn replace_embedded(
+        &mut self,
+        http_path: &str,
+        file: &EmbeddedFile,
+    ) -> Option<&mut EntryBuilder<'a>> {
+        self.replace_source(http_path, file.data_source())
+    }
+
+    fn replace_source(
+        &mut self,
+        http_path: &str,
+        new_source: DataSource,
+    ) -> Option<&mut EntryBuilder<'a>> {
+        let entry = self.assets.iter_mut().rev().find(|entry| {
+            matches!(&entry.kind, EntryBuilderKind::Single { http_path: p, .. } if p == http_path)
+        })?;
+        if let EntryBuilderKind::Single { source, .. } = &mut entry.kind {
+            *source = new_source;
+        }
+        entry.config.expected_sha256 = None;
+        Some(entry)
+    }
+
+    /// Returns a handle to configure all assets whose *unhashed HTTP path*
+    /// matches the given glob `selector`, e.g. `static/**` or `*.html`. This
+    /// includes assets added after calling this method. `*` does not match
+    /// `/`, use `**` for that.
+    ///
+    /// This is useful to avoid repeating the same configuration for many
+    /// entries. See [`GroupBuilder`] for details on how settings are
+    /// combined.
+    ///
+    /// **Panics** if `selector` is not a valid glob pattern.
+    pub fn group(&mut self, selector: &str) -> &mut GroupBuilder<'a> {
+        let selector = glob::Pattern::new(selector)
+            .unwrap_or_else(|e| panic!("invalid group selector '{}': {}", selector, e));
+        self.groups.push(GroupBuilder {
+            selector,
+            path_hash: None,
+            modifier: None,
+            cache_control: None,
+            sanitize_svg: false,
+            minify_js: false,
+            optimize_svg: false,
+            guard: None,
+        });
+        self.groups.last_mut().unwrap()
+    }
+
+    /// Registers a function that determines the cache policy for all assets
+    /// that do not have a `Cache-Control` value configured explicitly (via
+    /// [`EntryBuilder::with_cache_control`] or
+    /// [`GroupBuilder::with_cache_control`]). The result is available via
+    /// [`Asset::cache_control`][crate::Asset::cache_control].
+    ///
+    /// In prod mode, the function is called once per asset in
+    /// [`Self::build`]; in dev mode, it's called in every `Assets::get`.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use reinda::CachePolicy;
+    ///
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.set_cache_policy(|asset| {
+    ///     let path = asset.unhashed_http_path();
+    ///     if asset.is_filename_hashed() {
+    ///         CachePolicy::Immutable
+    ///     } else if path.ends_with(".html") {
+    ///         CachePolicy::NoStore
+    ///     } else {
+    ///         CachePolicy::MaxAge(Duration::from_secs(5 * 60))
+    ///     }
+    /// });
+    /// ```
+    pub fn set_cache_policy<F>(&mut self, policy: F) -> &mut Self
+    where
+        F: 'static + Send + Sync + Fn(&AssetMeta) -> CachePolicy,
+    {
+        self.cache_policy = Some(CachePolicyFn(Arc::new(policy)));
         self
     }
 
-    /// Replaces occurences of any of the given *unhashed HTTP paths* in this
-    /// asset with the corresponding *hashed HTTP path*. This is a specialized
-    /// version of [`Self::with_modifier`].
-    pub fn with_path_fixup<D, T>(&mut self, paths: D) -> &mut Self
+    /// Registers replacements that are applied to all text assets (see
+    /// [`Asset::is_text`][crate::Asset::is_text]), e.g. to insert a version
+    /// number everywhere `__VERSION__` appears. Can be called multiple times
+    /// to add more replacements.
+    ///
+    /// The replacements are performed after the asset's modifier ran, but
+    /// before the hash for the filename is calculated. Binary assets are
+    /// never touched.
+    ///
+    /// ```
+    /// let version = String::from("1.2.3");
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.global_replace([("__VERSION__", version)]);
+    /// ```
+    #[cfg(feature = "modifiers")]
+    pub fn global_replace<I, N, R>(&mut self, replacements: I) -> &mut Self
     where
-        D: IntoIterator<Item = T>,
-        T: Into<Cow<'static, str>>,
+        I: IntoIterator<Item = (N, R)>,
+        N: Into<Cow<'static, str>>,
+        R: Into<Cow<'static, str>>,
     {
-        self.modifier = Modifier::PathFixup(paths.into_iter().map(Into::into).collect());
+        self.global_replacements.extend(
+            replacements.into_iter().map(|(needle, replacement)| (needle.into(), replacement.into()))
+        );
         self
     }
 
-    /// Registers a modifier that modifies this asset's content, being able to
-    /// resolve *unhashed HTTP paths* to *hashed HTTP paths*.
+    /// Registers a modifier that is applied to all assets whose *unhashed
+    /// HTTP path* satisfies `predicate`, e.g. to inject a snippet into all
+    /// HTML files. Can be called multiple times; all matching modifiers are
+    /// applied in the order they were registered.
     ///
-    /// If you just need to replace paths, [`Self::with_path_fixup`] might work
-    /// for you. This is the more powerful version, allowing you to perform
-    /// arbitrary logic with the asset's content. In prod mode, this is called
-    /// once when you call [`Builder::build`]; in dev mode, it's called every
-    /// time the asset is loaded.
-    pub fn with_modifier<F, D, T>(&mut self, dependencies: D, modifier: F) -> &mut Self
+    /// ```
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.with_global_modifier(
+    ///     |path| path.ends_with(".html"),
+    ///     |html, ctx| {
+    ///         let comment = format!("<!-- {} -->\n", ctx.unhashed_http_path());
+    ///         [comment.as_bytes(), &html].concat().into()
+    ///     },
+    /// );
+    /// ```
+    ///
+    /// Unlike [`Builder::group`] with [`GroupBuilder::with_modifier`], the
+    /// global modifier does not replace the modifier of an entry, but runs
+    /// after it (and before [`Self::global_replace`]). Global modifiers
+    /// cannot declare dependencies, so [`ModifierContext::resolve_path`]
+    /// cannot be used.
+    pub fn with_global_modifier<P, F>(&mut self, predicate: P, modifier: F) -> &mut Self
     where
+        P: 'static + Send + Sync + Fn(&str) -> bool,
         F: 'static + Send + Sync + Fn(Bytes, ModifierContext) -> Bytes,
-        D: IntoIterator<Item = T>,
-        T: Into<Cow<'static, str>>,
     {
-        self.modifier = Modifier::Custom {
+        self.global_modifiers.push(GlobalModifier {
+            predicate: Arc::new(predicate),
             f: Arc::new(modifier),
-            deps: dependencies.into_iter().map(Into::into).collect(),
-        };
+        });
         self
     }
 
-    /// Returns all *unhashed HTTP paths* that are mounted by this entry. This
-    /// is mainly useful to pass as dependencies to [`Self::with_modifier`] or
-    /// [`Self::with_path_fixup`] of another entry.
-    pub fn http_paths(&self) -> Vec<Cow<'a, str>> {
-        match &self.kind {
-            EntryBuilderKind::Single { http_path, .. } => {
-                vec![http_path.clone()]
-            }
-            EntryBuilderKind::Glob { http_prefix, files, .. } => {
-                files.iter().map(|f| f.http_path(http_prefix).into()).collect()
-            }
-        }
+    /// Sets the function that is called for each [`Warning`] that occurs
+    /// while preparing assets. By default, warnings are printed to stderr.
+    pub fn on_warning<F>(&mut self, handler: F) -> &mut Self
+    where
+        F: 'static + Send + Sync + Fn(Warning),
+    {
+        self.warning_handler = WarningHandler::new(handler);
+        self
     }
 
-    /// Like [`Self::http_paths`] but asserting that there is only one path
-    /// added by this entry. If that's not the case, `None` is returned.
-    pub fn single_http_path(&self) -> Option<Cow<'a, str>> {
-        match &self.kind {
-            EntryBuilderKind::Single { http_path, .. } => Some(http_path.clone()),
-            EntryBuilderKind::Glob { http_prefix, files, .. } => {
-                if files.len() == 1 {
-                    Some(files[0].http_path(http_prefix).into())
-                } else {
-                    None
-                }
-            },
-        }
+    /// Sets the maximum number of files that are read from the file system
+    /// concurrently in [`Self::build`] in prod mode. Default: 64. Lower this
+    /// if you add many files loaded at runtime and run into file descriptor
+    /// limits. Has no effect in dev mode, where files are loaded on demand.
+    ///
+    /// **Panics** if `max` is 0.
+    pub fn set_max_concurrent_reads(&mut self, max: usize) -> &mut Self {
+        assert!(max > 0, "`max_concurrent_reads` must be at least 1");
+        self.max_concurrent_reads = max;
+        self
     }
-}
 
-impl GlobFile {
-    pub(crate) fn http_path(&self, http_prefix: &str) -> String {
-        format!("{http_prefix}{}", self.suffix)
+    /// Allows multiple entries to resolve to the same *unhashed HTTP path*,
+    /// in which case the entry added last wins. By default,
+    /// [`BuildError::DuplicatePath`] is returned by [`Self::build`] instead.
+    /// Files in directories added via [`Self::add_dir`] are only checked in
+    /// prod mode, as they are looked up lazily in dev mode.
+    pub fn allow_duplicate_paths(&mut self) -> &mut Self {
+        self.allow_duplicate_paths = true;
+        self
+    }
+
+    /// Registers the asset with the given *unhashed HTTP path* as error page
+    /// for the HTTP status code `status`, retrievable via
+    /// [`Assets::error_asset`]. The asset has to be added separately and is
+    /// still served under its own path as well, so that its modifiers and
+    /// hashed sub-resources work as usual. Calling this again for the same
+    /// status replaces the previous registration.
+    ///
+    /// ```
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.add_file("errors/404.html", "assets/404.html");
+    /// builder.set_error_asset(404, "errors/404.html");
+    /// ```
+    ///
+    /// **Panics** in [`Self::build`] if `http_path` does not refer to an
+    /// existing asset.
+    pub fn set_error_asset(
+        &mut self,
+        status: u16,
+        http_path: impl Into<Cow<'static, str>>,
+    ) -> &mut Self {
+        self.error_assets.insert(status, http_path.into());
+        self
+    }
+
+    /// Registers the asset with the given *unhashed HTTP path* as fallback
+    /// for unknown paths, retrievable via [`Assets::get_or_fallback`]. This
+    /// makes client-side routing of single-page apps work: `/users/42` is
+    /// answered with `index.html`. The asset has to be added separately and
+    /// is still served under its own path as well. The integrations (like
+    /// [`Assets::into_service`]) serve the fallback with status 200 for all
+    /// unknown paths instead of a 404. Calling this again replaces the
+    /// previous fallback.
+    ///
+    /// ```
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.add_file("index.html", "frontend/dist/index.html");
+    /// builder.set_fallback("index.html");
+    /// ```
+    ///
+    /// **Panics** in [`Self::build`] if `http_path` does not refer to an
+    /// existing asset.
+    pub fn set_fallback(&mut self, http_path: impl Into<Cow<'static, str>>) -> &mut Self {
+        self.fallback = Some(http_path.into());
+        self
+    }
+
+    /// Lets [`Assets::get`] resolve directory paths to the asset with the
+    /// given filename inside that directory, if no asset with the requested
+    /// path exists: with `"index.html"`, both `docs/` and `docs` resolve to
+    /// `docs/index.html` and the empty path resolves to `index.html`. Index
+    /// assets with hashed filenames are found via their *unhashed HTTP path*.
+    ///
+    /// Note that relative links in `docs/index.html` are resolved against
+    /// `/` by browsers if it's served as `docs` (without trailing slash). You
+    /// might want to redirect those requests.
+    ///
+    /// ```
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.add_file("docs/index.html", "docs/index.html");
+    /// builder.directory_index("index.html");
+    /// ```
+    pub fn directory_index(&mut self, filename: impl Into<Cow<'static, str>>) -> &mut Self {
+        self.directory_index = Some(filename.into());
+        self
+    }
+
+    /// Enables a stale-while-revalidate cache in dev mode, useful if the file
+    /// system is slow (e.g. Docker volumes on macOS). Loaded assets are cached
+    /// and served from cache if the cached content is at most `max_stale`
+    /// old; in that case, the asset is reloaded in the background so that a
+    /// later request sees the new content. Older cache entries are not used,
+    /// but the asset is loaded synchronously as without this cache.
+    ///
+    /// This means a change to a file might only be visible on the second
+    /// request after it. [`Assets::changes`] evicts all reported assets from
+    /// the cache, so that live reloading is not affected. Background reloads
+    /// are managed by [`Assets::runtime`]; after its shutdown, the cache is
+    /// not used anymore. Has no effect in prod mode.
+    pub fn dev_stale_while_revalidate(&mut self, max_stale: Duration) -> &mut Self {
+        self.dev_max_stale = Some(max_stale);
+        self
+    }
+
+    /// In dev mode, replaces the prefix `from` of all *FS paths* by `to`
+    /// before loading files. [`embed!`][crate::embed!] stores absolute paths
+    /// in dev mode, which become invalid if the executable is run on a
+    /// different machine or the checkout was moved (e.g. into a Docker
+    /// container). Only whole path components are matched. If several
+    /// prefixes match, the one added first is used. Has no effect in prod
+    /// mode, where embedded files are part of the executable.
+    ///
+    /// ```
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.remap_prefix("/home/ci/checkout", "/app");
+    /// ```
+    pub fn remap_prefix(&mut self, from: impl Into<PathBuf>, to: impl Into<PathBuf>) -> &mut Self {
+        self.path_remaps.push((from.into(), to.into()));
+        self
+    }
+
+    /// Registers `mime` as MIME type of all assets with the file extension
+    /// `extension` (case-insensitive, with or without leading `.`), see
+    /// [`Asset::mime`][crate::Asset::mime]. Takes precedence over the
+    /// built-in mappings.
+    ///
+    /// ```
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.add_mime_type("glb", "model/gltf-binary");
+    /// ```
+    pub fn add_mime_type(&mut self, extension: &str, mime: &'static str) -> &mut Self {
+        let extension = extension.strip_prefix('.').unwrap_or(extension);
+        self.mime_types.insert(extension.to_ascii_lowercase(), mime);
+        self
+    }
+
+    /// Restricts access to all source maps (assets ending in `.map`): they
+    /// are still included, but only served by the framework integrations if
+    /// `restriction` allows it. This is a shorthand for a
+    /// [`GroupBuilder::with_guard`] on `**/*.map`, so guards of entries and of
+    /// groups registered later take precedence.
+    ///
+    /// ```
+    /// use reinda::Restriction;
+    ///
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.restrict_source_maps(Restriction::HeaderToken("X-Debug-Token".into()));
+    /// ```
+    pub fn restrict_source_maps(&mut self, restriction: Restriction) -> &mut Self {
+        self.group("**/*.map").with_guard(move |req| restriction.allows(req));
+        self
+    }
+
+    /// Brotli-compresses all assets in [`Self::build`] that are not stored in
+    /// compressed form already, for serving them via
+    /// [`Asset::content_encoded`][crate::Asset::content_encoded] like
+    /// compressed embedded files. This affects files added at runtime (e.g.
+    /// via [`Self::add_file`]) as well as embedded files that were changed
+    /// while building (e.g. by a modifier). Assets that do not compress well
+    /// (e.g. images) are skipped. Has no effect in dev mode.
+    ///
+    /// Method is only available if the crate feature `compress` is enabled.
+    #[cfg(feature = "compress")]
+    pub fn compress_runtime_files(&mut self) -> &mut Self {
+        self.compress_runtime_files = true;
+        self
+    }
+
+    /// Lets [`Asset::content_encoded`][crate::Asset::content_encoded] compress
+    /// assets on demand if no stored encoding is accepted by the client,
+    /// e.g. for clients that only accept gzip. Compression runs on tokio's
+    /// blocking thread pool, once per asset and encoding even for concurrent
+    /// requests. Compressed data is cached and reused, evicting the least
+    /// recently used entries to keep the total size of the cache below
+    /// `memory_budget` bytes. Supports Brotli (with feature `compress`) and
+    /// gzip (with feature `gzip`). Has no effect in dev mode.
+    ///
+    /// ```
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.compress_on_demand(16 * 1024 * 1024);
+    /// ```
+    ///
+    /// Method is only available if the crate feature `compress` or `gzip` is
+    /// enabled.
+    #[cfg(any(feature = "compress", feature = "gzip"))]
+    pub fn compress_on_demand(&mut self, memory_budget: usize) -> &mut Self {
+        self.compression_cache_budget = Some(memory_budget);
+        self
+    }
+
+    /// Additionally stores all assets gzip-compressed, for clients that do
+    /// not accept Brotli (see [`Asset::content_encoded`][crate::Asset::content_encoded]).
+    /// The compression happens in [`Self::build`] in prod mode; assets that
+    /// do not compress well (e.g. images) are skipped. Has no effect in dev
+    /// mode.
+    ///
+    /// Method is only available if the crate feature `gzip` is enabled.
+    #[cfg(feature = "gzip")]
+    pub fn enable_gzip(&mut self) -> &mut Self {
+        self.gzip = true;
+        self
+    }
+
+    /// Sets the default [`HashPlacement`] for all entries and groups with
+    /// [`EntryBuilder::with_hash`]. Placements set via
+    /// [`EntryBuilder::with_hash_placement`] take precedence.
+    ///
+    /// Method is only available if the crate feature `hash` is enabled.
+    #[cfg(feature = "hash")]
+    pub fn hash_placement(&mut self, placement: HashPlacement) -> &mut Self {
+        self.hash_placement = Some(placement);
+        self
+    }
+
+    /// Sets a salt that is mixed into all filename hashes. Changing the salt
+    /// changes all *hashed HTTP paths* without changing any file contents,
+    /// invalidating all client and CDN caches, e.g. after a cache poisoning
+    /// incident. No salt is used by default. As filenames are only hashed in
+    /// prod mode, this has no effect in dev mode.
+    ///
+    /// ```
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.hash_salt(std::env::var("ASSET_SALT").unwrap_or_default());
+    /// ```
+    ///
+    /// Method is only available if the crate feature `hash` is enabled.
+    #[cfg(feature = "hash")]
+    pub fn hash_salt(&mut self, salt: impl Into<Vec<u8>>) -> &mut Self {
+        self.hash_salt = salt.into();
+        self
+    }
+
+    /// Sets the hash function used for filename hashes, see
+    /// [`ContentHasher`][crate::ContentHasher]. [`Sha256Hasher`][crate::Sha256Hasher]
+    /// is used by default. As filenames are only hashed in prod mode, this
+    /// has no effect in dev mode.
+    ///
+    /// Method is only available if the crate feature `hash` is enabled.
+    #[cfg(feature = "hash")]
+    pub fn content_hasher(&mut self, hasher: impl 'static + crate::ContentHasher) -> &mut Self {
+        self.hasher = crate::hash::DynHasher::new(hasher);
+        self
+    }
+
+    /// Sets how many bytes of the content hash (see [`Self::content_hasher`])
+    /// are put into filenames by [`EntryBuilder::with_hash`] and friends. If
+    /// the hasher's digest is shorter (e.g. 16 bytes for `Xxh3Hasher`), the
+    /// whole digest is used instead. With the default base64 encoding, every
+    /// 3 bytes are encoded as 4 characters, so multiples of 3 work best.
+    /// Shorter hashes mean shorter URLs, but make it more likely that a
+    /// changed file ends up with the same *hashed HTTP path* and clients
+    /// keep using the stale version. The default is 9 bytes (12 characters
+    /// with the default encoding). Lengths set via
+    /// [`EntryBuilder::with_hash_length`] take precedence.
+    ///
+    /// ```
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.set_hash_length(6); // 8 characters
+    /// ```
+    ///
+    /// Panics if `bytes` is smaller than 6 or larger than 32.
+    ///
+    /// Method is only available if the crate feature `hash` is enabled.
+    #[cfg(feature = "hash")]
+    pub fn set_hash_length(&mut self, bytes: usize) -> &mut Self {
+        check_hash_length(bytes);
+        self.hash_length = Some(bytes);
+        self
+    }
+
+    /// Sets the value of the path parameter `name`. All occurences of
+    /// `{name}` in *unhashed HTTP paths* are replaced by `value` in
+    /// [`Self::build`]. This allows mounting the same assets under a URL
+    /// space determined at runtime, e.g. scoped by app version for canary
+    /// deployments.
+    ///
+    /// Parameters are resolved everywhere paths are specified: in paths passed
+    /// to `add_*`, in dependencies and path fixups, in [`EntryBuilder::after`],
+    /// in [`Builder::group`] selectors and in [`Builder::set_error_asset`].
+    /// Afterwards, only the resolved paths exist, e.g. for [`Assets::get`] and
+    /// [`ModifierContext::resolve_path`].
+    ///
+    /// ```
+    /// let version = "1.2.3";
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.add_file("v{version}/static/app.js", "dist/app.js");
+    /// builder.add_file("v{version}/index.html", "dist/index.html")
+    ///     .after(["v{version}/static/app.js"]);
+    /// builder.set_path_param("version", version);
+    /// ```
+    ///
+    /// **Panics** in `build` if a path contains `{name}` where `name` consists
+    /// of only alphanumeric characters and `_`, but no parameter of that name
+    /// is set. Other uses of braces are left untouched.
+    pub fn set_path_param(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        value: impl Into<Cow<'static, str>>,
+    ) -> &mut Self {
+        self.path_params.insert(name.into(), value.into());
+        self
+    }
+
+    /// Sets the value of the variable `name`, e.g. an API base URL only known
+    /// at runtime. Variables can be accessed by modifiers via
+    /// [`ModifierContext::variable`] and are inserted for `{{: var:name :}}`
+    /// fragments in templates (see [`EntryBuilder::with_template`]). Setting a
+    /// variable again overwrites the previous value.
+    ///
+    /// ```
+    /// let mut builder = reinda::Assets::builder();
+    /// # #[cfg(feature = "modifiers")]
+    /// builder.add_str("config.js", "const API = '{{: var:api_url :}}';").with_template();
+    /// builder.add_variable("api_url", "https://api.example.com");
+    /// ```
+    pub fn add_variable(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        value: impl Into<Cow<'static, str>>,
+    ) -> &mut Self {
+        self.variables.insert(name.into(), value.into());
+        self
+    }
+
+    fn push(&mut self, kind: EntryBuilderKind<'a>) -> &mut EntryBuilder<'a> {
+        self.assets.push(EntryBuilder {
+            kind,
+            config: EntryConfig {
+                path_hash: PathHash::None,
+                modifier: Modifier::None,
+                cache_control: None,
+                mime: None,
+                after: vec![],
+                expected_sha256: None,
+                variants: vec![],
+                locale_variants: vec![],
+                validators: vec![],
+                sanitize_svg: false,
+                minify_js: false,
+                optimize_svg: false,
+                prepend: None,
+                append: None,
+                extensions: Extensions::default(),
+                guard: None,
+                #[cfg(feature = "metrics")]
+                metrics_tag: None,
+                #[cfg(feature = "hash")]
+                hash_length: None,
+                #[cfg(feature = "modifiers")]
+                binary_policy: BinaryPolicy::default(),
+                #[cfg(feature = "modifiers")]
+                auto_path_fixup: false,
+            },
+        });
+        self.assets.last_mut().unwrap()
+    }
+
+    /// Builds `Assets` from the configured assets. In prod mode, everything is
+    /// loaded, processed, and assembled into a fast data structure. In dev
+    /// mode, those steps are deferred to later.
+    pub async fn build(mut self) -> Result<Assets, BuildError> {
+        self.resolve_path_params();
+        #[cfg(feature = "hash")]
+        self.apply_hash_defaults();
+        crate::imp::AssetsInner::build(self).await.map(Assets)
+    }
+
+    /// Sets the placement configured via [`Self::hash_placement`] for all
+    /// entries and groups that did not specify one, and the length configured
+    /// via [`Self::set_hash_length`] for all entries.
+    #[cfg(feature = "hash")]
+    fn apply_hash_defaults(&mut self) {
+        if let Some(default) = self.hash_length {
+            for entry in &mut self.assets {
+                entry.config.hash_length.get_or_insert(default);
+            }
+        }
+
+        let Some(default) = &self.hash_placement else {
+            return;
+        };
+        let path_hashes = self.assets.iter_mut().map(|entry| &mut entry.config.path_hash)
+            .chain(self.groups.iter_mut().filter_map(|group| group.path_hash.as_mut()));
+        for path_hash in path_hashes {
+            if let PathHash::Auto(placement @ None) = path_hash {
+                *placement = Some(default.clone());
+            }
+        }
+    }
+
+    /// Replaces all path parameters in all paths stored in this builder.
+    fn resolve_path_params(&mut self) {
+        let params = &self.path_params;
+        let resolve_all = |paths: &mut Vec<Cow<'static, str>>| {
+            paths.iter_mut().for_each(|p| resolve_path_params(params, p));
+        };
+        let resolve_modifier = |modifier: &mut Modifier| {
+            if let Some(deps) = modifier.dependencies_mut() {
+                resolve_all(deps);
+            }
+        };
+
+        for entry in &mut self.assets {
+            match &mut entry.kind {
+                EntryBuilderKind::Single { http_path, .. } => resolve_path_params(params, http_path),
+                EntryBuilderKind::Glob { http_prefix, .. } | EntryBuilderKind::Dir { http_prefix, .. } => {
+                    resolve_path_params(params, http_prefix);
+                }
+            }
+            resolve_modifier(&mut entry.config.modifier);
+            resolve_all(&mut entry.config.after);
+            resolve_all(&mut entry.config.variants);
+            for (_, path) in &mut entry.config.locale_variants {
+                resolve_path_params(params, path);
+            }
+        }
+        for group in &mut self.groups {
+            let mut selector = Cow::Borrowed(group.selector.as_str());
+            resolve_path_params(params, &mut selector);
+            if let Cow::Owned(selector) = selector {
+                group.selector = glob::Pattern::new(&selector)
+                    .unwrap_or_else(|e| panic!("invalid group selector '{}': {}", selector, e));
+            }
+            if let Some(modifier) = &mut group.modifier {
+                resolve_modifier(modifier);
+            }
+        }
+        for path in self.error_assets.values_mut().chain(&mut self.fallback) {
+            resolve_path_params(params, path);
+        }
+    }
+}
+
+impl<'a> EntryBuilder<'a> {
+    /// Adds the hash of this asset's content to its HTTP filename (in prod mode).
+    ///
+    /// This helps a lot with caching on the web. If you include the content
+    /// hash in the filename then you can set very strong caching headers, like
+    /// `Cache-control :public, max-age=31536000, immutable`, meaning that the
+    /// browser can cache that file for essentially infinitely long time
+    /// without relying on `If-Modified-Since` or `E-Tag` headers.
+    ///
+    /// In dev mode, hashes are never inserted.
+    ///
+    /// The hash is inserted after the first `.` in the filename and an
+    /// additional `.` is added after the hash. Example: `bundle.js.map`
+    /// becomes `bundle.sbfNUtVcqxUK.js.map`. If there is no `.` in the
+    /// filename, a `-` and then the hash is appended, e.g. `foo-sbfNUtVcqxUK`.
+    /// Leading dots of the filename are ignored, so `.htaccess` becomes
+    /// `.htaccess-sbfNUtVcqxUK` and `.well-known/.env.json` becomes
+    /// `.well-known/.env.sbfNUtVcqxUK.json`. Dots in directory names (like
+    /// `.well-known/`) never matter.
+    ///
+    /// Method is only available if the crate feature `hash` is enabled.
+    #[cfg(feature = "hash")]
+    pub fn with_hash(&mut self) -> &mut Self {
+        self.config.path_hash = PathHash::Auto(None);
+        self
+    }
+
+    /// Ensures that no hash is ever inserted into the filename of this asset,
+    /// even if a matching group (see [`Builder::group`]) specifies
+    /// [`GroupBuilder::with_hash`]. Overrides previous calls to
+    /// [`Self::with_hash`] and vice versa.
+    ///
+    /// This is useful for assets that need a stable name but still refer to
+    /// hashed assets, like a service worker: its modifier can depend on
+    /// hashed assets and insert their *hashed HTTP paths* via
+    /// [`ModifierContext::resolve_path`], and the service worker is
+    /// re-generated whenever they change. Assets without hash are never
+    /// hashed in the first place, so this is only needed to opt out of
+    /// group settings or to make the intent explicit.
+    ///
+    /// ```
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.group("*.js").with_cache_control("public, max-age=31536000, immutable");
+    /// builder.add_file("sw.js", "assets/sw.js")
+    ///     .stable_name()
+    ///     .with_cache_control("no-cache")
+    ///     .with_modifier(["app.js"], |content, ctx| {
+    ///         let script = format!("const APP = '{}';\n", ctx.resolve_path("app.js"));
+    ///         [script.as_bytes(), &content].concat().into()
+    ///     });
+    /// ```
+    pub fn stable_name(&mut self) -> &mut Self {
+        self.config.path_hash = PathHash::Stable;
+        self
+    }
+
+    /// Like [`Self::with_hash`], but inserts the hash as specified by
+    /// `placement` instead of the default.
+    ///
+    /// ```
+    /// use reinda::HashPlacement;
+    ///
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.add_file("jquery.min.js", "assets/jquery.min.js")
+    ///     .with_hash_placement(HashPlacement::BeforeExt); // jquery.min.HASH.js
+    /// ```
+    ///
+    /// Method is only available if the crate feature `hash` is enabled.
+    #[cfg(feature = "hash")]
+    pub fn with_hash_placement(&mut self, placement: HashPlacement) -> &mut Self {
+        self.config.path_hash = PathHash::Auto(Some(placement));
+        self
+    }
+
+    /// Overrides [`Builder::set_hash_length`] for this entry. Like there, the
+    /// length is capped at the digest size of the content hasher. Does not
+    /// enable hashing by itself, use [`Self::with_hash`] for that.
+    ///
+    /// ```
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.add_file("app.js", "assets/app.js")
+    ///     .with_hash()
+    ///     .with_hash_length(12); // app.HASH.js with a 16 character hash
+    /// ```
+    ///
+    /// Panics if `bytes` is smaller than 6 or larger than 32.
+    ///
+    /// Method is only available if the crate feature `hash` is enabled.
+    #[cfg(feature = "hash")]
+    pub fn with_hash_length(&mut self, bytes: usize) -> &mut Self {
+        check_hash_length(bytes);
+        self.config.hash_length = Some(bytes);
+        self
+    }
+
+    /// Like [`Self::with_hash`], but the *hashed HTTP path* is `prefix`,
+    /// followed by the hash, followed by `suffix`. The *unhashed HTTP path*
+    /// is not part of it at all.
+    ///
+    /// ```
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.add_file("app.js", "assets/app.js")
+    ///     .with_hash_between("js/app-", ".min.js"); // js/app-HASH.min.js
+    /// ```
+    ///
+    /// Method is only available if the crate feature `hash` is enabled.
+    ///
+    /// **Panics** if this is a glob or directory entry, as all of its files
+    /// would get the same path. Use [`Self::with_hash_between_fn`] instead.
+    #[cfg(feature = "hash")]
+    pub fn with_hash_between(
+        &mut self,
+        prefix: impl Into<Cow<'a, str>>,
+        suffix: impl Into<Cow<'a, str>>,
+    ) -> &mut Self {
+        assert!(
+            matches!(self.kind, EntryBuilderKind::Single { .. }),
+            "`with_hash_between` cannot be used for glob or directory entries, \
+                use `with_hash_between_fn` instead",
+        );
+        self.config.path_hash = PathHash::InBetween { prefix: prefix.into(), suffix: suffix.into() };
+        self
+    }
+
+    /// Like [`Self::with_hash_between`], but `f` is called for each file
+    /// with its *unhashed HTTP path* and returns the prefix and suffix
+    /// between which the hash is inserted. Works for glob and directory
+    /// entries as well.
+    ///
+    /// ```
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.add_dir("fonts/", "assets/fonts")
+    ///     // `fonts/inter.woff2` -> `fonts/inter-HASH.woff2`
+    ///     .with_hash_between_fn(|path| match path.rsplit_once('.') {
+    ///         Some((stem, ext)) => (format!("{stem}-"), format!(".{ext}")),
+    ///         None => (format!("{path}-"), String::new()),
+    ///     });
+    /// ```
+    ///
+    /// Method is only available if the crate feature `hash` is enabled.
+    #[cfg(feature = "hash")]
+    pub fn with_hash_between_fn<F>(&mut self, f: F) -> &mut Self
+    where
+        F: 'static + Send + Sync + Fn(&str) -> (String, String),
+    {
+        self.with_hash_placement(HashPlacement::custom(move |path, hash| {
+            let (prefix, suffix) = f(path);
+            format!("{prefix}{hash}{suffix}")
+        }))
+    }
+
+    /// Replaces occurences of any of the given *unhashed HTTP paths* in this
+    /// asset with the corresponding *hashed HTTP path*. This is a specialized
+    /// version of [`Self::with_modifier`].
+    ///
+    /// For binary assets (see [`Asset::is_text`][crate::Asset::is_text]),
+    /// by default only replacements that do not change the length of the
+    /// file are performed, as binary formats often store offsets or lengths.
+    /// All other replacements are skipped and a
+    /// [`Warning::BinaryFixupSkipped`] is emitted. This can be changed via
+    /// [`Self::with_binary_policy`]. For more control, use
+    /// [`Self::with_modifier`] with [`util::patch_many`][crate::util::patch_many].
+    #[cfg(feature = "modifiers")]
+    pub fn with_path_fixup<D, T>(&mut self, paths: D) -> &mut Self
+    where
+        D: IntoIterator<Item = T>,
+        T: Into<Cow<'static, str>>,
+    {
+        self.config.modifier = Modifier::PathFixup(paths.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Like [`Self::with_path_fixup`], but instead of listing the paths,
+    /// the content of this asset is searched for the *unhashed HTTP paths*
+    /// of all assets with hashed filenames, which then become dependencies
+    /// of this asset. Other paths occurring in this asset are never changed.
+    ///
+    /// ```
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.add_file("index.html", "assets/index.html").with_auto_path_fixup();
+    /// builder.add_file("style.css", "assets/style.css").with_hash();
+    /// builder.add_file("bundle.js", "assets/bundle.js").with_hash();
+    /// ```
+    ///
+    /// The search uses the raw content of the asset and is performed in
+    /// `Builder::build` in prod mode and in `Assets::dry_run_prod`. If two
+    /// assets reference each other this way, building fails with
+    /// [`BuildError::CyclicDependencies`][crate::BuildError::CyclicDependencies].
+    /// Paths passed to [`Self::with_path_fixup`] before are kept.
+    #[cfg(feature = "modifiers")]
+    pub fn with_auto_path_fixup(&mut self) -> &mut Self {
+        if !matches!(self.config.modifier, Modifier::PathFixup(_)) {
+            self.config.modifier = Modifier::PathFixup(vec![]);
+        }
+        self.config.auto_path_fixup = true;
+        self
+    }
+
+    /// Treats this asset as template with the following fragments.
+    /// Whitespace inside fragments is ignored. This replaces any other
+    /// modifier of this entry.
+    ///
+    /// - `{{: path:<unhashed HTTP path> :}}` is replaced by the *hashed HTTP
+    ///   path* of the given asset, which becomes a dependency of this asset.
+    /// - `{{: var:<name> :}}` is replaced by the value of the variable set via
+    ///   [`Builder::add_variable`].
+    /// - `{{: include:<unhashed HTTP path> :}}` is replaced by the content of
+    ///   the given asset, after all its modifications. It also becomes a
+    ///   dependency of this asset; including assets in a cycle is an error.
+    /// - `{{: if dev :}} ... {{: endif :}}` keeps its content only in dev
+    ///   mode, `{{: if prod :}}` only in prod mode (including
+    ///   `Assets::dry_run_prod`). An optional `{{: else :}}` section is kept
+    ///   otherwise. Conditionals can be nested; fragments in removed sections
+    ///   have no effect.
+    ///
+    /// ```
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.add_file("index.html", "assets/index.html").with_template();
+    /// builder.add_file("style.css", "assets/style.css").with_hash();
+    /// ```
+    ///
+    /// With `index.html` containing:
+    ///
+    /// ```html
+    /// <link rel="stylesheet" href="/{{: path:style.css :}}">
+    /// ```
+    ///
+    /// Like with [`Self::with_auto_path_fixup`], dependencies are determined
+    /// from the raw content in `Builder::build` in prod mode and in
+    /// `Assets::dry_run_prod`. Invalid templates, unknown assets and unset
+    /// variables result in
+    /// [`BuildError::Template`][crate::BuildError::Template] there and in
+    /// [`LoadErrorKind::Template`][crate::LoadErrorKind::Template] when
+    /// loading the asset in dev mode, where paths are not hashed.
+    ///
+    /// Method is only available if the crate feature `modifiers` is enabled.
+    #[cfg(feature = "modifiers")]
+    pub fn with_template(&mut self) -> &mut Self {
+        self.config.modifier = Modifier::Template(vec![]);
+        self
+    }
+
+    /// Inserts `content` at the start of this asset, e.g. a license header.
+    /// This happens right after loading the source, so all modifiers see the
+    /// prepended content and it influences the hash. Calling this again
+    /// replaces the previously set content. Works in dev mode as well.
+    ///
+    /// ```
+    /// let mut builder = reinda::Assets::builder();
+    /// # #[cfg(feature = "hash")]
+    /// builder.add_file("bundle.js", "examples/assets/bundle.js")
+    ///     .with_prepend("/*! (c) 2024 ACME, MIT license */\n")
+    ///     .with_hash();
+    /// ```
+    ///
+    /// The prepended content is not searched when determining the
+    /// dependencies of [`Self::with_auto_path_fixup`] and
+    /// [`Self::with_template`].
+    pub fn with_prepend(&mut self, content: impl Into<Bytes>) -> &mut Self {
+        self.config.prepend = Some(content.into());
+        self
+    }
+
+    /// Like [`Self::with_prepend`], but inserts `content` at the end of this
+    /// asset.
+    pub fn with_append(&mut self, content: impl Into<Bytes>) -> &mut Self {
+        self.config.append = Some(content.into());
+        self
+    }
+
+    /// Sets what [`Self::with_path_fixup`] does with replacements that would
+    /// change the length of this asset if it's binary. See [`BinaryPolicy`].
+    ///
+    /// ```
+    /// use reinda::BinaryPolicy;
+    ///
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.add_file("app.wasm", "assets/app.wasm")
+    ///     .with_path_fixup(["logo.png"])
+    ///     .with_binary_policy(BinaryPolicy::Error);
+    /// ```
+    ///
+    /// Method is only available if the crate feature `modifiers` is enabled.
+    #[cfg(feature = "modifiers")]
+    pub fn with_binary_policy(&mut self, policy: BinaryPolicy) -> &mut Self {
+        self.config.binary_policy = policy;
+        self
+    }
+
+    /// Registers a modifier that modifies this asset's content, being able to
+    /// resolve *unhashed HTTP paths* to *hashed HTTP paths*.
+    ///
+    /// If you just need to replace paths, [`Self::with_path_fixup`] might work
+    /// for you. This is the more powerful version, allowing you to perform
+    /// arbitrary logic with the asset's content. In prod mode, this is called
+    /// once when you call [`Builder::build`]; in dev mode, it's called every
+    /// time the asset is loaded.
+    pub fn with_modifier<F, D, T>(&mut self, dependencies: D, modifier: F) -> &mut Self
+    where
+        F: 'static + Send + Sync + Fn(Bytes, ModifierContext) -> Bytes,
+        D: IntoIterator<Item = T>,
+        T: Into<Cow<'static, str>>,
+    {
+        self.config.modifier = Modifier::Custom {
+            f: Arc::new(modifier),
+            deps: dependencies.into_iter().map(Into::into).collect(),
+            module_prefix: Cow::Borrowed(""),
+        };
+        self
+    }
+
+    /// Makes sure that this asset is processed after the assets with the given
+    /// *unhashed HTTP paths*, even if it does not depend on them via
+    /// [`Self::with_modifier`] or [`Self::with_path_fixup`]. Can be called
+    /// multiple times to add more constraints.
+    ///
+    /// In prod mode, assets are processed in [`Builder::build`] in an order
+    /// determined by their dependencies. This is useful if modifiers interact
+    /// in ways not captured by those. Like with dependencies, cycles result
+    /// in [`BuildError::CyclicDependencies`]. In dev mode, this has no effect
+    /// as all modifiers run lazily.
+    ///
+    /// **Panics** in `Builder::build` if any of the given paths does not
+    /// refer to an existing asset.
+    pub fn after<D, T>(&mut self, paths: D) -> &mut Self
+    where
+        D: IntoIterator<Item = T>,
+        T: Into<Cow<'static, str>>,
+    {
+        self.config.after.extend(paths.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets the `Cache-Control` header value that should be used when serving
+    /// this asset, retrievable via [`Asset::cache_control`][crate::Asset::cache_control].
+    /// Reinda itself does not serve anything, so this is just carried through.
+    pub fn with_cache_control(&mut self, value: impl Into<Cow<'static, str>>) -> &mut Self {
+        self.config.cache_control = Some(value.into());
+        self
+    }
+
+    /// Sets the MIME type of this asset, returned by
+    /// [`Asset::mime`][crate::Asset::mime] instead of the one determined by
+    /// the path. Useful for paths without or with unusual extensions.
+    ///
+    /// ```
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.add_str("healthz", "ok").with_mime("text/plain");
+    /// ```
+    pub fn with_mime(&mut self, mime: &'static str) -> &mut Self {
+        self.config.mime = Some(mime);
+        self
+    }
+
+    /// Sets the value of the `tag` label of the `reinda_served_bytes_total`
+    /// metric for this asset. Use few distinct tags (e.g. `"js"` or
+    /// `"images"`) to keep the number of time series small. Assets without
+    /// tag are recorded with an empty `tag`.
+    ///
+    /// Method is only available if the crate feature `metrics` is enabled.
+    #[cfg(feature = "metrics")]
+    pub fn with_metrics_tag(&mut self, tag: impl Into<Cow<'static, str>>) -> &mut Self {
+        self.config.metrics_tag = Some(tag.into());
+        self
+    }
+
+    /// Restricts access to this asset: the framework integrations only serve
+    /// it if `guard` returns `true` for the request, and respond with the
+    /// error asset for 403 (see [`Builder::set_error_asset`]) or an empty 403
+    /// response otherwise. Custom servers can check the guard via
+    /// [`Asset::is_allowed`][crate::Asset::is_allowed].
+    ///
+    /// ```
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.add_file("admin.html", "assets/admin.html")
+    ///     .with_guard(|req| req.header("authorization") == Some("Bearer secret"));
+    /// ```
+    pub fn with_guard<F>(&mut self, guard: F) -> &mut Self
+    where
+        F: 'static + Send + Sync + Fn(&RequestContext) -> bool,
+    {
+        self.config.guard = Some(Guard(Arc::new(guard)));
+        self
+    }
+
+    /// Registers the assets with the given *unhashed HTTP paths* as
+    /// alternative formats of this asset, in order of preference. Example:
+    /// for `logo.png`, register `["logo.avif", "logo.webp"]`. The variants
+    /// have to be added separately and are still served under their own
+    /// paths. [`Asset::best_variant`][crate::Asset::best_variant] selects
+    /// among them based on the `Accept` header of a request.
+    ///
+    /// The MIME type of each variant is determined by its extension. Can be
+    /// called multiple times to add more variants.
+    ///
+    /// **Panics** in `Builder::build` if any of the given paths does not
+    /// refer to an existing asset. In dev mode, paths matching a glob or
+    /// directory entry are only checked when requested.
+    pub fn with_variants<D, T>(&mut self, paths: D) -> &mut Self
+    where
+        D: IntoIterator<Item = T>,
+        T: Into<Cow<'static, str>>,
+    {
+        self.config.variants.extend(paths.into_iter().map(Into::into));
+        self
+    }
+
+    /// Registers the assets with the given *unhashed HTTP paths* as
+    /// translations of this asset into the given languages, in order of
+    /// preference. Example: for `index.html`, register
+    /// `[("en", "index.en.html"), ("de", "index.de.html")]`. The variants
+    /// have to be added separately and are still served under their own
+    /// paths. [`Assets::get_localized`][crate::Assets::get_localized] selects
+    /// among them based on the `Accept-Language` header of a request, with
+    /// this asset itself as default.
+    ///
+    /// Can be called multiple times to add more variants.
+    ///
+    /// **Panics** in `Builder::build` if any of the given paths does not
+    /// refer to an existing asset. In dev mode, paths matching a glob or
+    /// directory entry are only checked when requested.
+    pub fn with_locale_variants<D, L, T>(&mut self, variants: D) -> &mut Self
+    where
+        D: IntoIterator<Item = (L, T)>,
+        L: Into<Cow<'static, str>>,
+        T: Into<Cow<'static, str>>,
+    {
+        self.config.locale_variants.extend(
+            variants.into_iter().map(|(lang, path)| (lang.into(), path.into()))
+        );
+        self
+    }
+
+    /// Pins the SHA-256 hash (given as 64 hex digits) of this asset's source.
+    /// Useful for files loaded at runtime that must not change unnoticed,
+    /// e.g. security critical scripts or files with licensing requirements.
+    /// The raw content, before any modifier or replacement, is checked. For
+    /// glob entries, every matched file is checked against the same hash.
+    ///
+    /// In prod mode, a mismatch makes [`Builder::build`] fail with
+    /// [`BuildError::ChecksumMismatch`]. In dev mode, the check happens every
+    /// time the asset is loaded, resulting in
+    /// [`LoadErrorKind::ChecksumMismatch`][crate::LoadErrorKind::ChecksumMismatch].
+    ///
+    /// Method is only available if the crate feature `hash` is enabled.
+    ///
+    /// **Panics** if `hex` is not a valid hex encoded SHA-256 hash.
+    #[cfg(feature = "hash")]
+    pub fn expect_sha256(&mut self, hex: &str) -> &mut Self {
+        let digest = Sha256::from_hex(hex)
+            .unwrap_or_else(|| panic!("invalid SHA-256 hash '{}': expected 64 hex digits", hex));
+        self.config.expected_sha256 = Some(digest);
+        self
+    }
+
+    /// Registers a function that checks the final content of this asset (after
+    /// modifiers and replacements), e.g. to assert that a JSON file parses or
+    /// that an SVG contains no scripts. Can be called multiple times to add
+    /// more validators, which run in the order they were added. For glob
+    /// entries, every matched file is validated.
+    ///
+    /// In prod mode, a rejected asset makes [`Builder::build`] fail with
+    /// [`BuildError::Validation`]. In dev mode, validation happens every time
+    /// the asset is loaded, resulting in
+    /// [`LoadErrorKind::Validation`][crate::LoadErrorKind::Validation].
+    ///
+    /// ```
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.add_file("config.json", "assets/config.json")
+    ///     .validate(|bytes| match bytes.first() {
+    ///         Some(b'{') => Ok(()),
+    ///         _ => Err("expected JSON object".into()),
+    ///     });
+    /// ```
+    pub fn validate<F>(&mut self, validator: F) -> &mut Self
+    where
+        F: 'static + Send + Sync + Fn(&[u8]) -> Result<(), String>,
+    {
+        self.config.validators.push(Validator(Arc::new(validator)));
+        self
+    }
+
+    /// Sanitizes this asset with [`util::sanitize_svg`][crate::util::sanitize_svg]
+    /// after all modifiers and replacements ran, right before the validators
+    /// (see [`Self::validate`]). An asset that is not a well-formed SVG file
+    /// is treated like a failed validation.
+    ///
+    /// Method is only available if the crate feature `sanitize-svg` is
+    /// enabled.
+    #[cfg(feature = "sanitize-svg")]
+    pub fn sanitize_svg(&mut self) -> &mut Self {
+        self.config.sanitize_svg = true;
+        self
+    }
+
+    /// Minifies this asset with [`util::minify_js`][crate::util::minify_js]
+    /// after all modifiers and replacements ran, before the hash for the
+    /// filename is calculated. This only happens in prod mode (and in
+    /// [`Assets::dry_run_prod`][crate::Assets::dry_run_prod]); in dev mode,
+    /// the unminified code is served for easier debugging. If the asset is
+    /// not valid JavaScript, building fails with [`BuildError::Minify`].
+    ///
+    /// ```
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.add_file("app.js", "assets/app.js").minify_js().with_hash();
+    /// ```
+    ///
+    /// Method is only available if the crate feature `minify-js` is enabled.
+    #[cfg(feature = "minify-js")]
+    pub fn minify_js(&mut self) -> &mut Self {
+        self.config.minify_js = true;
+        self
+    }
+
+    /// Optimizes this SVG with [`util::optimize_svg`][crate::util::optimize_svg].
+    /// Like [`Self::minify_js`], this happens right before the hash is
+    /// calculated, only in prod mode. If the asset is not a well-formed SVG,
+    /// building fails with [`BuildError::Minify`].
+    ///
+    /// ```
+    /// const EMBEDS: reinda::Embeds = reinda::embed! {
+    ///     base_path: "examples/assets",
+    ///     files: ["*.svg"],
+    /// };
+    ///
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.add_embedded("icons/", &EMBEDS["*.svg"]).optimize_svg().with_hash();
+    /// ```
+    ///
+    /// Method is only available if the crate feature `optimize-svg` is
+    /// enabled.
+    #[cfg(feature = "optimize-svg")]
+    pub fn optimize_svg(&mut self) -> &mut Self {
+        self.config.optimize_svg = true;
+        self
+    }
+
+    /// Attaches arbitrary data to this asset (e.g. auth requirements or
+    /// routing hints), which can be retrieved via
+    /// [`Asset::extension`][crate::Asset::extension], for example in a
+    /// middleware. Only one value per type is stored: attaching another value
+    /// of the same type replaces the previous one. For glob entries, all
+    /// matched files share the value.
+    ///
+    /// ```
+    /// struct RequiresLogin;
+    ///
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.add_file("admin.html", "assets/admin.html").with_extension(RequiresLogin);
+    /// ```
+    pub fn with_extension<T: 'static + Send + Sync>(&mut self, value: T) -> &mut Self {
+        self.config.extensions.0.insert(TypeId::of::<T>(), Arc::new(value));
+        self
+    }
+
+    /// Only serves files of this glob or directory entry for which `filter`
+    /// returns `true`. The filter is called with the path of the file
+    /// relative to the entry's HTTP prefix, i.e. the part matched by the
+    /// glob (see [`ModifierContext::glob_suffix`][crate::ModifierContext::glob_suffix]).
+    /// This allows excluding a few files without a more specific embed
+    /// pattern. Can be called multiple times, in which case a file has to
+    /// pass all filters.
+    ///
+    /// ```
+    /// const EMBEDS: reinda::Embeds = reinda::embed! {
+    ///     base_path: "examples/assets",
+    ///     files: ["bundle.*"],
+    /// };
+    ///
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.add_embedded("", &EMBEDS["bundle.*"])
+    ///     .filter(|path| !path.ends_with(".test.js"));
+    /// ```
+    ///
+    /// In dev mode, the filter also applies to files that are created after
+    /// compilation. Note that [`Self::glob_mount`] does not take filters into
+    /// account.
+    ///
+    /// **Panics** if this is a single file entry.
+    pub fn filter<F>(&mut self, filter: F) -> &mut Self
+    where
+        F: 'static + Send + Sync + Fn(&str) -> bool,
+    {
+        let filter = GlobFilter(Arc::new(filter));
+        match &mut self.kind {
+            EntryBuilderKind::Single { http_path, .. } => {
+                panic!("`EntryBuilder::filter` called for single file entry '{}'", http_path);
+            }
+            EntryBuilderKind::Glob { files, #[cfg(dev_mode)] filters, .. } => {
+                files.retain(|file| (filter.0)(file.suffix));
+                #[cfg(dev_mode)]
+                filters.push(filter);
+            }
+            EntryBuilderKind::Dir { filters, .. } => filters.push(filter),
+        }
+        self
+    }
+
+    /// Changes the *unhashed HTTP path* of each file of this glob or
+    /// directory entry. `map` is called with the path of the file relative to
+    /// the entry's HTTP prefix (see [`Self::filter`]) and returns the new
+    /// relative path, which is then prefixed again. This allows, for example,
+    /// lowercasing paths or dropping a directory level:
+    ///
+    /// ```
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.add_dir("fonts/", "assets/vendor/fonts")
+    ///     .map_http_paths(|path| path.trim_start_matches("dist/").to_lowercase());
+    /// ```
+    ///
+    /// Filters always see the unmapped path. If called multiple times, the
+    /// mappings are applied in order. The mapping is applied in both modes.
+    /// As it cannot be reversed, the directory is walked in dev mode by
+    /// [`Builder::build`] and again on every call to
+    /// [`Assets::changes`][crate::Assets::changes], so files added later are
+    /// only served after being reported as changed. Like filters, mappings
+    /// are not reflected in [`Self::glob_mount`].
+    ///
+    /// **Panics** if this is a single file entry.
+    pub fn map_http_paths<F>(&mut self, map: F) -> &mut Self
+    where
+        F: 'static + Send + Sync + Fn(&str) -> String,
+    {
+        let mapper = PathMapper(Arc::new(map));
+        match &mut self.kind {
+            EntryBuilderKind::Single { http_path, .. } => {
+                panic!("`EntryBuilder::map_http_paths` called for single file entry '{}'", http_path);
+            }
+            EntryBuilderKind::Glob { files, #[cfg(dev_mode)] path_mapper, .. } => {
+                for file in files {
+                    file.mapped_suffix = Some(mapper.apply(file.http_suffix()));
+                }
+                #[cfg(dev_mode)]
+                {
+                    *path_mapper = Some(mapper.after(path_mapper.take()));
+                }
+            }
+            EntryBuilderKind::Dir { path_mapper, .. } => {
+                *path_mapper = Some(mapper.after(path_mapper.take()));
+            }
+        }
+        self
+    }
+
+    /// Loads this entry from `fs_path` in dev mode instead of the path
+    /// recorded by `embed!` (or passed to `add_file`/`add_dir`), e.g. to
+    /// serve the live output directory of a bundler while prod mode embeds
+    /// the release build. Has no effect in prod mode.
+    ///
+    /// ```
+    /// const EMBEDS: reinda::Embeds = reinda::embed! {
+    ///     base_path: "examples/assets",
+    ///     files: ["index.html", "bundle.*"],
+    /// };
+    ///
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.add_embedded("index.html", &EMBEDS["index.html"])
+    ///     .with_dev_path("frontend/index.html");
+    /// builder.add_embedded("", &EMBEDS["bundle.*"]).with_dev_path("frontend/build");
+    /// ```
+    ///
+    /// For single file entries, `fs_path` is the file. For glob entries, it
+    /// is the directory containing the files matched by the glob, i.e. the
+    /// one that the leading non-glob segments of the embed pattern refer to.
+    /// For directory entries, it replaces the directory. In dev mode, the
+    /// files of glob entries are still the ones found at compile time, plus
+    /// the ones matching the glob in `fs_path` on request.
+    pub fn with_dev_path(&mut self, fs_path: impl Into<PathBuf>) -> &mut Self {
+        let fs_path = fs_path.into();
+        #[cfg(dev_mode)]
+        match &mut self.kind {
+            EntryBuilderKind::Single { source, .. } => *source = DataSource::File(fs_path),
+            EntryBuilderKind::Glob { files, dev_dir, .. } => {
+                for file in files {
+                    file.source = DataSource::File(fs_path.join(file.suffix));
+                }
+                *dev_dir = Some(fs_path);
+            }
+            EntryBuilderKind::Dir { fs_path: dir, .. } => *dir = fs_path,
+        }
+        #[cfg(prod_mode)]
+        let _ = fs_path;
+        self
+    }
+
+    /// Returns all *unhashed HTTP paths* that are mounted by this entry. This
+    /// is mainly useful to pass as dependencies to [`Self::with_modifier`] or
+    /// [`Self::with_path_fixup`] of another entry. Empty for entries added via
+    /// [`Builder::add_dir`], as the directory is not walked yet.
+    pub fn http_paths(&self) -> Vec<Cow<'a, str>> {
+        match &self.kind {
+            EntryBuilderKind::Single { http_path, .. } => {
+                vec![http_path.clone()]
+            }
+            EntryBuilderKind::Glob { http_prefix, files, .. } => {
+                files.iter().map(|f| f.http_path(http_prefix).into()).collect()
+            }
+            EntryBuilderKind::Dir { .. } => vec![],
+        }
+    }
+
+    /// For glob and directory entries, returns the [`GlobMount`] describing
+    /// which paths are served by this entry. Returns `None` for single file
+    /// entries. Path parameters (see [`Builder::set_path_param`]) are not yet
+    /// resolved.
+    pub fn glob_mount(&self) -> Option<GlobMount> {
+        self.kind.glob_mount()
+    }
+
+    /// Like [`Self::http_paths`] but asserting that there is only one path
+    /// added by this entry. If that's not the case, `None` is returned.
+    pub fn single_http_path(&self) -> Option<Cow<'a, str>> {
+        match &self.kind {
+            EntryBuilderKind::Single { http_path, .. } => Some(http_path.clone()),
+            EntryBuilderKind::Glob { http_prefix, files, .. } => {
+                if files.len() == 1 {
+                    Some(files[0].http_path(http_prefix).into())
+                } else {
+                    None
+                }
+            },
+            EntryBuilderKind::Dir { .. } => None,
+        }
+    }
+}
+
+impl<'a> GroupBuilder<'a> {
+    /// Like [`EntryBuilder::with_hash`], but for all assets in this group.
+    #[cfg(feature = "hash")]
+    pub fn with_hash(&mut self) -> &mut Self {
+        self.path_hash = Some(PathHash::Auto(None));
+        self
+    }
+
+    /// Like [`EntryBuilder::with_path_fixup`], but for all assets in this
+    /// group.
+    #[cfg(feature = "modifiers")]
+    pub fn with_path_fixup<D, T>(&mut self, paths: D) -> &mut Self
+    where
+        D: IntoIterator<Item = T>,
+        T: Into<Cow<'static, str>>,
+    {
+        self.modifier = Some(Modifier::PathFixup(paths.into_iter().map(Into::into).collect()));
+        self
+    }
+
+    /// Like [`EntryBuilder::with_modifier`], but for all assets in this group.
+    /// The modifier is called once per asset.
+    pub fn with_modifier<F, D, T>(&mut self, dependencies: D, modifier: F) -> &mut Self
+    where
+        F: 'static + Send + Sync + Fn(Bytes, ModifierContext) -> Bytes,
+        D: IntoIterator<Item = T>,
+        T: Into<Cow<'static, str>>,
+    {
+        self.modifier = Some(Modifier::Custom {
+            f: Arc::new(modifier),
+            deps: dependencies.into_iter().map(Into::into).collect(),
+            module_prefix: Cow::Borrowed(""),
+        });
+        self
+    }
+
+    /// Like [`EntryBuilder::with_cache_control`], but for all assets in this
+    /// group.
+    pub fn with_cache_control(&mut self, value: impl Into<Cow<'static, str>>) -> &mut Self {
+        self.cache_control = Some(value.into());
+        self
+    }
+
+    /// Like [`EntryBuilder::with_guard`], but for all assets in this group,
+    /// e.g. to restrict access to source maps via `builder.group("**/*.map")`.
+    pub fn with_guard<F>(&mut self, guard: F) -> &mut Self
+    where
+        F: 'static + Send + Sync + Fn(&RequestContext) -> bool,
+    {
+        self.guard = Some(Guard(Arc::new(guard)));
+        self
+    }
+
+    /// Like [`EntryBuilder::sanitize_svg`], but for all assets in this group,
+    /// e.g. `builder.group("**/*.svg").sanitize_svg()`.
+    #[cfg(feature = "sanitize-svg")]
+    pub fn sanitize_svg(&mut self) -> &mut Self {
+        self.sanitize_svg = true;
+        self
+    }
+
+    /// Like [`EntryBuilder::minify_js`], but for all assets in this group,
+    /// e.g. `builder.group("**/*.js").minify_js()`.
+    #[cfg(feature = "minify-js")]
+    pub fn minify_js(&mut self) -> &mut Self {
+        self.minify_js = true;
+        self
+    }
+
+    /// Like [`EntryBuilder::optimize_svg`], but for all assets in this group,
+    /// e.g. `builder.group("**/*.svg").optimize_svg()`.
+    #[cfg(feature = "optimize-svg")]
+    pub fn optimize_svg(&mut self) -> &mut Self {
+        self.optimize_svg = true;
+        self
+    }
+
+    pub(crate) fn matches(&self, unhashed_http_path: &str) -> bool {
+        self.selector.matches_with(unhashed_http_path, glob::MatchOptions {
+            require_literal_separator: true,
+            ..Default::default()
+        })
+    }
+}
+
+/// Fills in all settings that are not configured by an entry itself from the
+/// groups matching `unhashed_http_path`. Later groups take precedence.
+pub(crate) fn apply_groups<'a>(
+    groups: &[GroupBuilder<'a>],
+    unhashed_http_path: &str,
+    config: &mut EntryConfig<'a>,
+) {
+    for group in groups.iter().rev().filter(|g| g.matches(unhashed_http_path)) {
+        if let (PathHash::None, Some(group_hash)) = (&config.path_hash, &group.path_hash) {
+            config.path_hash = group_hash.clone();
+        }
+        if let (Modifier::None, Some(group_modifier)) = (&config.modifier, &group.modifier) {
+            config.modifier = group_modifier.clone();
+        }
+        if config.cache_control.is_none() {
+            config.cache_control.clone_from(&group.cache_control);
+        }
+        config.sanitize_svg |= group.sanitize_svg;
+        config.minify_js |= group.minify_js;
+        config.optimize_svg |= group.optimize_svg;
+        if config.guard.is_none() {
+            config.guard.clone_from(&group.guard);
+        }
+    }
+}
+
+/// Replaces all `{name}` in `path` by the value of parameter `name`. Leaves
+/// `path` untouched (and borrowed) if it contains no parameters.
+fn resolve_path_params<'p>(
+    params: &HashMap<Cow<'static, str>, Cow<'static, str>>,
+    path: &mut Cow<'p, str>,
+) {
+    let is_param_name = |s: &str| {
+        !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    };
+
+    let mut out = String::new();
+    let mut rest = &**path;
+    let mut replaced = false;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else { break };
+        let name = &rest[start + 1..start + len];
+        out.push_str(&rest[..start]);
+        if is_param_name(name) {
+            let value = params.get(name).unwrap_or_else(|| {
+                panic!("path parameter '{}' used in '{}' is not set", name, path)
+            });
+            out.push_str(value);
+            replaced = true;
+        } else {
+            out.push_str(&rest[start..=start + len]);
+        }
+        rest = &rest[start + len + 1..];
+    }
+
+    if replaced {
+        out.push_str(rest);
+        *path = Cow::Owned(out);
+    }
+}
+
+/// Applies the global replacements to `content` if it's a text file.
+#[cfg(feature = "modifiers")]
+pub(crate) fn apply_global_replacements(
+    replacements: &[(Cow<'static, str>, Cow<'static, str>)],
+    unhashed_http_path: &str,
+    content: Bytes,
+) -> Bytes {
+    if replacements.is_empty() || !crate::text::classify(unhashed_http_path, &content).is_text {
+        return content;
+    }
+
+    let replacements = replacements.iter()
+        .map(|(needle, replacement)| (needle.as_bytes(), replacement.as_bytes()))
+        .collect::<Vec<_>>();
+    crate::util::replace_many(&content, &replacements).into()
+}
+
+/// Replaces all given *unhashed HTTP paths* in `original` with their hashed
+/// version from `path_map`, see `EntryBuilder::with_path_fixup`.
+#[cfg(all(feature = "modifiers", any(prod_mode, feature = "manifest")))]
+pub(crate) fn path_fixup(
+    original: Bytes,
+    paths: &[Cow<'static, str>],
+    path_map: &crate::hash::PathMap,
+    unhashed_http_path: &str,
+    policy: BinaryPolicy,
+    warning_handler: &crate::diagnostics::WarningHandler,
+) -> Result<Bytes, BuildError> {
+    use aho_corasick::AhoCorasick;
+    use crate::diagnostics::Warning;
+
+    // In binary files, we by default only perform replacements that keep the
+    // length the same, as otherwise we would likely corrupt the file.
+    let keep_length = policy != BinaryPolicy::Force
+        && !crate::text::classify(unhashed_http_path, &original).is_text;
+    let mut needles = Vec::new();
+    for path in paths {
+        let Some(hashed) = path_map.get(path) else {
+            continue;
+        };
+        let occurs = || !path.is_empty()
+            && original.windows(path.len()).any(|w| w == path.as_bytes());
+        if keep_length && hashed.len() != path.len() && occurs() {
+            let asset = unhashed_http_path.to_owned();
+            let path = path.to_string();
+            match policy {
+                BinaryPolicy::Error => return Err(BuildError::BinaryFixup { http_path: asset, path }),
+                _ => warning_handler.emit(Warning::BinaryFixupSkipped { asset, path }),
+            }
+        } else {
+            needles.push(&**path);
+        }
+    }
+    // Prefer longer paths, e.g. `bundle.js.map` over `bundle.js`.
+    let replacer = AhoCorasick::builder()
+        .match_kind(aho_corasick::MatchKind::LeftmostLongest)
+        .build(needles)
+        .unwrap();
+    let mut out = Vec::with_capacity(original.len());
+    replacer.replace_all_with_bytes(&original, &mut out, |_, needle, out| {
+        let needle = std::str::from_utf8(needle).unwrap(); // Input was str
+        let hashed = path_map.get(needle).unwrap(); // we checked this above
+        out.extend_from_slice(hashed.as_bytes());
+        true
+    });
+    Ok(out.into())
+}
+
+/// Extends the `PathFixup` modifier of all assets configured with
+/// `EntryBuilder::with_auto_path_fixup` by the *unhashed HTTP paths* of all
+/// hashed assets that occur in their raw content. `raw_content` is only
+/// called for those assets.
+#[cfg(all(feature = "modifiers", any(prod_mode, feature = "manifest")))]
+pub(crate) fn resolve_auto_path_fixups<'c>(
+    mut assets: Vec<(&str, &mut EntryConfig<'c>)>,
+    raw_content: impl Fn(&str) -> Bytes,
+) {
+    use aho_corasick::{AhoCorasick, MatchKind};
+
+    if assets.iter().all(|(_, config)| !config.auto_path_fixup) {
+        return;
+    }
+
+    let hashed = assets.iter()
+        .filter(|(_, config)| !matches!(config.path_hash, PathHash::None | PathHash::Stable))
+        .map(|(path, _)| path.to_string())
+        .collect::<Vec<_>>();
+    let searcher = AhoCorasick::builder()
+        .match_kind(MatchKind::LeftmostLongest)
+        .build(&hashed)
+        .unwrap();
+
+    for (path, config) in &mut assets {
+        let Modifier::PathFixup(deps) = &mut config.modifier else { continue };
+        if !config.auto_path_fixup {
+            continue;
+        }
+
+        let content = raw_content(path);
+        for found in searcher.find_iter(&*content) {
+            let found = &hashed[found.pattern()];
+            if found != path && !deps.iter().any(|dep| dep == found) {
+                deps.push(found.clone().into());
+            }
+        }
+    }
+}
+
+/// Fills the dependencies of all assets configured with
+/// `EntryBuilder::with_template` with the assets referred to by their raw
+/// content. `raw_content` is only called for those assets.
+#[cfg(all(feature = "modifiers", any(prod_mode, feature = "manifest")))]
+pub(crate) fn resolve_templates<'c>(
+    mut assets: Vec<(&str, &mut EntryConfig<'c>)>,
+    raw_content: impl Fn(&str) -> Bytes,
+) -> Result<(), BuildError> {
+    let known = assets.iter().map(|(path, _)| *path).collect::<ahash::HashSet<_>>();
+    for (path, config) in &mut assets {
+        let Modifier::Template(deps) = &mut config.modifier else { continue };
+        let err = |msg| BuildError::Template { http_path: path.to_string(), msg };
+
+        let content = raw_content(path);
+        let template = crate::fragments::Template::parse(&content, true).map_err(err)?;
+        for dep in template.dependencies() {
+            if !known.contains(dep) {
+                return Err(err(format!("asset '{}' does not exist", dep)));
+            }
+            if !deps.iter().any(|d| d == dep) {
+                deps.push(dep.to_owned().into());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Renders `content` as template (see `EntryBuilder::with_template`) for prod
+/// or dev mode, with `resolve_path` and `include` returning the HTTP path and
+/// content of an asset, respectively, or `None` if it does not exist.
+#[cfg(feature = "modifiers")]
+pub(crate) fn render_template(
+    content: &[u8],
+    prod: bool,
+    variables: &Variables,
+    resolve_path: impl Fn(&str) -> Option<String>,
+    include: impl Fn(&str) -> Option<Bytes>,
+) -> Result<Bytes, String> {
+    use crate::fragments::{Fragment, Template};
+
+    let out = Template::parse(content, prod)?.render(|fragment| match fragment {
+        Fragment::Path(path) => resolve_path(path)
+            .map(|resolved| resolved.into_bytes().into())
+            .ok_or_else(|| format!("asset '{}' does not exist", path)),
+        Fragment::Var(name) => variables.get(name)
+            .map(|value| value.as_bytes().into())
+            .ok_or_else(|| format!("variable '{}' is not set", name)),
+        Fragment::Include(path) => include(path)
+            .map(|content| content.to_vec().into())
+            .ok_or_else(|| format!("asset '{}' does not exist", path)),
+    })?;
+    Ok(out.into())
+}
+
+/// Returns the path relative to `dir` (with `/` as separator) and the full
+/// path of all files in `dir` (recursively) whose relative path matches
+/// `filter`, sorted by path. The file system is accessed on tokio's blocking
+/// pool.
+pub(crate) async fn walk_dir(
+    dir: &std::path::Path,
+    filter: impl Fn(&str) -> bool,
+) -> Result<Vec<(String, PathBuf)>, BuildError> {
+    let dir = dir.to_owned();
+    let mut files = tokio::task::spawn_blocking(move || walk_dir_blocking(&dir))
+        .await
+        .unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))?;
+    files.retain(|(relative, _)| filter(relative));
+    Ok(files)
+}
+
+/// Like [`walk_dir`], but returns all files and accesses the file system on
+/// the current thread.
+///
+/// Symlinks are followed, but every directory is only visited once, so
+/// symlinks pointing to a parent directory do not cause an endless loop.
+/// Dangling symlinks are skipped.
+pub(crate) fn walk_dir_blocking(dir: &std::path::Path) -> Result<Vec<(String, PathBuf)>, BuildError> {
+    let io_err = |path: &std::path::Path| {
+        let path = path.to_owned();
+        move |err| BuildError::Io { err, path }
+    };
+
+    let mut out = Vec::new();
+    let mut visited = std::collections::HashSet::new();
+    let mut stack = vec![dir.to_owned()];
+    while let Some(current) = stack.pop() {
+        if !visited.insert(current.canonicalize().map_err(io_err(&current))?) {
+            continue;
+        }
+
+        for entry in std::fs::read_dir(&current).map_err(io_err(&current))? {
+            let path = entry.map_err(io_err(&current))?.path();
+            let metadata = match std::fs::metadata(&path) {
+                Ok(metadata) => metadata,
+                Err(_) if std::fs::symlink_metadata(&path).is_ok_and(|m| m.is_symlink()) => continue,
+                Err(err) => return Err(io_err(&path)(err)),
+            };
+            if metadata.is_dir() {
+                stack.push(path);
+                continue;
+            }
+
+            let Some(relative) = path.strip_prefix(dir).ok().and_then(|p| p.to_str()) else {
+                continue;
+            };
+            out.push((relative.replace('\\', "/"), path));
+        }
+    }
+
+    out.sort();
+    Ok(out)
+}
+
+impl EntryBuilderKind<'_> {
+    pub(crate) fn glob_mount(&self) -> Option<GlobMount> {
+        match self {
+            EntryBuilderKind::Single { .. } => None,
+            EntryBuilderKind::Glob { http_prefix, glob, .. } => Some(GlobMount {
+                http_prefix: http_prefix.clone().into_owned(),
+                pattern: glob.suffix.clone(),
+            }),
+            EntryBuilderKind::Dir { http_prefix, pattern, .. } => Some(GlobMount {
+                http_prefix: http_prefix.clone().into_owned(),
+                pattern: pattern.clone(),
+            }),
+        }
+    }
+
+    /// The path passed to `add_*` when adding this entry.
+    fn mount_path(&self) -> &str {
+        match self {
+            EntryBuilderKind::Single { http_path, .. } => http_path,
+            EntryBuilderKind::Glob { http_prefix, .. } | EntryBuilderKind::Dir { http_prefix, .. } => {
+                http_prefix
+            }
+        }
+    }
+}
+
+impl GlobFile {
+    pub(crate) fn http_path(&self, http_prefix: &str) -> String {
+        join_glob_path(http_prefix, self.http_suffix())
+    }
+
+    /// The part of the *unhashed HTTP path* after the HTTP prefix.
+    pub(crate) fn http_suffix(&self) -> &str {
+        self.mapped_suffix.as_deref().unwrap_or(self.suffix)
+    }
+}
+
+/// Returns the *unhashed HTTP path* of a file matched by a glob entry.
+pub(crate) fn join_glob_path(http_prefix: &str, suffix: &str) -> String {
+    format!("{http_prefix}{suffix}")
+}
+
+/// Allowed range for `Builder::set_hash_length`. Below 6 bytes (48 bits),
+/// collisions between versions of a file become too likely.
+#[cfg(feature = "hash")]
+const HASH_LENGTH_RANGE: std::ops::RangeInclusive<usize> = 6..=32;
+
+/// Panics if `length` is not in `HASH_LENGTH_RANGE`.
+#[cfg(feature = "hash")]
+pub(crate) fn check_hash_length(length: usize) {
+    assert!(
+        HASH_LENGTH_RANGE.contains(&length),
+        "hash length must be between {} and {} bytes, but is {}",
+        HASH_LENGTH_RANGE.start(),
+        HASH_LENGTH_RANGE.end(),
+        length,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_params() {
+        let mut params = HashMap::default();
+        params.insert("version".into(), "1.2".into());
+        params.insert("a_b".into(), "x".into());
+        let resolve = |s: &'static str| {
+            let mut path = Cow::Borrowed(s);
+            resolve_path_params(&params, &mut path);
+            path
+        };
+
+        assert!(matches!(resolve("static/app.js"), Cow::Borrowed("static/app.js")));
+        assert!(matches!(resolve("{not a param}/{}"), Cow::Borrowed(_)));
+        assert_eq!(resolve("v{version}/static/"), "v1.2/static/");
+        assert_eq!(resolve("{version}{a_b}/{a_b}"), "1.2x/x");
+        assert_eq!(resolve("{ x }/{version}/{"), "{ x }/1.2/{");
+    }
+
+    #[test]
+    #[should_panic(expected = "path parameter 'nope' used in 'v{nope}/' is not set")]
+    fn missing_path_param() {
+        resolve_path_params(&HashMap::default(), &mut Cow::Borrowed("v{nope}/"));
     }
 }
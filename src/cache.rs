@@ -0,0 +1,84 @@
+//! Types related to HTTP caching.
+
+use std::{borrow::Cow, fmt, sync::Arc, time::Duration};
+
+
+/// A caching policy for an asset, which can be turned into a `Cache-Control`
+/// header value via [`CachePolicy::header_value`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CachePolicy {
+    /// `no-store`: the response must not be stored in any cache.
+    NoStore,
+
+    /// `no-cache`: the response may be stored, but must be revalidated before
+    /// each use.
+    NoCache,
+
+    /// `public, max-age=<secs>`: the response can be cached for the given
+    /// duration. Sub-second precision is ignored.
+    MaxAge(Duration),
+
+    /// `public, max-age=31536000, immutable`: the response can be cached
+    /// basically forever. Only use this for assets with hashed filenames!
+    Immutable,
+
+    /// An arbitrary `Cache-Control` header value.
+    Custom(Cow<'static, str>),
+}
+
+impl CachePolicy {
+    /// Returns the value for the `Cache-Control` header.
+    pub fn header_value(&self) -> Cow<'static, str> {
+        match self {
+            CachePolicy::NoStore => "no-store".into(),
+            CachePolicy::NoCache => "no-cache".into(),
+            CachePolicy::MaxAge(d) => format!("public, max-age={}", d.as_secs()).into(),
+            CachePolicy::Immutable => "public, max-age=31536000, immutable".into(),
+            CachePolicy::Custom(v) => v.clone(),
+        }
+    }
+}
+
+/// Information about an asset passed to the cache policy function registered
+/// via [`Builder::set_cache_policy`][crate::Builder::set_cache_policy].
+#[derive(Debug)]
+pub struct AssetMeta<'a> {
+    pub(crate) unhashed_http_path: &'a str,
+    pub(crate) http_path: &'a str,
+    pub(crate) is_filename_hashed: bool,
+}
+
+impl<'a> AssetMeta<'a> {
+    /// The *unhashed HTTP path* of the asset.
+    pub fn unhashed_http_path(&self) -> &'a str {
+        self.unhashed_http_path
+    }
+
+    /// The *hashed HTTP path* of the asset. In dev mode or for assets without
+    /// hashed filename, this is the same as [`Self::unhashed_http_path`].
+    pub fn http_path(&self) -> &'a str {
+        self.http_path
+    }
+
+    /// Whether the filename of this asset contains a hash, see
+    /// [`Asset::is_filename_hashed`][crate::Asset::is_filename_hashed].
+    pub fn is_filename_hashed(&self) -> bool {
+        self.is_filename_hashed
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct CachePolicyFn(pub(crate) Arc<dyn Send + Sync + Fn(&AssetMeta) -> CachePolicy>);
+
+impl CachePolicyFn {
+    pub(crate) fn header_value(&self, meta: &AssetMeta) -> Cow<'static, str> {
+        (self.0)(meta).header_value()
+    }
+}
+
+impl fmt::Debug for CachePolicyFn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CachePolicyFn")
+    }
+}
@@ -0,0 +1,120 @@
+//! Verifying asset sources against pinned checksums, see
+//! [`EntryBuilder::expect_sha256`][crate::EntryBuilder::expect_sha256].
+
+use std::fmt;
+
+
+/// A SHA-256 digest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Sha256([u8; 32]);
+
+impl Sha256 {
+    /// Parses a hex encoded digest (upper or lower case). Returns `None` if
+    /// `hex` is not exactly 64 hex digits.
+    #[cfg(feature = "hash")]
+    pub(crate) fn from_hex(hex: &str) -> Option<Self> {
+        // `from_str_radix` would also accept a leading `+`.
+        if hex.len() != 64 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        let mut out = [0; 32];
+        for (byte, chunk) in out.iter_mut().zip(hex.as_bytes().chunks(2)) {
+            let s = std::str::from_utf8(chunk).ok()?;
+            *byte = u8::from_str_radix(s, 16).ok()?;
+        }
+        Some(Self(out))
+    }
+
+    pub(crate) fn of(data: &[u8]) -> Self {
+        use sha2::Digest;
+
+        Self(sha2::Sha256::digest(data).into())
+    }
+}
+
+impl fmt::Display for Sha256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|b| write!(f, "{:02x}", b))
+    }
+}
+
+/// Checks `content` against the `expected` digest, if any. On mismatch, the
+/// actual digest is returned.
+#[cfg_attr(not(feature = "hash"), allow(unused_variables))]
+pub(crate) fn verify(expected: Option<Sha256>, content: &[u8]) -> Result<(), Sha256> {
+    match expected {
+        None => Ok(()),
+
+        #[cfg(feature = "hash")]
+        Some(expected) => {
+            let actual = Sha256::of(content);
+            if actual == expected { Ok(()) } else { Err(actual) }
+        }
+
+        // `EntryBuilder::expect_sha256` is only available with `hash`.
+        #[cfg(not(feature = "hash"))]
+        Some(_) => unreachable!(),
+    }
+}
+
+/// Returns a strong entity tag for `content`, including quotes.
+#[cfg(any(feature = "hash", feature = "http"))]
+#[cfg_attr(all(dev_mode, not(feature = "http")), allow(dead_code))]
+pub(crate) fn etag(content: &[u8]) -> String {
+    let hex = Sha256::of(content).to_string();
+    format!("\"{}\"", &hex[..32])
+}
+
+/// Returns whether the `If-None-Match` header value `header` matches `etag`,
+/// using the weak comparison as required by RFC 9110.
+#[cfg(any(feature = "hash", feature = "http"))]
+pub(crate) fn matches_etag(header: &str, etag: &str) -> bool {
+    header.split(',')
+        .map(|tag| tag.trim())
+        .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag)
+}
+
+
+#[cfg(all(test, feature = "hash"))]
+mod tests {
+    use super::*;
+
+    const HELLO: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    #[test]
+    fn hex_roundtrip() {
+        let digest = Sha256::from_hex(HELLO).unwrap();
+        assert_eq!(digest.to_string(), HELLO);
+        assert_eq!(Sha256::from_hex(&HELLO.to_uppercase()), Some(digest));
+        assert_eq!(Sha256::of(b"hello"), digest);
+    }
+
+    #[test]
+    fn invalid_hex() {
+        assert_eq!(Sha256::from_hex(""), None);
+        assert_eq!(Sha256::from_hex(&HELLO[1..]), None);
+        assert_eq!(Sha256::from_hex(&format!("{}0", HELLO)), None);
+        assert_eq!(Sha256::from_hex(&HELLO.replace('c', "g")), None);
+        assert_eq!(Sha256::from_hex(&HELLO.replacen("2c", "+c", 1)), None);
+    }
+
+    #[test]
+    fn verify_content() {
+        let digest = Sha256::from_hex(HELLO).unwrap();
+        assert_eq!(verify(None, b"anything"), Ok(()));
+        assert_eq!(verify(Some(digest), b"hello"), Ok(()));
+        assert_eq!(verify(Some(digest), b"hello\n"), Err(Sha256::of(b"hello\n")));
+    }
+
+    #[test]
+    fn if_none_match() {
+        let etag = etag(b"hello");
+        assert_eq!(etag, format!("\"{}\"", &HELLO[..32]));
+        assert!(matches_etag(&etag, &etag));
+        assert!(matches_etag("*", &etag));
+        assert!(matches_etag(&format!("\"x\", W/{etag}"), &etag));
+        assert!(!matches_etag("\"x\"", &etag));
+        assert!(!matches_etag("", &etag));
+    }
+}
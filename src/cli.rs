@@ -0,0 +1,175 @@
+//! Inspecting the asset pipeline from the command line, e.g. to diff the
+//! resolved paths, sizes and dependencies between branches in CI without
+//! starting the server.
+//!
+//! Implement [`AssetPipeline`] for a type that configures a [`Builder`]
+//! exactly like your application does and call [`run`] from a small binary:
+//!
+//! ```no_run
+//! use reinda::{cli::AssetPipeline, Builder};
+//!
+//! const EMBEDS: reinda::Embeds = reinda::embed! {
+//!     base_path: "examples/assets",
+//!     files: ["index.html", "style.css"],
+//! };
+//!
+//! struct App;
+//!
+//! impl AssetPipeline for App {
+//!     fn configure<'a>(&'a self, builder: &mut Builder<'a>) {
+//!         builder.add_embedded("index.html", &EMBEDS["index.html"])
+//!             .with_path_fixup(["style.css"]);
+//!         builder.add_embedded("style.css", &EMBEDS["style.css"]).with_hash();
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() -> std::process::ExitCode {
+//!     reinda::cli::run(&App).await
+//! }
+//! ```
+//!
+//! Module is only available if the crate feature `cli` is enabled.
+
+use std::{io, process::ExitCode};
+
+use crate::{Assets, Builder, EntryBuilder};
+
+
+/// Adapter to describe the assets of an application, see [the module
+/// docs][self].
+pub trait AssetPipeline {
+    /// Adds all assets to `builder`, the same way the application does.
+    fn configure<'a>(&'a self, builder: &mut Builder<'a>);
+}
+
+/// A part of the output of [`write_report`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Section {
+    /// The path manifest as JSON, see [`Assets::dry_run_prod`]. Contains the
+    /// *hashed HTTP paths* of prod mode, even in dev mode.
+    Manifest,
+
+    /// The size in bytes of all assets listed in the manifest, by *unhashed
+    /// HTTP path*. In dev mode, paths are not hashed, so sizes of assets with
+    /// path fixups can differ slightly from prod mode.
+    Sizes,
+
+    /// The dependencies of all entries, declared via
+    /// [`EntryBuilder::with_modifier`], [`EntryBuilder::with_path_fixup`] and
+    /// [`EntryBuilder::after`]. Path parameters are not resolved.
+    Deps,
+}
+
+impl Section {
+    /// All sections in the order they are printed by [`run`] by default.
+    pub const ALL: [Section; 3] = [Section::Manifest, Section::Sizes, Section::Deps];
+
+    fn name(self) -> &'static str {
+        match self {
+            Section::Manifest => "manifest",
+            Section::Sizes => "sizes",
+            Section::Deps => "deps",
+        }
+    }
+}
+
+/// Parses the command line arguments, prints the requested sections to
+/// stdout and returns the exit code. Arguments are section names
+/// (`manifest`, `sizes`, `deps`); without any, all sections are printed.
+/// Errors are printed to stderr.
+pub async fn run(pipeline: &impl AssetPipeline) -> ExitCode {
+    let mut sections = Vec::new();
+    for arg in std::env::args().skip(1) {
+        match Section::ALL.iter().find(|s| s.name() == arg) {
+            Some(section) => sections.push(*section),
+            None => {
+                eprintln!("unknown section '{}', expected 'manifest', 'sizes' or 'deps'", arg);
+                return ExitCode::from(2);
+            }
+        }
+    }
+    if sections.is_empty() {
+        sections.extend(Section::ALL);
+    }
+
+    match write_report(pipeline, &sections, io::stdout().lock()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Builds the assets of `pipeline` and writes the given sections to `out`,
+/// each introduced by a `# <name>` line. All lists are sorted, so that the
+/// output is stable and can be diffed.
+pub async fn write_report(
+    pipeline: &impl AssetPipeline,
+    sections: &[Section],
+    mut out: impl io::Write,
+) -> io::Result<()> {
+    let mut builder = Assets::builder();
+    pipeline.configure(&mut builder);
+    let deps = dependencies(&builder);
+    let assets = builder.build().await.map_err(io::Error::other)?;
+    let manifest = assets.dry_run_prod().await.map_err(io::Error::other)?;
+
+    for section in sections {
+        writeln!(out, "# {}", section.name())?;
+        match section {
+            Section::Manifest => {
+                manifest.write_json(&mut out)?;
+                writeln!(out)?;
+            }
+            Section::Sizes => {
+                for path in manifest.assets.keys() {
+                    if let Some(asset) = assets.0.get_unhashed(path) {
+                        writeln!(out, "{:>10}  {}", asset.content().await?.len(), path)?;
+                    }
+                }
+            }
+            Section::Deps => {
+                for (path, deps) in &deps {
+                    writeln!(out, "{} -> {}", path, deps.join(", "))?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the dependencies of all entries that have any, sorted by path.
+fn dependencies(builder: &Builder<'_>) -> Vec<(String, Vec<String>)> {
+    let mut out = Vec::new();
+    for entry in &builder.assets {
+        let mut deps = entry.config.modifier.dependencies()
+            .into_iter()
+            .flatten()
+            .chain(&entry.config.after)
+            .map(|dep| dep.to_string())
+            .collect::<Vec<_>>();
+        if deps.is_empty() {
+            continue;
+        }
+        deps.sort();
+        deps.dedup();
+
+        for path in entry_paths(entry) {
+            out.push((path, deps.clone()));
+        }
+    }
+    out.sort();
+    out
+}
+
+/// All *unhashed HTTP paths* of `entry`, or the HTTP prefix for directories.
+fn entry_paths(entry: &EntryBuilder<'_>) -> Vec<String> {
+    match entry.glob_mount() {
+        Some(mount) if entry.http_paths().is_empty() => vec![mount.http_prefix().to_owned()],
+        _ => entry.http_paths().into_iter().map(Into::into).collect(),
+    }
+}
@@ -0,0 +1,100 @@
+//! Evaluating conditional requests, see [`Conditional`].
+
+use crate::{checksum, util::format_http_date, Asset};
+
+
+/// The result of evaluating the conditional request headers `If-None-Match`
+/// and `If-Modified-Since` for an asset, deciding between `200 OK` and
+/// `304 Not Modified`. Used by all framework integrations of this crate and
+/// useful for hand-written handlers.
+///
+/// ```no_run
+/// # async fn foo(asset: reinda::Asset, req_headers: Vec<(String, String)>) {
+/// use reinda::Conditional;
+///
+/// let content = asset.content().await.unwrap();
+/// let headers = req_headers.iter().map(|(k, v)| (k.as_str(), v.as_str()));
+/// let conditional = Conditional::evaluate(&asset, &content, headers);
+/// for (name, value) in conditional.headers() {
+///     // Add header to response
+/// }
+/// if conditional.is_not_modified() {
+///     // Respond with status 304 and an empty body
+/// }
+/// # }
+/// ```
+///
+/// Type is only available if the crate feature `hash` or `http` is enabled.
+#[derive(Debug, Clone)]
+pub struct Conditional {
+    not_modified: bool,
+    etag: String,
+    last_modified: Option<String>,
+    cache_control: Option<String>,
+}
+
+impl Conditional {
+    /// Evaluates the request headers `req_headers` (names are compared
+    /// case-insensitively) for `asset` with the loaded `content`. The content
+    /// is only used to compute the `ETag` in dev mode, where
+    /// [`Asset::etag`] returns `None`.
+    ///
+    /// The response is `304 Not Modified` if an `If-None-Match` header
+    /// matches the `ETag` or, if there is no `If-None-Match` header, the
+    /// asset was not modified since the time given by `If-Modified-Since`
+    /// (see [`Asset::is_modified_since`]), as required by RFC 9110.
+    pub fn evaluate<'h>(
+        asset: &Asset,
+        content: &[u8],
+        req_headers: impl IntoIterator<Item = (&'h str, &'h str)>,
+    ) -> Self {
+        let etag = asset.etag().map_or_else(|| checksum::etag(content), str::to_owned);
+
+        let mut if_none_match = None;
+        let mut if_modified_since = None;
+        for (name, value) in req_headers {
+            if name.eq_ignore_ascii_case("if-none-match") {
+                let matches = checksum::matches_etag(value, &etag);
+                if_none_match = Some(if_none_match.unwrap_or(false) || matches);
+            } else if name.eq_ignore_ascii_case("if-modified-since") && if_modified_since.is_none() {
+                if_modified_since = Some(value);
+            }
+        }
+        let not_modified = match if_none_match {
+            Some(matches) => matches,
+            None => if_modified_since.is_some_and(|v| !asset.is_modified_since(v)),
+        };
+
+        Self {
+            not_modified,
+            etag,
+            last_modified: asset.last_modified().map(format_http_date),
+            cache_control: asset.cache_control().map(str::to_owned),
+        }
+    }
+
+    /// Returns `true` if a `304 Not Modified` response without body should be
+    /// sent.
+    pub fn is_not_modified(&self) -> bool {
+        self.not_modified
+    }
+
+    /// Returns the status code of the response, i.e. 304 or 200.
+    pub fn status(&self) -> u16 {
+        if self.not_modified { 304 } else { 200 }
+    }
+
+    /// Returns the headers that have to be included in both, `200` and `304`
+    /// responses: `ETag` and, if available, `Last-Modified` and
+    /// `Cache-Control`. Header names are lowercase.
+    pub fn headers(&self) -> impl '_ + Iterator<Item = (&'static str, &str)> {
+        std::iter::once(("etag", &*self.etag))
+            .chain(self.last_modified.as_deref().map(|v| ("last-modified", v)))
+            .chain(self.cache_control.as_deref().map(|v| ("cache-control", v)))
+    }
+
+    /// Returns the entity tag sent in the `ETag` header, including quotes.
+    pub fn etag(&self) -> &str {
+        &self.etag
+    }
+}
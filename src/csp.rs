@@ -0,0 +1,94 @@
+//! Content Security Policy hashes of inline scripts and styles, see
+//! [`Asset::csp_hashes`][crate::Asset::csp_hashes].
+
+use crate::sri::attr_value;
+
+
+/// Returns the CSP hash sources (`'sha256-...'`, including quotes) of all
+/// non-empty inline `<script>` and `<style>` blocks in `html`, without
+/// duplicates and in order of appearance. Scripts with `src` attribute are
+/// skipped.
+pub(crate) fn inline_hashes(html: &[u8]) -> Vec<String> {
+    let mut hashes = Vec::new();
+    let mut pos = 0;
+    while let Some((start, name)) = find_block(html, pos) {
+        let Some(open_end) = html[start..].iter().position(|&b| b == b'>').map(|i| start + i + 1) else {
+            break;
+        };
+        let closing = format!("</{}", name);
+        let Some(close) = find_ci(html, open_end, closing.as_bytes()) else {
+            break;
+        };
+        pos = close + closing.len();
+
+        let has_src = std::str::from_utf8(&html[start..open_end]).ok()
+            .is_some_and(|tag| attr_value(tag, "src").is_some());
+        let content = &html[open_end..close];
+        if (name == "script" && has_src) || content.is_empty() {
+            continue;
+        }
+
+        let hash = sha256(content);
+        if !hashes.contains(&hash) {
+            hashes.push(hash);
+        }
+    }
+    hashes
+}
+
+/// Returns the CSP hash source of `content`, e.g. `'sha256-...'`.
+fn sha256(content: &[u8]) -> String {
+    use base64::Engine;
+    use sha2::Digest;
+
+    let digest = sha2::Sha256::digest(content);
+    format!("'sha256-{}'", base64::engine::general_purpose::STANDARD.encode(digest))
+}
+
+/// Returns the index and name of the next `<script` or `<style` tag at or
+/// after `pos`.
+fn find_block(html: &[u8], pos: usize) -> Option<(usize, &'static str)> {
+    (pos..html.len()).find_map(|i| {
+        ["script", "style"].iter().copied()
+            .find(|name| {
+                let tag = &html[i..];
+                tag.len() > name.len() + 1
+                    && tag[0] == b'<'
+                    && tag[1..][..name.len()].eq_ignore_ascii_case(name.as_bytes())
+                    && (tag[name.len() + 1].is_ascii_whitespace() || tag[name.len() + 1] == b'>')
+            })
+            .map(|name| (i, name))
+    })
+}
+
+/// Returns the index of the next case-insensitive occurrence of `needle` at or
+/// after `pos`.
+fn find_ci(haystack: &[u8], pos: usize, needle: &[u8]) -> Option<usize> {
+    haystack.get(pos..)?
+        .windows(needle.len())
+        .position(|w| w.eq_ignore_ascii_case(needle))
+        .map(|i| pos + i)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes() {
+        let html = br#"<html><head>
+            <SCRIPT>alert('Hello, world.');</SCRIPT>
+            <script src="/app.js"></script>
+            <style media="print">body { color: black }</style>
+            <script>alert('Hello, world.');</script>
+            <scripts>nope</scripts>
+            <style></style>
+        </head></html>"#;
+        assert_eq!(inline_hashes(html), [
+            "'sha256-qznLcsROx4GACP2dm0UCKCzCG+HiZ1guq6ZZDob/Tng='",
+            sha256(b"body { color: black }").as_str(),
+        ]);
+        assert!(inline_hashes(b"<script>unterminated").is_empty());
+    }
+}
@@ -1,6 +1,8 @@
 use std::{collections::VecDeque, mem};
 use ahash::{HashMap, HashMapExt, HashSet};
 
+use crate::builder::EntryConfig;
+
 
 #[derive(Debug)]
 pub(crate) struct DepGraph<'a>(HashMap<&'a str, NodeData<'a>>);
@@ -20,6 +22,41 @@ impl<'a> DepGraph<'a> {
         Self(HashMap::new())
     }
 
+    /// Builds the graph of all given assets (*unhashed HTTP path* and
+    /// config), with edges for modifier dependencies and `after`.
+    ///
+    /// **Panics** if an asset references a dependency or variant that does
+    /// not exist.
+    pub(crate) fn of_assets<'c: 'a>(
+        assets: impl Clone + Iterator<Item = (&'a str, &'a EntryConfig<'c>)>,
+    ) -> Self {
+        let known = assets.clone().map(|(path, _)| path).collect::<HashSet<_>>();
+        let mut graph = Self::new();
+        for (unhashed_http_path, config) in assets {
+            graph.add_asset(unhashed_http_path);
+            let deps = config.modifier.dependencies().unwrap_or_default();
+            let locale_variants = config.locale_variants.iter().map(|(_, path)| path);
+            for variant in config.variants.iter().chain(locale_variants) {
+                if !known.contains(variant.as_ref()) {
+                    panic!(
+                        "Asset '{}' specified variant '{}' but that asset does not exist",
+                        unhashed_http_path, variant,
+                    );
+                }
+            }
+            for dep in deps.iter().chain(&config.after) {
+                if !known.contains(dep.as_ref()) {
+                    panic!(
+                        "Asset '{}' specified dependency '{}' but that asset does not exist",
+                        unhashed_http_path, dep,
+                    );
+                }
+                graph.add_dependency(unhashed_http_path, dep);
+            }
+        }
+        graph
+    }
+
     /// Explicitly adds an asset to the graph. This makes sure this asset is
     /// included in the topological sort. It is as if it would register an
     /// external dependency on `id`. Assets are automatically created when you
@@ -0,0 +1,63 @@
+//! Non-fatal problems detected while preparing assets.
+
+use std::{fmt, sync::Arc};
+
+
+/// A non-fatal problem reinda noticed. By default, warnings are printed to
+/// stderr; use [`Builder::on_warning`][crate::Builder::on_warning] to handle
+/// them differently.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Warning {
+    /// A path fixup was not performed in a binary asset because the *hashed
+    /// HTTP path* has a different length than the *unhashed* one, which
+    /// would likely corrupt the file.
+    BinaryFixupSkipped {
+        /// *Unhashed HTTP path* of the asset in which the replacement was
+        /// skipped.
+        asset: String,
+
+        /// The *unhashed HTTP path* that was not replaced.
+        path: String,
+    },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::BinaryFixupSkipped { asset, path } => write!(
+                f,
+                "skipped path fixup of '{}' in binary asset '{}' as the hashed path has \
+                    a different length",
+                path,
+                asset,
+            ),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct WarningHandler(Arc<dyn Send + Sync + Fn(Warning)>);
+
+impl WarningHandler {
+    pub(crate) fn new(f: impl 'static + Send + Sync + Fn(Warning)) -> Self {
+        Self(Arc::new(f))
+    }
+
+    #[cfg_attr(any(dev_mode, not(feature = "modifiers")), allow(dead_code))]
+    pub(crate) fn emit(&self, warning: Warning) {
+        (self.0)(warning)
+    }
+}
+
+impl Default for WarningHandler {
+    fn default() -> Self {
+        Self::new(|warning| eprintln!("[reinda] warning: {}", warning))
+    }
+}
+
+impl fmt::Debug for WarningHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("WarningHandler")
+    }
+}
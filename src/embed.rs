@@ -2,6 +2,7 @@
 
 use std::ops;
 
+#[cfg(feature = "full")]
 use crate::DataSource;
 
 
@@ -61,10 +62,28 @@ pub struct EmbeddedFile {
     #[doc(hidden)]
     pub content: &'static [u8],
 
-    /// Whether the `content` field is compressed.
+    /// How the `content` field is compressed.
     #[cfg(prod_mode)]
     #[doc(hidden)]
-    pub compressed: bool,
+    pub compression: EmbeddedCompression,
+
+    /// Modification time of the file at build time in seconds since the UNIX
+    /// epoch.
+    #[cfg(prod_mode)]
+    #[doc(hidden)]
+    pub modified: Option<u64>,
+}
+
+/// Compression of [`EmbeddedFile::content`], emitted by `embed!`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[doc(hidden)]
+pub enum EmbeddedCompression {
+    None,
+    Brotli,
+    /// Brotli with a custom dictionary, shared by all files of one `embed!`
+    /// invocation. The dictionary itself is stored compressed with Brotli.
+    BrotliDictionary(&'static [u8]),
+    Zstd,
 }
 
 impl Embeds {
@@ -169,25 +188,89 @@ impl EmbeddedFile {
         self.path
     }
 
+    /// Returns the modification time of the file. In prod mode, that's the
+    /// time captured by `embed!` at compile time (clamped to
+    /// `SOURCE_DATE_EPOCH` if that env variable is set), with a precision of
+    /// one second. In dev mode, the file system is queried. `None` if the time
+    /// is not available.
+    pub fn modified(&self) -> Option<std::time::SystemTime> {
+        #[cfg(prod_mode)]
+        {
+            self.modified.map(|secs| std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs))
+        }
+
+        #[cfg(dev_mode)]
+        {
+            std::fs::metadata(self.full_path).and_then(|m| m.modified()).ok()
+        }
+    }
+
     /// Returns the contents of the embedded file. This method might decompress
     /// data, so try calling it only once for each file to avoid doing
     /// duplicate work.
     #[cfg(prod_mode)]
     pub fn content(&self) -> std::borrow::Cow<'static, [u8]> {
-        #[cfg(feature = "compress")]
-        if self.compressed {
-            let mut decompressed = Vec::new();
-            brotli::BrotliDecompress(&mut &*self.content, &mut decompressed)
-                .expect("unexpected error while decompressing Brotli");
-            decompressed.into()
-        } else {
-            self.content.into()
+        match self.compression {
+            EmbeddedCompression::None => self.content.into(),
+
+            #[cfg(feature = "compress")]
+            EmbeddedCompression::Brotli => {
+                let mut decompressed = Vec::new();
+                brotli::BrotliDecompress(&mut &*self.content, &mut decompressed)
+                    .expect("unexpected error while decompressing Brotli");
+                decompressed.into()
+            }
+
+            #[cfg(feature = "compress")]
+            EmbeddedCompression::BrotliDictionary(dict) => {
+                use std::io::Read;
+
+                let dict = brotli_dictionary(dict).to_vec();
+                let mut decompressed = Vec::new();
+                brotli::Decompressor::new_with_custom_dict(self.content, 4096, dict.into())
+                    .read_to_end(&mut decompressed)
+                    .expect("unexpected error while decompressing Brotli");
+                decompressed.into()
+            }
+
+            #[cfg(feature = "compress-zstd")]
+            EmbeddedCompression::Zstd => zstd::stream::decode_all(self.content)
+                .expect("unexpected error while decompressing zstd")
+                .into(),
+
+            // `embed!` only compresses if the corresponding feature is enabled.
+            #[allow(unreachable_patterns)]
+            _ => unreachable!(),
+        }
+    }
+
+    /// Returns the contents of the embedded file as string, like
+    /// `include_str!`, or an error if it is not valid UTF-8. Like
+    /// [`Self::content`], this might decompress data. Uncompressed contents
+    /// are borrowed from the executable.
+    #[cfg(prod_mode)]
+    pub fn as_str(&self) -> Result<std::borrow::Cow<'static, str>, std::str::Utf8Error> {
+        match self.content() {
+            std::borrow::Cow::Borrowed(slice) => std::str::from_utf8(slice).map(Into::into),
+            std::borrow::Cow::Owned(vec) => String::from_utf8(vec)
+                .map(Into::into)
+                .map_err(|e| e.utf8_error()),
         }
+    }
 
-        #[cfg(not(feature = "compress"))]
-        { self.content.into() }
+    /// Returns the encoded forms of the content stored in the executable.
+    #[cfg(all(feature = "full", prod_mode))]
+    fn encoded(&self) -> Vec<(crate::Encoding, bytes::Bytes)> {
+        let encoding = match self.compression {
+            // Clients don't have the dictionary.
+            EmbeddedCompression::None | EmbeddedCompression::BrotliDictionary(_) => return vec![],
+            EmbeddedCompression::Brotli => crate::Encoding::Brotli,
+            EmbeddedCompression::Zstd => crate::Encoding::Zstd,
+        };
+        vec![(encoding, bytes::Bytes::from_static(self.content))]
     }
 
+    #[cfg(feature = "full")]
     pub(crate) fn data_source(&self) -> DataSource {
         #[cfg(dev_mode)]
         { DataSource::File(self.full_path.into()) }
@@ -198,7 +281,32 @@ impl EmbeddedFile {
                 std::borrow::Cow::Borrowed(slice) => slice.into(),
                 std::borrow::Cow::Owned(vec) => vec.into(),
             };
-            DataSource::Loaded(bytes)
+            DataSource::Embedded {
+                path: self.path,
+                content: bytes,
+                encoded: self.encoded(),
+                modified: self.modified(),
+            }
         }
     }
 }
+
+/// Returns the decompressed Brotli dictionary. All files of one `embed!` share
+/// the same dictionary, so it is only decompressed once and cached, keyed by
+/// its address.
+#[cfg(all(prod_mode, feature = "compress"))]
+fn brotli_dictionary(dict: &'static [u8]) -> std::sync::Arc<[u8]> {
+    use std::{collections::HashMap, sync::{Arc, Mutex, OnceLock}};
+
+    static CACHE: OnceLock<Mutex<HashMap<usize, Arc<[u8]>>>> = OnceLock::new();
+
+    let mut cache = CACHE.get_or_init(Default::default).lock().unwrap();
+    cache.entry(dict.as_ptr() as usize)
+        .or_insert_with(|| {
+            let mut decompressed = Vec::new();
+            brotli::BrotliDecompress(&mut &*dict, &mut decompressed)
+                .expect("unexpected error while decompressing Brotli dictionary");
+            decompressed.into()
+        })
+        .clone()
+}
@@ -0,0 +1,242 @@
+//! Content encodings (compression formats), see [`Asset::content_encoded`][crate::Asset::content_encoded].
+
+#[cfg(all(any(feature = "compress", feature = "gzip"), prod_mode))]
+use std::sync::{Arc, Mutex};
+
+#[cfg(all(any(feature = "compress", feature = "gzip"), prod_mode))]
+use ahash::HashMap;
+#[cfg(all(any(feature = "compress", feature = "gzip"), prod_mode))]
+use bytes::Bytes;
+#[cfg(all(any(feature = "compress", feature = "gzip"), prod_mode))]
+use tokio::sync::OnceCell;
+
+use crate::negotiate;
+
+
+/// A content encoding in which assets can be stored, i.e. a value of the
+/// `Content-Encoding` header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Encoding {
+    /// Brotli, `br`.
+    Brotli,
+
+    /// Zstandard, `zstd`. Only stored for files embedded with zstd
+    /// compression (feature `compress-zstd`).
+    Zstd,
+
+    /// Gzip, `gzip`. Only stored if enabled via
+    /// [`Builder::enable_gzip`][crate::Builder::enable_gzip].
+    Gzip,
+}
+
+impl Encoding {
+    /// All encodings, in order of preference when serving.
+    pub(crate) const ALL: &'static [Encoding] = &[Encoding::Brotli, Encoding::Zstd, Encoding::Gzip];
+
+    /// Returns the value for the `Content-Encoding` header, e.g. `br`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Encoding::Brotli => "br",
+            Encoding::Zstd => "zstd",
+            Encoding::Gzip => "gzip",
+        }
+    }
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// A set of content encodings accepted by a client, usually parsed from the
+/// `Accept-Encoding` header via [`AcceptedEncodings::from_header`]. The
+/// default value accepts no encodings, i.e. only uncompressed content.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AcceptedEncodings(u8);
+
+impl AcceptedEncodings {
+    /// Parses an `Accept-Encoding` header value like `gzip, br;q=0.8`.
+    /// Encodings with a quality value of 0 are not accepted, `*` accepts all
+    /// encodings not listed explicitly.
+    ///
+    /// ```
+    /// use reinda::{AcceptedEncodings, Encoding};
+    ///
+    /// assert!(AcceptedEncodings::from_header("gzip, br").accepts(Encoding::Brotli));
+    /// assert!(!AcceptedEncodings::from_header("*, br;q=0").accepts(Encoding::Brotli));
+    /// ```
+    pub fn from_header(accept_encoding: &str) -> Self {
+        Encoding::ALL.iter()
+            .filter(|e| negotiate::coding_quality(accept_encoding, e.as_str()) > 0.0)
+            .fold(Self::default(), |acc, e| acc.with(*e))
+    }
+
+    /// Returns a set containing all encodings.
+    pub fn all() -> Self {
+        Encoding::ALL.iter().fold(Self::default(), |acc, e| acc.with(*e))
+    }
+
+    /// Returns this set with `encoding` added.
+    pub fn with(self, encoding: Encoding) -> Self {
+        Self(self.0 | encoding.bit())
+    }
+
+    /// Returns whether `encoding` is in this set.
+    pub fn accepts(&self, encoding: Encoding) -> bool {
+        self.0 & encoding.bit() != 0
+    }
+}
+
+/// Compression ratio up to which an encoded form is worth storing, like the
+/// default `compression_threshold` of `embed!`.
+#[cfg(all(any(feature = "compress", feature = "gzip"), prod_mode))]
+const THRESHOLD: f32 = 0.85;
+
+/// Returns `compressed` if it is sufficiently smaller than `original`.
+#[cfg(all(any(feature = "compress", feature = "gzip"), prod_mode))]
+fn worthwhile(original: &[u8], compressed: Vec<u8>) -> Option<Bytes> {
+    let ratio = compressed.len() as f32 / original.len() as f32;
+    (ratio < THRESHOLD).then(|| compressed.into())
+}
+
+/// Compresses `content` with Brotli, returning `None` if it does not
+/// compress well.
+#[cfg(all(feature = "compress", prod_mode))]
+pub(crate) fn brotli(content: &[u8]) -> Option<Bytes> {
+    let mut compressed = Vec::new();
+    brotli::BrotliCompress(&mut &*content, &mut compressed, &brotli::enc::BrotliEncoderParams {
+        quality: 9,
+        ..Default::default()
+    }).expect("writing into `Vec` cannot fail");
+    worthwhile(content, compressed)
+}
+
+/// Compresses `content` with gzip, returning `None` if it does not compress
+/// well.
+#[cfg(all(feature = "gzip", prod_mode))]
+pub(crate) fn gzip(content: &[u8]) -> Option<Bytes> {
+    use std::io::Write;
+    use flate2::{write::GzEncoder, Compression};
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(content).expect("writing into `Vec` cannot fail");
+    let compressed = encoder.finish().expect("writing into `Vec` cannot fail");
+    worthwhile(content, compressed)
+}
+
+/// Encodings that can be produced at runtime, in order of preference.
+#[cfg(all(any(feature = "compress", feature = "gzip"), prod_mode))]
+const ON_DEMAND: &[Encoding] = &[
+    #[cfg(feature = "compress")]
+    Encoding::Brotli,
+    #[cfg(feature = "gzip")]
+    Encoding::Gzip,
+];
+
+/// Compresses `content` with `encoding`, which has to be in `ON_DEMAND`.
+#[cfg(all(any(feature = "compress", feature = "gzip"), prod_mode))]
+fn compress(encoding: Encoding, content: &[u8]) -> Option<Bytes> {
+    match encoding {
+        #[cfg(feature = "compress")]
+        Encoding::Brotli => brotli(content),
+        #[cfg(feature = "gzip")]
+        Encoding::Gzip => gzip(content),
+        _ => unreachable!(),
+    }
+}
+
+/// Cache for assets compressed on demand, see
+/// [`Builder::compress_on_demand`][crate::Builder::compress_on_demand]. Evicts
+/// the least recently used entries to stay within the memory budget.
+#[cfg(all(any(feature = "compress", feature = "gzip"), prod_mode))]
+#[derive(Debug)]
+pub(crate) struct CompressionCache {
+    budget: usize,
+    inner: Mutex<CacheInner>,
+}
+
+#[cfg(all(any(feature = "compress", feature = "gzip"), prod_mode))]
+#[derive(Debug, Default)]
+struct CacheInner {
+    /// Keyed by asset ID and encoding. `None` if the asset does not compress
+    /// well, so that we do not try again.
+    entries: HashMap<(usize, Encoding), CacheEntry>,
+
+    /// Compressions currently running, so that concurrent requests for the
+    /// same key wait for them instead of compressing again.
+    in_flight: HashMap<(usize, Encoding), Arc<OnceCell<Option<Bytes>>>>,
+    size: usize,
+    clock: u64,
+}
+
+#[cfg(all(any(feature = "compress", feature = "gzip"), prod_mode))]
+#[derive(Debug)]
+struct CacheEntry {
+    content: Option<Bytes>,
+    last_used: u64,
+}
+
+#[cfg(all(any(feature = "compress", feature = "gzip"), prod_mode))]
+impl CompressionCache {
+    pub(crate) fn new(budget: usize) -> Self {
+        Self { budget, inner: Default::default() }
+    }
+
+    /// Returns `content` of the asset with ID `asset` compressed with the most
+    /// preferred of the `accepted` encodings that can be produced at runtime.
+    /// The result is cached. Compression runs on tokio's blocking thread
+    /// pool and only once for concurrent calls with the same asset and
+    /// encoding.
+    pub(crate) async fn get(
+        &self,
+        asset: usize,
+        content: &Bytes,
+        accepted: AcceptedEncodings,
+    ) -> Option<(Encoding, Bytes)> {
+        let encoding = *ON_DEMAND.iter().find(|e| accepted.accepts(**e))?;
+        let key = (asset, encoding);
+        let cell = {
+            let mut inner = self.inner.lock().unwrap();
+            inner.clock += 1;
+            let now = inner.clock;
+            if let Some(entry) = inner.entries.get_mut(&key) {
+                entry.last_used = now;
+                return entry.content.clone().map(|c| (encoding, c));
+            }
+            inner.in_flight.entry(key).or_default().clone()
+        };
+
+        // Compress without holding the lock, as that can take a while.
+        let compressed = cell.get_or_init(|| {
+            let content = content.clone();
+            async move {
+                tokio::task::spawn_blocking(move || compress(encoding, &content))
+                    .await
+                    .unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))
+            }
+        }).await.clone();
+
+        let mut inner = self.inner.lock().unwrap();
+        // Only the first caller finishing stores the result.
+        if !inner.in_flight.get(&key).is_some_and(|c| Arc::ptr_eq(c, &cell)) {
+            return compressed.map(|c| (encoding, c));
+        }
+        inner.in_flight.remove(&key);
+        let len = compressed.as_ref().map_or(0, |c| c.len());
+        if len <= self.budget {
+            if let Some(old) = inner.entries.remove(&key) {
+                inner.size -= old.content.map_or(0, |c| c.len());
+            }
+            while inner.size + len > self.budget {
+                let (&lru, _) = inner.entries.iter().min_by_key(|(_, e)| e.last_used).unwrap();
+                let evicted = inner.entries.remove(&lru).unwrap();
+                inner.size -= evicted.content.map_or(0, |c| c.len());
+            }
+            let last_used = inner.clock;
+            inner.size += len;
+            inner.entries.insert(key, CacheEntry { content: compressed.clone(), last_used });
+        }
+
+        compressed.map(|c| (encoding, c))
+    }
+}
@@ -0,0 +1,259 @@
+//! Templates with `{{: kind:argument :}}` fragments, see
+//! [`EntryBuilder::with_template`][crate::EntryBuilder::with_template].
+
+use std::borrow::Cow;
+
+
+const OPEN: &[u8] = b"{{:";
+const CLOSE: &[u8] = b":}}";
+
+/// A parsed template, borrowing from the source.
+#[derive(Debug)]
+pub(crate) struct Template<'a> {
+    parts: Vec<Part<'a>>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Part<'a> {
+    Raw(&'a [u8]),
+    Fragment(Fragment<'a>),
+}
+
+/// A `{{: ... :}}` fragment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Fragment<'a> {
+    /// `{{: path:style.css :}}`, replaced by the *hashed HTTP path* of the
+    /// given asset.
+    Path(&'a str),
+
+    /// `{{: var:name :}}`, replaced by the value of the variable.
+    Var(&'a str),
+
+    /// `{{: include:header.html :}}`, replaced by the content of the given
+    /// asset.
+    Include(&'a str),
+}
+
+/// Any `{{: ... :}}`, including the ones controlling conditional sections.
+enum Token<'a> {
+    Fragment(Fragment<'a>),
+    /// `{{: if dev :}}` or `{{: if prod :}}`, with the condition evaluated.
+    If(bool),
+    Else,
+    EndIf,
+}
+
+/// An `{{: if ... :}}` that was not yet closed.
+struct Conditional {
+    offset: usize,
+    condition: bool,
+    in_else: bool,
+}
+
+impl Conditional {
+    fn is_active(&self) -> bool {
+        self.condition != self.in_else
+    }
+}
+
+impl<'a> Template<'a> {
+    /// Parses `src`, only keeping the sections of conditionals that are
+    /// active in prod or dev mode, depending on `prod`.
+    pub(crate) fn parse(src: &'a [u8], prod: bool) -> Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut conditionals = Vec::<Conditional>::new();
+        let mut rest = src;
+        loop {
+            let active = conditionals.iter().all(Conditional::is_active);
+            let Some(start) = find(rest, OPEN) else { break };
+            let offset = src.len() - rest.len() + start;
+            let after_open = &rest[start + OPEN.len()..];
+            let end = find(after_open, CLOSE)
+                .ok_or_else(|| format!("unterminated fragment at byte {}", offset))?;
+            let inner = std::str::from_utf8(&after_open[..end])
+                .map_err(|_| format!("fragment at byte {} is not valid UTF-8", offset))?;
+            let token = parse_token(inner.trim(), prod)
+                .map_err(|e| format!("invalid fragment at byte {}: {}", offset, e))?;
+
+            if start > 0 && active {
+                parts.push(Part::Raw(&rest[..start]));
+            }
+            match token {
+                Token::Fragment(fragment) if active => parts.push(Part::Fragment(fragment)),
+                Token::Fragment(_) => {}
+                Token::If(condition) => {
+                    conditionals.push(Conditional { offset, condition, in_else: false });
+                }
+                Token::Else => match conditionals.last_mut() {
+                    Some(c) if !c.in_else => c.in_else = true,
+                    _ => return Err(format!("unexpected 'else' at byte {}", offset)),
+                },
+                Token::EndIf => {
+                    conditionals.pop()
+                        .ok_or_else(|| format!("unexpected 'endif' at byte {}", offset))?;
+                }
+            }
+            rest = &after_open[end + CLOSE.len()..];
+        }
+        if let Some(c) = conditionals.last() {
+            return Err(format!("'if' at byte {} is not closed by 'endif'", c.offset));
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Raw(rest));
+        }
+
+        Ok(Self { parts })
+    }
+
+    /// Returns the *unhashed HTTP paths* of all assets this template refers
+    /// to, in order of occurence and possibly with duplicates.
+    #[cfg_attr(all(dev_mode, not(feature = "manifest")), allow(dead_code))]
+    pub(crate) fn dependencies(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.parts.iter().filter_map(|part| match part {
+            Part::Fragment(Fragment::Path(path) | Fragment::Include(path)) => Some(*path),
+            Part::Fragment(Fragment::Var(_)) | Part::Raw(_) => None,
+        })
+    }
+
+    /// Returns the *unhashed HTTP paths* of all assets included by this
+    /// template, in order of occurence and possibly with duplicates.
+    #[cfg_attr(prod_mode, allow(dead_code))]
+    pub(crate) fn includes(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.parts.iter().filter_map(|part| match part {
+            Part::Fragment(Fragment::Include(path)) => Some(*path),
+            _ => None,
+        })
+    }
+
+    /// Renders the template, replacing each fragment by what `eval` returns
+    /// for it.
+    pub(crate) fn render<'o>(
+        &self,
+        mut eval: impl FnMut(Fragment<'a>) -> Result<Cow<'o, [u8]>, String>,
+    ) -> Result<Vec<u8>, String> {
+        let mut out = Vec::new();
+        for part in &self.parts {
+            match part {
+                Part::Raw(raw) => out.extend_from_slice(raw),
+                Part::Fragment(fragment) => out.extend_from_slice(&eval(*fragment)?),
+            }
+        }
+        Ok(out)
+    }
+}
+
+fn parse_token(inner: &str, prod: bool) -> Result<Token<'_>, String> {
+    if let Some(condition) = inner.strip_prefix("if ") {
+        return match condition.trim() {
+            "dev" => Ok(Token::If(!prod)),
+            "prod" => Ok(Token::If(prod)),
+            other => Err(format!("unknown condition '{}', expected 'dev' or 'prod'", other)),
+        };
+    }
+
+    let (kind, arg) = inner.split_once(':').unwrap_or((inner, ""));
+    let arg = arg.trim();
+    let fragment = match kind.trim() {
+        "else" if arg.is_empty() => return Ok(Token::Else),
+        "endif" if arg.is_empty() => return Ok(Token::EndIf),
+        "path" if arg.is_empty() => return Err("'path' requires an argument".into()),
+        "path" => Fragment::Path(arg),
+        "var" if arg.is_empty() => return Err("'var' requires an argument".into()),
+        "var" => Fragment::Var(arg),
+        "include" if arg.is_empty() => return Err("'include' requires an argument".into()),
+        "include" => Fragment::Include(arg),
+        other => return Err(format!("unknown fragment kind '{}'", other)),
+    };
+    Ok(Token::Fragment(fragment))
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(src: &str) -> Result<String, String> {
+        render_in(src, true)
+    }
+
+    fn render_in(src: &str, prod: bool) -> Result<String, String> {
+        let template = Template::parse(src.as_bytes(), prod)?;
+        let out = template.render(|fragment| match fragment {
+            Fragment::Path(path) => Ok(format!("<{}>", path).into_bytes().into()),
+            Fragment::Var(name) => Ok(name.to_uppercase().into_bytes().into()),
+            Fragment::Include(path) => Ok(format!("[{}]", path).into_bytes().into()),
+        })?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn no_fragments() {
+        assert_eq!(render("").unwrap(), "");
+        assert_eq!(render("body { color: red; }").unwrap(), "body { color: red; }");
+        assert_eq!(render("{{ x }} {: y :}").unwrap(), "{{ x }} {: y :}");
+    }
+
+    #[test]
+    fn path() {
+        assert_eq!(render("{{: path:a.js :}}").unwrap(), "<a.js>");
+        assert_eq!(render("x{{:path:a.js:}}y").unwrap(), "x<a.js>y");
+        assert_eq!(
+            render("<script src=\"/{{: path: a.js :}}\"></script>{{: path:b/c.css :}}").unwrap(),
+            "<script src=\"/<a.js>\"></script><b/c.css>",
+        );
+    }
+
+    #[test]
+    fn var() {
+        assert_eq!(render("const URL = '{{: var:api_url :}}';").unwrap(), "const URL = 'API_URL';");
+        assert_eq!(render("{{: var:a :}}{{: path:a :}}").unwrap(), "A<a>");
+    }
+
+    #[test]
+    fn include() {
+        assert_eq!(render("<body>{{: include:header.html :}}</body>").unwrap(), "<body>[header.html]</body>");
+    }
+
+    #[test]
+    fn conditionals() {
+        let src = "a{{: if dev :}}b{{: path:x :}}{{: endif :}}c{{: if prod :}}d{{: else :}}e{{: endif :}}";
+        assert_eq!(render_in(src, true).unwrap(), "acd");
+        assert_eq!(render_in(src, false).unwrap(), "ab<x>ce");
+
+        let src = "{{:if prod:}}1{{:if dev:}}2{{:else:}}3{{:endif:}}4{{:else:}}5{{:endif:}}";
+        assert_eq!(render_in(src, true).unwrap(), "134");
+        assert_eq!(render_in(src, false).unwrap(), "5");
+    }
+
+    #[test]
+    fn dependencies() {
+        let template = Template::parse(
+            b"{{: path:a.js :}} {{: var:b :}} {{: include:b.css :}} {{: path:a.js :}}",
+            true,
+        ).unwrap();
+        assert_eq!(template.dependencies().collect::<Vec<_>>(), ["a.js", "b.css", "a.js"]);
+        assert_eq!(template.includes().collect::<Vec<_>>(), ["b.css"]);
+
+        let template = Template::parse(b"{{: if dev :}}{{: include:a.js :}}{{: endif :}}", true)
+            .unwrap();
+        assert_eq!(template.dependencies().count(), 0);
+    }
+
+    #[test]
+    fn errors() {
+        assert!(render("{{: path:a.js").unwrap_err().contains("unterminated"));
+        assert!(render("{{: path :}}").unwrap_err().contains("requires an argument"));
+        assert!(render("{{: var: :}}").unwrap_err().contains("requires an argument"));
+        assert!(render("{{: foo:bar :}}").unwrap_err().contains("unknown fragment kind 'foo'"));
+        assert!(render("{{: if test :}}").unwrap_err().contains("unknown condition 'test'"));
+        assert!(render("{{: if dev :}}").unwrap_err().contains("not closed"));
+        assert!(render("{{: endif :}}").unwrap_err().contains("unexpected 'endif'"));
+        assert!(render("{{: else :}}").unwrap_err().contains("unexpected 'else'"));
+        assert!(render("{{: if dev :}}{{: else :}}{{: else :}}{{: endif :}}")
+            .unwrap_err().contains("unexpected 'else'"));
+    }
+}
@@ -0,0 +1,90 @@
+//! Per-asset access control, see [`EntryBuilder::with_guard`][crate::EntryBuilder::with_guard].
+
+use std::{borrow::Cow, fmt, sync::Arc};
+
+
+/// Information about an HTTP request passed to guards registered via
+/// [`EntryBuilder::with_guard`][crate::EntryBuilder::with_guard]. The
+/// framework integrations create this automatically; custom servers can
+/// create it via [`RequestContext::new`] and check it with
+/// [`Asset::is_allowed`][crate::Asset::is_allowed].
+#[derive(Debug, Clone)]
+pub struct RequestContext<'a> {
+    method: &'a str,
+    path: &'a str,
+    headers: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> RequestContext<'a> {
+    /// Creates a new context from the request method, the requested path
+    /// (without leading `/`) and the request headers.
+    pub fn new<H>(method: &'a str, path: &'a str, headers: H) -> Self
+    where
+        H: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        Self { method, path, headers: headers.into_iter().collect() }
+    }
+
+    /// The request method, e.g. `GET`.
+    pub fn method(&self) -> &'a str {
+        self.method
+    }
+
+    /// The requested path (without leading `/`), i.e. the *hashed HTTP path*
+    /// of the asset.
+    pub fn path(&self) -> &'a str {
+        self.path
+    }
+
+    /// Returns the value of the first header with the given name (compared
+    /// case-insensitively), if any. Headers with values that are not valid
+    /// UTF-8 are not available.
+    pub fn header(&self, name: &str) -> Option<&'a str> {
+        self.headers.iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| *value)
+    }
+}
+
+/// Condition under which restricted assets are served, see
+/// [`Builder::restrict_source_maps`][crate::Builder::restrict_source_maps].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Restriction {
+    /// Never serve the assets.
+    Deny,
+
+    /// Only serve the assets to requests containing a header with the given
+    /// name, regardless of its value. Make sure that clients cannot set it
+    /// themselves, e.g. by letting a reverse proxy remove it from external
+    /// requests. For anything more involved, use
+    /// [`GroupBuilder::with_guard`][crate::GroupBuilder::with_guard].
+    HeaderToken(Cow<'static, str>),
+}
+
+impl Restriction {
+    pub(crate) fn allows(&self, req: &RequestContext) -> bool {
+        match self {
+            Restriction::Deny => false,
+            Restriction::HeaderToken(name) => req.header(name).is_some(),
+        }
+    }
+}
+
+/// A function registered via [`EntryBuilder::with_guard`][crate::EntryBuilder::with_guard].
+#[derive(Clone)]
+pub(crate) struct Guard(pub(crate) Arc<GuardFn>);
+
+type GuardFn = dyn Send + Sync + Fn(&RequestContext) -> bool;
+
+impl Guard {
+    pub(crate) fn allows(&self, req: &RequestContext) -> bool {
+        (self.0)(req)
+    }
+}
+
+impl fmt::Debug for Guard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Guard")
+    }
+}
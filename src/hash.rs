@@ -1,24 +1,146 @@
+#[cfg(feature = "full")]
 use bytes::Bytes;
 
+#[cfg(feature = "full")]
+use crate::builder::EntryConfig;
+#[cfg(all(feature = "full", feature = "hash"))]
 use crate::PathHash;
+#[cfg(all(feature = "full", feature = "hash"))]
+use crate::builder::HashPlacement;
+#[cfg(all(feature = "full", feature = "hash"))]
+use std::{fmt, sync::Arc};
 
 
+/// How many bytes of the hash are used and encoded in the filename by
+/// default. We use a multiple of 3, as base64 encodes 3 bytes
+/// with 4 chars. With a multiple of 3 input bytes, we do not waste base64
+/// chars.
+#[cfg(feature = "hash")]
+const DEFAULT_HASH_LENGTH: usize = 9;
+
+/// Computes the hashes that [`EntryBuilder::with_hash`][crate::EntryBuilder::with_hash]
+/// puts into filenames. [`Sha256Hasher`] is used by default, others can be
+/// set via [`Builder::content_hasher`][crate::Builder::content_hasher].
+/// Implement this trait yourself to produce the same hashes as other tools,
+/// e.g. your JS bundler.
+///
+/// Trait is only available if the crate feature `hash` is enabled.
+#[cfg(feature = "hash")]
+pub trait ContentHasher: Send + Sync {
+    /// Returns the digest of `salt` followed by `content`. The salt is set
+    /// via [`Builder::hash_salt`][crate::Builder::hash_salt] and empty by
+    /// default. Only the first bytes of the digest are used (see
+    /// [`Builder::set_hash_length`][crate::Builder::set_hash_length]), or
+    /// all of it if it's shorter.
+    fn digest(&self, salt: &[u8], content: &[u8]) -> Vec<u8>;
+
+    /// Encodes the truncated digest for use in filenames. The result must
+    /// only contain characters that are allowed in URL paths. Defaults to
+    /// URL-safe base64 without padding.
+    fn encode(&self, digest: &[u8]) -> String {
+        use base64::Engine;
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(digest)
+    }
+}
+
+/// SHA-256 as [`ContentHasher`]. This is the default.
+#[cfg(feature = "hash")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sha256Hasher;
+
+#[cfg(feature = "hash")]
+impl ContentHasher for Sha256Hasher {
+    fn digest(&self, salt: &[u8], content: &[u8]) -> Vec<u8> {
+        use sha2::{Digest, Sha256};
+        Sha256::new().chain_update(salt).chain_update(content).finalize().to_vec()
+    }
+}
+
+/// BLAKE3 as [`ContentHasher`], producing 32 byte digests.
+///
+/// Only available if the crate feature `blake3` is enabled.
+#[cfg(feature = "blake3")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Blake3Hasher;
+
+#[cfg(feature = "blake3")]
+impl ContentHasher for Blake3Hasher {
+    fn digest(&self, salt: &[u8], content: &[u8]) -> Vec<u8> {
+        blake3::Hasher::new().update(salt).update(content).finalize().as_bytes().to_vec()
+    }
+}
+
+/// 128 bit XXH3 as [`ContentHasher`], producing 16 byte digests (big
+/// endian). Not cryptographically secure, but very fast.
+///
+/// Only available if the crate feature `xxhash` is enabled.
+#[cfg(feature = "xxhash")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Xxh3Hasher;
+
+#[cfg(feature = "xxhash")]
+impl ContentHasher for Xxh3Hasher {
+    fn digest(&self, salt: &[u8], content: &[u8]) -> Vec<u8> {
+        let mut hasher = xxhash_rust::xxh3::Xxh3::new();
+        hasher.update(salt);
+        hasher.update(content);
+        hasher.digest128().to_be_bytes().to_vec()
+    }
+}
+
+/// The hasher configured in the builder.
+#[cfg(all(feature = "full", feature = "hash"))]
+#[derive(Clone)]
+pub(crate) struct DynHasher(Arc<dyn ContentHasher>);
+
+#[cfg(all(feature = "full", feature = "hash"))]
+impl DynHasher {
+    pub(crate) fn new(hasher: impl 'static + ContentHasher) -> Self {
+        Self(Arc::new(hasher))
+    }
+}
+
+#[cfg(all(feature = "full", feature = "hash"))]
+impl Default for DynHasher {
+    fn default() -> Self {
+        Self::new(Sha256Hasher)
+    }
+}
+
+#[cfg(all(feature = "full", feature = "hash"))]
+impl fmt::Debug for DynHasher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ContentHasher")
+    }
+}
+
+#[cfg(feature = "full")]
 #[derive(Debug)]
 pub(crate) struct PathMap<'a> {
     #[cfg(feature = "hash")]
     map: ahash::HashMap<&'a str, String>,
 
+    /// Mixed into all hashes, see `Builder::hash_salt`.
+    #[cfg(feature = "hash")]
+    salt: &'a [u8],
+
+    #[cfg(feature = "hash")]
+    hasher: &'a DynHasher,
+
     #[cfg(not(feature = "hash"))]
     map: std::marker::PhantomData<&'a ()>,
 }
 
+#[cfg(feature = "full")]
 impl<'a> PathMap<'a> {
+    #[cfg(not(feature = "hash"))]
     pub(crate) fn new() -> Self {
-        #[cfg(feature = "hash")]
-        { Self { map: ahash::HashMap::default() } }
+        Self { map: std::marker::PhantomData }
+    }
 
-        #[cfg(not(feature = "hash"))]
-        { Self { map: std::marker::PhantomData } }
+    #[cfg(feature = "hash")]
+    pub(crate) fn with_hasher(hasher: &'a DynHasher, salt: &'a [u8]) -> Self {
+        Self { map: ahash::HashMap::default(), salt, hasher }
     }
 
     pub(crate) fn get(&self, path: &str) -> Option<&str> {
@@ -33,9 +155,9 @@ impl<'a> PathMap<'a> {
     }
 }
 
-#[cfg(not(feature = "hash"))]
+#[cfg(all(feature = "full", not(feature = "hash")))]
 pub(crate) fn path_of<'a>(
-    _: PathHash<'_>,
+    _: &EntryConfig<'_>,
     path: &'a str,
     _: &Bytes,
     _: &mut PathMap<'a>,
@@ -44,49 +166,244 @@ pub(crate) fn path_of<'a>(
 }
 
 
-#[cfg(feature = "hash")]
+#[cfg(all(feature = "full", feature = "hash"))]
 pub(crate) fn path_of<'a>(
-    hash: PathHash<'_>,
+    config: &EntryConfig<'_>,
     path: &'a str,
     content: &Bytes,
     map: &mut PathMap<'a>,
 ) -> String {
-    use sha2::{Digest, Sha256};
-    use base64::Engine;
+    let hash = || {
+        let length = config.hash_length.unwrap_or(DEFAULT_HASH_LENGTH);
+        salted_hash(&*map.hasher.0, map.salt, content, length)
+    };
+    let out = match &config.path_hash {
+        PathHash::None | PathHash::Stable => return path.to_owned(),
+        PathHash::Auto(placement) => {
+            let placement = placement.as_ref().unwrap_or(&HashPlacement::AfterFirstDot);
+            place_hash(placement, path, &hash())
+        }
+        PathHash::InBetween { prefix, suffix } => format!("{prefix}{}{suffix}", hash()),
+    };
+
+    // Add entry to path map
+    map.map.insert(path, out.clone());
+
+    out
+}
 
+/// Inserts the hash of `content` into the filename of `path` as with
+/// `HashPlacement::AfterFirstDot`.
+#[cfg(feature = "hash")]
+pub(crate) fn auto_hashed_path(path: &str, content: &[u8]) -> String {
+    let name_start = filename_start(path);
+    let dot = path[name_start..].find('.').map(|pos| name_start + pos);
+    insert_hash(path, dot, &encoded_hash(content))
+}
 
-    /// How many bytes of the 32 byte (256 bit) hash are used and encoded in the
-    /// filename. We use a multiple of 9, as base64 encodes 3 bytes with 4
-    /// chars. With a multiple of 3 input bytes, we do not waste base64 chars.
-    const HASH_BYTES_IN_FILENAME: usize = 9;
+/// Settings for [`hashed_path`], mirroring the corresponding builder
+/// settings. The defaults are the same as for [`Builder`][crate::Builder].
+///
+/// ```
+/// use reinda::{HashOptions, HashPlacement};
+///
+/// let options = HashOptions::new()
+///     .placement(HashPlacement::BeforeExt)
+///     .length(6)
+///     .salt("2024-05-01");
+/// ```
+#[cfg(all(feature = "full", feature = "hash"))]
+#[derive(Debug, Clone)]
+pub struct HashOptions {
+    placement: HashPlacement,
+    length: usize,
+    salt: Vec<u8>,
+    hasher: DynHasher,
+}
 
+#[cfg(all(feature = "full", feature = "hash"))]
+impl HashOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Self {
+            placement: HashPlacement::default(),
+            length: DEFAULT_HASH_LENGTH,
+            salt: vec![],
+            hasher: DynHasher::default(),
+        }
+    }
 
-    let (first_part, hash_prefix, second_part) = match hash {
-        PathHash::None => return path.to_owned(),
-        PathHash::Auto => {
-            let last_seg_start = path.rfind('/').map(|p| p + 1).unwrap_or(0);
-            let (pos, hash_prefix) = match path[last_seg_start..].find('.') {
-                Some(pos) => (last_seg_start + pos, '.'),
-                None => (path.len(), '-'),
-            };
+    /// See [`EntryBuilder::with_hash_placement`][crate::EntryBuilder::with_hash_placement].
+    pub fn placement(mut self, placement: HashPlacement) -> Self {
+        self.placement = placement;
+        self
+    }
 
-            (&path[..pos], Some(hash_prefix), &path[pos..])
-        },
-        PathHash::InBetween { prefix, suffix } => (prefix, None, suffix),
+    /// See [`Builder::set_hash_length`][crate::Builder::set_hash_length]. The
+    /// length is capped at the digest size of the hasher. Panics if `bytes`
+    /// is smaller than 6 or larger than 32.
+    pub fn length(mut self, bytes: usize) -> Self {
+        crate::builder::check_hash_length(bytes);
+        self.length = bytes;
+        self
+    }
+
+    /// See [`Builder::hash_salt`][crate::Builder::hash_salt].
+    pub fn salt(mut self, salt: impl Into<Vec<u8>>) -> Self {
+        self.salt = salt.into();
+        self
+    }
+
+    /// See [`Builder::content_hasher`][crate::Builder::content_hasher].
+    pub fn hasher(mut self, hasher: impl 'static + ContentHasher) -> Self {
+        self.hasher = DynHasher::new(hasher);
+        self
+    }
+}
+
+#[cfg(all(feature = "full", feature = "hash"))]
+impl Default for HashOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the *hashed HTTP path* under which an asset with the given
+/// *unhashed HTTP path* and (final) content is served in prod mode, if
+/// configured with [`EntryBuilder::with_hash`][crate::EntryBuilder::with_hash]
+/// and the given options. Useful for external tooling like deploy or cache
+/// purge scripts. This is exactly the function `reinda` uses internally and
+/// its output only changes in semver-breaking releases.
+///
+/// ```
+/// use reinda::{hashed_path, HashOptions};
+///
+/// assert_eq!(hashed_path("js/app.min.js", b"foo", &HashOptions::new()), "js/app.LCa0a2j_xo_5.min.js");
+/// ```
+#[cfg(all(feature = "full", feature = "hash"))]
+pub fn hashed_path(unhashed_path: &str, content: &[u8], options: &HashOptions) -> String {
+    let hash = salted_hash(&*options.hasher.0, &options.salt, content, options.length);
+    place_hash(&options.placement, unhashed_path, &hash)
+}
+
+/// Inserts `hash` into `path` according to `placement`.
+#[cfg(all(feature = "full", feature = "hash"))]
+pub(crate) fn place_hash(placement: &HashPlacement, path: &str, hash: &str) -> String {
+    let name_start = filename_start(path);
+    let dot = match placement {
+        HashPlacement::AfterFirstDot => path[name_start..].find('.'),
+        HashPlacement::BeforeExt => path[name_start..].rfind('.'),
+        HashPlacement::Suffix => None,
+        HashPlacement::Custom(f) => return f(path, hash),
     };
+    insert_hash(path, dot.map(|pos| name_start + pos), hash)
+}
 
-    // Calculate hash
-    let hash = Sha256::digest(&content);
+/// Returns the start of the filename in `path`, skipping leading dots (as in
+/// `.htaccess`).
+#[cfg(feature = "hash")]
+fn filename_start(path: &str) -> usize {
+    let last_seg = path.rsplit('/').next().unwrap_or(path);
+    path.len() - last_seg.trim_start_matches('.').len()
+}
 
-    // Concat everything including the base64 encoded hash
-    let mut out = first_part.to_owned();
-    out.extend(hash_prefix);
-    base64::engine::general_purpose::URL_SAFE_NO_PAD
-        .encode_string(&hash.as_slice()[..HASH_BYTES_IN_FILENAME], &mut out);
-    out.push_str(second_part);
+/// Inserts `hash` before the `.` at `dot`, followed by another `.`, or at the
+/// end after a `-` if `dot` is `None`.
+#[cfg(feature = "hash")]
+fn insert_hash(path: &str, dot: Option<usize>, hash: &str) -> String {
+    match dot {
+        Some(pos) => {
+            let (first, second) = path.split_at(pos);
+            format!("{first}.{hash}{second}")
+        }
+        None => format!("{path}-{hash}"),
+    }
+}
 
-    // Add entry to path map
-    map.map.insert(path, out.clone());
+/// Returns the URL-safe base64 encoded (truncated) SHA-256 hash of `content`.
+#[cfg(feature = "hash")]
+pub(crate) fn encoded_hash(content: &[u8]) -> String {
+    salted_hash(&Sha256Hasher, &[], content, DEFAULT_HASH_LENGTH)
+}
 
-    out
+/// Like [`encoded_hash`], but using `hasher`, with `salt` prepended to
+/// `content` and using the first `length` bytes of the hash. An empty salt
+/// results in the same hash.
+#[cfg(feature = "hash")]
+fn salted_hash(hasher: &dyn ContentHasher, salt: &[u8], content: &[u8], length: usize) -> String {
+    let digest = hasher.digest(salt, content);
+    hasher.encode(&digest[..length.min(digest.len())])
+}
+
+#[cfg(all(test, feature = "hash"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auto_placement() {
+        let hashed = |path| auto_hashed_path(path, b"foo");
+        let hash = &hashed("x")[2..];
+
+        assert_eq!(hashed("bundle.js.map"), format!("bundle.{hash}.js.map"));
+        assert_eq!(hashed("static/app.js"), format!("static/app.{hash}.js"));
+        assert_eq!(hashed("LICENSE"), format!("LICENSE-{hash}"));
+        assert_eq!(hashed(".htaccess"), format!(".htaccess-{hash}"));
+        assert_eq!(hashed("..weird"), format!("..weird-{hash}"));
+        assert_eq!(hashed("conf/.env.json"), format!("conf/.env.{hash}.json"));
+        assert_eq!(hashed(".well-known/security.txt"), format!(".well-known/security.{hash}.txt"));
+        assert_eq!(
+            hashed(".well-known/apple-app-site-association"),
+            format!(".well-known/apple-app-site-association-{hash}"),
+        );
+        assert_eq!(hashed("dir.d/file"), format!("dir.d/file-{hash}"));
+    }
+
+    #[test]
+    fn salt() {
+        let salted = |salt: &[u8], content| {
+            salted_hash(&Sha256Hasher, salt, content, DEFAULT_HASH_LENGTH)
+        };
+        assert_eq!(salted(b"", b"foo"), encoded_hash(b"foo"));
+        assert_ne!(salted(b"2024-05-01", b"foo"), encoded_hash(b"foo"));
+        assert_ne!(salted(b"2024-05-01", b"foo"), salted(b"2024-05-02", b"foo"));
+    }
+
+    #[test]
+    fn length() {
+        let hash = |length| salted_hash(&Sha256Hasher, b"", b"foo", length);
+        assert_eq!(hash(6), encoded_hash(b"foo")[..8]);
+        assert_eq!(hash(32).len(), 43);
+        assert!(hash(32).starts_with(&encoded_hash(b"foo")));
+    }
+
+    #[test]
+    fn custom_hasher() {
+        struct Hex;
+        impl ContentHasher for Hex {
+            fn digest(&self, salt: &[u8], content: &[u8]) -> Vec<u8> {
+                salt.iter().chain(content).copied().collect()
+            }
+            fn encode(&self, digest: &[u8]) -> String {
+                digest.iter().map(|b| format!("{b:02x}")).collect()
+            }
+        }
+
+        assert_eq!(salted_hash(&Hex, b"a", b"bc", 9), "616263");
+        assert_eq!(salted_hash(&Hex, b"", b"abcdefghijk", 9), "616263646566676869");
+    }
+
+    #[test]
+    #[cfg(feature = "full")]
+    fn placements() {
+        let place = |placement, path| place_hash(&placement, path, "HASH");
+
+        assert_eq!(place(HashPlacement::AfterFirstDot, "jquery.min.js"), "jquery.HASH.min.js");
+        assert_eq!(place(HashPlacement::BeforeExt, "jquery.min.js"), "jquery.min.HASH.js");
+        assert_eq!(place(HashPlacement::BeforeExt, "a.b/.env.local"), "a.b/.env.HASH.local");
+        assert_eq!(place(HashPlacement::BeforeExt, "a.b/LICENSE"), "a.b/LICENSE-HASH");
+        assert_eq!(place(HashPlacement::Suffix, "jquery.min.js"), "jquery.min.js-HASH");
+
+        let custom = HashPlacement::custom(|path, hash| format!("{hash}/{path}"));
+        assert_eq!(place(custom, "js/app.js"), "HASH/js/app.js");
+    }
 }
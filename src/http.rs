@@ -0,0 +1,81 @@
+//! Framework-agnostic HTTP responses, see [`Asset::response`].
+
+use bytes::Bytes;
+use http::{header, HeaderMap, HeaderValue, Response, StatusCode};
+
+use crate::{
+    serve::{content_type, load_encoded},
+    Asset, Conditional, LoadError,
+};
+
+
+impl Asset {
+    /// Loads this asset and builds a response for a `GET` request with the
+    /// given request headers, for use with `hyper` or any other stack based
+    /// on the `http` crate. The response:
+    /// - contains the best variant according to the `Accept` header (see
+    ///   [`Self::best_variant`]) and `Vary: accept` if this asset has variants,
+    /// - has the headers `Content-Type` (with `charset` for text assets),
+    ///   `Content-Length`, `ETag` and, if available, `Last-Modified` and
+    ///   `Cache-Control`,
+    /// - is encoded according to the `Accept-Encoding` header if possible
+    ///   (see [`Self::content_encoded`]), with `Content-Encoding` and
+    ///   `Vary: accept-encoding` set accordingly,
+    /// - has status `304 Not Modified` and an empty body if the
+    ///   `If-None-Match` header matches the `ETag` or, without
+    ///   `If-None-Match`, the asset was not modified since the time given by
+    ///   `If-Modified-Since` (see [`Conditional`]), `200 OK` otherwise.
+    ///
+    /// For `HEAD` requests, replace the body by an empty one.
+    ///
+    /// ```no_run
+    /// # async fn foo(assets: reinda::Assets, req: http::Request<()>) {
+    /// let asset = assets.get("logo.png").unwrap();
+    /// let response = asset.response(req.headers()).await.unwrap();
+    /// # }
+    /// ```
+    ///
+    /// Method is only available if the crate feature `http` is enabled.
+    pub async fn response(&self, req_headers: &HeaderMap) -> Result<Response<Bytes>, LoadError> {
+        let accept = req_headers.get(header::ACCEPT).and_then(|v| v.to_str().ok());
+        let variant = match accept {
+            Some(accept) if self.has_variants() => self.best_variant(accept),
+            _ => self.clone(),
+        };
+
+        let accept_encoding = req_headers.get(header::ACCEPT_ENCODING).and_then(|v| v.to_str().ok());
+        let (content, encoding_headers) = load_encoded(&variant, accept_encoding).await?;
+        let headers = req_headers.iter()
+            .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?)));
+        let conditional = Conditional::evaluate(&variant, &content, headers);
+
+        let mut response = if conditional.is_not_modified() {
+            let mut response = Response::new(Bytes::new());
+            *response.status_mut() = StatusCode::NOT_MODIFIED;
+            response
+        } else {
+            let len = content.len();
+            let mut response = Response::new(content);
+            let headers = response.headers_mut();
+            if let Ok(value) = HeaderValue::from_str(&content_type(&variant)) {
+                headers.insert(header::CONTENT_TYPE, value);
+            }
+            headers.insert(header::CONTENT_LENGTH, len.into());
+            response
+        };
+
+        let headers = response.headers_mut();
+        for (name, value) in encoding_headers {
+            headers.append(name, HeaderValue::from_static(value));
+        }
+        for (name, value) in conditional.headers() {
+            if let Ok(value) = HeaderValue::from_str(value) {
+                headers.insert(name, value);
+            }
+        }
+        if self.has_variants() {
+            headers.append(header::VARY, HeaderValue::from_static("accept"));
+        }
+        Ok(response)
+    }
+}
@@ -1,21 +1,34 @@
-use std::{io, marker::PhantomData, path::{Path, PathBuf}, sync::Arc};
+use std::{
+    any::Any, borrow::Cow, future::Future, io::Read, marker::PhantomData,
+    panic::{self, AssertUnwindSafe}, path::{Path, PathBuf}, pin::Pin, sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
-use ahash::{HashMap, HashMapExt};
+use ahash::{HashMap, HashMapExt, HashSet};
 use bytes::Bytes;
 
 use crate::{
-    builder::EntryBuilderKind,
-    Asset, BuildError, Builder, DataSource, Modifier, ModifierContext, SplitGlob,
+    builder::{
+        apply_groups, join_glob_path, passes_filters, walk_dir, walk_dir_blocking, EntryBuilderKind,
+        EntryConfig, GlobFilter, GlobalModifier, GroupBuilder, PathMapper, Variables,
+    },
+    cache::{AssetMeta, CachePolicyFn},
+    guard::Guard,
+    checksum,
+    text::{self, TextInfo},
+    watch::ChangeEvent,
+    Asset, BuildError, Builder, DataSource, LoadError, LoadErrorKind, Modifier, ModifierContext,
+    AssetsRuntime, ContentStream, Encoding, GlobMount, PathHash,
 };
 
 
 #[derive(Debug, Clone)]
 pub(crate) struct AssetsInner(Arc<AssetsEvenMoreInner>);
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub(crate) struct AssetsEvenMoreInner {
     /// All specified assets, but not yet loaded.
-    assets: HashMap<String, (DataSource, Modifier)>,
+    assets: HashMap<String, DevAsset>,
 
     /// List of glob patterns that were added. This is only relevant for the dev
     /// mode where we want to be able to load files dynamically in `get` that
@@ -23,51 +36,219 @@ pub(crate) struct AssetsEvenMoreInner {
     ///
     /// Sorted by the length of `http_prefix`, starting with the longest.
     globs: Vec<DevGlobEntry>,
+
+    /// Groups from the builder, needed to configure files dynamically matched
+    /// by globs.
+    groups: Vec<GroupBuilder<'static>>,
+
+    cache_policy: Option<CachePolicyFn>,
+
+    mime_types: crate::mime::CustomTypes,
+
+    #[cfg(feature = "modifiers")]
+    global_replacements: Vec<(Cow<'static, str>, Cow<'static, str>)>,
+
+    global_modifiers: Vec<GlobalModifier>,
+
+    variables: Variables,
+
+    /// Maps HTTP status codes to *unhashed HTTP paths*.
+    error_assets: HashMap<u16, Cow<'static, str>>,
+
+    /// *Unhashed HTTP path* of the fallback asset.
+    fallback: Option<Cow<'static, str>>,
+
+    /// Filename set via `Builder::directory_index`.
+    directory_index: Option<Cow<'static, str>>,
+
+    /// Only set if enabled via `Builder::dev_stale_while_revalidate`.
+    cache: Option<SwrCache>,
+
+    runtime: AssetsRuntime,
+
+    /// Only needed for `dry_run_prod`.
+    #[cfg(all(feature = "manifest", feature = "hash"))]
+    hash_salt: Vec<u8>,
+    #[cfg(all(feature = "manifest", feature = "hash"))]
+    hasher: crate::hash::DynHasher,
+    #[cfg(all(feature = "manifest", feature = "modifiers"))]
+    warning_handler: crate::diagnostics::WarningHandler,
+}
+
+/// Stale-while-revalidate cache of loaded asset contents.
+#[derive(Debug)]
+struct SwrCache {
+    max_stale: Duration,
+    entries: Mutex<HashMap<String, CacheEntry>>,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    content: Bytes,
+    loaded_at: Instant,
+    refreshing: bool,
+}
+
+#[derive(Debug, Clone)]
+struct DevAsset {
+    source: DataSource,
+    config: EntryConfig<'static>,
+    /// Path relative to the HTTP prefix, for assets matched by a glob.
+    glob_suffix: Option<String>,
 }
 
 #[derive(Debug, Clone)]
 struct DevGlobEntry {
-    http_prefix: String,
-    glob: SplitGlob,
-    modifier: Modifier,
-    base_path: &'static Path,
+    mount: GlobMount,
+    /// See `EntryBuilder::filter`.
+    filters: Vec<GlobFilter>,
+    /// See `EntryBuilder::map_http_paths`.
+    path_mapper: Option<PathMapper>,
+    /// Leading segments of the embed pattern, see [`SplitGlob`]. Empty for
+    /// directories added via `add_dir`.
+    fs_prefix: &'static str,
+    config: EntryConfig<'static>,
+    base_path: PathBuf,
+    /// Only set if `path_mapper` is, since that mapping cannot be reversed:
+    /// maps *HTTP paths* to the files served under them. Filled in `build`
+    /// and updated in `changes`.
+    mapped_files: Option<Arc<Mutex<HashMap<String, PathBuf>>>>,
 }
 
 impl AssetsInner {
     pub(crate) async fn build(builder: Builder<'_>) -> Result<Self, BuildError> {
+        let remaps = &builder.path_remaps;
+        let remap_source = |source| match source {
+            DataSource::File(path) => DataSource::File(remap_path(remaps, path)),
+            other => other,
+        };
+
         // Collect all glob entries we have.
         let globs = builder.assets.iter().filter_map(|ab| {
-            if let EntryBuilderKind::Glob { http_prefix, glob, base_path, .. } = &ab.kind {
-                Some(DevGlobEntry {
-                    http_prefix: http_prefix.clone().into_owned(),
-                    glob: glob.clone(),
-                    modifier: ab.modifier.clone(),
-                    base_path: Path::new(*base_path),
-                })
-            } else {
-                None
-            }
-        }).collect();
+            let (fs_prefix, base_path, filters, path_mapper) = match &ab.kind {
+                EntryBuilderKind::Glob { glob, base_path, dev_dir, filters, path_mapper, .. } => {
+                    match dev_dir {
+                        Some(dir) => ("", dir.clone(), filters, path_mapper),
+                        None => (glob.prefix, base_path.into(), filters, path_mapper),
+                    }
+                }
+                EntryBuilderKind::Dir { fs_path, filters, path_mapper, .. } => {
+                    ("", fs_path.clone(), filters, path_mapper)
+                }
+                EntryBuilderKind::Single { .. } => return None,
+            };
+            Some(DevGlobEntry {
+                mount: ab.kind.glob_mount()?,
+                filters: filters.clone(),
+                path_mapper: path_mapper.clone(),
+                fs_prefix,
+                config: into_static(ab.config.clone()),
+                base_path: remap_path(remaps, base_path),
+                mapped_files: path_mapper.as_ref().map(|_| Default::default()),
+            })
+        }).collect::<Vec<_>>();
+        for glob in &globs {
+            // Unreadable directories are reported by `warm_up`.
+            let files = walk_dir(&glob.dir(), |_| true).await.unwrap_or_default();
+            glob.set_mapped_files(files);
+        }
+
+        let groups = builder.groups.into_iter().map(|g| GroupBuilder {
+            selector: g.selector,
+            path_hash: g.path_hash.map(static_path_hash),
+            modifier: g.modifier,
+            cache_control: g.cache_control,
+            sanitize_svg: g.sanitize_svg,
+            minify_js: g.minify_js,
+            optimize_svg: g.optimize_svg,
+            guard: g.guard,
+        }).collect::<Vec<_>>();
 
         // Collect all files we know about.
-        let mut assets = HashMap::with_capacity(builder.assets.len());
+        let mut assets = HashMap::<String, DevAsset>::with_capacity(builder.assets.len());
+        let allow_duplicate_paths = builder.allow_duplicate_paths;
+        let mut add = |http_path: String, asset: DevAsset| match assets.get(&http_path) {
+            Some(prev) if !allow_duplicate_paths => Err(BuildError::DuplicatePath {
+                first: prev.source.describe(),
+                second: asset.source.describe(),
+                http_path,
+            }),
+            _ => {
+                assets.insert(http_path, asset);
+                Ok(())
+            }
+        };
         for ab in builder.assets {
             match ab.kind {
                 EntryBuilderKind::Single { http_path, source } => {
-                    assets.insert(http_path.into_owned(), (source, ab.modifier));
+                    let source = remap_source(source);
+                    let asset = DevAsset::new(&groups, &http_path, source, into_static(ab.config), None);
+                    add(http_path.into_owned(), asset)?;
                 }
                 EntryBuilderKind::Glob { http_prefix, files, .. } => {
                     for file in files {
-                        assets.insert(
-                            file.http_path(&http_prefix),
-                            (file.source, ab.modifier.clone()),
-                        );
+                        let http_path = file.http_path(&http_prefix);
+                        let config = into_static(ab.config.clone());
+                        let suffix = Some(file.http_suffix().to_owned());
+                        let source = remap_source(file.source);
+                        let asset = DevAsset::new(&groups, &http_path, source, config, suffix);
+                        add(http_path, asset)?;
                     }
                 }
+                // Served lazily via `globs`.
+                EntryBuilderKind::Dir { .. } => {}
             }
         }
 
-        Ok(Self(Arc::new(AssetsEvenMoreInner { assets, globs })))
+        let inner = AssetsEvenMoreInner {
+            assets,
+            globs,
+            groups,
+            cache_policy: builder.cache_policy,
+            mime_types: builder.mime_types,
+            #[cfg(feature = "modifiers")]
+            global_replacements: builder.global_replacements,
+            global_modifiers: builder.global_modifiers,
+            variables: builder.variables,
+            error_assets: builder.error_assets,
+            fallback: builder.fallback,
+            directory_index: builder.directory_index,
+            cache: builder.dev_max_stale.map(|max_stale| SwrCache {
+                max_stale,
+                entries: Mutex::new(HashMap::new()),
+            }),
+            runtime: AssetsRuntime::new(),
+            #[cfg(all(feature = "manifest", feature = "hash"))]
+            hash_salt: builder.hash_salt,
+            #[cfg(all(feature = "manifest", feature = "hash"))]
+            hasher: builder.hasher,
+            #[cfg(all(feature = "manifest", feature = "modifiers"))]
+            warning_handler: builder.warning_handler,
+        };
+        for (status, path) in &inner.error_assets {
+            if !inner.assets.contains_key(path.as_ref()) && inner.match_globs(path).is_none() {
+                panic!("Error asset '{}' for status {} does not exist", path, status);
+            }
+        }
+        if let Some(path) = &inner.fallback {
+            if !inner.assets.contains_key(path.as_ref()) && inner.match_globs(path).is_none() {
+                panic!("Fallback asset '{}' does not exist", path);
+            }
+        }
+        for (path, asset) in &inner.assets {
+            let variants = asset.config.variants.iter()
+                .chain(asset.config.locale_variants.iter().map(|(_, v)| v));
+            for variant in variants {
+                if !inner.assets.contains_key(variant.as_ref()) && inner.match_globs(variant).is_none() {
+                    panic!(
+                        "Asset '{}' specified variant '{}' but that asset does not exist",
+                        path, variant,
+                    );
+                }
+            }
+        }
+
+        Ok(Self(Arc::new(inner)))
     }
 
     pub(crate) fn get(&self, http_path: &str) -> Option<Asset> {
@@ -77,38 +258,398 @@ impl AssetsInner {
             // and if so, we check the file system.
             .or_else(|| {
                 self.0.match_globs(http_path)
-                    .filter(|(path, _)| path.exists())
-                    .map(|(path, modifier)| (DataSource::File(path), modifier))
+                    .filter(|(path, _, _)| path.is_file())
+                    .map(|(path, suffix, glob)| DevAsset::new(
+                        &self.0.groups,
+                        http_path,
+                        DataSource::File(path),
+                        glob.config.clone(),
+                        Some(suffix.to_owned()),
+                    ))
+            })
+            .map(|mut asset| {
+                if asset.config.cache_control.is_none() {
+                    asset.config.cache_control = self.0.cache_policy.as_ref()
+                        .map(|policy| policy.header_value(&AssetMeta {
+                            unhashed_http_path: http_path,
+                            http_path,
+                            is_filename_hashed: false,
+                        }));
+                }
+
+                Asset(AssetInner {
+                    http_path: http_path.to_owned(),
+                    asset,
+                    assets: self.0.clone(),
+                })
             })
-            .map(|(source, modifier)| Asset(AssetInner {
-                source,
-                modifier,
-                assets: self.0.clone(),
-            }))
+    }
+
+    /// Paths are never hashed in dev mode.
+    pub(crate) fn get_unhashed(&self, unhashed_http_path: &str) -> Option<Asset> {
+        self.get(unhashed_http_path)
+    }
+
+    pub(crate) fn directory_index(&self) -> Option<&str> {
+        self.0.directory_index.as_deref()
+    }
+
+    pub(crate) fn error_asset(&self, status: u16) -> Option<Asset> {
+        self.0.error_assets.get(&status).and_then(|path| self.get(path))
+    }
+
+    pub(crate) fn fallback(&self) -> Option<Asset> {
+        self.0.fallback.as_ref().and_then(|path| self.get(path))
+    }
+
+    pub(crate) fn glob_mounts(&self) -> impl '_ + Iterator<Item = &GlobMount> {
+        self.0.globs.iter().map(|glob| &glob.mount)
+    }
+
+    #[cfg(feature = "report")]
+    pub(crate) fn build_report(&self) -> Option<&crate::report::BuildReport> {
+        None
+    }
+
+    pub(crate) fn processing_order(&self) -> Option<&[String]> {
+        None
+    }
+
+    pub(crate) async fn warm_up(&self) -> Vec<LoadError> {
+        let mut errors = Vec::new();
+        for glob in &self.0.globs {
+            let dir = glob.dir();
+            if let Err(e) = tokio::fs::read_dir(&dir).await {
+                errors.push(LoadError {
+                    http_path: glob.mount.http_prefix().to_owned(),
+                    fs_path: Some(dir),
+                    kind: LoadErrorKind::Io(e),
+                });
+            }
+        }
+
+        // Loading via `Asset::content` also fills the cache, if enabled.
+        for http_path in self.0.assets.keys() {
+            if let Some(asset) = self.get(http_path) {
+                if let Err(e) = asset.0.content().await {
+                    errors.push(e);
+                }
+            }
+        }
+
+        errors.sort_by(|a, b| a.http_path.cmp(&b.http_path));
+        errors
+    }
+
+    /// Returns pairs of *unhashed* and *hashed HTTP paths* of all assets,
+    /// which are always the same in dev mode.
+    #[cfg(feature = "manifest")]
+    pub(crate) fn unhashed_paths(&self) -> impl '_ + Iterator<Item = (&str, &str)> {
+        self.0.assets.keys().map(|path| (&**path, &**path))
+    }
+
+    /// Executes the prod build pipeline on all assets and returns pairs of
+    /// *unhashed* and *hashed HTTP paths*, see `Assets::dry_run_prod`.
+    #[cfg(feature = "manifest")]
+    pub(crate) async fn dry_run_prod(&self) -> Result<Vec<(String, String)>, BuildError> {
+        let inner = &self.0;
+
+        // In prod mode, all files matched by globs are loaded when building.
+        let mut assets = inner.assets.clone();
+        for glob in &inner.globs {
+            for (suffix, fs_path) in walk_dir(&glob.dir(), |_| true).await? {
+                let Some(http_path) = glob.http_path_for(&suffix) else { continue };
+                if let std::collections::hash_map::Entry::Vacant(entry) = assets.entry(http_path) {
+                    let http_path = entry.key();
+                    let source = DataSource::File(fs_path);
+                    let config = glob.config.clone();
+                    let suffix = Some(http_path[glob.mount.http_prefix().len()..].to_owned());
+                    let asset = DevAsset::new(&inner.groups, http_path, source, config, suffix);
+                    entry.insert(asset);
+                }
+            }
+        }
+
+        for (status, path) in &inner.error_assets {
+            if !assets.contains_key(path.as_ref()) {
+                panic!("Error asset '{}' for status {} does not exist", path, status);
+            }
+        }
+        if let Some(path) = &inner.fallback {
+            if !assets.contains_key(path.as_ref()) {
+                panic!("Fallback asset '{}' does not exist", path);
+            }
+        }
+
+        #[cfg(feature = "modifiers")]
+        {
+            let mut raw_contents = HashMap::new();
+            let needs_raw = |asset: &DevAsset| {
+                asset.config.auto_path_fixup || matches!(asset.config.modifier, Modifier::Template(_))
+            };
+            for (path, asset) in assets.iter().filter(|(_, asset)| needs_raw(asset)) {
+                raw_contents.insert(path.clone(), asset.load_raw(path).await?);
+            }
+            crate::builder::resolve_auto_path_fixups(
+                assets.iter_mut().map(|(path, asset)| (&**path, &mut asset.config)).collect(),
+                |path| raw_contents[path].clone(),
+            );
+            crate::builder::resolve_templates(
+                assets.iter_mut().map(|(path, asset)| (&**path, &mut asset.config)).collect(),
+                |path| raw_contents[path].clone(),
+            )?;
+        }
+
+        let dep_graph = crate::dep_graph::DepGraph::of_assets(
+            assets.iter().map(|(path, asset)| (&**path, &asset.config)),
+        );
+        let sorting = dep_graph.topological_sort().map_err(|cycle| {
+            BuildError::CyclicDependencies(cycle.into_iter().map(|s| s.to_owned()).collect())
+        })?;
+
+        #[cfg(feature = "hash")]
+        let mut path_map = crate::hash::PathMap::with_hasher(&inner.hasher, &inner.hash_salt);
+        #[cfg(not(feature = "hash"))]
+        let mut path_map = crate::hash::PathMap::new();
+        let pipeline = crate::builder::Pipeline {
+            variables: &inner.variables,
+            global_modifiers: &inner.global_modifiers,
+            #[cfg(feature = "modifiers")]
+            global_replacements: &inner.global_replacements,
+            #[cfg(feature = "modifiers")]
+            warning_handler: &inner.warning_handler,
+        };
+        let mut contents = HashMap::new();
+        let mut out = Vec::with_capacity(sorting.len());
+        for path in sorting {
+            let asset = &assets[path];
+            let raw = asset.load_raw(path).await?;
+            let context_inner = || ModifierContextInner {
+                assets: inner.clone(),
+                dry_run: Some(DryRun { path_map: &path_map, contents: &contents }),
+                _dummy: PhantomData,
+            };
+            let glob_suffix = asset.glob_suffix.as_deref();
+            let content = crate::builder::process_asset(
+                &pipeline, path, &asset.config, glob_suffix, raw, &path_map, context_inner,
+            )?.content;
+
+            let final_path = crate::hash::path_of(&asset.config, path, &content, &mut path_map);
+            contents.insert(path, content);
+            out.push((path.to_owned(), final_path));
+        }
+
+        Ok(out)
+    }
+
+    pub(crate) fn runtime(&self) -> &AssetsRuntime {
+        &self.0.runtime
     }
 
     pub(crate) fn len(&self) -> usize {
         self.0.assets.len()
     }
 
+    pub(crate) fn changes(&self, changed_fs_paths: &[PathBuf]) -> ChangeEvent {
+        // Files might have been added or removed.
+        if !changed_fs_paths.is_empty() {
+            for glob in self.0.globs.iter().filter(|glob| glob.mapped_files.is_some()) {
+                glob.set_mapped_files(walk_dir_blocking(&glob.dir()).unwrap_or_default());
+            }
+        }
+
+        let is_changed = |path: &Path| changed_fs_paths.iter().any(|changed| same_file(path, changed));
+
+        // Find all assets directly loaded from a changed file.
+        let mut changed = self.0.assets.iter()
+            .filter(|(_, asset)| match &asset.source {
+                DataSource::File(path) => is_changed(path),
+                DataSource::Embedded { .. } | DataSource::Custom(_) => false,
+            })
+            .map(|(http_path, _)| http_path.clone())
+            .collect::<HashSet<_>>();
+
+        // Files not known at build time, matched by a glob.
+        for fs_path in changed_fs_paths {
+            changed.extend(self.0.globs.iter().filter_map(|glob| glob.http_path_of(fs_path)));
+        }
+
+        // Follow reverse dependencies until no new assets are found.
+        let mut affected = HashSet::default();
+        let mut queue = changed.iter().cloned().collect::<Vec<_>>();
+        while let Some(dependee) = queue.pop() {
+            for (http_path, asset) in &self.0.assets {
+                let depends = asset.config.modifier.dependencies()
+                    .is_some_and(|deps| deps.iter().any(|d| *d == dependee));
+                if depends && !changed.contains(http_path) && affected.insert(http_path.clone()) {
+                    queue.push(http_path.clone());
+                }
+            }
+        }
+
+        if let Some(cache) = &self.0.cache {
+            let mut entries = cache.entries.lock().unwrap();
+            for http_path in changed.iter().chain(&affected) {
+                entries.remove(http_path);
+            }
+        }
+
+        let mut changed = changed.into_iter().collect::<Vec<_>>();
+        let mut affected = affected.into_iter().collect::<Vec<_>>();
+        changed.sort();
+        affected.sort();
+        ChangeEvent { changed, affected }
+    }
+
     pub(crate) fn iter(&self) -> impl '_ + Iterator<Item = (&str, Asset)> {
         self.0.assets.keys().flat_map(move |key| self.get(key).map(|a| (&**key, a)))
     }
 }
 
 impl AssetsEvenMoreInner {
-    fn match_globs(&self, http_path: &str) -> Option<(PathBuf, Modifier)> {
+    /// Returns the file served under `http_path` by any glob entry, the part
+    /// of `http_path` after the entry's prefix and the entry itself.
+    fn match_globs<'p>(&self, http_path: &'p str) -> Option<(PathBuf, &'p str, &DevGlobEntry)> {
         self.globs.iter().find_map(|item| {
-            http_path.strip_prefix(&item.http_prefix)
-                .filter(|suffix| item.glob.suffix.matches(suffix))
-                .map(|suffix| (
-                    item.base_path.join(item.glob.prefix).join(suffix),
-                    item.modifier.clone(),
-                ))
+            item.lookup(http_path).map(|(path, suffix)| (path, suffix, item))
+        })
+    }
+}
+
+impl DevGlobEntry {
+    /// The directory that glob suffixes are relative to.
+    fn dir(&self) -> PathBuf {
+        self.base_path.join(self.fs_prefix)
+    }
+
+    /// Returns the *HTTP path* under which the file at `suffix` (relative to
+    /// [`Self::dir`]) is served by this entry, if it matches.
+    fn http_path_for(&self, suffix: &str) -> Option<String> {
+        let unmapped = join_glob_path(self.mount.http_prefix(), suffix);
+        if !self.mount.matches(&unmapped) || !passes_filters(&self.filters, suffix) {
+            return None;
+        }
+        match &self.path_mapper {
+            Some(mapper) => Some(join_glob_path(self.mount.http_prefix(), &mapper.apply(suffix))),
+            None => Some(unmapped),
+        }
+    }
+
+    /// Returns the file that would be served under `http_path` by this entry
+    /// and the part of `http_path` after the prefix.
+    fn lookup<'p>(&self, http_path: &'p str) -> Option<(PathBuf, &'p str)> {
+        let http_suffix = http_path.strip_prefix(self.mount.http_prefix())?;
+        match &self.path_mapper {
+            None => {
+                self.http_path_for(http_suffix)?;
+                Some((self.dir().join(http_suffix), http_suffix))
+            }
+
+            Some(_) => {
+                let files = self.mapped_files.as_ref()?.lock().unwrap();
+                files.get(http_path).map(|path| (path.clone(), http_suffix))
+            }
+        }
+    }
+
+    /// Replaces `mapped_files` (if set) by the *HTTP paths* of `files`, as
+    /// returned by `walk_dir`.
+    fn set_mapped_files(&self, files: Vec<(String, PathBuf)>) {
+        let Some(mapped_files) = &self.mapped_files else { return };
+        let mut map = HashMap::new();
+        for (suffix, path) in files {
+            if let Some(http_path) = self.http_path_for(&suffix) {
+                map.entry(http_path).or_insert(path);
+            }
+        }
+        *mapped_files.lock().unwrap() = map;
+    }
+
+    /// Returns the *HTTP path* under which the file at `fs_path` would be
+    /// served by this glob entry, if it matches.
+    fn http_path_of(&self, fs_path: &Path) -> Option<String> {
+        let dir = self.dir().canonicalize().ok()?;
+        let fs_path = fs_path.canonicalize().ok()?;
+        let suffix = fs_path.strip_prefix(dir).ok()?.to_str()?.replace('\\', "/");
+        self.http_path_for(&suffix)
+    }
+}
+
+impl DevAsset {
+    fn new(
+        groups: &[GroupBuilder<'static>],
+        http_path: &str,
+        source: DataSource,
+        mut config: EntryConfig<'static>,
+        glob_suffix: Option<String>,
+    ) -> Self {
+        apply_groups(groups, http_path, &mut config);
+        Self { source, config, glob_suffix }
+    }
+
+    /// Loads the source like `Builder::build` in prod mode would.
+    #[cfg(feature = "manifest")]
+    async fn load_raw(&self, http_path: &str) -> Result<Bytes, BuildError> {
+        self.source.load().await.map_err(|(err, fs_path)| match fs_path {
+            Some(fs_path) => BuildError::Io { err, path: fs_path.to_owned() },
+            None => BuildError::Source { err, http_path: http_path.to_owned() },
         })
     }
 }
 
+/// Replaces the prefix of `path` according to the first matching remap, see
+/// `Builder::remap_prefix`.
+fn remap_path(remaps: &[(PathBuf, PathBuf)], path: PathBuf) -> PathBuf {
+    remaps.iter()
+        .find_map(|(from, to)| path.strip_prefix(from).ok().map(|rest| to.join(rest)))
+        .unwrap_or(path)
+}
+
+/// Makes `config` `'static` so that it can be stored in `Assets`.
+fn into_static(config: EntryConfig<'_>) -> EntryConfig<'static> {
+    EntryConfig {
+        path_hash: static_path_hash(config.path_hash),
+        modifier: config.modifier,
+        cache_control: config.cache_control,
+        mime: config.mime,
+        after: config.after,
+        expected_sha256: config.expected_sha256,
+        variants: config.variants,
+        locale_variants: config.locale_variants,
+        validators: config.validators,
+        sanitize_svg: config.sanitize_svg,
+        minify_js: config.minify_js,
+        optimize_svg: config.optimize_svg,
+        prepend: config.prepend,
+        append: config.append,
+        extensions: config.extensions,
+        guard: config.guard,
+        #[cfg(feature = "metrics")]
+        metrics_tag: config.metrics_tag,
+        #[cfg(feature = "hash")]
+        hash_length: config.hash_length,
+        #[cfg(feature = "modifiers")]
+        binary_policy: config.binary_policy,
+        #[cfg(feature = "modifiers")]
+        auto_path_fixup: config.auto_path_fixup,
+    }
+}
+
+/// Hashes are never used in dev mode, but kept for
+/// [`AssetsInner::dry_run_prod`].
+fn static_path_hash(path_hash: PathHash<'_>) -> PathHash<'static> {
+    match path_hash {
+        PathHash::None => PathHash::None,
+        PathHash::Stable => PathHash::Stable,
+        #[cfg(feature = "hash")]
+        PathHash::Auto(placement) => PathHash::Auto(placement),
+        PathHash::InBetween { prefix, suffix } => PathHash::InBetween {
+            prefix: Cow::Owned(prefix.into_owned()),
+            suffix: Cow::Owned(suffix.into_owned()),
+        },
+    }
+}
+
 
 /// An asset.
 ///
@@ -116,8 +657,8 @@ impl AssetsEvenMoreInner {
 /// matters).
 #[derive(Debug, Clone)]
 pub(crate) struct AssetInner {
-    source: DataSource,
-    modifier: Modifier,
+    http_path: String,
+    asset: DevAsset,
     assets: Arc<AssetsEvenMoreInner>,
 }
 
@@ -125,49 +666,384 @@ impl AssetInner {
     /// Returns the contents of this asset. Will be loaded from the file system
     /// in dev mode, potentially returning IO errors. In prod mode, the file
     /// contents are already loaded and this method always returns `Ok(_)`.
-    pub(crate) async fn content(&self) -> Result<Bytes, io::Error> {
-        let bytes = self.source.load().await.map_err(|(e, _)| e)?;
+    pub(crate) async fn content(&self) -> Result<Bytes, LoadError> {
+        let Some(cache) = &self.assets.cache else {
+            return self.load().await;
+        };
+
+        // Serve from cache if the entry is not too old, refreshing it in the
+        // background.
+        {
+            let mut entries = cache.entries.lock().unwrap();
+            if let Some(entry) = entries.get_mut(&self.http_path) {
+                if entry.loaded_at.elapsed() <= cache.max_stale {
+                    if !entry.refreshing {
+                        let this = self.clone();
+                        entry.refreshing = self.assets.runtime.spawn(async move {
+                            let result = this.load().await;
+                            this.store_in_cache(result.ok());
+                        });
+                    }
+
+                    // After shutdown, we cannot refresh in the background.
+                    if entry.refreshing {
+                        return Ok(entry.content.clone());
+                    }
+                }
+            }
+        }
+
+        let result = self.load().await;
+        self.store_in_cache(result.as_ref().ok().cloned());
+        result
+    }
+
+    /// Stores `content` in the cache or removes the entry if it's `None`,
+    /// e.g. because loading failed.
+    fn store_in_cache(&self, content: Option<Bytes>) {
+        let Some(cache) = &self.assets.cache else { return };
+        let mut entries = cache.entries.lock().unwrap();
+        match content {
+            Some(content) => {
+                let entry = CacheEntry { content, loaded_at: Instant::now(), refreshing: false };
+                entries.insert(self.http_path.clone(), entry);
+            }
+            None => {
+                entries.remove(&self.http_path);
+            }
+        }
+    }
+
+    /// Loads the asset from its source and applies all modifications.
+    async fn load(&self) -> Result<Bytes, LoadError> {
+        #[cfg(feature = "metrics")]
+        let start = Instant::now();
+        let out = self.load_included_by(&[]).await;
+        #[cfg(feature = "metrics")]
+        crate::metrics::dev_load(start.elapsed());
+        out
+    }
+
+    /// Like `load`, with `chain` containing the *HTTP paths* of all assets
+    /// whose templates (transitively) include this asset, to detect cycles.
+    fn load_included_by<'s>(
+        &'s self,
+        chain: &'s [&'s str],
+    ) -> Pin<Box<dyn 's + Send + Future<Output = Result<Bytes, LoadError>>>> {
+        Box::pin(async move {
+            let bytes = self.load_source().await?;
+            let bytes = crate::builder::prepend_append(&self.asset.config, bytes);
+            let includes = self.load_includes(&bytes, chain).await?;
+            self.process(bytes, &includes)
+        })
+    }
 
-        // Apply modifications, if specified.
-        let modified =  match &self.modifier {
+    /// Loads all assets included by the template of this asset, if it is
+    /// configured with `EntryBuilder::with_template`.
+    #[cfg_attr(not(feature = "modifiers"), allow(unused_mut, unused_variables))]
+    async fn load_includes(
+        &self,
+        bytes: &[u8],
+        chain: &[&str],
+    ) -> Result<HashMap<String, Bytes>, LoadError> {
+        let mut out = HashMap::new();
+        #[cfg(feature = "modifiers")]
+        if let Modifier::Template(_) = self.asset.config.modifier {
+            let err = |msg| self.err(LoadErrorKind::Template(msg));
+            let template = crate::fragments::Template::parse(bytes, false).map_err(err)?;
+            let chain = chain.iter().copied().chain([&*self.http_path]).collect::<Vec<_>>();
+            let assets = AssetsInner(self.assets.clone());
+            for path in template.includes() {
+                if let Some(pos) = chain.iter().position(|p| *p == path) {
+                    let cycle = chain[pos..].iter().chain([&path]).copied().collect::<Vec<_>>();
+                    return Err(err(format!("cyclic includes: {}", cycle.join(" -> "))));
+                }
+                if out.contains_key(path) {
+                    continue;
+                }
+                let Some(asset) = assets.get(path) else {
+                    return Err(err(format!("asset '{}' does not exist", path)));
+                };
+                out.insert(path.to_owned(), asset.0.load_included_by(&chain).await?);
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// Returns the SHA-256 digest of the unmodified source.
+    pub(crate) async fn source_sha256(&self) -> Result<checksum::Sha256, LoadError> {
+        self.load_source().await.map(|bytes| checksum::Sha256::of(&bytes))
+    }
+
+    /// Returns whether loading changes the content of the source.
+    pub(crate) async fn was_modified(&self) -> Result<bool, LoadError> {
+        let source = self.load_source().await?;
+        let bytes = crate::builder::prepend_append(&self.asset.config, source.clone());
+        let includes = self.load_includes(&bytes, &[]).await?;
+        Ok(self.process(bytes, &includes)? != source)
+    }
+
+    fn err(&self, kind: LoadErrorKind) -> LoadError {
+        let fs_path = match &self.asset.source {
+            DataSource::File(path) => Some(path.clone()),
+            DataSource::Embedded { .. } | DataSource::Custom(_) => None,
+        };
+        LoadError { http_path: self.http_path.clone(), fs_path, kind }
+    }
+
+    /// Loads the source and verifies its checksum, if one is expected.
+    async fn load_source(&self) -> Result<Bytes, LoadError> {
+        let err = |kind| self.err(kind);
+        let bytes = self.asset.source.load().await.map_err(|(e, _)| err(LoadErrorKind::Io(e)))?;
+        let expected_sha256 = self.asset.config.expected_sha256;
+        checksum::verify(expected_sha256, &bytes).map_err(|actual| {
+            err(LoadErrorKind::ChecksumMismatch {
+                expected: expected_sha256.unwrap().to_string(),
+                actual: actual.to_string(),
+            })
+        })?;
+        Ok(bytes)
+    }
+
+    /// Applies all modifications to the source `bytes`, with `includes`
+    /// returned by `load_includes`.
+    #[cfg_attr(not(feature = "modifiers"), allow(unused_variables))]
+    fn process(&self, bytes: Bytes, includes: &HashMap<String, Bytes>) -> Result<Bytes, LoadError> {
+        let err = |kind| self.err(kind);
+
+        let context = |declared_deps, module_prefix| ModifierContext {
+            declared_deps,
+            module_prefix,
+            unhashed_http_path: &self.http_path,
+            glob_suffix: self.asset.glob_suffix.as_deref(),
+            variables: &self.assets.variables,
+            is_prod: false,
+            hash_configured: !matches!(self.asset.config.path_hash, PathHash::None | PathHash::Stable),
+            inner: ModifierContextInner {
+                assets: self.assets.clone(),
+                #[cfg(feature = "manifest")]
+                dry_run: None,
+                _dummy: PhantomData,
+            },
+        };
+
+        // Apply modifications, if specified. We catch panics here to be able
+        // to show a nice error instead of tearing down the request handler.
+        let catch_panic = |f: &dyn Fn() -> Bytes| panic::catch_unwind(AssertUnwindSafe(f))
+            .map_err(|payload| err(LoadErrorKind::ModifierPanic(panic_message(payload))));
+        let modified =  match &self.asset.config.modifier {
             Modifier::None => bytes,
 
             // Since in dev mode, hashed paths are not used, no
             // modifications are necessary.
+            #[cfg(feature = "modifiers")]
             Modifier::PathFixup(_) => bytes,
 
-            // The `PathMap::empty()` might allocate but we are in dev mode,
-            // we don't care.
-            Modifier::Custom { f, deps } => f(bytes, ModifierContext {
-                declared_deps: &deps,
-                inner: ModifierContextInner {
-                    assets: self.assets.clone(),
-                    _dummy: PhantomData,
-                },
-            }),
+            #[cfg(feature = "modifiers")]
+            Modifier::Template(_) => {
+                let assets = AssetsInner(self.assets.clone());
+                let resolve = |dep: &str| assets.get(dep).map(|_| dep.to_owned());
+                let include = |dep: &str| includes.get(dep).cloned();
+                crate::builder::render_template(&bytes, false, &self.assets.variables, resolve, include)
+                    .map_err(|msg| err(LoadErrorKind::Template(msg)))?
+            }
+
+            Modifier::Custom { f, deps, module_prefix } => {
+                catch_panic(&|| f(bytes.clone(), context(deps, module_prefix)))?
+            }
         };
+        let modified = self.assets.global_modifiers.iter()
+            .filter(|m| m.applies_to(&self.http_path))
+            .try_fold(modified, |content, m| catch_panic(&|| (m.f)(content.clone(), context(&[], ""))))?;
+
+        #[cfg(feature = "modifiers")]
+        let modified = crate::builder::apply_global_replacements(
+            &self.assets.global_replacements,
+            &self.http_path,
+            modified,
+        );
+
+        crate::builder::sanitize_and_validate(&self.asset.config, modified)
+            .map_err(|msg| err(LoadErrorKind::Validation(msg)))
+    }
 
-        Ok(modified)
+    /// Opens the file of this asset for streaming, if its content is not
+    /// changed or checked while loading. Returns `None` otherwise.
+    pub(crate) async fn stream_from_file(&self) -> Option<Result<ContentStream, LoadError>> {
+        let DataSource::File(path) = &self.asset.source else { return None };
+        let config = &self.asset.config;
+        #[cfg(feature = "modifiers")]
+        let has_replacements = !self.assets.global_replacements.is_empty();
+        #[cfg(not(feature = "modifiers"))]
+        let has_replacements = false;
+        let passthrough = matches!(config.modifier, Modifier::None)
+            && config.expected_sha256.is_none()
+            && config.prepend.is_none()
+            && config.append.is_none()
+            && config.validators.is_empty()
+            && !config.sanitize_svg
+            && !has_replacements
+            && !self.assets.global_modifiers.iter().any(|m| m.applies_to(&self.http_path));
+        if !passthrough {
+            return None;
+        }
+
+        Some(ContentStream::from_file(path).await.map_err(|e| LoadError {
+            http_path: self.http_path.clone(),
+            fs_path: Some(path.clone()),
+            kind: LoadErrorKind::Io(e),
+        }))
     }
 
     pub(crate) fn is_filename_hashed(&self) -> bool {
         false
     }
+
+    pub(crate) fn cache_control(&self) -> Option<&str> {
+        self.asset.config.cache_control.as_deref()
+    }
+
+    pub(crate) fn mime(&self) -> Option<&'static str> {
+        self.asset.config.mime
+            .or_else(|| crate::mime::from_path(&self.http_path, &self.assets.mime_types))
+    }
+
+    #[cfg(any(feature = "hash", feature = "http"))]
+    pub(crate) fn etag(&self) -> Option<&str> {
+        None
+    }
+
+    pub(crate) fn extension<T: 'static>(&self) -> Option<&T> {
+        self.asset.config.extensions.get()
+    }
+
+    pub(crate) fn guard(&self) -> Option<&Guard> {
+        self.asset.config.guard.as_ref()
+    }
+
+    #[cfg(feature = "metrics")]
+    pub(crate) fn metrics_tag(&self) -> Option<&str> {
+        self.asset.config.metrics_tag.as_deref()
+    }
+
+    pub(crate) fn encoded(&self) -> &[(Encoding, Bytes)] {
+        &[]
+    }
+
+    #[cfg(any(feature = "axum", feature = "http", feature = "warp"))]
+    pub(crate) fn varies_by_encoding(&self) -> bool {
+        false
+    }
+
+    pub(crate) fn last_modified(&self) -> Option<std::time::SystemTime> {
+        None
+    }
+
+    #[cfg(any(feature = "compress", feature = "gzip"))]
+    pub(crate) async fn compress_on_demand(&self, _: crate::AcceptedEncodings) -> Option<(Encoding, Bytes)> {
+        None
+    }
+
+    pub(crate) fn variants(&self) -> Vec<Asset> {
+        let assets = AssetsInner(self.assets.clone());
+        self.asset.config.variants.iter().filter_map(|path| assets.get(path)).collect()
+    }
+
+    pub(crate) fn locale_variants(&self) -> Vec<(Cow<'static, str>, Asset)> {
+        let assets = AssetsInner(self.assets.clone());
+        self.asset.config.locale_variants.iter()
+            .filter_map(|(lang, path)| Some((lang.clone(), assets.get(path)?)))
+            .collect()
+    }
+
+    pub(crate) fn dependencies(&self) -> Vec<(String, Option<&'static str>)> {
+        let assets = AssetsInner(self.assets.clone());
+        self.asset.config.modifier.dependencies().unwrap_or_default().iter()
+            .filter_map(|dep| Some((dep.to_string(), assets.get(dep)?.mime())))
+            .collect()
+    }
+
+    pub(crate) fn text_info(&self) -> TextInfo {
+        match &self.asset.source {
+            DataSource::File(path) => {
+                // We only read the start of the file. If that fails, we can
+                // still classify by extension.
+                let mut prefix = Vec::new();
+                let _ = std::fs::File::open(path)
+                    .and_then(|f| f.take(text::SNIFF_LEN as u64).read_to_end(&mut prefix));
+                text::classify(&self.http_path, &prefix)
+            }
+            DataSource::Embedded { content, .. } => text::classify(&self.http_path, content),
+
+            // We cannot load custom sources synchronously, so we can only
+            // classify by extension.
+            DataSource::Custom(_) => text::classify(&self.http_path, &[]),
+        }
+    }
+}
+
+
+/// Checks whether two paths refer to the same file, taking relative paths and
+/// symlinks into account. Deleted files can only be compared literally.
+fn same_file(a: &Path, b: &Path) -> bool {
+    a == b || match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    payload.downcast_ref::<&str>().map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "<non-string panic payload>".into())
 }
 
 
 #[derive(Debug)]
 pub(crate) struct ModifierContextInner<'a> {
     assets: Arc<AssetsEvenMoreInner>,
+    /// Only set in `AssetsInner::dry_run_prod`.
+    #[cfg(feature = "manifest")]
+    dry_run: Option<DryRun<'a>>,
     _dummy: PhantomData<&'a ()>,
 }
 
+/// State of the prod pipeline executed by `AssetsInner::dry_run_prod`.
+#[cfg(feature = "manifest")]
+#[derive(Debug)]
+struct DryRun<'a> {
+    path_map: &'a crate::hash::PathMap<'a>,
+    /// Final contents of all assets processed so far, by *unhashed HTTP
+    /// path*.
+    #[cfg_attr(not(any(feature = "hash", feature = "modifiers")), allow(dead_code))]
+    contents: &'a HashMap<&'a str, Bytes>,
+}
+
 impl<'a> ModifierContextInner<'a> {
     pub(crate) fn resolve_path<'b>(&'b self, path: &'b str) -> Option<&'b str> {
+        #[cfg(feature = "manifest")]
+        if let Some(hashed) = self.dry_run.as_ref().and_then(|d| d.path_map.get(path)) {
+            return Some(hashed);
+        }
+
         if self.assets.assets.contains_key(path) || self.assets.match_globs(path).is_some() {
             Some(path)
         } else {
             None
         }
     }
+
+    /// Dependencies are not loaded before modifiers are called in dev mode,
+    /// except in `dry_run_prod`.
+    #[cfg(any(feature = "hash", all(feature = "manifest", feature = "modifiers")))]
+    pub(crate) fn content_of(&self, unhashed_http_path: &str) -> Option<Bytes> {
+        #[cfg(feature = "manifest")]
+        if let Some(dry_run) = &self.dry_run {
+            return dry_run.contents.get(unhashed_http_path).cloned();
+        }
+
+        let _ = unhashed_http_path;
+        None
+    }
 }
@@ -1,69 +1,186 @@
-use std::{borrow::Cow, fmt, io};
+use std::{
+    borrow::Cow,
+    fmt, io,
+    path::PathBuf,
+    str::Utf8Error,
+    sync::{Arc, OnceLock},
+};
 
 use ahash::{HashMap, HashMapExt};
 use bytes::Bytes;
 
 use crate::{
-    builder::EntryBuilderKind, Asset, BuildError, Builder, DataSource, Modifier,
-    ModifierContext, EntryBuilder, PathHash,
+    builder::{apply_groups, join_glob_path, passes_filters, walk_dir, EntryBuilderKind, EntryConfig, Extensions, Pipeline}, Asset, BuildError, Builder, DataSource, LoadError,
+    EntryBuilder, Encoding, GlobMount, PathHash, AssetsRuntime, ContentStream,
+    checksum,
     dep_graph::DepGraph,
+    cache::AssetMeta,
+    guard::Guard,
     hash::PathMap,
+    text::TextInfo,
 };
 
 
 #[derive(Clone)]
 pub(crate) struct AssetsInner {
     assets: HashMap<String, Asset>,
+
+    /// Maps HTTP status codes to *hashed HTTP paths*.
+    error_assets: HashMap<u16, String>,
+
+    /// *Hashed HTTP path* of the fallback asset.
+    fallback: Option<String>,
+
+    glob_mounts: Vec<GlobMount>,
+
+    /// *Unhashed HTTP paths* in the order they were processed.
+    processing_order: Vec<String>,
+
+    /// Maps *unhashed HTTP paths* to *hashed HTTP paths*.
+    hashed_paths: HashMap<String, String>,
+
+    /// Filename set via `Builder::directory_index`.
+    directory_index: Option<Cow<'static, str>>,
+
+    runtime: AssetsRuntime,
+
+    #[cfg(feature = "report")]
+    report: crate::report::BuildReport,
 }
 
 
 #[derive(Debug, Clone)]
 pub(crate) struct AssetInner {
     content: Bytes,
+    /// Whether `content` differs from the source, e.g. due to modifiers.
+    was_modified: bool,
+    /// The file `content` was loaded from, if it was not changed while
+    /// building. Used to stream it in `Asset::content_stream`.
+    fs_path: Option<PathBuf>,
+    source_sha256: checksum::Sha256,
     hashed_filename: bool,
+    cache_control: Option<Cow<'static, str>>,
+    text_info: TextInfo,
+    mime: Option<&'static str>,
+    variants: Vec<Asset>,
+    locale_variants: Vec<(Cow<'static, str>, Asset)>,
+    /// *Hashed HTTP paths* and MIME types of the modifier dependencies.
+    dependencies: Vec<(String, Option<&'static str>)>,
+    extensions: Extensions,
+    guard: Option<Guard>,
+    #[cfg(feature = "metrics")]
+    metrics_tag: Option<Cow<'static, str>>,
+    encoded: Vec<(Encoding, Bytes)>,
+    last_modified: Option<std::time::SystemTime>,
+    /// The content as string, validated on first use by `Asset::text`.
+    text: Arc<OnceLock<Result<Arc<str>, Utf8Error>>>,
+    /// The cache and the ID of this asset in it.
+    #[cfg(any(feature = "compress", feature = "gzip"))]
+    compression_cache: Option<(std::sync::Arc<crate::encoding::CompressionCache>, usize)>,
+    #[cfg(any(feature = "hash", feature = "http"))]
+    etag: String,
+    /// CSP hashes of inline scripts and styles, only for HTML files.
+    #[cfg(feature = "hash")]
+    csp_hashes: Vec<String>,
 }
 
 impl AssetsInner {
-    pub(crate) async fn build(builder: Builder<'_>) -> Result<Self, BuildError> {
+    pub(crate) async fn build<'a>(builder: Builder<'a>) -> Result<Self, BuildError> {
         // First we flatten our entries into a list of files to be loaded/resolved.
-        let mut unresolved = HashMap::with_capacity(builder.assets.len());
-        for EntryBuilder { kind, path_hash, modifier } in builder.assets {
+        let mut unresolved = HashMap::<String, UnresolvedAsset<'a>>::with_capacity(builder.assets.len());
+        let cache_policy = builder.cache_policy;
+        #[cfg(feature = "modifiers")]
+        let global_replacements = builder.global_replacements;
+        let global_modifiers = builder.global_modifiers;
+        let variables = builder.variables;
+        #[cfg(feature = "modifiers")]
+        let warning_handler = builder.warning_handler;
+        let groups = &builder.groups;
+        let mime_types = builder.mime_types;
+        #[cfg(feature = "gzip")]
+        let gzip = builder.gzip;
+        #[cfg(feature = "compress")]
+        let compress_runtime_files = builder.compress_runtime_files;
+        #[cfg(any(feature = "compress", feature = "gzip"))]
+        let compression_cache = builder.compression_cache_budget
+            .map(|budget| std::sync::Arc::new(crate::encoding::CompressionCache::new(budget)));
+        let allow_duplicate_paths = builder.allow_duplicate_paths;
+        let mut add = |http_path: String, mut asset: UnresolvedAsset<'a>| {
+            apply_groups(groups, &http_path, &mut asset.config);
+            match unresolved.get(&http_path) {
+                Some(prev) if !allow_duplicate_paths => Err(BuildError::DuplicatePath {
+                    first: prev.source.describe(),
+                    second: asset.source.describe(),
+                    http_path,
+                }),
+                _ => {
+                    unresolved.insert(http_path, asset);
+                    Ok(())
+                }
+            }
+        };
+        let mut glob_mounts = Vec::new();
+        for EntryBuilder { kind, config } in builder.assets {
+            glob_mounts.extend(kind.glob_mount());
             match kind {
                 EntryBuilderKind::Single { http_path, source } => {
-                    unresolved.insert(http_path.into_owned(), UnresolvedAsset {
-                        source,
-                        modifier,
-                        path_hash,
-                    });
+                    add(http_path.into_owned(), UnresolvedAsset { source, config, glob_suffix: None })?;
                 }
                 EntryBuilderKind::Glob { http_prefix, files, .. } => {
                     for file in files {
                         let key = file.http_path(http_prefix.as_ref());
                         let value = UnresolvedAsset {
+                            glob_suffix: Some(file.http_suffix().to_owned()),
                             source: file.source,
-                            modifier: modifier.clone(),
-                            path_hash,
+                            config: config.clone(),
                         };
-                        unresolved.insert(key, value);
+                        add(key, value)?;
+                    }
+                }
+                EntryBuilderKind::Dir { http_prefix, fs_path, pattern, filters, path_mapper } => {
+                    let filter = |path: &str| pattern.matches(path) && passes_filters(&filters, path);
+                    for (suffix, path) in walk_dir(&fs_path, filter).await? {
+                        let suffix = match &path_mapper {
+                            Some(mapper) => mapper.apply(&suffix),
+                            None => suffix,
+                        };
+                        let http_path = join_glob_path(&http_prefix, &suffix);
+                        let value = UnresolvedAsset {
+                            source: DataSource::File(path),
+                            config: config.clone(),
+                            glob_suffix: Some(suffix),
+                        };
+                        add(http_path, value)?;
                     }
                 }
             };
         }
 
+        let mut raw_contents = load_all(&unresolved, builder.max_concurrent_reads).await?;
+        #[cfg(feature = "modifiers")]
+        crate::builder::resolve_auto_path_fixups(
+            unresolved.iter_mut().map(|(path, asset)| (&**path, &mut asset.config)).collect(),
+            |path| raw_contents[path].clone(),
+        );
+        #[cfg(feature = "modifiers")]
+        crate::builder::resolve_templates(
+            unresolved.iter_mut().map(|(path, asset)| (&**path, &mut asset.config)).collect(),
+            |path| raw_contents[path].clone(),
+        )?;
+
         // Next: build the dep graph.
-        let mut dep_graph = DepGraph::new();
-        for (unhashed_http_path, asset) in &unresolved {
-            dep_graph.add_asset(&unhashed_http_path);
-            if let Some(deps) = asset.modifier.dependencies() {
-                for dep in deps {
-                    if !unresolved.contains_key(dep.as_ref()) {
-                        panic!(
-                            "Asset '{}' specified dependency '{}' but that asset does not exist",
-                            unhashed_http_path, dep,
-                        );
-                    }
-                    dep_graph.add_dependency(&unhashed_http_path, &dep);
-                }
+        let dep_graph = DepGraph::of_assets(
+            unresolved.iter().map(|(path, asset)| (&**path, &asset.config)),
+        );
+
+        for (status, path) in &builder.error_assets {
+            if !unresolved.contains_key(path.as_ref()) {
+                panic!("Error asset '{}' for status {} does not exist", path, status);
+            }
+        }
+        if let Some(path) = &builder.fallback {
+            if !unresolved.contains_key(path.as_ref()) {
+                panic!("Fallback asset '{}' does not exist", path);
             }
         }
 
@@ -72,43 +189,227 @@ impl AssetsInner {
             BuildError::CyclicDependencies(cycle.into_iter().map(|s| s.to_owned()).collect())
         })?;
         let mut assets = HashMap::new();
+        #[cfg(feature = "hash")]
+        let mut path_map = PathMap::with_hasher(&builder.hasher, &builder.hash_salt);
+        #[cfg(not(feature = "hash"))]
         let mut path_map = PathMap::new();
+        let pipeline = Pipeline {
+            variables: &variables,
+            global_modifiers: &global_modifiers,
+            #[cfg(feature = "modifiers")]
+            global_replacements: &global_replacements,
+            #[cfg(feature = "modifiers")]
+            warning_handler: &warning_handler,
+        };
+        let mut error_assets = HashMap::new();
+        let mut final_paths = HashMap::new();
+        #[cfg(feature = "report")]
+        let mut reports = Vec::new();
+        let processing_order = sorting.iter().map(|&path| path.to_owned()).collect();
         for path in sorting {
             let asset = unresolved.get(path).unwrap();
 
-            // Apply modifier
-            let raw = asset.source.load().await
-                .map_err(|(err, path)| BuildError::Io { err, path: path.to_owned() })?;
-            let content = match &asset.modifier {
-                Modifier::None => raw,
-                Modifier::PathFixup(paths) => path_fixup(raw, paths, &path_map),
-                Modifier::Custom { f, deps } => {
-                    f(raw, ModifierContext {
-                        declared_deps: &deps,
-                        inner: ModifierContextInner {
-                            path_map: &path_map,
-                            unresolved: &unresolved,
-                        },
-                    })
-                },
+            let raw = raw_contents.remove(path).unwrap();
+            let source_sha256 = checksum::Sha256::of(&raw);
+            let original = raw.clone();
+            let context_inner = || ModifierContextInner {
+                path_map: &path_map,
+                unresolved: &unresolved,
+                assets: &assets,
+                final_paths: &final_paths,
+            };
+            let glob_suffix = asset.glob_suffix.as_deref();
+            let processed = crate::builder::process_asset(
+                &pipeline, path, &asset.config, glob_suffix, raw, &path_map, context_inner,
+            )?;
+            let content = processed.content;
+
+            // Stored encoded forms can only be served if the content did not
+            // change.
+            #[cfg_attr(not(any(feature = "compress", feature = "gzip")), allow(unused_mut))]
+            let mut encoded = match &asset.source {
+                DataSource::Embedded { encoded, .. } if content == original => encoded.clone(),
+                _ => vec![],
+            };
+            #[cfg(feature = "compress")]
+            if compress_runtime_files && encoded.is_empty() {
+                encoded.extend(crate::encoding::brotli(&content).map(|c| (Encoding::Brotli, c)));
+            }
+            #[cfg(feature = "gzip")]
+            if gzip {
+                encoded.extend(crate::encoding::gzip(&content).map(|c| (Encoding::Gzip, c)));
+            }
+
+            // The source's modification time says nothing about content
+            // produced by modifiers, which might depend on other assets.
+            let last_modified = match content == original {
+                true => asset.source.modified().await,
+                false => None,
             };
 
             // Potentially hash filename
-            let final_path = crate::hash::path_of(asset.path_hash, &path, &content, &mut path_map);
+            let final_path = crate::hash::path_of(&asset.config, path, &content, &mut path_map);
+
+            for (status, _) in builder.error_assets.iter().filter(|(_, p)| *p == path) {
+                error_assets.insert(*status, final_path.clone());
+            }
+
+            let dependencies = asset.config.modifier.dependencies().unwrap_or_default().iter()
+                .map(|dep| {
+                    let final_path = &final_paths[dep.as_ref()];
+                    (final_path.clone(), assets[final_path].0.mime)
+                })
+                .collect();
+
+            let text_info = crate::text::classify(path, &content);
+            let mime = asset.config.mime.or_else(|| crate::mime::from_path(path, &mime_types));
+            let hashed_filename = !matches!(asset.config.path_hash, PathHash::None | PathHash::Stable);
+            let cache_control = asset.config.cache_control.clone().or_else(|| {
+                cache_policy.as_ref().map(|policy| policy.header_value(&AssetMeta {
+                    unhashed_http_path: path,
+                    http_path: &final_path,
+                    is_filename_hashed: hashed_filename,
+                }))
+            });
+
+            #[cfg(feature = "report")]
+            reports.push(crate::report::AssetReport {
+                unhashed_http_path: path.to_owned(),
+                http_path: final_path.clone(),
+                source: crate::report::ReportSource::of(&asset.source),
+                source_sha256: source_sha256.to_string(),
+                transformations: processed.transformations,
+                sha256: crate::checksum::Sha256::of(&content).to_string(),
+                size: content.len(),
+                cache_control: cache_control.as_deref().map(Into::into),
+            });
 
-            assets.insert(final_path, Asset(AssetInner {
+            assets.insert(final_path.clone(), Asset(AssetInner {
+                #[cfg(any(feature = "hash", feature = "http"))]
+                etag: crate::checksum::etag(&content),
+                #[cfg(feature = "hash")]
+                csp_hashes: match mime == Some("text/html") {
+                    true => crate::csp::inline_hashes(&content),
+                    false => vec![],
+                },
+                was_modified: content != original,
+                fs_path: match &asset.source {
+                    DataSource::File(path) if content == original => Some(path.clone()),
+                    _ => None,
+                },
+                source_sha256,
                 content,
-                hashed_filename: !matches!(asset.path_hash, PathHash::None),
+                text_info,
+                hashed_filename,
+                cache_control,
+                mime,
+                variants: vec![],
+                locale_variants: vec![],
+                dependencies,
+                extensions: asset.config.extensions.clone(),
+                guard: asset.config.guard.clone(),
+                #[cfg(feature = "metrics")]
+                metrics_tag: asset.config.metrics_tag.clone(),
+                encoded,
+                last_modified,
+                text: Default::default(),
+                #[cfg(any(feature = "compress", feature = "gzip"))]
+                compression_cache: compression_cache.clone().map(|cache| (cache, assets.len())),
             }));
+            final_paths.insert(path, final_path);
+        }
+
+        // Attach variants. They are cloned before any variants are attached,
+        // so variants never have variants themselves.
+        let variants = unresolved.iter()
+            .filter(|(_, asset)| {
+                !asset.config.variants.is_empty() || !asset.config.locale_variants.is_empty()
+            })
+            .map(|(path, asset)| {
+                let get = |path: &str| assets[&final_paths[path]].clone();
+                let variants = asset.config.variants.iter()
+                    .map(|v| get(v))
+                    .collect::<Vec<_>>();
+                let locale_variants = asset.config.locale_variants.iter()
+                    .map(|(lang, v)| (lang.clone(), get(v)))
+                    .collect::<Vec<_>>();
+                (&final_paths[path.as_str()], variants, locale_variants)
+            })
+            .collect::<Vec<_>>();
+        for (final_path, variants, locale_variants) in variants {
+            let asset = &mut assets.get_mut(final_path).unwrap().0;
+            asset.variants = variants;
+            asset.locale_variants = locale_variants;
         }
 
-        Ok(Self { assets })
+        #[cfg(feature = "report")]
+        let report = {
+            reports.sort_by(|a, b| a.unhashed_http_path.cmp(&b.unhashed_http_path));
+            crate::report::BuildReport { assets: reports }
+        };
+
+        Ok(Self {
+            assets,
+            error_assets,
+            fallback: builder.fallback.as_ref().map(|path| final_paths[path.as_ref()].clone()),
+            glob_mounts,
+            processing_order,
+            hashed_paths: final_paths.into_iter().map(|(k, v)| (k.to_owned(), v)).collect(),
+            directory_index: builder.directory_index,
+            runtime: AssetsRuntime::new(),
+            #[cfg(feature = "report")]
+            report,
+        })
     }
 
     pub(crate) fn get(&self, http_path: &str) -> Option<Asset> {
         self.assets.get(http_path).cloned()
     }
 
+    pub(crate) fn get_unhashed(&self, unhashed_http_path: &str) -> Option<Asset> {
+        self.hashed_paths.get(unhashed_http_path).and_then(|path| self.get(path))
+    }
+
+    pub(crate) fn directory_index(&self) -> Option<&str> {
+        self.directory_index.as_deref()
+    }
+
+    pub(crate) fn error_asset(&self, status: u16) -> Option<Asset> {
+        self.error_assets.get(&status).and_then(|path| self.get(path))
+    }
+
+    pub(crate) fn fallback(&self) -> Option<Asset> {
+        self.fallback.as_ref().and_then(|path| self.get(path))
+    }
+
+    pub(crate) fn glob_mounts(&self) -> impl '_ + Iterator<Item = &GlobMount> {
+        self.glob_mounts.iter()
+    }
+
+    pub(crate) fn processing_order(&self) -> Option<&[String]> {
+        Some(&self.processing_order)
+    }
+
+    /// Everything was already loaded in `build`.
+    pub(crate) async fn warm_up(&self) -> Vec<LoadError> {
+        Vec::new()
+    }
+
+    /// Returns pairs of *unhashed* and *hashed HTTP paths* of all assets.
+    #[cfg(feature = "manifest")]
+    pub(crate) fn unhashed_paths(&self) -> impl '_ + Iterator<Item = (&str, &str)> {
+        self.hashed_paths.iter().map(|(k, v)| (&**k, &**v))
+    }
+
+    #[cfg(feature = "report")]
+    pub(crate) fn build_report(&self) -> Option<&crate::report::BuildReport> {
+        Some(&self.report)
+    }
+
+    pub(crate) fn runtime(&self) -> &AssetsRuntime {
+        &self.runtime
+    }
+
     pub(crate) fn len(&self) -> usize {
         self.assets.len()
     }
@@ -128,27 +429,127 @@ impl AssetInner {
     /// Returns the contents of this asset. Will be loaded from the file system
     /// in dev mode, potentially returning IO errors. In prod mode, the file
     /// contents are already loaded and this method always returns `Ok(_)`.
-    pub(crate) async fn content(&self) -> Result<Bytes, io::Error> {
+    pub(crate) async fn content(&self) -> Result<Bytes, LoadError> {
         Ok(self.content.clone())
     }
 
+    /// Streams the content from the file it was loaded from, if any, and if
+    /// that file was not changed since building.
+    pub(crate) async fn stream_from_file(&self) -> Option<Result<ContentStream, LoadError>> {
+        let path = self.fs_path.as_ref()?;
+        let len = self.content.len() as u64;
+        ContentStream::from_unchanged_file(path, len, self.last_modified).await.ok()?.map(Ok)
+    }
+
+    pub(crate) fn was_modified(&self) -> bool {
+        self.was_modified
+    }
+
+    pub(crate) fn source_sha256(&self) -> checksum::Sha256 {
+        self.source_sha256
+    }
+
     pub(crate) fn is_filename_hashed(&self) -> bool {
         self.hashed_filename
     }
+
+    pub(crate) fn cache_control(&self) -> Option<&str> {
+        self.cache_control.as_deref()
+    }
+
+    pub(crate) fn mime(&self) -> Option<&'static str> {
+        self.mime
+    }
+
+    #[cfg(any(feature = "hash", feature = "http"))]
+    pub(crate) fn etag(&self) -> Option<&str> {
+        Some(&self.etag)
+    }
+
+    #[cfg(feature = "hash")]
+    pub(crate) fn csp_hashes(&self) -> &[String] {
+        &self.csp_hashes
+    }
+
+    pub(crate) fn extension<T: 'static>(&self) -> Option<&T> {
+        self.extensions.get()
+    }
+
+    pub(crate) fn guard(&self) -> Option<&Guard> {
+        self.guard.as_ref()
+    }
+
+    #[cfg(feature = "metrics")]
+    pub(crate) fn metrics_tag(&self) -> Option<&str> {
+        self.metrics_tag.as_deref()
+    }
+
+    pub(crate) fn encoded(&self) -> &[(Encoding, Bytes)] {
+        &self.encoded
+    }
+
+    /// Returns whether `Asset::content_encoded` might return encoded content.
+    #[cfg(any(feature = "axum", feature = "http", feature = "warp"))]
+    pub(crate) fn varies_by_encoding(&self) -> bool {
+        #[cfg(any(feature = "compress", feature = "gzip"))]
+        if self.compression_cache.is_some() {
+            return true;
+        }
+        !self.encoded.is_empty()
+    }
+
+    pub(crate) fn last_modified(&self) -> Option<std::time::SystemTime> {
+        self.last_modified
+    }
+
+    pub(crate) fn text(&self) -> Result<Arc<str>, Utf8Error> {
+        self.text.get_or_init(|| std::str::from_utf8(&self.content).map(Into::into)).clone()
+    }
+
+    #[cfg(any(feature = "compress", feature = "gzip"))]
+    pub(crate) async fn compress_on_demand(
+        &self,
+        accepted: crate::AcceptedEncodings,
+    ) -> Option<(Encoding, Bytes)> {
+        let (cache, id) = self.compression_cache.as_ref()?;
+        cache.get(*id, &self.content, accepted).await
+    }
+
+    pub(crate) fn variants(&self) -> Vec<Asset> {
+        self.variants.clone()
+    }
+
+    pub(crate) fn locale_variants(&self) -> Vec<(Cow<'static, str>, Asset)> {
+        self.locale_variants.clone()
+    }
+
+    pub(crate) fn dependencies(&self) -> Vec<(String, Option<&'static str>)> {
+        self.dependencies.clone()
+    }
+
+    pub(crate) fn text_info(&self) -> TextInfo {
+        self.text_info
+    }
 }
 
 
 #[derive(Debug)]
 struct UnresolvedAsset<'a> {
     source: DataSource,
-    modifier: Modifier,
-    path_hash: PathHash<'a>,
+    config: EntryConfig<'a>,
+    /// Path relative to the HTTP prefix, for assets matched by a glob.
+    glob_suffix: Option<String>,
 }
 
 #[derive(Debug)]
 pub(crate) struct ModifierContextInner<'a> {
     path_map: &'a PathMap<'a>,
     unresolved: &'a HashMap<String, UnresolvedAsset<'a>>,
+    /// All assets built so far, which includes all dependencies.
+    #[cfg_attr(not(any(feature = "hash", feature = "modifiers")), allow(dead_code))]
+    assets: &'a HashMap<String, Asset>,
+    #[cfg_attr(not(any(feature = "hash", feature = "modifiers")), allow(dead_code))]
+    final_paths: &'a HashMap<&'a str, String>,
 }
 
 impl<'a> ModifierContextInner<'a> {
@@ -161,21 +562,52 @@ impl<'a> ModifierContextInner<'a> {
             }
         })
     }
+
+    /// Returns the final content of the given dependency.
+    #[cfg(any(feature = "hash", feature = "modifiers"))]
+    pub(crate) fn content_of(&self, unhashed_http_path: &str) -> Option<Bytes> {
+        let final_path = self.final_paths.get(unhashed_http_path)?;
+        Some(self.assets[final_path].0.content.clone())
+    }
 }
 
-fn path_fixup(original: Bytes, paths: &[Cow<'static, str>], path_map: &PathMap) -> Bytes {
-    use aho_corasick::AhoCorasick;
-
-    let needles = paths.iter()
-        .map(AsRef::as_ref)
-        .filter(|path| path_map.get(path).is_some());
-    let replacer = AhoCorasick::new(needles).unwrap();
-    let mut out = Vec::with_capacity(original.len());
-    replacer.replace_all_with_bytes(&original, &mut out, |_, needle, out| {
-        let needle = std::str::from_utf8(needle).unwrap(); // Input was str
-        let hashed = path_map.get(needle).unwrap(); // we checked this above
-        out.extend_from_slice(hashed.as_bytes());
-        true
-    });
-    out.into()
+/// Loads the sources of all assets, reading at most `max_concurrent` files
+/// from the file system at the same time. Returns a map from *unhashed HTTP
+/// path* to raw content.
+async fn load_all(
+    unresolved: &HashMap<String, UnresolvedAsset<'_>>,
+    max_concurrent: usize,
+) -> Result<HashMap<String, Bytes>, BuildError> {
+    type TaskOutput = (String, Result<Bytes, (io::Error, Option<PathBuf>)>);
+
+    let mut out = HashMap::with_capacity(unresolved.len());
+    let mut tasks = tokio::task::JoinSet::<TaskOutput>::new();
+    let mut handle_result = |result: Result<TaskOutput, tokio::task::JoinError>| {
+        let (http_path, result) = result.expect("task reading file panicked");
+        let bytes = match result {
+            Ok(bytes) => bytes,
+            Err((err, Some(path))) => return Err(BuildError::Io { err, path }),
+            Err((err, None)) => return Err(BuildError::Source { err, http_path }),
+        };
+        out.insert(http_path, bytes);
+        Ok::<_, BuildError>(())
+    };
+
+    for (http_path, asset) in unresolved {
+        while tasks.len() >= max_concurrent {
+            handle_result(tasks.join_next().await.unwrap())?;
+        }
+
+        let http_path = http_path.clone();
+        let source = asset.source.clone();
+        tasks.spawn(async move {
+            let result = source.load().await.map_err(|(err, path)| (err, path.map(Into::into)));
+            (http_path, result)
+        });
+    }
+    while let Some(result) = tasks.join_next().await {
+        handle_result(result)?;
+    }
+
+    Ok(out)
 }
@@ -0,0 +1,181 @@
+//! Rendering self-contained HTML documents, see
+//! [`Assets::render_inlined`][crate::Assets::render_inlined].
+
+use std::io;
+
+use base64::Engine;
+
+use crate::{
+    sri::{attr_value, find_tag},
+    Assets, LoadError, LoadErrorKind, TextError,
+};
+
+
+impl Assets {
+    /// Returns the HTML asset with the given *hashed HTTP path* as fully
+    /// self-contained document, e.g. for emails or PDF rendering: stylesheets
+    /// referenced via `<link rel="stylesheet">` are inlined as `<style>`
+    /// blocks and images referenced via `<img src>` or CSS `url()` are
+    /// converted to `data:` URIs.
+    ///
+    /// References are resolved relative to the referencing file. Absolute
+    /// paths have to start with `/` followed by `url_prefix`, the path under
+    /// which the assets are served. References that do not resolve to an
+    /// asset (e.g. external URLs) are left unchanged.
+    ///
+    /// ```no_run
+    /// # async fn foo(assets: reinda::Assets) -> Result<(), reinda::TextError> {
+    /// let email = assets.render_inlined("emails/welcome.html", "assets/").await?;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// Method is only available if the crate feature `hash` is enabled.
+    pub async fn render_inlined(&self, http_path: &str, url_prefix: &str) -> Result<String, TextError> {
+        let html = self.load_text(http_path).await?;
+        let base = parent(http_path);
+
+        let mut out = String::with_capacity(html.len());
+        let mut pos = 0;
+        while let Some(start) = find_tag(html.as_bytes(), pos, &["link", "img"]) {
+            let Some(end) = html[start..].find('>').map(|i| start + i + 1) else {
+                break;
+            };
+            let tag = &html[start..end];
+            out.push_str(&html[pos..start]);
+            pos = end;
+
+            let is_img = tag[1..4].eq_ignore_ascii_case("img");
+            let is_stylesheet = !is_img && attr_value(tag, "rel")
+                .is_some_and(|rel| rel.eq_ignore_ascii_case("stylesheet"));
+            let href = attr_value(tag, "href").filter(|_| is_stylesheet);
+            let src = attr_value(tag, "src").filter(|_| is_img);
+            if let Some(path) = href.and_then(|href| resolve(base, href, url_prefix)) {
+                let css = self.load_text(&path).await?;
+                let css = self.inline_urls(&css, parent(&path), url_prefix).await?;
+                match attr_value(tag, "media") {
+                    Some(media) => out.push_str(&format!("<style media=\"{}\">", media)),
+                    None => out.push_str("<style>"),
+                }
+                out.push_str(&css);
+                out.push_str("</style>");
+            } else if let Some(data_uri) = self.data_uri_for(src, base, url_prefix).await? {
+                let src = src.unwrap();
+                let offset = src.as_ptr() as usize - tag.as_ptr() as usize;
+                out.push_str(&tag[..offset]);
+                out.push_str(&data_uri);
+                out.push_str(&tag[offset + src.len()..]);
+            } else {
+                out.push_str(tag);
+            }
+        }
+        out.push_str(&html[pos..]);
+
+        // `url()` in `<style>` blocks and `style` attributes.
+        self.inline_urls(&out, base, url_prefix).await
+    }
+
+    /// Replaces all `url()` references in `css` that resolve to an asset by
+    /// `data:` URIs.
+    async fn inline_urls(&self, css: &str, base: &str, url_prefix: &str) -> Result<String, TextError> {
+        let mut out = String::with_capacity(css.len());
+        let mut pos = 0;
+        while let Some(start) = css[pos..].find("url(").map(|i| pos + i + "url(".len()) {
+            let Some(end) = css[start..].find(')').map(|i| start + i) else {
+                break;
+            };
+            let reference = css[start..end].trim().trim_matches(|c| c == '"' || c == '\'');
+            out.push_str(&css[pos..start]);
+            match self.data_uri_for(Some(reference), base, url_prefix).await? {
+                Some(data_uri) => out.push_str(&format!("\"{}\"", data_uri)),
+                None => out.push_str(&css[start..end]),
+            }
+            pos = end;
+        }
+        out.push_str(&css[pos..]);
+        Ok(out)
+    }
+
+    /// Returns the `data:` URI of the asset `reference` resolves to, if any.
+    async fn data_uri_for(
+        &self,
+        reference: Option<&str>,
+        base: &str,
+        url_prefix: &str,
+    ) -> Result<Option<String>, TextError> {
+        let Some(asset) = reference
+            .and_then(|r| resolve(base, r, url_prefix))
+            .and_then(|path| self.get(&path))
+        else {
+            return Ok(None);
+        };
+
+        let content = asset.content().await.map_err(TextError::Load)?;
+        let mime = asset.mime().unwrap_or("application/octet-stream");
+        let encoded = base64::engine::general_purpose::STANDARD.encode(content);
+        Ok(Some(format!("data:{};base64,{}", mime, encoded)))
+    }
+
+    async fn load_text(&self, http_path: &str) -> Result<String, TextError> {
+        let asset = self.get(http_path).ok_or_else(|| TextError::Load(LoadError {
+            http_path: http_path.to_owned(),
+            fs_path: None,
+            kind: LoadErrorKind::Io(io::ErrorKind::NotFound.into()),
+        }))?;
+        asset.text().await
+    }
+}
+
+/// Returns the directory part of `path`, including the trailing `/`.
+fn parent(path: &str) -> &str {
+    path.rfind('/').map_or("", |i| &path[..=i])
+}
+
+/// Resolves `reference`, found in a file in directory `base`, to an *HTTP
+/// path*. Returns `None` for URLs with scheme, protocol-relative URLs,
+/// absolute paths outside of `url_prefix` and paths leaving the root.
+fn resolve(base: &str, reference: &str, url_prefix: &str) -> Option<String> {
+    let reference = reference.split(['?', '#']).next().unwrap_or_default();
+    if reference.is_empty() || reference.starts_with("//") || reference.contains(':') {
+        return None;
+    }
+
+    let path = match reference.strip_prefix('/') {
+        Some(absolute) => absolute.strip_prefix(url_prefix.trim_start_matches('/'))?.to_owned(),
+        None => format!("{}{}", base, reference),
+    };
+
+    let mut segments = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => { segments.pop()?; }
+            s => segments.push(s),
+        }
+    }
+    Some(segments.join("/"))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_references() {
+        assert_eq!(resolve("", "style.css", "").as_deref(), Some("style.css"));
+        assert_eq!(resolve("static/", "../fonts/a.woff2?v=1", "").as_deref(), Some("fonts/a.woff2"));
+        assert_eq!(resolve("static/", "/assets/logo.png", "assets/").as_deref(), Some("logo.png"));
+        assert_eq!(resolve("static/", "/assets/logo.png", "/assets/").as_deref(), Some("logo.png"));
+        assert_eq!(resolve("", "/other/logo.png", "assets/"), None);
+        assert_eq!(resolve("", "../logo.png", ""), None);
+        assert_eq!(resolve("", "https://example.com/a.png", ""), None);
+        assert_eq!(resolve("", "//example.com/a.png", ""), None);
+        assert_eq!(resolve("", "data:image/png;base64,AAAA", ""), None);
+    }
+
+    #[test]
+    fn parents() {
+        assert_eq!(parent("index.html"), "");
+        assert_eq!(parent("static/css/style.css"), "static/css/");
+    }
+}
@@ -0,0 +1,83 @@
+//! Merging JSON files, see [`Builder::add_merged_json`][crate::Builder::add_merged_json].
+
+use std::{io, path::PathBuf};
+
+use bytes::Bytes;
+use serde_json::Value;
+
+use crate::AssetSource;
+
+
+/// Source that loads several JSON files and deep-merges them into one.
+pub(crate) struct MergedJson {
+    pub(crate) paths: Vec<PathBuf>,
+}
+
+impl AssetSource for MergedJson {
+    async fn load(&self) -> io::Result<Bytes> {
+        let mut out = Value::Object(Default::default());
+        for path in &self.paths {
+            let raw = tokio::fs::read(path).await.map_err(|e| {
+                io::Error::new(e.kind(), format!("failed to read '{}': {}", path.display(), e))
+            })?;
+            let value = serde_json::from_slice(&raw).map_err(|e| io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("'{}' is not valid JSON: {}", path.display(), e),
+            ))?;
+            merge(&mut out, value);
+        }
+
+        Ok(serde_json::to_vec(&out)?.into())
+    }
+}
+
+/// Merges `overlay` into `base`: objects are merged recursively, all other
+/// values (including arrays) in `overlay` replace those in `base`.
+fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => { base.insert(key, value); }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::*;
+
+    #[test]
+    fn deep_merge() {
+        let mut base = json!({
+            "api": { "url": "http://localhost", "timeout": 5 },
+            "features": ["a", "b"],
+            "debug": true,
+        });
+        merge(&mut base, json!({
+            "api": { "url": "https://example.com" },
+            "features": ["c"],
+            "debug": null,
+            "new": { "x": 1 },
+        }));
+        assert_eq!(base, json!({
+            "api": { "url": "https://example.com", "timeout": 5 },
+            "features": ["c"],
+            "debug": null,
+            "new": { "x": 1 },
+        }));
+    }
+
+    #[test]
+    fn non_object_base() {
+        let mut base = json!([1, 2]);
+        merge(&mut base, json!({ "a": 1 }));
+        assert_eq!(base, json!({ "a": 1 }));
+    }
+}
@@ -108,12 +108,111 @@
 //!
 //! - **`compress`** (enabled by default): if enabled, embedded files are
 //!   compressed. This often noticably reduces the binary size of the
-//!   executable. This feature adds the `brotli` dependency.
+//!   executable. The compressed form can also be served directly to clients
+//!   accepting Brotli, see [`Asset::content_encoded`]. This feature adds the
+//!   `brotli` dependency.
+//!
+//! - **`compress-zstd`**: allows `embed!` to compress files with zstd instead
+//!   (see the `compression` field of [`embed!`]), which can then be served to
+//!   clients accepting `zstd`. Adds the `zstd` dependency.
+//!
+//! - **`full`** (enabled by default): enables [`Assets`] and [`Builder`]
+//!   with all their configuration. Without it, only [`minimal::Assets`] is
+//!   available, which is enough for tiny services that just want [`embed!`]
+//!   and hashed filenames. This feature adds the `ahash`, `bytes`, `sha2`
+//!   and `tokio` dependencies.
 //!
 //! - **`hash`** (enabled by default): is required for support of filename
 //!   hashing (see above). This feature adds the `base64` and `sha2`
 //!   dependencies.
 //!
+//! - **`blake3`**, **`xxhash`**: enable [`Blake3Hasher`] and [`Xxh3Hasher`]
+//!   to use instead of SHA-256 for filename hashes, see
+//!   [`Builder::content_hasher`]. These features add the `blake3` and
+//!   `xxhash-rust` dependencies, respectively.
+//!
+//! - **`modifiers`** (enabled by default): enables the built-in modifiers
+//!   [`EntryBuilder::with_path_fixup`] and [`Builder::global_replace`] as
+//!   well as the replace functions in [`util`]. Custom modifiers via
+//!   [`EntryBuilder::with_modifier`] are always available. This feature adds
+//!   the `aho-corasick` dependency.
+//!
+//! - **`sanitize-svg`**: enables [`util::sanitize_svg`] and
+//!   [`EntryBuilder::sanitize_svg`] to remove scripts and external references
+//!   from SVG files. This feature adds the `xmlparser` dependency.
+//!
+//! - **`optimize-svg`**: enables [`util::optimize_svg`] and
+//!   [`EntryBuilder::optimize_svg`] to strip metadata, comments and
+//!   whitespace from SVG files in prod mode. This feature adds the
+//!   `xmlparser` dependency.
+//!
+//! - **`minify-js`**: enables [`util::minify_js`] and
+//!   [`EntryBuilder::minify_js`] to minify JavaScript files in prod mode.
+//!   This feature adds the `minify-js` dependency.
+//!
+//! - **`axum`**: enables [`Assets::into_router`] to serve assets with
+//!   `axum`. This feature adds the `axum` dependency.
+//!
+//! - **`tower`**: enables [`Assets::into_service`] to serve assets as
+//!   `tower::Service`. Implies `http` and adds the `http-body-util` and
+//!   `tower-service` dependencies.
+//!
+//! - **`warp`**: enables [`warp::assets_filter`] to serve assets with
+//!   `warp`. This feature adds the `warp` dependency.
+//!
+//! - **`http`**: enables [`Asset::response`] to build responses for any
+//!   stack based on the `http` crate. This feature adds the `http` and
+//!   `sha2` dependencies.
+//!
+//! - **`http-body`**: enables [`AssetBody`], a response body implementing
+//!   `http_body::Body`. This feature adds the `http-body` dependency.
+//!
+//! - **`json`**: enables [`Builder::add_merged_json`] to serve several
+//!   deep-merged JSON files as one asset. This feature adds the `serde_json`
+//!   dependency.
+//!
+//! - **`minijinja`**: enables [`Assets::minijinja_loader`] to use assets as
+//!   `minijinja` templates. This feature adds the `minijinja` dependency.
+//!
+//! - **`tera`**: enables [`Assets::to_tera`] to use assets as `tera`
+//!   templates. This feature adds the `tera` dependency.
+//!
+//! - **`manifest`**: enables [`Assets::manifest`] to export the mapping from
+//!   unhashed to hashed paths as versioned JSON, e.g. for server-side
+//!   rendering in other languages, and [`Assets::dry_run_prod`]. This feature
+//!   adds the `serde` and `serde_json` dependencies.
+//!
+//! - **`gzip`**: enables [`Builder::enable_gzip`] to additionally store
+//!   assets gzip-compressed. Adds the `flate2` dependency.
+//!
+//! - **`report`**: enables [`Assets::build_report`], recording which assets
+//!   were included and how they were processed, serializable as JSON. This
+//!   feature adds the `serde`, `serde_json` and `sha2` dependencies.
+//!
+//! - **`metrics`**: records metrics via the `metrics` crate facade, to be
+//!   exported by any `metrics` exporter (e.g. for Prometheus): the counters
+//!   `reinda_lookups_total` and `reinda_lookup_misses_total` for
+//!   [`Assets::get`], the histogram `reinda_dev_load_duration_seconds` for
+//!   loading assets in dev mode and the counter `reinda_served_bytes_total`
+//!   for response bodies sent by the framework integrations, labeled by
+//!   [`EntryBuilder::with_metrics_tag`]. This feature adds the `metrics`
+//!   dependency.
+//!
+//! - **`cli`**: enables [`cli`] to print the resolved manifest, sizes and
+//!   dependencies of your assets from the command line, see
+//!   `examples/cli.rs`. Implies `manifest`.
+//!
+//! - **`url`**: enables [`Builder::add_url`] and [`UrlSource`] to load assets
+//!   via HTTP(S). This feature adds the `reqwest` dependency.
+//!
+//! - **`embed-urls`**: enables the `urls` field of [`embed!`] to download
+//!   files at compile time. This feature adds the `ureq` and `sha2`
+//!   dependencies to `reinda-macros`.
+//!
+//! - **`test-util`**: enables [`test_util`] with reference implementations
+//!   of the path computations `reinda` performs (like inserting hashes), for
+//!   tools that need to predict hashed paths. Implies `full` and `hash`.
+//!
 //! - **`always-prod`**: enabled *prod* mode even when compiled in debug mode.
 //!   See the section about "prod" and "dev" mode above.
 //!
@@ -130,29 +229,156 @@
 
 #![deny(missing_debug_implementations)]
 
-use std::{borrow::Cow, fmt, io, path::{Path, PathBuf}, sync::Arc};
+use std::path::Path;
+#[cfg(feature = "full")]
+use std::{borrow::Cow, fmt, io, path::PathBuf, sync::Arc};
 
+#[cfg(feature = "full")]
 use bytes::Bytes;
 
+#[cfg(feature = "axum")]
+mod axum;
+#[cfg(feature = "http-body")]
+mod body;
+#[cfg(feature = "full")]
 mod builder;
+#[cfg(feature = "full")]
+mod cache;
+#[cfg(feature = "full")]
+mod checksum;
+#[cfg(feature = "cli")]
+pub mod cli;
+#[cfg(all(feature = "full", any(feature = "hash", feature = "http")))]
+mod conditional;
+#[cfg(all(feature = "full", feature = "hash"))]
+mod csp;
+#[cfg(feature = "full")]
+mod diagnostics;
+#[cfg(feature = "full")]
+mod encoding;
+#[cfg(all(feature = "full", feature = "modifiers"))]
+mod fragments;
+#[cfg(feature = "full")]
+mod guard;
+#[cfg(feature = "http")]
+mod http;
 mod embed;
-#[cfg(prod_mode)]
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "manifest")]
+mod manifest;
+#[cfg(all(feature = "full", feature = "hash"))]
+mod inline;
+#[cfg(feature = "metrics")]
+mod metrics;
+#[cfg(feature = "full")]
+mod mime;
+#[cfg(feature = "full")]
+mod module;
+#[cfg(feature = "full")]
+mod negotiate;
+#[cfg(feature = "full")]
+mod proxy;
+#[cfg(feature = "full")]
+mod runtime;
+#[cfg(any(feature = "axum", feature = "full", feature = "http", feature = "warp"))]
+mod serve;
+#[cfg(feature = "tower")]
+mod service;
+#[cfg(feature = "minify-js")]
+mod minify;
+#[cfg(feature = "full")]
+pub mod service_worker;
+#[cfg(feature = "full")]
+mod source;
+#[cfg(feature = "full")]
+mod stream;
+#[cfg(all(feature = "full", feature = "hash"))]
+mod sri;
+#[cfg(any(feature = "sanitize-svg", feature = "optimize-svg"))]
+#[cfg_attr(not(all(feature = "sanitize-svg", feature = "optimize-svg")), allow(dead_code))]
+mod svg;
+#[cfg(feature = "url")]
+mod url;
+#[cfg(feature = "report")]
+mod report;
+#[cfg(any(feature = "minijinja", feature = "tera"))]
+mod templates;
+#[cfg(feature = "full")]
+mod text;
+#[cfg(feature = "full")]
+mod watch;
+#[cfg(feature = "warp")]
+pub mod warp;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+#[cfg(any(prod_mode, feature = "hash", feature = "manifest"))]
+#[cfg_attr(dev_mode, allow(dead_code))]
 mod hash;
-#[cfg(prod_mode)]
+#[cfg(any(all(prod_mode, feature = "full"), feature = "manifest"))]
 mod dep_graph;
+pub mod minimal;
 pub mod util;
 
+#[cfg(feature = "full")]
 #[cfg_attr(prod_mode, path = "imp_prod.rs")]
 #[cfg_attr(dev_mode, path = "imp_dev.rs")]
 mod imp;
 
 
 
+pub use self::embed::{EmbeddedEntry, EmbeddedFile, EmbeddedGlob, Embeds};
+
+#[doc(hidden)]
+pub use self::embed::EmbeddedCompression;
+
+#[cfg(feature = "full")]
 pub use self::{
-    builder::{Builder, EntryBuilder},
-    embed::{EmbeddedEntry, EmbeddedFile, EmbeddedGlob, Embeds},
+    builder::{Builder, EntryBuilder, GroupBuilder},
+    cache::{AssetMeta, CachePolicy},
+    diagnostics::Warning,
+    encoding::{AcceptedEncodings, Encoding},
+    guard::{RequestContext, Restriction},
+    module::AssetModule,
+    runtime::AssetsRuntime,
+    source::AssetSource,
+    stream::{ContentStream, RangeRequest},
+    watch::ChangeEvent,
 };
 
+#[cfg(feature = "http-body")]
+pub use self::body::AssetBody;
+
+#[cfg(all(feature = "full", any(feature = "hash", feature = "http")))]
+pub use self::conditional::Conditional;
+
+#[cfg(feature = "report")]
+pub use self::report::{AssetReport, BuildReport, ReportSource};
+
+#[cfg(feature = "manifest")]
+pub use self::manifest::{Manifest, ManifestEntry};
+
+#[cfg(feature = "url")]
+pub use self::url::UrlSource;
+
+#[cfg(all(feature = "full", feature = "hash"))]
+pub use self::{
+    builder::HashPlacement,
+    hash::{hashed_path, ContentHasher, HashOptions, Sha256Hasher},
+};
+
+#[cfg(feature = "blake3")]
+pub use self::hash::Blake3Hasher;
+
+#[cfg(feature = "xxhash")]
+pub use self::hash::Xxh3Hasher;
+
+#[cfg(all(feature = "full", feature = "modifiers"))]
+pub use self::builder::BinaryPolicy;
+
+#[cfg(feature = "tower")]
+pub use self::service::AssetService;
+
 
 
 /// Embeds files into the executable.
@@ -166,10 +392,22 @@ pub use self::{
 /// };
 /// ```
 ///
-/// The following fields can be specified, with only `files` being mandatory:
+/// The following fields can be specified, with only `files` being mandatory
+/// (unless `urls` is given):
 ///
 /// - **`files`** (array of strings): list of paths or patterns of files that
-///   should be embedded.
+///   should be embedded. Files matched by several entries are only stored
+///   once, but a warning is printed at compile time, as they are usually
+///   served twice by accident.
+///
+/// - **`urls`** (array of `(path, url, sha256)` string tuples): files that are
+///   downloaded at compile time and then embedded like local files under
+///   `path`. The SHA-256 digest is mandatory and the build fails if the
+///   downloaded file does not match it. Downloads are cached by digest in
+///   `$REINDA_URL_CACHE` (default: `reinda-url-cache` in the temporary
+///   directory), so each file is only downloaded once and subsequent builds
+///   work offline. In dev mode, the file is loaded from that cache at
+///   runtime. Requires the `embed-urls` feature.
 ///
 /// - **`base_path`** (string): a base path that is prefixed to all values in
 ///   `files`. Relative to `Cargo.toml`. Empty if unspecified. For a path `path`
@@ -187,11 +425,24 @@ pub use self::{
 ///   binary size, but also means that it has to be decompressed and that the
 ///   compressed and decompressed version will be in memory. Default: `0.85`.
 ///
-/// - **`compression_quality`** (int): sets the Brotli compression quality (from
-///   1 to 11). Default: `9`.
+/// - **`compression_quality`** (int): sets the compression quality (from 1
+///   to 11 for Brotli, from 1 to 22 for zstd). Default: `9`.
+///
+/// - **`compression`** (string): the compression algorithm, `"brotli"`,
+///   `"zstd"` or `"none"`. Default: `"brotli"` if the `compress` feature is
+///   enabled, otherwise `"zstd"` if `compress-zstd` is enabled.
+///
+/// - **`brotli_dictionary`** (bool): if set to true, a custom Brotli
+///   dictionary is built from the beginnings of all local files and stored
+///   once. Files are then compressed using that dictionary, which helps a lot
+///   with many small, similar files (e.g. icons or translation files) that
+///   don't compress well on their own. The dictionary is only used if it
+///   saves more than it costs. As clients don't have the dictionary, files
+///   compressed with it can't be served in compressed form. Requires Brotli
+///   compression. Default: `false`.
 ///
-/// For compression to be used at all, the `compress` feature needs to be
-/// enabled.
+/// For compression to be used at all, the `compress` or `compress-zstd`
+/// feature needs to be enabled.
 ///
 /// All entries in `files` falls in one of two categories. Either it's a plain
 /// path without any (non-escaped) glob meta characters (`*?[]`), then the
@@ -215,20 +466,70 @@ pub use reinda_macros::embed;
 ///
 /// You create an instance of this by using [`Self::builder`] and eventually
 /// call [`Builder::build`].
+///
+/// This type (as well as [`Asset`]) is `Send + Sync + 'static` in both modes,
+/// so it can be stored in a global or in the shared state of your web
+/// framework. Use [`Self::into_static`] to get a `&'static Assets` or a
+/// `OnceLock` if the assets need to be accessible from everywhere:
+///
+/// ```
+/// use std::sync::OnceLock;
+///
+/// static ASSETS: OnceLock<reinda::Assets> = OnceLock::new();
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> Result<(), reinda::BuildError> {
+/// let assets = reinda::Assets::builder().build().await?;
+/// ASSETS.set(assets).expect("assets already initialized");
+///
+/// // Later, e.g. in a request handler:
+/// let asset = ASSETS.get().unwrap().get("index.html");
+/// # Ok(())
+/// # }
+/// ```
 #[derive(Debug, Clone)]
+#[cfg(feature = "full")]
 pub struct Assets(imp::AssetsInner);
 
+#[cfg(feature = "full")]
 impl Assets {
     /// Returns a builder, allowing you to add and configure assets.
     pub fn builder<'a>() -> Builder<'a> {
-        Builder { assets: vec![] }
+        Builder::new()
     }
 
     /// Retrieves an asset by *hashed HTTP path*. In prod mode, this is just a
     /// fast hash map lookup. In dev mode, the asset is loaded from the file
-    /// system.
+    /// system. Directory paths can be resolved to index files, see
+    /// [`Builder::directory_index`].
     pub fn get(&self, http_path: &str) -> Option<Asset> {
-        self.0.get(http_path)
+        let asset = self.0.get(http_path).or_else(|| {
+            let index = self.0.directory_index()?;
+            let dir = http_path.trim_end_matches('/');
+            let index_path = match dir.is_empty() {
+                true => index.to_owned(),
+                false => format!("{}/{}", dir, index),
+            };
+            self.0.get_unhashed(&index_path)
+        });
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::lookup(asset.is_some());
+        asset
+    }
+
+    /// Resolves all of `http_paths` via [`Self::get`] in one call, e.g. to
+    /// look up all sub-resources of a page for preload headers. The paths
+    /// are partitioned into found and missing ones, both in input order.
+    pub fn get_many<'p>(&self, http_paths: impl IntoIterator<Item = &'p str>) -> Lookup<'p> {
+        let mut out = Lookup { found: Vec::new(), missing: Vec::new() };
+        for path in http_paths {
+            match self.get(path) {
+                Some(asset) => out.found.push((path, asset)),
+                None => out.missing.push(path),
+            }
+        }
+        out
     }
 
     /// Returns the number of assets. For glob patterns, see [`Self::iter`] for
@@ -246,6 +547,101 @@ impl Assets {
     pub fn iter(&self) -> impl '_ + Iterator<Item = (&str, Asset)> {
         self.0.iter()
     }
+
+    /// Like [`Self::iter`], but also yields the MIME type of each asset (see
+    /// [`Asset::mime`]).
+    pub fn iter_with_mime(&self) -> impl '_ + Iterator<Item = (&str, Asset, Option<&'static str>)> {
+        self.iter().map(|(path, asset)| {
+            let mime = asset.mime();
+            (path, asset, mime)
+        })
+    }
+
+    /// Like [`Self::get`], but returns the translation of the asset that is
+    /// most acceptable according to the `Accept-Language` header value
+    /// `accept_language`, see [`Asset::best_locale`]. The response should
+    /// contain a `Vary: Accept-Language` header if the asset has locale
+    /// variants.
+    ///
+    /// ```no_run
+    /// # async fn foo(assets: reinda::Assets) {
+    /// let index = assets.get_localized("index.html", "de-AT, de;q=0.9, en;q=0.5");
+    /// # }
+    /// ```
+    pub fn get_localized(&self, http_path: &str, accept_language: &str) -> Option<Asset> {
+        self.get(http_path).map(|asset| asset.best_locale(accept_language))
+    }
+
+    /// Returns the asset registered as error page for the HTTP status code
+    /// `status` via [`Builder::set_error_asset`], if any.
+    pub fn error_asset(&self, status: u16) -> Option<Asset> {
+        self.0.error_asset(status)
+    }
+
+    /// Returns the fallback asset registered via [`Builder::set_fallback`],
+    /// if any.
+    pub fn fallback(&self) -> Option<Asset> {
+        self.0.fallback()
+    }
+
+    /// Like [`Self::get`], but returns the fallback asset (see
+    /// [`Builder::set_fallback`]) if no asset with the given path exists.
+    pub fn get_or_fallback(&self, http_path: &str) -> Option<Asset> {
+        self.get(http_path).or_else(|| self.fallback())
+    }
+
+    /// Returns the mounts of all glob entries, e.g. to register routes for
+    /// them. See [`GlobMount`].
+    pub fn glob_mounts(&self) -> impl '_ + Iterator<Item = &GlobMount> {
+        self.0.glob_mounts()
+    }
+
+    /// Returns the *unhashed HTTP paths* of all assets in the order in which
+    /// they were processed by [`Builder::build`], i.e. a topological order
+    /// of their dependencies (declared via [`EntryBuilder::with_modifier`],
+    /// path fixups and [`EntryBuilder::after`]).
+    /// Every asset comes after all its dependencies; the order of
+    /// independent assets is unspecified. Useful to debug why a modifier saw
+    /// a stale path.
+    ///
+    /// Only available in prod mode, returns `None` in dev mode where assets
+    /// are processed on demand.
+    pub fn processing_order(&self) -> Option<&[String]> {
+        self.0.processing_order()
+    }
+
+    /// Loads every asset known at build time once and checks that the
+    /// directories of all glob entries exist, so that missing files show up
+    /// at server start instead of as 404s on the first page load. If
+    /// [`Builder::dev_stale_while_revalidate`] is enabled, this also fills
+    /// the cache.
+    ///
+    /// Returns all errors, sorted by *HTTP path* (for glob entries, the
+    /// HTTP prefix of their mount). In prod mode, everything was already
+    /// loaded by [`Builder::build`], so this does nothing and returns an
+    /// empty list.
+    pub async fn warm_up(&self) -> Vec<LoadError> {
+        self.0.warm_up().await
+    }
+
+    /// Leaks `self` and returns a `'static` reference to it. Useful as you
+    /// usually build assets once and keep them for the whole runtime of your
+    /// application, e.g. to pass them to request handlers requiring
+    /// `'static` data without wrapping them in an `Arc`.
+    pub fn into_static(self) -> &'static Self {
+        Box::leak(Box::new(self))
+    }
+}
+
+/// Result of [`Assets::get_many`].
+#[derive(Debug, Clone)]
+#[cfg(feature = "full")]
+pub struct Lookup<'p> {
+    /// Paths that were found, together with their asset.
+    pub found: Vec<(&'p str, Asset)>,
+
+    /// Paths for which no asset exists.
+    pub missing: Vec<&'p str>,
 }
 
 
@@ -254,48 +650,474 @@ impl Assets {
 /// Very cheap to clone (in prod mode anyway, which is the only thing that
 /// matters).
 #[derive(Debug, Clone)]
+#[cfg(feature = "full")]
 pub struct Asset(imp::AssetInner);
 
+#[cfg(feature = "full")]
 impl Asset {
     /// Returns the contents of this asset. Will be loaded from the file system
-    /// in dev mode, potentially returning IO errors. In prod mode, the file
-    /// contents are already loaded and this method always returns `Ok(_)` and
-    /// never yield.
-    pub async fn content(&self) -> Result<Bytes, io::Error> {
+    /// in dev mode, potentially returning errors that contain the *HTTP path*,
+    /// the *FS path* and what exactly failed (see [`LoadError`]). In prod
+    /// mode, the file contents are already loaded and this method always
+    /// returns `Ok(_)` and never yield.
+    ///
+    /// In dev mode, panics in modifiers are caught and returned as error. The
+    /// error can be rendered as HTML error page via [`LoadError::to_html`],
+    /// which is useful to show in the browser during development.
+    /// `LoadError` converts into `io::Error` (keeping all context in its
+    /// message), so `?` works in functions returning `io::Result`.
+    pub async fn content(&self) -> Result<Bytes, LoadError> {
         self.0.content().await
     }
 
+    /// Returns the content of this asset as string, e.g. to pass templates to
+    /// other engines. Returns an error if the content is not valid UTF-8. In
+    /// prod mode, the content is only validated on the first call.
+    pub async fn text(&self) -> Result<String, TextError> {
+        #[cfg(prod_mode)]
+        {
+            self.0.text().map(|text| text.to_string()).map_err(TextError::InvalidUtf8)
+        }
+
+        #[cfg(dev_mode)]
+        {
+            let content = self.content().await.map_err(TextError::Load)?;
+            std::str::from_utf8(&content).map(str::to_owned).map_err(TextError::InvalidUtf8)
+        }
+    }
+
+    /// Like [`Self::content`], but returns the content in one of the `accepted`
+    /// encodings if this asset is stored in that form, together with the
+    /// encoding for the `Content-Encoding` header. Otherwise, the
+    /// uncompressed content and `None` are returned.
+    ///
+    /// In prod mode, files embedded with compression (features `compress` and
+    /// `compress-zstd`) are stored as Brotli or zstd, unless the content was
+    /// changed while building (e.g. by a modifier). With
+    /// [`Builder::compress_runtime_files`], all other assets are
+    /// Brotli-compressed while building as well. With
+    /// [`Builder::enable_gzip`], assets are additionally stored
+    /// gzip-compressed. If several stored encodings are accepted, Brotli is
+    /// preferred over zstd over gzip. If none of them is accepted, the content
+    /// can be compressed on demand, see [`Builder::compress_on_demand`]. In
+    /// dev mode, the content is never encoded. As the response depends on the
+    /// `Accept-Encoding` header, include `Vary: accept-encoding` if
+    /// [`Self::encodings`] is non-empty or compression on demand is enabled.
+    ///
+    /// ```no_run
+    /// use reinda::AcceptedEncodings;
+    ///
+    /// # async fn foo(asset: reinda::Asset) -> Result<(), reinda::LoadError> {
+    /// let accepted = AcceptedEncodings::from_header("gzip, deflate, br");
+    /// let (body, encoding) = asset.content_encoded(accepted).await?;
+    /// if let Some(encoding) = encoding {
+    ///     // Set `Content-Encoding: {encoding.as_str()}`
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub async fn content_encoded(
+        &self,
+        accepted: AcceptedEncodings,
+    ) -> Result<(Bytes, Option<Encoding>), LoadError> {
+        if let Some((encoding, content)) = self.0.encoded().iter().find(|(e, _)| accepted.accepts(*e)) {
+            return Ok((content.clone(), Some(*encoding)));
+        }
+
+        #[cfg(any(feature = "compress", feature = "gzip"))]
+        if let Some((encoding, content)) = self.0.compress_on_demand(accepted).await {
+            return Ok((content, Some(encoding)));
+        }
+
+        Ok((self.content().await?, None))
+    }
+
+    /// Returns the content of this asset as [`ContentStream`], which can be
+    /// restricted to a byte range via [`ContentStream::slice`] to answer
+    /// `Range` requests (see [`RangeRequest`]). In dev mode, files without
+    /// modifier, checksum, sanitization or validators are streamed from the
+    /// file system instead of being loaded into memory. In prod mode, files
+    /// whose content was not changed while building are streamed from the
+    /// file system as well, unless the file was changed since building.
+    ///
+    /// ```no_run
+    /// use reinda::RangeRequest;
+    ///
+    /// # async fn foo(asset: reinda::Asset, range_header: &str) -> std::io::Result<()> {
+    /// let stream = asset.content_stream().await?;
+    /// let len = stream.len();
+    /// match RangeRequest::parse(range_header, len) {
+    ///     RangeRequest::Partial(range) => {
+    ///         let body = stream.slice(range).await?;
+    ///         // Respond with 206 and `Content-Range` ...
+    ///     }
+    ///     // ...
+    /// #   _ => {}
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub async fn content_stream(&self) -> Result<ContentStream, LoadError> {
+        if let Some(stream) = self.0.stream_from_file().await {
+            return stream;
+        }
+
+        self.content().await.map(ContentStream::from_bytes)
+    }
+
+    /// Returns the encodings in which this asset is stored, in order of
+    /// preference, see [`Self::content_encoded`].
+    pub fn encodings(&self) -> impl '_ + Iterator<Item = Encoding> {
+        self.0.encoded().iter().map(|(encoding, _)| *encoding)
+    }
+
     /// Returns whether this asset's filename contains a hash. Specifically, it
     /// returns true iff [`EntryBuilder::with_hash`] was called *and* you are
     /// compiling in prod mode.
     pub fn is_filename_hashed(&self) -> bool {
         self.0.is_filename_hashed()
     }
+
+    /// Returns the `Cache-Control` header value configured via
+    /// [`EntryBuilder::with_cache_control`] or [`GroupBuilder::with_cache_control`],
+    /// or otherwise determined by [`Builder::set_cache_policy`], if any.
+    pub fn cache_control(&self) -> Option<&str> {
+        self.0.cache_control()
+    }
+
+    /// Returns the tag set via [`EntryBuilder::with_metrics_tag`], e.g. to
+    /// label metrics recorded by custom servers.
+    ///
+    /// Method is only available if the crate feature `metrics` is enabled.
+    #[cfg(feature = "metrics")]
+    pub fn metrics_tag(&self) -> Option<&str> {
+        self.0.metrics_tag()
+    }
+
+    /// Returns the value of type `T` attached to this asset via
+    /// [`EntryBuilder::with_extension`], if any.
+    ///
+    /// ```no_run
+    /// struct RequiresLogin;
+    ///
+    /// # fn foo(asset: reinda::Asset) {
+    /// if asset.extension::<RequiresLogin>().is_some() {
+    ///     // Check session...
+    /// }
+    /// # }
+    /// ```
+    pub fn extension<T: 'static>(&self) -> Option<&T> {
+        self.0.extension()
+    }
+
+    /// Returns whether the guard registered via [`EntryBuilder::with_guard`]
+    /// allows the request described by `req`. Returns `true` if this asset
+    /// has no guard.
+    pub fn is_allowed(&self, req: &RequestContext) -> bool {
+        self.0.guard().is_none_or(|guard| guard.allows(req))
+    }
+
+    /// Returns the recommended caching policy for this asset, i.e. what to
+    /// put into the `Cache-Control` header:
+    /// - the value returned by [`Self::cache_control`], if any,
+    /// - [`CachePolicy::Immutable`] for assets with hashed filenames, as their
+    ///   content never changes under the same path,
+    /// - [`CachePolicy::NoCache`] otherwise, i.e. clients may cache the asset
+    ///   but have to revalidate it before each use.
+    ///
+    /// ```no_run
+    /// # fn foo(asset: reinda::Asset) {
+    /// let header_value = asset.cache_policy().header_value();
+    /// # }
+    /// ```
+    pub fn cache_policy(&self) -> CachePolicy {
+        match self.cache_control() {
+            Some(value) => CachePolicy::Custom(value.to_owned().into()),
+            None if self.is_filename_hashed() => CachePolicy::Immutable,
+            None => CachePolicy::NoCache,
+        }
+    }
+
+    /// Returns whether this asset is likely a text file (as opposed to a
+    /// binary file like an image or font). This is determined by the file
+    /// extension of the HTTP path and, for unknown extensions, by inspecting
+    /// the first bytes of the content. In prod mode, this is determined once
+    /// in [`Builder::build`]; in dev mode, the start of the file is read on
+    /// each call.
+    ///
+    /// This is a heuristic and is meant for things like logging or to avoid
+    /// mangling binary files, not as a security mechanism.
+    pub fn is_text(&self) -> bool {
+        self.0.text_info().is_text
+    }
+
+    /// Returns the charset of this asset, if it is a text file and its
+    /// encoding is known. Currently, this is `Some("utf-8")` for text files
+    /// that are valid UTF-8 and `None` otherwise. Useful for the `charset`
+    /// parameter of the `Content-Type` header.
+    pub fn charset(&self) -> Option<&'static str> {
+        self.0.text_info().charset
+    }
+
+    /// Returns the MIME type of this asset, determined by the extension of
+    /// its *unhashed HTTP path* (or, for some well-known files, its
+    /// filename), e.g. `text/css` for `style.css`. Returns `None` if the
+    /// extension is unknown. Additional extensions can be registered via
+    /// [`Builder::add_mime_type`], or the type of a single entry can be set
+    /// via [`EntryBuilder::with_mime`]. In prod mode, this is determined once
+    /// in [`Builder::build`].
+    ///
+    /// Text types do not include a `charset` parameter, see [`Self::charset`]
+    /// for that.
+    pub fn mime(&self) -> Option<&'static str> {
+        self.0.mime()
+    }
+
+    /// Returns the strong entity tag of this asset for the `ETag` header,
+    /// including quotes. In prod mode, this is computed from the content once
+    /// in [`Builder::build`]. In dev mode, where the content can change at
+    /// any time, this returns `None`.
+    ///
+    /// Method is only available if the crate feature `hash` or `http` is
+    /// enabled.
+    #[cfg(any(feature = "hash", feature = "http"))]
+    pub fn etag(&self) -> Option<&str> {
+        self.0.etag()
+    }
+
+    /// Returns whether the `If-None-Match` header value `if_none_match`
+    /// matches [`Self::etag`], i.e. whether the client's cached copy is up to
+    /// date and a `304 Not Modified` response can be sent. Always returns
+    /// `false` in dev mode.
+    ///
+    /// ```no_run
+    /// # fn foo(asset: reinda::Asset, if_none_match: &str) {
+    /// if asset.is_not_modified(if_none_match) {
+    ///     // Respond with 304 and the `ETag` header, but without body.
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// Method is only available if the crate feature `hash` or `http` is
+    /// enabled.
+    #[cfg(any(feature = "hash", feature = "http"))]
+    pub fn is_not_modified(&self, if_none_match: &str) -> bool {
+        self.etag().is_some_and(|etag| checksum::matches_etag(if_none_match, etag))
+    }
+
+    /// Returns the Subresource Integrity string of this asset's content, i.e.
+    /// `sha384-` followed by the base64 encoded SHA-384 digest, for use in
+    /// `integrity` attributes. To add those to HTML files referencing other
+    /// assets, see [`ModifierContext::inject_integrity`].
+    ///
+    /// Method is only available if the crate feature `hash` is enabled.
+    #[cfg(feature = "hash")]
+    pub async fn sri_hash(&self) -> Result<String, LoadError> {
+        self.content().await.map(|content| sri::sha384(&content))
+    }
+
+    /// Returns the CSP hash sources (like `'sha256-...'`, including quotes) of
+    /// all inline `<script>` and `<style>` blocks of this asset, if it is an
+    /// HTML file, for use in `script-src` and `style-src` directives of a
+    /// `Content-Security-Policy` header. In prod mode, they are computed once
+    /// in [`Builder::build`]. Scripts with `src` attribute and empty blocks
+    /// are skipped.
+    ///
+    /// ```no_run
+    /// # async fn foo(assets: reinda::Assets) -> Result<(), reinda::LoadError> {
+    /// let index = assets.get("index.html").unwrap();
+    /// let csp = format!("script-src 'self' {}", index.csp_hashes().await?.join(" "));
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// Method is only available if the crate feature `hash` is enabled.
+    #[cfg(feature = "hash")]
+    pub async fn csp_hashes(&self) -> Result<Vec<String>, LoadError> {
+        #[cfg(prod_mode)]
+        {
+            Ok(self.0.csp_hashes().to_vec())
+        }
+
+        #[cfg(dev_mode)]
+        {
+            if self.mime() != Some("text/html") {
+                return Ok(vec![]);
+            }
+            self.content().await.map(|content| csp::inline_hashes(&content))
+        }
+    }
+
+    /// Returns whether the content of this asset differs from its source,
+    /// i.e. whether it was transformed while loading, e.g. by a modifier,
+    /// [`Builder::global_replace`] or [`EntryBuilder::sanitize_svg`]. Useful
+    /// for debugging endpoints and audits. In prod mode, this is determined
+    /// in [`Builder::build`]. In dev mode, the source is loaded and processed
+    /// on every call; as path fixups are not applied in dev mode, they don't
+    /// count as modification there.
+    pub async fn was_modified(&self) -> Result<bool, LoadError> {
+        #[cfg(prod_mode)]
+        {
+            Ok(self.0.was_modified())
+        }
+
+        #[cfg(dev_mode)]
+        {
+            self.0.was_modified().await
+        }
+    }
+
+    /// Returns the hex encoded SHA-256 digest of the source of this asset
+    /// before any modifications, e.g. to compare it against the file on
+    /// disk. In prod mode, it is computed in [`Builder::build`]; in dev mode,
+    /// the source is loaded on every call.
+    pub async fn source_sha256(&self) -> Result<String, LoadError> {
+        #[cfg(prod_mode)]
+        {
+            Ok(self.0.source_sha256().to_string())
+        }
+
+        #[cfg(dev_mode)]
+        {
+            self.0.source_sha256().await.map(|digest| digest.to_string())
+        }
+    }
+
+    /// Returns the modification time of this asset's source for the
+    /// `Last-Modified` header (formatted via [`util::format_http_date`]).
+    /// Only available in prod mode and only for assets whose content was not
+    /// changed while building (e.g. by a modifier). For embedded files, this
+    /// is the time captured by `embed!` at compile time, for files added via
+    /// `add_file` the file's modification time when [`Builder::build`] ran.
+    /// Mostly useful for assets without hashed filename.
+    pub fn last_modified(&self) -> Option<std::time::SystemTime> {
+        self.0.last_modified()
+    }
+
+    /// Returns whether this asset was modified after the time given by the
+    /// `If-Modified-Since` header value `if_modified_since`. If `false`, a
+    /// `304 Not Modified` response can be sent. Returns `true` if
+    /// [`Self::last_modified`] is `None` or the header is not a valid HTTP
+    /// date. According to RFC 9110, this must be ignored if the request
+    /// contains an `If-None-Match` header.
+    pub fn is_modified_since(&self, if_modified_since: &str) -> bool {
+        // HTTP dates have a precision of one second.
+        let secs = |t: std::time::SystemTime| {
+            t.duration_since(std::time::UNIX_EPOCH).map_or(0, |d| d.as_secs())
+        };
+        match (self.last_modified(), util::parse_http_date(if_modified_since)) {
+            (Some(modified), Some(since)) => secs(modified) > secs(since),
+            _ => true,
+        }
+    }
+
+    /// Returns whether alternative formats of this asset were registered via
+    /// [`EntryBuilder::with_variants`]. If so, the response should contain a
+    /// `Vary: Accept` header.
+    pub fn has_variants(&self) -> bool {
+        !self.0.variants().is_empty()
+    }
+
+    /// Returns values for `Link` headers preloading the dependencies of this
+    /// asset (declared via [`EntryBuilder::with_modifier`] or
+    /// [`EntryBuilder::with_path_fixup`]), e.g. the scripts and stylesheets
+    /// of an HTML page, for HTTP/2 or `103 Early Hints` responses. The
+    /// *hashed HTTP paths* are prefixed with `url_prefix`, the path under
+    /// which the assets are served. Dependencies for which preloading is not
+    /// useful, like HTML files, are skipped.
+    ///
+    /// ```no_run
+    /// # async fn foo(assets: reinda::Assets) {
+    /// let index = assets.get("index.html").unwrap();
+    /// for link in index.preload_links("/assets/") {
+    ///     // e.g. `</assets/bundle.d5f3ac9a.js>; rel=preload; as=script`
+    /// }
+    /// # }
+    /// ```
+    pub fn preload_links(&self, url_prefix: &str) -> Vec<String> {
+        self.0.dependencies().into_iter()
+            .filter_map(|(path, mime)| {
+                let (dest, cors) = mime::preload_destination(mime?)?;
+                let crossorigin = if cors { "; crossorigin" } else { "" };
+                Some(format!("<{url_prefix}{path}>; rel=preload; as={dest}{crossorigin}"))
+            })
+            .collect()
+    }
+
+    /// Returns whether translations of this asset were registered via
+    /// [`EntryBuilder::with_locale_variants`].
+    pub fn has_locale_variants(&self) -> bool {
+        !self.0.locale_variants().is_empty()
+    }
+
+    /// Returns the translation of this asset (see
+    /// [`EntryBuilder::with_locale_variants`]) that is most acceptable
+    /// according to the `Accept-Language` header value `accept_language`.
+    /// Among equally acceptable ones, the first registered variant wins. If
+    /// no variant is acceptable, this asset is returned.
+    pub fn best_locale(&self, accept_language: &str) -> Asset {
+        let mut best = (0.0, None);
+        for (lang, candidate) in self.0.locale_variants() {
+            let q = negotiate::language_quality(accept_language, &lang);
+            if q > best.0 {
+                best = (q, Some(candidate));
+            }
+        }
+        best.1.unwrap_or_else(|| self.clone())
+    }
+
+    /// Returns the variant of this asset (see [`EntryBuilder::with_variants`])
+    /// that is most acceptable according to the `Accept` header value
+    /// `accept`. Among equally acceptable ones, the first registered variant
+    /// wins, with this asset itself coming last. If no variant is acceptable,
+    /// this asset is returned. Variants with unknown MIME type are never
+    /// selected.
+    ///
+    /// ```no_run
+    /// # async fn foo(assets: reinda::Assets) {
+    /// let logo = assets.get("logo.png").unwrap();
+    /// let chosen = logo.best_variant("image/avif,image/webp,image/*;q=0.8");
+    /// # }
+    /// ```
+    pub fn best_variant(&self, accept: &str) -> Asset {
+        let quality = |asset: &Asset| {
+            asset.mime().map_or(0.0, |mime| negotiate::mime_quality(accept, mime))
+        };
+
+        let mut best = (0.0, None);
+        for candidate in self.0.variants().into_iter().chain(Some(self.clone())) {
+            let q = quality(&candidate);
+            if q > best.0 {
+                best = (q, Some(candidate));
+            }
+        }
+        best.1.unwrap_or_else(|| self.clone())
+    }
 }
 
 /// Passed to the modifier closure, e.g. allowing you to resolve *unhashed HTTP
 /// paths* to *hashed ones*.
 #[derive(Debug)]
+#[cfg(feature = "full")]
 pub struct ModifierContext<'a> {
     declared_deps: &'a [Cow<'static, str>],
+    module_prefix: &'a str,
+    unhashed_http_path: &'a str,
+    glob_suffix: Option<&'a str>,
+    variables: &'a builder::Variables,
+    is_prod: bool,
+    hash_configured: bool,
     inner: imp::ModifierContextInner<'a>,
 }
 
+#[cfg(feature = "full")]
 impl<'a> ModifierContext<'a> {
-    /// Resolves an *unhashed HTTP path* to the *hashed HTTP path*.
+    /// Resolves an *unhashed HTTP path* to the *hashed HTTP path*. For
+    /// modifiers added by an [`AssetModule`], `unhashed_http_path` can also
+    /// be relative to the module's prefix.
     ///
     /// **Panics** if the passed `unhashed_http_path` was not declared as
     /// dependency in `with_modifier` or does not refer to an existing asset.
     pub fn resolve_path<'b>(&'b self, unhashed_http_path: &'b str) -> &'b str {
-        if !self.declared_deps.iter().any(|dep| dep == unhashed_http_path) {
-            panic!(
-                "called `ModifierContext::resolve_path` with '{}', \
-                    but that was not specified as dependency",
-                unhashed_http_path,
-            );
-        }
-
-        self.inner.resolve_path(unhashed_http_path).unwrap_or_else(|| {
+        let dep = self.declared_dep(unhashed_http_path, "resolve_path");
+        self.inner.resolve_path(dep).unwrap_or_else(|| {
             panic!(
                 "called `ModifierContext::resolve_path` with '{}', \
                     but no asset with that path exists",
@@ -304,12 +1126,128 @@ impl<'a> ModifierContext<'a> {
         })
     }
 
+    /// Returns the Subresource Integrity string (`sha384-...`) of the given
+    /// dependency, for use in `integrity` attributes. Returns `None` in dev
+    /// mode, where modifiers run before dependencies are loaded.
+    ///
+    /// **Panics** if the passed `unhashed_http_path` was not declared as
+    /// dependency in `with_modifier`.
+    ///
+    /// Method is only available if the crate feature `hash` is enabled.
+    #[cfg(feature = "hash")]
+    pub fn integrity(&self, unhashed_http_path: &str) -> Option<String> {
+        let dep = self.declared_dep(unhashed_http_path, "integrity");
+        self.inner.content_of(dep).map(|content| sri::sha384(&content))
+    }
+
+    /// Adds `integrity` attributes (see [`Self::integrity`]) to all
+    /// `<script>` and `<link>` tags in `html` whose `src` or `href` refers to
+    /// a declared dependency, by its unhashed or hashed path, optionally with
+    /// leading `/`. Tags that already have an `integrity` attribute are not
+    /// changed. Does nothing in dev mode.
+    ///
+    /// ```
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.add_file("index.html", "examples/assets/index.html")
+    ///     .with_modifier(["bundle.js"], |html, ctx| {
+    ///         ctx.inject_integrity(&html).into()
+    ///     });
+    /// ```
+    ///
+    /// Method is only available if the crate feature `hash` is enabled.
+    #[cfg(feature = "hash")]
+    pub fn inject_integrity(&self, html: &[u8]) -> Vec<u8> {
+        sri::inject(html, |value| {
+            let value = value.strip_prefix('/').unwrap_or(value);
+            let dep = self.declared_deps.iter().find(|dep| {
+                *dep == value || self.inner.resolve_path(dep) == Some(value)
+            })?;
+            self.inner.content_of(dep).map(|content| sri::sha384(&content))
+        })
+    }
+
+    /// Returns the declared dependency matching `unhashed_http_path`, which
+    /// can be relative to the module prefix. Panics otherwise.
+    fn declared_dep(&self, unhashed_http_path: &str, method: &str) -> &'a str {
+        self.declared_deps.iter()
+            .find(|dep| dep.strip_prefix(self.module_prefix) == Some(unhashed_http_path))
+            .or_else(|| self.declared_deps.iter().find(|dep| *dep == unhashed_http_path))
+            .unwrap_or_else(|| panic!(
+                "called `ModifierContext::{}` with '{}', \
+                    but that was not specified as dependency",
+                method,
+                unhashed_http_path,
+            ))
+    }
+
     /// Returns the dependencies you passed to [`EntryBuilder::with_modifier`],
     /// in the same order. This is just for convenience and to avoid cloning
     /// the dependency list.
     pub fn dependencies(&self) -> &'a [Cow<'static, str>] {
         self.declared_deps
     }
+
+    /// Returns the *unhashed HTTP path* of the asset being modified. Useful
+    /// for modifiers of glob entries, which run once for every matched file.
+    ///
+    /// ```
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.add_file("index.html", "examples/assets/index.html")
+    ///     .with_modifier::<_, _, &str>([], |html, ctx| {
+    ///         let comment = format!("<!-- {} -->\n", ctx.unhashed_http_path());
+    ///         [comment.as_bytes(), &html].concat().into()
+    ///     });
+    /// ```
+    pub fn unhashed_http_path(&self) -> &'a str {
+        self.unhashed_http_path
+    }
+
+    /// Returns the part of the *unhashed HTTP path* that was matched by the
+    /// glob, i.e. the path without the entry's HTTP prefix. Returns `None` if
+    /// the asset was not added via a glob or directory entry.
+    pub fn glob_suffix(&self) -> Option<&'a str> {
+        self.glob_suffix
+    }
+
+    /// Returns the value of the variable set via [`Builder::add_variable`],
+    /// or `None` if it is not set.
+    ///
+    /// ```
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.add_variable("api_url", "https://api.example.com");
+    /// builder.add_file("bundle.js", "examples/assets/bundle.js")
+    ///     .with_modifier::<_, _, &str>([], |js, ctx| {
+    ///         let url = ctx.variable("api_url").unwrap();
+    ///         [format!("const API = '{}';\n", url).as_bytes(), &js].concat().into()
+    ///     });
+    /// ```
+    pub fn variable(&self, name: &str) -> Option<&'a str> {
+        self.variables.get(name).map(|value| &**value)
+    }
+
+    /// Returns whether the modifier runs for prod mode, i.e. in
+    /// [`Builder::build`] in prod mode or in `Assets::dry_run_prod`. This
+    /// allows a single modifier to produce different output per mode:
+    ///
+    /// ```
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.add_file("bundle.js", "examples/assets/bundle.js")
+    ///     .with_modifier::<_, _, &str>([], |js, ctx| {
+    ///         let level = if ctx.is_prod() { "warn" } else { "debug" };
+    ///         [format!("const LOG_LEVEL = '{}';\n", level).as_bytes(), &js].concat().into()
+    ///     });
+    /// ```
+    pub fn is_prod(&self) -> bool {
+        self.is_prod
+    }
+
+    /// Returns whether the asset being modified is configured to have a
+    /// hashed filename, e.g. via [`EntryBuilder::with_hash`]. Unlike
+    /// [`Asset::is_filename_hashed`], this is also `true` in dev mode, where
+    /// paths are never hashed.
+    pub fn is_hash_configured(&self) -> bool {
+        self.hash_configured
+    }
 }
 
 // =========================================================================================
@@ -319,89 +1257,426 @@ impl<'a> ModifierContext<'a> {
 /// Errors that might happen during [`Builder::build`], when loading and resolving files.
 #[derive(Debug)]
 #[non_exhaustive]
+#[cfg(feature = "full")]
 pub enum BuildError {
     Io {
         err: std::io::Error,
         path: PathBuf,
     },
     CyclicDependencies(Vec<String>),
+
+    /// Loading an asset from an [`AssetSource`] failed.
+    Source {
+        err: std::io::Error,
+        http_path: String,
+    },
+
+    /// The source of an asset did not match the hash pinned via
+    /// [`EntryBuilder::expect_sha256`]. Hashes are hex encoded.
+    ChecksumMismatch {
+        http_path: String,
+        expected: String,
+        actual: String,
+    },
+
+    /// A validator registered via [`EntryBuilder::validate`] rejected the
+    /// content of an asset.
+    Validation {
+        http_path: String,
+        msg: String,
+    },
+
+    /// Two entries (or files matched by globs) resolved to the same *unhashed
+    /// HTTP path*. `first` and `second` describe the sources of both. See
+    /// [`Builder::allow_duplicate_paths`] to allow this.
+    DuplicatePath {
+        http_path: String,
+        first: String,
+        second: String,
+    },
+
+    /// A path fixup would have changed the length of a binary asset with
+    /// [`BinaryPolicy::Error`] configured. `path` is the *unhashed HTTP
+    /// path* that should have been replaced.
+    #[cfg(feature = "modifiers")]
+    BinaryFixup {
+        http_path: String,
+        path: String,
+    },
+
+    /// An asset configured with [`EntryBuilder::minify_js`] or
+    /// [`EntryBuilder::optimize_svg`] could not be minified, most likely
+    /// because it is not valid JavaScript or SVG, respectively.
+    #[cfg(any(feature = "minify-js", feature = "optimize-svg"))]
+    Minify {
+        http_path: String,
+        msg: String,
+    },
+
+    /// The template of an asset configured with
+    /// [`EntryBuilder::with_template`] is invalid or refers to an asset that
+    /// does not exist.
+    #[cfg(feature = "modifiers")]
+    Template {
+        http_path: String,
+        msg: String,
+    },
 }
 
+#[cfg(feature = "full")]
 impl fmt::Display for BuildError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             BuildError::Io { err, path }
                 => write!(f, "IO error while accessing '{}': '{}'", path.display(), err),
             BuildError::CyclicDependencies(cycle) => write!(f, "cyclic dependencies: {:?}", cycle),
+            BuildError::Source { err, http_path }
+                => write!(f, "failed to load source of asset '{}': {}", http_path, err),
+            BuildError::ChecksumMismatch { http_path, expected, actual } => write!(
+                f,
+                "SHA-256 hash of asset '{}' is {}, but {} was expected",
+                http_path, actual, expected,
+            ),
+            BuildError::Validation { http_path, msg }
+                => write!(f, "validation of asset '{}' failed: {}", http_path, msg),
+            BuildError::DuplicatePath { http_path, first, second }
+                => write!(f, "path '{}' is used by {} and {}", http_path, first, second),
+            #[cfg(feature = "modifiers")]
+            BuildError::BinaryFixup { http_path, path } => write!(
+                f,
+                "path fixup of '{}' would change the length of binary asset '{}'",
+                path, http_path,
+            ),
+            #[cfg(any(feature = "minify-js", feature = "optimize-svg"))]
+            BuildError::Minify { http_path, msg }
+                => write!(f, "failed to minify asset '{}': {}", http_path, msg),
+            #[cfg(feature = "modifiers")]
+            BuildError::Template { http_path, msg }
+                => write!(f, "failed to render template '{}': {}", http_path, msg),
         }
     }
 }
 
+#[cfg(feature = "full")]
 impl std::error::Error for BuildError {}
 
+/// Error returned by [`Asset::content`], which can only happen in dev mode.
+#[derive(Debug)]
+#[cfg_attr(prod_mode, allow(dead_code))]
+#[cfg(feature = "full")]
+pub struct LoadError {
+    pub(crate) http_path: String,
+    pub(crate) fs_path: Option<PathBuf>,
+    pub(crate) kind: LoadErrorKind,
+}
+
+/// What exactly went wrong when loading an asset.
+#[derive(Debug)]
+#[non_exhaustive]
+#[cfg(feature = "full")]
+pub enum LoadErrorKind {
+    /// Reading the file failed.
+    Io(io::Error),
+
+    /// The modifier of the asset panicked. Contains the panic message.
+    ModifierPanic(String),
+
+    /// The source of the asset did not match the hash pinned via
+    /// [`EntryBuilder::expect_sha256`]. Hashes are hex encoded.
+    ChecksumMismatch {
+        expected: String,
+        actual: String,
+    },
+
+    /// A validator registered via [`EntryBuilder::validate`] rejected the
+    /// content. Contains the validator's message.
+    Validation(String),
+
+    /// The template of an asset configured with
+    /// [`EntryBuilder::with_template`] could not be rendered.
+    #[cfg(feature = "modifiers")]
+    Template(String),
+}
+
+#[cfg(feature = "full")]
+impl LoadError {
+    /// The *HTTP path* of the asset that failed to load.
+    pub fn http_path(&self) -> &str {
+        &self.http_path
+    }
+
+    /// The *FS path* from which the asset was loaded, if it was loaded from
+    /// the file system.
+    pub fn fs_path(&self) -> Option<&Path> {
+        self.fs_path.as_deref()
+    }
+
+    /// What went wrong.
+    pub fn kind(&self) -> &LoadErrorKind {
+        &self.kind
+    }
+
+    /// Renders this error as a self-contained HTML page, meant to be shown in
+    /// the browser instead of a bare "500 Internal Server Error" during
+    /// development. Do not show this to users in production as it contains
+    /// file system paths.
+    pub fn to_html(&self) -> String {
+        let (what, details) = match &self.kind {
+            LoadErrorKind::Io(e) => ("IO error", e.to_string()),
+            LoadErrorKind::ModifierPanic(msg) => ("Modifier panicked", msg.clone()),
+            LoadErrorKind::ChecksumMismatch { expected, actual } => (
+                "Checksum mismatch",
+                format!("expected SHA-256 {expected}\nactual SHA-256   {actual}"),
+            ),
+            LoadErrorKind::Validation(msg) => ("Validation failed", msg.clone()),
+            #[cfg(feature = "modifiers")]
+            LoadErrorKind::Template(msg) => ("Template error", msg.clone()),
+        };
+        let fs_path = self.fs_path.as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|| "-".into());
+
+        format!(
+            "<!DOCTYPE html>\n\
+            <html>\n\
+            <head>\n\
+            <meta charset=\"utf-8\">\n\
+            <title>Failed to load asset</title>\n\
+            <style>\n\
+            body {{ font-family: sans-serif; margin: 32px; }}\n\
+            h1 {{ color: #b00; }}\n\
+            th {{ text-align: left; padding-right: 16px; }}\n\
+            pre {{ background: #fee; padding: 16px; white-space: pre-wrap; }}\n\
+            </style>\n\
+            </head>\n\
+            <body>\n\
+            <h1>Failed to load asset</h1>\n\
+            <table>\n\
+            <tr><th>HTTP path</th><td><code>{}</code></td></tr>\n\
+            <tr><th>FS path</th><td><code>{}</code></td></tr>\n\
+            <tr><th>Error</th><td>{}</td></tr>\n\
+            </table>\n\
+            <pre>{}</pre>\n\
+            </body>\n\
+            </html>\n",
+            util::escape_html(&self.http_path),
+            util::escape_html(&fs_path),
+            what,
+            util::escape_html(&details),
+        )
+    }
+}
+
+#[cfg(feature = "full")]
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to load asset '{}'", self.http_path)?;
+        if let Some(fs_path) = &self.fs_path {
+            write!(f, " from '{}'", fs_path.display())?;
+        }
+        match &self.kind {
+            LoadErrorKind::Io(e) => write!(f, ": {}", e),
+            LoadErrorKind::ModifierPanic(msg) => write!(f, ": modifier panicked: {}", msg),
+            LoadErrorKind::ChecksumMismatch { expected, actual } => {
+                write!(f, ": SHA-256 hash is {}, but {} was expected", actual, expected)
+            }
+            LoadErrorKind::Validation(msg) => write!(f, ": validation failed: {}", msg),
+            #[cfg(feature = "modifiers")]
+            LoadErrorKind::Template(msg) => write!(f, ": failed to render template: {}", msg),
+        }
+    }
+}
+
+#[cfg(feature = "full")]
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            LoadErrorKind::Io(e) => Some(e),
+            LoadErrorKind::ModifierPanic(_)
+            | LoadErrorKind::ChecksumMismatch { .. }
+            | LoadErrorKind::Validation(_) => None,
+            #[cfg(feature = "modifiers")]
+            LoadErrorKind::Template(_) => None,
+        }
+    }
+}
+
+/// Error returned by [`Asset::text`].
+#[derive(Debug)]
+#[non_exhaustive]
+#[cfg(feature = "full")]
+pub enum TextError {
+    /// Loading the asset failed, which can only happen in dev mode.
+    Load(LoadError),
+
+    /// The content is not valid UTF-8.
+    InvalidUtf8(std::str::Utf8Error),
+}
+
+#[cfg(feature = "full")]
+impl fmt::Display for TextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextError::Load(e) => e.fmt(f),
+            TextError::InvalidUtf8(e) => write!(f, "asset is not valid UTF-8: {}", e),
+        }
+    }
+}
+
+#[cfg(feature = "full")]
+impl std::error::Error for TextError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TextError::Load(e) => Some(e),
+            TextError::InvalidUtf8(e) => Some(e),
+        }
+    }
+}
+
+#[cfg(feature = "full")]
+impl From<LoadError> for io::Error {
+    /// Keeps the [`io::ErrorKind`] of IO errors and wraps `e`, so that the
+    /// message still names the asset and file.
+    fn from(e: LoadError) -> Self {
+        let kind = match &e.kind {
+            LoadErrorKind::Io(inner) => inner.kind(),
+            LoadErrorKind::ModifierPanic(_) => io::ErrorKind::Other,
+            LoadErrorKind::ChecksumMismatch { .. } | LoadErrorKind::Validation(_) => {
+                io::ErrorKind::InvalidData
+            }
+            #[cfg(feature = "modifiers")]
+            LoadErrorKind::Template(_) => io::ErrorKind::InvalidData,
+        };
+        io::Error::new(kind, e)
+    }
+}
+
 
 
 // =========================================================================================
 // ===== Various types
 // =========================================================================================
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 #[cfg_attr(any(dev_mode, not(feature = "hash")), allow(dead_code))]
+#[cfg(feature = "full")]
 enum PathHash<'a> {
     None,
-    Auto,
+    /// Like `None`, but not overridden by groups.
+    Stable,
+    /// `None` means [`HashPlacement::AfterFirstDot`], unless the builder
+    /// specifies a different default.
+    #[cfg(feature = "hash")]
+    Auto(Option<HashPlacement>),
     InBetween {
-        prefix: &'a str,
-        suffix: &'a str,
+        prefix: Cow<'a, str>,
+        suffix: Cow<'a, str>,
     },
 }
 
 #[derive(Debug, Clone)]
+#[cfg(feature = "full")]
 enum DataSource {
     File(PathBuf),
+    /// An embedded file in prod mode. In dev mode, embedded files are loaded
+    /// from the file system via `File`.
     #[cfg_attr(dev_mode, allow(dead_code))]
-    Loaded(Bytes),
+    Embedded {
+        path: &'static str,
+        content: Bytes,
+        /// Encoded forms of `content` stored in the executable.
+        encoded: Vec<(Encoding, Bytes)>,
+        modified: Option<std::time::SystemTime>,
+    },
+    Custom(source::CustomSource),
 }
 
+#[cfg(feature = "full")]
 impl DataSource {
-    async fn load(&self) -> Result<Bytes, (io::Error, &Path)> {
+    /// Loads the data. On error, the *FS path* is returned as well, if any.
+    async fn load(&self) -> Result<Bytes, (io::Error, Option<&Path>)> {
         match self {
             DataSource::File(path) => tokio::fs::read(path).await
                 .map(Into::into)
-                .map_err(|err| (err, &**path)),
-            DataSource::Loaded(bytes) => Ok(bytes.clone()),
+                .map_err(|err| (err, Some(&**path))),
+            DataSource::Embedded { content, .. } => Ok(content.clone()),
+            DataSource::Custom(source) => source.load().await.map_err(|err| (err, None)),
+        }
+    }
+
+    /// Returns a description of `self` for error messages.
+    fn describe(&self) -> String {
+        match self {
+            DataSource::File(path) => format!("file '{}'", path.display()),
+            DataSource::Embedded { path, .. } => format!("embedded file '{}'", path),
+            DataSource::Custom(_) => "custom source".into(),
+        }
+    }
+
+    /// Returns the modification time of the data, if known.
+    #[cfg_attr(dev_mode, allow(dead_code))]
+    async fn modified(&self) -> Option<std::time::SystemTime> {
+        match self {
+            DataSource::File(path) => tokio::fs::metadata(path).await.ok()?.modified().ok(),
+            DataSource::Embedded { modified, .. } => *modified,
+            DataSource::Custom(source) => source.modified().await,
         }
     }
 }
 
 
 #[derive(Clone)]
+#[cfg(feature = "full")]
 enum Modifier {
     None,
+    #[cfg(feature = "modifiers")]
     #[cfg_attr(dev_mode, allow(dead_code))]
     PathFixup(Vec<Cow<'static, str>>),
+    /// Dependencies are filled from the template when building.
+    #[cfg(feature = "modifiers")]
+    Template(Vec<Cow<'static, str>>),
     Custom {
         f: Arc<dyn Send + Sync + Fn(Bytes, ModifierContext) -> Bytes>,
         deps: Vec<Cow<'static, str>>,
+        /// Prefix of the `AssetModule` that added this modifier, if any.
+        module_prefix: Cow<'static, str>,
     },
 }
 
+#[cfg(feature = "full")]
 impl Modifier {
-    #[cfg(prod_mode)]
     fn dependencies(&self) -> Option<&[Cow<'static, str>]> {
         match self {
             Modifier::None => None,
+            #[cfg(feature = "modifiers")]
+            Modifier::PathFixup(deps) => Some(deps),
+            #[cfg(feature = "modifiers")]
+            Modifier::Template(deps) => Some(deps),
+            Modifier::Custom { deps, .. } => Some(deps),
+        }
+    }
+
+    fn dependencies_mut(&mut self) -> Option<&mut Vec<Cow<'static, str>>> {
+        match self {
+            Modifier::None => None,
+            #[cfg(feature = "modifiers")]
             Modifier::PathFixup(deps) => Some(deps),
+            #[cfg(feature = "modifiers")]
+            Modifier::Template(deps) => Some(deps),
             Modifier::Custom { deps, .. } => Some(deps),
         }
     }
 }
 
+#[cfg(feature = "full")]
 impl std::fmt::Debug for Modifier {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Modifier::None => write!(f, "None"),
+            #[cfg(feature = "modifiers")]
             Modifier::PathFixup(_) => write!(f, "PathFixup"),
+            #[cfg(feature = "modifiers")]
+            Modifier::Template(_) => write!(f, "Template"),
             Modifier::Custom { .. } => write!(f, "Custom"),
         }
     }
@@ -410,6 +1685,7 @@ impl std::fmt::Debug for Modifier {
 /// A glob patttern split after all leading fixed path segments.
 #[cfg_attr(test, derive(PartialEq, Eq))]
 #[derive(Debug, Clone)]
+#[cfg(feature = "full")]
 struct SplitGlob {
     /// All leading path segments from the glob that do not contain glob meta
     /// characters.
@@ -417,13 +1693,25 @@ struct SplitGlob {
 
     /// The second part of the glob, starting with a segment having glob meta
     /// characters.
-    #[cfg_attr(prod_mode, allow(dead_code))]
     suffix: glob::Pattern,
 }
 
+#[cfg(feature = "full")]
 impl SplitGlob {
     fn new(glob: &'static str) -> Self {
-        let offset = Path::new(glob).components().find_map(|component| {
+        let (prefix, suffix) = split_glob(glob);
+        Self {
+            prefix,
+            // The `expect` is fine as the glob was already parsed at compile time.
+            suffix: glob::Pattern::new(suffix).expect("invalid glob"),
+        }
+    }
+}
+
+/// Splits `glob` after all leading path segments that do not contain glob
+/// meta characters.
+pub(crate) fn split_glob(glob: &str) -> (&str, &str) {
+    let offset = Path::new(glob).components().find_map(|component| {
             let std::path::Component::Normal(seg) = component else {
                 return None;
             };
@@ -435,20 +1723,100 @@ impl SplitGlob {
             }
 
             None
-        }).unwrap_or(glob.len());
+    }).unwrap_or(glob.len());
 
-        let (prefix, suffix) = glob.split_at(offset);
+    glob.split_at(offset)
+}
 
-        Self {
-            prefix,
-            // The `expect` is fine as the glob was already parsed at compile time.
-            suffix: glob::Pattern::new(suffix).expect("invalid glob"),
-        }
+/// Describes which *unhashed HTTP paths* a glob entry serves: all paths
+/// starting with [`Self::http_prefix`] where the remainder matches
+/// [`Self::pattern`].
+///
+/// In dev mode, files matching a glob are served even if they did not exist
+/// at compile time. Routers can use this to register precise routes for glob
+/// entries instead of a catch-all. Obtained via [`EntryBuilder::glob_mount`]
+/// or [`Assets::glob_mounts`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlobMount {
+    http_prefix: String,
+    pattern: glob::Pattern,
+}
+
+impl GlobMount {
+    /// Creates the mount that results from mounting the embed pattern `glob`
+    /// under `http_prefix`, as done by [`Builder::add_embedded_glob`]: the
+    /// leading segments of `glob` without glob characters are stripped.
+    ///
+    /// ```
+    /// use reinda::GlobMount;
+    ///
+    /// let mount = GlobMount::new("animals/", "foo/bar/*.svg");
+    /// assert_eq!(mount.http_prefix(), "animals/");
+    /// assert_eq!(mount.pattern(), "*.svg");
+    /// assert!(mount.matches("animals/cat.svg"));
+    /// assert!(!mount.matches("foo/bar/cat.svg"));
+    /// ```
+    ///
+    /// **Panics** if `glob` is not a valid glob pattern.
+    pub fn new(http_prefix: impl Into<String>, glob: &str) -> Self {
+        let (_, suffix) = split_glob(glob);
+        let pattern = glob::Pattern::new(suffix)
+            .unwrap_or_else(|e| panic!("invalid glob '{}': {}", glob, e));
+        Self { http_prefix: http_prefix.into(), pattern }
+    }
+
+    /// The *HTTP path* prefix all served paths start with.
+    pub fn http_prefix(&self) -> &str {
+        &self.http_prefix
+    }
+
+    /// The glob pattern that the part after [`Self::http_prefix`] has to
+    /// match.
+    pub fn pattern(&self) -> &str {
+        self.pattern.as_str()
+    }
+
+    /// Returns whether `http_path` is served by this mount (provided the
+    /// corresponding file exists).
+    pub fn matches(&self, http_path: &str) -> bool {
+        self.suffix_of(http_path).is_some()
+    }
+
+    /// Returns the part of `http_path` after the prefix, if it matches.
+    fn suffix_of<'p>(&self, http_path: &'p str) -> Option<&'p str> {
+        http_path.strip_prefix(&self.http_prefix).filter(|suffix| self.pattern.matches(suffix))
     }
 }
 
 
-#[cfg(test)]
+// `Assets` is commonly stored in globals or shared state of web frameworks, so
+// all public types have to stay `Send + Sync + 'static`, in both modes.
+const _: () = {
+    const fn assert_thread_safe<T: Send + Sync + 'static>() {}
+
+    assert_thread_safe::<Embeds>();
+    assert_thread_safe::<GlobMount>();
+    assert_thread_safe::<minimal::Assets>();
+    assert_thread_safe::<minimal::Asset<'static>>();
+
+    #[cfg(feature = "full")]
+    {
+        assert_thread_safe::<Assets>();
+        assert_thread_safe::<Asset>();
+        assert_thread_safe::<Builder<'static>>();
+        assert_thread_safe::<AssetsRuntime>();
+        assert_thread_safe::<ChangeEvent>();
+        assert_thread_safe::<Warning>();
+        assert_thread_safe::<BuildError>();
+        assert_thread_safe::<LoadError>();
+    }
+
+    #[cfg(feature = "tower")]
+    assert_thread_safe::<AssetService>();
+};
+
+
+#[cfg(all(test, feature = "full"))]
 mod tests {
     use super::*;
 
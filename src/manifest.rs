@@ -0,0 +1,138 @@
+//! Path manifests for other languages, see [`Assets::manifest`].
+
+use std::{collections::BTreeMap, io};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Assets, BuildError};
+
+
+/// Maps *unhashed HTTP paths* to the paths under which assets are served,
+/// for server-side rendering layers written in other languages (Node,
+/// Python, ...) that need to reference assets. Create it via
+/// [`Assets::manifest`], e.g. in a `--write-manifest` mode of your server
+/// binary, and load the JSON from the other layer.
+///
+/// # JSON schema
+///
+/// ```json
+/// {
+///   "version": 1,
+///   "assets": {
+///     "index.html": { "path": "index.html" },
+///     "static/app.js": { "path": "static/app.LCa0a2j_xo_5.js" }
+///   }
+/// }
+/// ```
+///
+/// - `version`: currently always [`Manifest::VERSION`]. It is increased when
+///   fields are removed or their meaning changes. New fields might be added
+///   without increasing it, so consumers should ignore unknown fields.
+/// - `assets`: object with one entry per asset, keyed by *unhashed HTTP
+///   path* (without leading `/`), sorted by key.
+///   - `path`: the *hashed HTTP path* (without leading `/`). Equal to the key
+///     if the asset has no hashed filename.
+///
+/// Paths are only hashed in prod mode, so create the manifest with a binary
+/// built in prod mode. Files matched by glob entries are only listed if they
+/// existed at compile time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Manifest {
+    /// Schema version, see above.
+    pub version: u32,
+
+    /// All assets, keyed by *unhashed HTTP path*.
+    pub assets: BTreeMap<String, ManifestEntry>,
+}
+
+/// A single asset in the [`Manifest`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ManifestEntry {
+    /// The *hashed HTTP path*.
+    pub path: String,
+}
+
+impl Manifest {
+    /// The schema version written by this version of `reinda`.
+    pub const VERSION: u32 = 1;
+
+    /// Returns the *hashed HTTP path* for the given *unhashed HTTP path*, if
+    /// that asset exists.
+    pub fn resolve(&self, unhashed_http_path: &str) -> Option<&str> {
+        self.assets.get(unhashed_http_path).map(|entry| &*entry.path)
+    }
+
+    /// Returns this manifest as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("failed to serialize manifest")
+    }
+
+    /// Writes this manifest as pretty-printed JSON to `writer`.
+    pub fn write_json(&self, writer: impl io::Write) -> Result<(), io::Error> {
+        serde_json::to_writer_pretty(writer, self).map_err(Into::into)
+    }
+
+    /// Parses a manifest from JSON. Fails if the JSON is invalid or has a
+    /// different [`Self::VERSION`].
+    pub fn from_json(json: &str) -> Result<Self, io::Error> {
+        let manifest: Self = serde_json::from_str(json)?;
+        if manifest.version != Self::VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported manifest version {}", manifest.version),
+            ));
+        }
+        Ok(manifest)
+    }
+
+    fn from_paths<'a>(paths: impl Iterator<Item = (&'a str, &'a str)>) -> Self {
+        let assets = paths
+            .map(|(unhashed, hashed)| (unhashed.to_owned(), ManifestEntry { path: hashed.to_owned() }))
+            .collect();
+        Self { version: Self::VERSION, assets }
+    }
+}
+
+impl Assets {
+    /// Returns the [`Manifest`] mapping all *unhashed HTTP paths* to *hashed
+    /// HTTP paths*. In dev mode, both are always the same.
+    ///
+    /// Method is only available if the crate feature `manifest` is enabled.
+    pub fn manifest(&self) -> Manifest {
+        Manifest::from_paths(self.0.unhashed_paths())
+    }
+
+    /// Executes the full prod build pipeline (loading, checksums, modifiers,
+    /// validation, hashing and dependency checks) even in dev mode and
+    /// returns the [`Manifest`] prod mode would produce. Intended for tests,
+    /// so that CI catches prod-only failures without a release build:
+    ///
+    /// ```no_run
+    /// # async fn foo(assets: reinda::Assets) {
+    /// let manifest = assets.dry_run_prod().await.unwrap();
+    /// assert!(manifest.resolve("index.html").is_some());
+    /// # }
+    /// ```
+    ///
+    /// In dev mode, files are loaded from the file system, including all
+    /// files currently matched by glob entries. In prod mode, all of this
+    /// already happened in [`Builder::build`][crate::Builder::build], so
+    /// this just returns [`Self::manifest`].
+    ///
+    /// Method is only available if the crate feature `manifest` is enabled.
+    ///
+    /// **Panics** in the same situations as `Builder::build` in prod mode,
+    /// e.g. if a modifier panics or a dependency does not exist.
+    pub async fn dry_run_prod(&self) -> Result<Manifest, BuildError> {
+        #[cfg(prod_mode)]
+        { Ok(self.manifest()) }
+
+        #[cfg(dev_mode)]
+        {
+            let paths = self.0.dry_run_prod().await?;
+            Ok(Manifest::from_paths(paths.iter().map(|(k, v)| (&**k, &**v))))
+        }
+    }
+}
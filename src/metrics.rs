@@ -0,0 +1,24 @@
+//! Metrics recorded via the `metrics` crate, see the crate feature `metrics`.
+
+
+/// Records a lookup via [`Assets::get`][crate::Assets::get].
+pub(crate) fn lookup(found: bool) {
+    ::metrics::counter!("reinda_lookups_total").increment(1);
+    if !found {
+        ::metrics::counter!("reinda_lookup_misses_total").increment(1);
+    }
+}
+
+/// Records how long loading and processing an asset took in dev mode.
+#[cfg(dev_mode)]
+pub(crate) fn dev_load(duration: std::time::Duration) {
+    ::metrics::histogram!("reinda_dev_load_duration_seconds").record(duration);
+}
+
+/// Records that a response body with `bytes` bytes of `asset` was sent by
+/// one of the framework integrations.
+#[cfg(any(feature = "axum", feature = "tower", feature = "warp"))]
+pub(crate) fn served(asset: &crate::Asset, bytes: usize) {
+    let tag = asset.metrics_tag().unwrap_or_default().to_owned();
+    ::metrics::counter!("reinda_served_bytes_total", "tag" => tag).increment(bytes as u64);
+}
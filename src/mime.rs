@@ -0,0 +1,140 @@
+//! Determining MIME types of assets.
+
+/// Known file extensions and their MIME types. Text types get a `charset`
+/// parameter elsewhere, if appropriate.
+const TYPES: &[(&str, &str)] = &[
+    ("avif", "image/avif"),
+    ("bin", "application/octet-stream"),
+    ("br", "application/x-brotli"),
+    ("css", "text/css"),
+    ("csv", "text/csv"),
+    ("gif", "image/gif"),
+    ("gz", "application/gzip"),
+    ("htm", "text/html"),
+    ("html", "text/html"),
+    ("ico", "image/x-icon"),
+    ("jpeg", "image/jpeg"),
+    ("jpg", "image/jpeg"),
+    ("js", "text/javascript"),
+    ("json", "application/json"),
+    ("map", "application/json"),
+    ("md", "text/markdown"),
+    ("mjs", "text/javascript"),
+    ("mp3", "audio/mpeg"),
+    ("mp4", "video/mp4"),
+    ("ogg", "audio/ogg"),
+    ("otf", "font/otf"),
+    ("pdf", "application/pdf"),
+    ("png", "image/png"),
+    ("svg", "image/svg+xml"),
+    ("toml", "application/toml"),
+    ("tsv", "text/tab-separated-values"),
+    ("ttf", "font/ttf"),
+    ("txt", "text/plain"),
+    ("wasm", "application/wasm"),
+    ("webm", "video/webm"),
+    ("webmanifest", "application/manifest+json"),
+    ("webp", "image/webp"),
+    ("woff", "font/woff"),
+    ("woff2", "font/woff2"),
+    ("xml", "application/xml"),
+    ("yaml", "application/yaml"),
+    ("yml", "application/yaml"),
+    ("zip", "application/zip"),
+];
+
+/// Well-known files without extension and their MIME types.
+const FILENAMES: &[(&str, &str)] = &[
+    ("apple-app-site-association", "application/json"),
+    ("apple-developer-merchantid-domain-association", "text/plain"),
+];
+
+/// Custom mappings from lowercase extensions to MIME types, registered via
+/// [`Builder::add_mime_type`][crate::Builder::add_mime_type].
+pub(crate) type CustomTypes = ahash::HashMap<String, &'static str>;
+
+/// Returns the MIME type for the extension of `path` or, for extensionless
+/// files, for its filename, if it's known. `custom` takes precedence over
+/// the built-in types.
+pub(crate) fn from_path(path: &str, custom: &CustomTypes) -> Option<&'static str> {
+    match crate::text::extension(path) {
+        Some(ext) => custom.get(&ext)
+            .or_else(|| TYPES.iter().find(|(e, _)| *e == ext).map(|(_, mime)| mime))
+            .copied(),
+        None => {
+            let filename = path.rsplit('/').next().unwrap_or(path);
+            FILENAMES.iter().find(|(f, _)| *f == filename).map(|(_, mime)| *mime)
+        }
+    }
+}
+
+/// Returns the value of the `as` attribute for preloading a resource with
+/// MIME type `mime` and whether it has to be fetched in CORS mode, i.e.
+/// needs the `crossorigin` attribute. `None` if preloading is not useful.
+pub(crate) fn preload_destination(mime: &str) -> Option<(&'static str, bool)> {
+    let (ty, _) = mime.split_once('/')?;
+    match (ty, mime) {
+        (_, "text/css") => Some(("style", false)),
+        (_, "text/javascript") => Some(("script", false)),
+        (_, "application/json" | "application/manifest+json" | "application/wasm") => {
+            Some(("fetch", true))
+        }
+        ("font", _) => Some(("font", true)),
+        ("image", _) => Some(("image", false)),
+        _ => None,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_path(path: &str) -> Option<&'static str> {
+        super::from_path(path, &CustomTypes::default())
+    }
+
+    #[test]
+    fn by_extension() {
+        assert_eq!(from_path("logo.PNG"), Some("image/png"));
+        assert_eq!(from_path("static/app.min.js"), Some("text/javascript"));
+        assert_eq!(from_path("fonts/a.woff2"), Some("font/woff2"));
+        assert_eq!(from_path("LICENSE"), None);
+        assert_eq!(from_path("archive.rar"), None);
+    }
+
+    #[test]
+    fn extensionless_and_dotfiles() {
+        assert_eq!(from_path(".well-known/assetlinks.json"), Some("application/json"));
+        assert_eq!(from_path(".well-known/security.txt"), Some("text/plain"));
+        assert_eq!(from_path(".well-known/apple-app-site-association"), Some("application/json"));
+        assert_eq!(from_path("apple-app-site-association.bak"), None);
+        assert_eq!(from_path(".htaccess"), None);
+        assert_eq!(from_path(".json"), None);
+        assert_eq!(from_path("config/.eslintrc.json"), Some("application/json"));
+    }
+
+    #[test]
+    fn custom() {
+        let mut custom = CustomTypes::default();
+        custom.insert("glb".into(), "model/gltf-binary");
+        custom.insert("js".into(), "application/javascript");
+        assert_eq!(super::from_path("models/a.GLB", &custom), Some("model/gltf-binary"));
+        assert_eq!(super::from_path("app.js", &custom), Some("application/javascript"));
+        assert_eq!(super::from_path("style.css", &custom), Some("text/css"));
+    }
+
+    #[test]
+    fn preload() {
+        assert_eq!(preload_destination("text/css"), Some(("style", false)));
+        assert_eq!(preload_destination("font/woff2"), Some(("font", true)));
+        assert_eq!(preload_destination("image/svg+xml"), Some(("image", false)));
+        assert_eq!(preload_destination("text/html"), None);
+        assert_eq!(preload_destination("nonsense"), None);
+    }
+
+    #[test]
+    fn sorted() {
+        assert!(TYPES.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+}
@@ -0,0 +1,20 @@
+//! Minifying JavaScript, see [`util::minify_js`][crate::util::minify_js].
+
+use minify_js::{Session, TopLevelMode};
+
+
+pub(crate) fn js(src: &[u8]) -> Result<Vec<u8>, String> {
+    // As a classic script, top-level names are kept, since other scripts might
+    // refer to them. Only if that fails to parse (e.g. due to `import`), the
+    // code is minified as module.
+    let session = Session::new();
+    let mut out = Vec::with_capacity(src.len());
+    if minify_js::minify(&session, TopLevelMode::Global, src, &mut out).is_ok() {
+        return Ok(out);
+    }
+
+    out.clear();
+    minify_js::minify(&session, TopLevelMode::Module, src, &mut out)
+        .map_err(|e| format!("invalid JavaScript: {:?}", e))?;
+    Ok(out)
+}
@@ -0,0 +1,167 @@
+//! A minimal, synchronous alternative to [`Assets`][crate::Assets] for tiny
+//! services.
+//!
+//! [`minimal::Assets`][Assets] is created directly from [`Embeds`] and only
+//! offers filename hashing as configuration: no modifiers, no builder, no
+//! async. All files are served under their [`EmbeddedFile::path`] and lookups
+//! are a binary search in a sorted slice. Together with disabling the default
+//! `full` feature (which removes the `tokio` and `ahash` dependencies), this
+//! keeps compile times and binary size small.
+//!
+//! ```ignore
+//! const EMBEDS: reinda::Embeds = reinda::embed! {
+//!     base_path: "assets",
+//!     files: ["index.html", "app.js", "icons/*.svg"],
+//! };
+//!
+//! let assets = reinda::minimal::Assets::with_hash(&EMBEDS, |path| path != "index.html");
+//! let app_js = assets.resolve_path("app.js").unwrap();
+//! let content = assets.get(app_js).unwrap().content()?;
+//! ```
+//!
+//! In dev mode, no hashes are inserted and files are read from the file
+//! system on each [`Asset::content`] call, just like with the full
+//! [`Assets`][crate::Assets].
+
+use std::{borrow::Cow, io};
+
+use crate::{EmbeddedFile, Embeds};
+
+
+/// Collection of embedded files, mapping from *hashed HTTP paths* to assets.
+/// See [the module docs][self].
+#[derive(Debug)]
+pub struct Assets {
+    /// Sorted by hashed HTTP path.
+    files: Box<[File]>,
+
+    /// Indices into `files`, sorted by unhashed HTTP path.
+    by_unhashed: Box<[usize]>,
+}
+
+#[derive(Debug)]
+struct File {
+    http_path: Cow<'static, str>,
+    embedded: &'static EmbeddedFile,
+
+    #[cfg(prod_mode)]
+    content: Cow<'static, [u8]>,
+}
+
+impl Assets {
+    /// Creates assets from all files in `embeds`, without hashed filenames.
+    pub fn new(embeds: &'static Embeds) -> Self {
+        Self::build(embeds, |_| false)
+    }
+
+    /// Creates assets from all files in `embeds`, inserting a hash into the
+    /// filename of all files for which `hash_filename` returns `true`. The
+    /// function is called with [`EmbeddedFile::path`]. The hash is inserted
+    /// as with [`EntryBuilder::with_hash`][crate::EntryBuilder::with_hash].
+    #[cfg(feature = "hash")]
+    pub fn with_hash(embeds: &'static Embeds, hash_filename: impl Fn(&str) -> bool) -> Self {
+        Self::build(embeds, hash_filename)
+    }
+
+    #[cfg_attr(any(dev_mode, not(feature = "hash")), allow(unused_variables))]
+    fn build(embeds: &'static Embeds, hash_filename: impl Fn(&str) -> bool) -> Self {
+        let mut files = embeds.entries()
+            .flat_map(|entry| entry.files())
+            .map(|embedded| {
+                #[cfg(prod_mode)]
+                {
+                    let content = embedded.content();
+
+                    #[cfg(feature = "hash")]
+                    let http_path = if hash_filename(embedded.path()) {
+                        crate::hash::auto_hashed_path(embedded.path(), &content).into()
+                    } else {
+                        embedded.path().into()
+                    };
+
+                    #[cfg(not(feature = "hash"))]
+                    let http_path = embedded.path().into();
+
+                    File { http_path, embedded, content }
+                }
+
+                #[cfg(dev_mode)]
+                { File { http_path: embedded.path().into(), embedded } }
+            })
+            .collect::<Vec<_>>();
+
+        // The same file can be embedded by multiple entries, in which case we
+        // only keep the first.
+        files.sort_by(|a, b| a.embedded.path().cmp(b.embedded.path()));
+        files.dedup_by(|a, b| a.embedded.path() == b.embedded.path());
+
+        files.sort_by(|a, b| a.http_path.cmp(&b.http_path));
+        let mut by_unhashed = (0..files.len()).collect::<Vec<_>>();
+        by_unhashed.sort_by_key(|&i| files[i].embedded.path());
+
+        Self { files: files.into(), by_unhashed: by_unhashed.into() }
+    }
+
+    /// Returns the asset with the given *hashed HTTP path*.
+    pub fn get(&self, http_path: &str) -> Option<Asset<'_>> {
+        self.files.binary_search_by(|f| (*f.http_path).cmp(http_path))
+            .ok()
+            .map(|i| Asset(&self.files[i]))
+    }
+
+    /// Resolves an *unhashed HTTP path* (i.e. [`EmbeddedFile::path`]) to the
+    /// *hashed HTTP path* under which the asset is served. Returns `None` if
+    /// no such asset exists.
+    pub fn resolve_path(&self, unhashed_http_path: &str) -> Option<&str> {
+        self.by_unhashed.binary_search_by(|&i| self.files[i].embedded.path().cmp(unhashed_http_path))
+            .ok()
+            .map(|i| &*self.files[self.by_unhashed[i]].http_path)
+    }
+
+    /// Returns the number of assets.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Returns `true` if there are no assets.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Returns an iterator over all assets, sorted by *hashed HTTP path*.
+    pub fn iter(&self) -> impl '_ + Iterator<Item = Asset<'_>> {
+        self.files.iter().map(Asset)
+    }
+}
+
+/// A single asset, obtained via [`Assets::get`].
+#[derive(Debug, Clone, Copy)]
+pub struct Asset<'a>(&'a File);
+
+impl<'a> Asset<'a> {
+    /// The *hashed HTTP path* of this asset.
+    pub fn http_path(&self) -> &'a str {
+        &self.0.http_path
+    }
+
+    /// The *unhashed HTTP path* of this asset, i.e. [`EmbeddedFile::path`].
+    pub fn unhashed_http_path(&self) -> &'static str {
+        self.0.embedded.path()
+    }
+
+    /// Returns whether a hash was inserted into the filename. Can be used to
+    /// set appropriate cache headers.
+    pub fn is_filename_hashed(&self) -> bool {
+        *self.0.http_path != *self.0.embedded.path()
+    }
+
+    /// Returns the content of this asset. In prod mode, this never fails and
+    /// never allocates. In dev mode, the file is read from the file system.
+    pub fn content(&self) -> io::Result<Cow<'a, [u8]>> {
+        #[cfg(prod_mode)]
+        { Ok(Cow::Borrowed(&self.0.content)) }
+
+        #[cfg(dev_mode)]
+        { std::fs::read(self.0.embedded.full_path).map(Cow::Owned) }
+    }
+}
@@ -0,0 +1,168 @@
+//! Composing assets of multiple crates, see [`AssetModule`] and
+//! [`Builder::merge`].
+
+use std::borrow::Cow;
+
+use crate::{builder::EntryBuilderKind, Builder, Modifier};
+
+
+/// A reusable set of assets, e.g. shipped by a library crate with an embedded
+/// admin panel, that a host application can mount under a prefix of its
+/// choosing via [`Builder::add_module`].
+///
+/// The module registers its assets with paths relative to that prefix,
+/// which is prepended to all paths afterwards. This works best with
+/// `embed!` in the library crate, as embedded files are part of the final
+/// executable regardless of the host's working directory.
+///
+/// ```
+/// // In the library crate:
+/// const EMBEDS: reinda::Embeds = reinda::embed! {
+///     base_path: "tests/files",
+///     files: ["peter.txt"],
+/// };
+///
+/// pub struct AdminPanel;
+///
+/// impl reinda::AssetModule for AdminPanel {
+///     fn register(&self, builder: &mut reinda::Builder<'_>) {
+///         builder.add_embedded("index.html", &EMBEDS["peter.txt"])
+///             .with_cache_control("no-cache");
+///     }
+/// }
+///
+/// // In the host application:
+/// let mut builder = reinda::Assets::builder();
+/// builder.add_module("admin/", AdminPanel); // serves `admin/index.html`
+/// ```
+///
+/// Closures taking `&mut Builder` implement this trait as well.
+pub trait AssetModule {
+    /// Adds all assets of this module to `builder`. All paths (HTTP paths,
+    /// modifier dependencies, variants, group selectors and error assets)
+    /// are relative to the prefix passed to [`Builder::add_module`], so are
+    /// the paths passed to [`ModifierContext::resolve_path`][crate::ModifierContext::resolve_path]
+    /// in modifiers of this module.
+    fn register(&self, builder: &mut Builder<'_>);
+}
+
+impl<F: Fn(&mut Builder<'_>)> AssetModule for F {
+    fn register(&self, builder: &mut Builder<'_>) {
+        self(builder)
+    }
+}
+
+impl<'a> Builder<'a> {
+    /// Lets `module` register its assets and mounts all of them under
+    /// `prefix`. See [`AssetModule`] for more information.
+    ///
+    /// Entries, groups and error assets added by `module` have `prefix`
+    /// prepended to all their paths. Builder-wide settings the module
+    /// changes (like [`Self::set_cache_policy`] or path parameters) apply to all
+    /// assets. `prefix` is usually a directory, i.e. ends with `/`.
+    pub fn add_module(&mut self, prefix: &str, module: impl AssetModule) -> &mut Self {
+        self.mount_under(prefix, |builder| module.register(builder))
+    }
+
+    /// Moves all entries and groups of `other` into this builder, e.g. a
+    /// builder returned by a library crate. Equivalent to
+    /// [`Self::merge_prefixed`] with an empty prefix.
+    ///
+    /// ```
+    /// fn admin_assets() -> reinda::Builder<'static> {
+    ///     let mut builder = reinda::Assets::builder();
+    ///     builder.add_file("admin.css", "tests/files/peter.txt");
+    ///     builder
+    /// }
+    ///
+    /// let mut builder = reinda::Assets::builder();
+    /// builder.add_file("index.html", "tests/files/peter.txt");
+    /// builder.merge(admin_assets());
+    /// ```
+    pub fn merge(&mut self, other: Builder<'a>) -> &mut Self {
+        self.merge_prefixed("", other)
+    }
+
+    /// Moves all entries and groups of `other` into this builder, prepending
+    /// `prefix` to all their paths as [`Self::add_module`] does.
+    ///
+    /// Error assets, the fallback, path parameters and variables of `other` are only
+    /// used if not set in this builder. All other builder-wide settings of
+    /// `other` (like its cache policy) are ignored, so configure them on
+    /// this builder instead.
+    pub fn merge_prefixed(&mut self, prefix: &str, other: Builder<'a>) -> &mut Self {
+        self.mount_under(prefix, |builder| {
+            builder.assets.extend(other.assets);
+            builder.groups.extend(other.groups);
+            for (status, path) in other.error_assets {
+                builder.error_assets.entry(status).or_insert(path);
+            }
+            if builder.fallback.is_none() {
+                builder.fallback = other.fallback;
+            }
+            for (name, value) in other.path_params {
+                builder.path_params.entry(name).or_insert(value);
+            }
+            for (name, value) in other.variables {
+                builder.variables.entry(name).or_insert(value);
+            }
+        })
+    }
+
+    /// Calls `register` and prepends `prefix` to all paths of entries,
+    /// groups and error assets added by it.
+    fn mount_under(&mut self, prefix: &str, register: impl FnOnce(&mut Self)) -> &mut Self {
+        let num_assets = self.assets.len();
+        let num_groups = self.groups.len();
+        let old_error_assets = self.error_assets.clone();
+        let old_fallback = self.fallback.clone();
+        register(self);
+
+        let prefixed = |path: &mut Cow<'_, str>| *path = format!("{}{}", prefix, path).into();
+        let prefix_all = |paths: &mut Vec<Cow<'static, str>>| paths.iter_mut().for_each(prefixed);
+        let prefix_modifier = |modifier: &mut Modifier| {
+            if let Some(deps) = modifier.dependencies_mut() {
+                prefix_all(deps);
+            }
+            if let Modifier::Custom { module_prefix, .. } = modifier {
+                prefixed(module_prefix);
+            }
+        };
+
+        for entry in &mut self.assets[num_assets..] {
+            match &mut entry.kind {
+                EntryBuilderKind::Single { http_path, .. } => prefixed(http_path),
+                EntryBuilderKind::Glob { http_prefix, .. }
+                | EntryBuilderKind::Dir { http_prefix, .. } => prefixed(http_prefix),
+            }
+            prefix_modifier(&mut entry.config.modifier);
+            prefix_all(&mut entry.config.after);
+            prefix_all(&mut entry.config.variants);
+            for (_, path) in &mut entry.config.locale_variants {
+                prefixed(path);
+            }
+        }
+
+        for group in &mut self.groups[num_groups..] {
+            let selector = format!("{}{}", glob::Pattern::escape(prefix), group.selector.as_str());
+            group.selector = glob::Pattern::new(&selector)
+                .expect("prefixing a valid glob pattern resulted in an invalid one");
+            if let Some(modifier) = &mut group.modifier {
+                prefix_modifier(modifier);
+            }
+        }
+
+        for (status, path) in &mut self.error_assets {
+            if old_error_assets.get(status) != Some(path) {
+                prefixed(path);
+            }
+        }
+        if self.fallback != old_fallback {
+            if let Some(path) = &mut self.fallback {
+                prefixed(path);
+            }
+        }
+
+        self
+    }
+}
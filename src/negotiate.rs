@@ -0,0 +1,134 @@
+//! Content negotiation via `Accept`-like headers.
+
+/// Returns the quality value (between 0 and 1) that the `Accept` header value
+/// `accept` assigns to the MIME type `mime`. The most specific matching media
+/// range counts, e.g. `image/webp` over `image/*` over `*/*`. Returns 0 if
+/// nothing matches.
+pub(crate) fn mime_quality(accept: &str, mime: &str) -> f32 {
+    let (ty, _) = mime.split_once('/').unwrap_or((mime, ""));
+    parse(accept)
+        .filter_map(|(range, q)| {
+            let specificity = if range.eq_ignore_ascii_case(mime) {
+                2
+            } else if range.strip_suffix("/*").is_some_and(|t| t.eq_ignore_ascii_case(ty)) {
+                1
+            } else if range == "*/*" {
+                0
+            } else {
+                return None;
+            };
+            Some((specificity, q))
+        })
+        .max_by_key(|(specificity, _)| *specificity)
+        .map_or(0.0, |(_, q)| q)
+}
+
+/// Returns the quality value (between 0 and 1) that the `Accept-Encoding`
+/// header value `accept` assigns to the content coding `coding`. An explicit
+/// entry takes precedence over `*`. Returns 0 if nothing matches.
+pub(crate) fn coding_quality(accept: &str, coding: &str) -> f32 {
+    parse(accept)
+        .filter_map(|(item, q)| {
+            let specificity = if item.eq_ignore_ascii_case(coding) {
+                1
+            } else if item == "*" {
+                0
+            } else {
+                return None;
+            };
+            Some((specificity, q))
+        })
+        .max_by_key(|(specificity, _)| *specificity)
+        .map_or(0.0, |(_, q)| q)
+}
+
+/// Returns the quality value (between 0 and 1) that the `Accept-Language`
+/// header value `accept` assigns to the language tag `lang`. A range matches
+/// the tag itself and more specific tags (`de` matches `de-AT`), with the
+/// most specific matching range counting. As a fallback, a range also
+/// matches less specific tags (`de-AT` matches `de`) and `*` matches all.
+/// Returns 0 if nothing matches.
+pub(crate) fn language_quality(accept: &str, lang: &str) -> f32 {
+    let is_prefix = |prefix: &str, tag: &str| {
+        tag.len() > prefix.len()
+            && tag.as_bytes()[prefix.len()] == b'-'
+            && tag[..prefix.len()].eq_ignore_ascii_case(prefix)
+    };
+    parse(accept)
+        .filter_map(|(range, q)| {
+            let specificity = if range.eq_ignore_ascii_case(lang) {
+                range.len() + 2
+            } else if is_prefix(range, lang) {
+                range.len() + 1
+            } else if is_prefix(lang, range) {
+                1
+            } else if range == "*" {
+                0
+            } else {
+                return None;
+            };
+            Some((specificity, q))
+        })
+        .max_by_key(|(specificity, _)| *specificity)
+        .map_or(0.0, |(_, q)| q)
+}
+
+/// Parses a header value like `text/html, image/*;q=0.8` into items and their
+/// quality values. Other parameters are ignored, invalid quality values are
+/// treated as 1.
+fn parse(header: &str) -> impl '_ + Iterator<Item = (&str, f32)> {
+    header.split(',').filter_map(|item| {
+        let mut parts = item.split(';').map(str::trim);
+        let value = parts.next().filter(|v| !v.is_empty())?;
+        let q = parts
+            .filter_map(|p| p.split_once('='))
+            .find(|(k, _)| k.trim().eq_ignore_ascii_case("q"))
+            .and_then(|(_, v)| v.trim().parse::<f32>().ok())
+            .map_or(1.0, |q| q.clamp(0.0, 1.0));
+        Some((value, q))
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quality() {
+        let accept = "image/avif,image/webp,image/*;q=0.8, */*;q=0.5, image/gif;q=0";
+        assert_eq!(mime_quality(accept, "image/avif"), 1.0);
+        assert_eq!(mime_quality(accept, "image/png"), 0.8);
+        assert_eq!(mime_quality(accept, "image/gif"), 0.0);
+        assert_eq!(mime_quality(accept, "text/css"), 0.5);
+        assert_eq!(mime_quality("text/html", "text/css"), 0.0);
+        assert_eq!(mime_quality("", "text/css"), 0.0);
+        assert_eq!(mime_quality("TEXT/*; charset=utf-8; Q=0.3", "text/css"), 0.3);
+        assert_eq!(mime_quality("text/css;q=nope", "text/css"), 1.0);
+    }
+
+    #[test]
+    fn language() {
+        let accept = "de-AT, de;q=0.9, en;q=0.5, *;q=0.1";
+        assert_eq!(language_quality(accept, "de-AT"), 1.0);
+        assert_eq!(language_quality(accept, "DE-at"), 1.0);
+        assert_eq!(language_quality(accept, "de"), 0.9);
+        assert_eq!(language_quality(accept, "de-CH"), 0.9);
+        assert_eq!(language_quality(accept, "en-US"), 0.5);
+        assert_eq!(language_quality(accept, "fr"), 0.1);
+        assert_eq!(language_quality("en-US", "en"), 1.0);
+        assert_eq!(language_quality("en-US, en;q=0.2", "en"), 0.2);
+        assert_eq!(language_quality("eng", "en"), 0.0);
+        assert_eq!(language_quality("", "en"), 0.0);
+    }
+
+    #[test]
+    fn coding() {
+        assert_eq!(coding_quality("gzip, br;q=0.8", "br"), 0.8);
+        assert_eq!(coding_quality("gzip, BR", "br"), 1.0);
+        assert_eq!(coding_quality("gzip", "br"), 0.0);
+        assert_eq!(coding_quality("*;q=0.5", "br"), 0.5);
+        assert_eq!(coding_quality("br;q=0, *", "br"), 0.0);
+        assert_eq!(coding_quality("", "br"), 0.0);
+    }
+}
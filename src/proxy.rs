@@ -0,0 +1,172 @@
+//! Helpers to let a reverse proxy (like nginx or Caddy) serve assets.
+//!
+//! The idea: your application stays the source of truth for all assets, but
+//! on startup writes them to a directory via [`Assets::export`]. The proxy
+//! then serves that directory, configured with a snippet generated by
+//! [`Assets::nginx_map`] or [`Assets::caddy_map`].
+
+use std::{
+    fmt::Write,
+    io,
+    path::{Component, Path},
+};
+
+use crate::{serve::content_type, Assets, Encoding};
+
+
+impl Assets {
+    /// Writes all assets to the directory `dir`, each at its *hashed HTTP
+    /// path*. Missing directories are created and existing files are
+    /// overwritten. In dev mode, the current content of each asset is
+    /// written.
+    ///
+    /// For each encoding an asset is stored in (see [`Asset::encodings`]),
+    /// the encoded content is written next to it, with `.br`, `.zst` or
+    /// `.gz` appended to the filename. That's where nginx and Caddy look for
+    /// precompressed files.
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidInput`] if an HTTP
+    /// path is absolute or contains `..`, as it would end up outside of
+    /// `dir`.
+    ///
+    /// [`Asset::encodings`]: crate::Asset::encodings
+    pub async fn export(&self, dir: impl AsRef<Path>) -> Result<(), io::Error> {
+        let dir = dir.as_ref();
+        for (http_path, asset) in self.iter() {
+            let is_relative = Path::new(http_path).components()
+                .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+            if !is_relative {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("cannot export asset '{}' as it would end up outside of '{}'",
+                        http_path, dir.display()),
+                ));
+            }
+
+            let path = dir.join(http_path);
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::write(&path, asset.content().await?).await?;
+            for (encoding, content) in asset.0.encoded() {
+                let mut encoded_path = path.clone().into_os_string();
+                encoded_path.push(".");
+                encoded_path.push(extension(*encoding));
+                tokio::fs::write(encoded_path, content).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns an nginx config snippet with one `location` block per asset,
+    /// serving it from `root` (the directory passed to [`Self::export`])
+    /// with the asset's `Content-Type`. If an asset has a `Cache-Control`
+    /// value configured, it is added as header. For assets stored in
+    /// encodings, `gzip_static`, `brotli_static` or `zstd_static` is enabled
+    /// so that the files written by [`Self::export`] are served (the latter
+    /// two require the corresponding nginx modules). The snippet is meant to
+    /// be included in a `server` block.
+    ///
+    /// Assets are listed sorted by *hashed HTTP path*, so that the output is
+    /// deterministic.
+    pub fn nginx_map(&self, root: impl AsRef<Path>) -> String {
+        let root = root.as_ref();
+        let mut out = String::new();
+        for (http_path, asset) in self.sorted() {
+            let fs_path = root.join(http_path);
+            writeln!(out, "location = {} {{", quote(&format!("/{http_path}"))).unwrap();
+            writeln!(out, "    alias {};", quote(&fs_path.to_string_lossy())).unwrap();
+            writeln!(out, "    types {{}}").unwrap();
+            writeln!(out, "    default_type {};", quote(&content_type(&asset))).unwrap();
+            for encoding in asset.encodings() {
+                let directive = match encoding {
+                    Encoding::Brotli => "brotli_static",
+                    Encoding::Zstd => "zstd_static",
+                    Encoding::Gzip => "gzip_static",
+                };
+                writeln!(out, "    {} on;", directive).unwrap();
+            }
+            if asset.encodings().next().is_some() {
+                writeln!(out, "    add_header Vary Accept-Encoding;").unwrap();
+            }
+            if let Some(cache_control) = asset.cache_control() {
+                writeln!(out, "    add_header Cache-Control {};", quote(cache_control)).unwrap();
+            }
+            out.push_str("}\n");
+        }
+        out
+    }
+
+    /// Returns a Caddyfile snippet serving all assets from `root` (the
+    /// directory passed to [`Self::export`]) via `file_server`. For each
+    /// asset, a `header` directive sets its `Content-Type` and, if
+    /// configured, its `Cache-Control` value. If any asset is stored in
+    /// encodings, `file_server` serves the precompressed files written by
+    /// [`Self::export`]. The snippet is meant to be included in a site block.
+    ///
+    /// Assets are listed sorted by *hashed HTTP path*, so that the output is
+    /// deterministic.
+    pub fn caddy_map(&self, root: impl AsRef<Path>) -> String {
+        let mut out = String::new();
+        writeln!(out, "root * {}", quote(&root.as_ref().to_string_lossy())).unwrap();
+        let mut encodings = Vec::new();
+        for (http_path, asset) in self.sorted() {
+            let location = quote(&format!("/{http_path}"));
+            writeln!(out, "header {} Content-Type {}", location, quote(&content_type(&asset)))
+                .unwrap();
+            if let Some(cache_control) = asset.cache_control() {
+                writeln!(out, "header {} Cache-Control {}", location, quote(cache_control))
+                    .unwrap();
+            }
+            encodings.extend(asset.encodings());
+        }
+
+        let precompressed = Encoding::ALL.iter()
+            .filter(|e| encodings.contains(e))
+            .map(|e| match e {
+                Encoding::Brotli => "br",
+                Encoding::Zstd => "zstd",
+                Encoding::Gzip => "gzip",
+            })
+            .collect::<Vec<_>>();
+        if precompressed.is_empty() {
+            out.push_str("file_server\n");
+        } else {
+            writeln!(out, "file_server {{\n    precompressed {}\n}}", precompressed.join(" "))
+                .unwrap();
+        }
+        out
+    }
+
+    fn sorted(&self) -> Vec<(&str, crate::Asset)> {
+        let mut assets = self.iter().collect::<Vec<_>>();
+        assets.sort_by_key(|(path, _)| *path);
+        assets
+    }
+}
+
+/// Returns the file extension of precompressed files in `encoding`, as
+/// expected by nginx and Caddy.
+fn extension(encoding: Encoding) -> &'static str {
+    match encoding {
+        Encoding::Brotli => "br",
+        Encoding::Zstd => "zst",
+        Encoding::Gzip => "gz",
+    }
+}
+
+/// Quotes the given string for nginx and Caddy config files, which both
+/// understand double quoted strings with backslash escapes.
+fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        if c == '"' || c == '\\' {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out.push('"');
+    out
+}
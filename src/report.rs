@@ -0,0 +1,146 @@
+//! Build reports listing all assets and how they were processed, see
+//! [`Assets::build_report`].
+
+use std::{io, path::PathBuf};
+
+use serde::Serialize;
+
+use crate::{builder::EntryConfig, Assets, DataSource, Modifier};
+
+
+/// Lists all assets of a prod-mode build, where they came from and how they
+/// were transformed. Useful for audits: serialize it via [`Self::to_json`]
+/// and keep it next to the binary.
+#[derive(Debug, Clone, Serialize)]
+#[non_exhaustive]
+pub struct BuildReport {
+    /// All assets, sorted by *unhashed HTTP path*.
+    pub assets: Vec<AssetReport>,
+}
+
+/// Information about a single asset in the [`BuildReport`].
+#[derive(Debug, Clone, Serialize)]
+#[non_exhaustive]
+pub struct AssetReport {
+    /// The *unhashed HTTP path*.
+    pub unhashed_http_path: String,
+
+    /// The *hashed HTTP path*, under which the asset is served.
+    pub http_path: String,
+
+    pub source: ReportSource,
+
+    /// Hex encoded SHA-256 hash of the source, before any transformations.
+    pub source_sha256: String,
+
+    /// Names of the transformations applied to the content, in order:
+    /// `prepend`, `append`, `path_fixup` (or `auto_path_fixup`), `template`,
+    /// `modifier` (set via `with_modifier`), `global_modifier` (set via
+    /// `with_global_modifier`), `global_replace` (only listed if it changed
+    /// the content), `minify_js`, `optimize_svg` and `sanitize_svg`.
+    pub transformations: Vec<&'static str>,
+
+    /// Hex encoded SHA-256 hash of the final content.
+    pub sha256: String,
+
+    /// Size of the final content in bytes.
+    pub size: usize,
+
+    pub cache_control: Option<String>,
+}
+
+/// Where the content of an asset came from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ReportSource {
+    /// Embedded into the executable via [`embed!`][crate::embed!]. `path` is
+    /// relative to the `base_path` given in the macro.
+    Embedded { path: String },
+
+    /// Loaded from the file system in [`Builder::build`][crate::Builder::build].
+    File { path: PathBuf },
+
+    /// Loaded from an [`AssetSource`][crate::AssetSource].
+    Custom,
+}
+
+impl BuildReport {
+    /// Returns this report as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("failed to serialize build report")
+    }
+
+    /// Writes this report as pretty-printed JSON to `writer`.
+    pub fn write_json(&self, writer: impl io::Write) -> Result<(), io::Error> {
+        serde_json::to_writer_pretty(writer, self).map_err(Into::into)
+    }
+}
+
+impl ReportSource {
+    #[cfg_attr(dev_mode, allow(dead_code))]
+    pub(crate) fn of(source: &DataSource) -> Self {
+        match source {
+            DataSource::File(path) => Self::File { path: path.clone() },
+            DataSource::Embedded { path, .. } => Self::Embedded { path: path.to_string() },
+            DataSource::Custom(_) => Self::Custom,
+        }
+    }
+}
+
+/// Returns the names of the transformations, see
+/// [`AssetReport::transformations`].
+#[cfg_attr(dev_mode, allow(dead_code))]
+pub(crate) fn transformations(
+    config: &EntryConfig,
+    global_modified: bool,
+    replaced: bool,
+) -> Vec<&'static str> {
+    let mut out = vec![];
+    if config.prepend.is_some() {
+        out.push("prepend");
+    }
+    if config.append.is_some() {
+        out.push("append");
+    }
+    match &config.modifier {
+        Modifier::None => {}
+        #[cfg(feature = "modifiers")]
+        Modifier::PathFixup(_) if config.auto_path_fixup => out.push("auto_path_fixup"),
+        #[cfg(feature = "modifiers")]
+        Modifier::PathFixup(_) => out.push("path_fixup"),
+        #[cfg(feature = "modifiers")]
+        Modifier::Template(_) => out.push("template"),
+        Modifier::Custom { .. } => out.push("modifier"),
+    }
+    if global_modified {
+        out.push("global_modifier");
+    }
+    if replaced {
+        out.push("global_replace");
+    }
+    #[cfg(feature = "minify-js")]
+    if config.minify_js {
+        out.push("minify_js");
+    }
+    #[cfg(feature = "optimize-svg")]
+    if config.optimize_svg {
+        out.push("optimize_svg");
+    }
+    #[cfg(feature = "sanitize-svg")]
+    if config.sanitize_svg {
+        out.push("sanitize_svg");
+    }
+    out
+}
+
+impl Assets {
+    /// Returns the report of what assets were included and how they were
+    /// processed by [`Builder::build`][crate::Builder::build]. Only available
+    /// in prod mode, returns `None` in dev mode.
+    ///
+    /// Method is only available if the crate feature `report` is enabled.
+    pub fn build_report(&self) -> Option<&BuildReport> {
+        self.0.build_report()
+    }
+}
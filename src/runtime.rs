@@ -0,0 +1,72 @@
+//! Managing background tasks, see [`AssetsRuntime`].
+
+use std::{fmt, future::Future, sync::{Arc, Mutex}};
+
+use tokio::task::JoinSet;
+
+use crate::Assets;
+
+
+/// Handle to the background tasks of an [`Assets`] instance, obtained via
+/// [`Assets::runtime`].
+///
+/// Currently, background tasks are only used in dev mode (see
+/// [`Builder::dev_stale_while_revalidate`][crate::Builder::dev_stale_while_revalidate]).
+/// Call [`Self::shutdown`] when shutting down your application to make sure
+/// no tasks are left running. Cheap to clone; all clones refer to the same
+/// tasks.
+#[derive(Clone)]
+pub struct AssetsRuntime(Arc<Mutex<Option<JoinSet<()>>>>);
+
+impl AssetsRuntime {
+    pub(crate) fn new() -> Self {
+        Self(Arc::new(Mutex::new(Some(JoinSet::new()))))
+    }
+
+    /// Stops spawning new background tasks and waits for all running ones to
+    /// finish. Afterwards, all work is done in the foreground, i.e. the
+    /// `Assets` stay fully usable. Calling this again returns immediately.
+    pub async fn shutdown(&self) {
+        let tasks = self.0.lock().unwrap().take();
+        if let Some(mut tasks) = tasks {
+            while tasks.join_next().await.is_some() {}
+        }
+    }
+
+    /// Returns whether [`Self::shutdown`] was called.
+    pub fn is_shut_down(&self) -> bool {
+        self.0.lock().unwrap().is_none()
+    }
+
+    /// Spawns `task` in the background, unless this runtime is shut down, in
+    /// which case `false` is returned. Has to be called within a Tokio
+    /// runtime.
+    #[cfg_attr(prod_mode, allow(dead_code))]
+    pub(crate) fn spawn<F>(&self, task: F) -> bool
+    where
+        F: 'static + Send + Future<Output = ()>,
+    {
+        let mut tasks = self.0.lock().unwrap();
+        let Some(tasks) = tasks.as_mut() else {
+            return false;
+        };
+
+        // Clean up finished tasks.
+        while tasks.try_join_next().is_some() {}
+        tasks.spawn(task);
+        true
+    }
+}
+
+impl fmt::Debug for AssetsRuntime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AssetsRuntime").field("is_shut_down", &self.is_shut_down()).finish()
+    }
+}
+
+impl Assets {
+    /// Returns the handle to the background tasks of these assets.
+    pub fn runtime(&self) -> AssetsRuntime {
+        self.0.runtime().clone()
+    }
+}
@@ -0,0 +1,116 @@
+//! Helpers shared by the integrations with web frameworks.
+
+use std::borrow::Cow;
+
+use crate::Asset;
+#[cfg(any(feature = "axum", feature = "http", feature = "warp"))]
+use crate::{AcceptedEncodings, LoadError};
+#[cfg(any(feature = "axum", feature = "tower", feature = "warp"))]
+use crate::RequestContext;
+
+
+/// Returns the value for the `Content-Type` header of `asset`.
+pub(crate) fn content_type(asset: &Asset) -> Cow<'static, str> {
+    let mime = asset.mime().unwrap_or("application/octet-stream");
+    match asset.charset() {
+        Some(charset) if asset.is_text() => format!("{mime}; charset={charset}").into(),
+        _ => mime.into(),
+    }
+}
+
+/// Loads the content of `asset`, encoded according to the value of the
+/// `Accept-Encoding` header if possible (see [`Asset::content_encoded`]).
+/// Also returns the `Content-Encoding` and `Vary` headers to add to the
+/// response.
+#[cfg(any(feature = "axum", feature = "http", feature = "warp"))]
+pub(crate) async fn load_encoded(
+    asset: &Asset,
+    accept_encoding: Option<&str>,
+) -> Result<(bytes::Bytes, Vec<(&'static str, &'static str)>), LoadError> {
+    let accepted = accept_encoding.map(AcceptedEncodings::from_header).unwrap_or_default();
+    let (content, encoding) = asset.content_encoded(accepted).await?;
+    let mut headers = Vec::new();
+    if let Some(encoding) = encoding {
+        headers.push(("content-encoding", encoding.as_str()));
+    }
+    if asset.0.varies_by_encoding() {
+        headers.push(("vary", "accept-encoding"));
+    }
+    Ok((content, headers))
+}
+
+/// Returns whether the guard of `asset` (see
+/// [`EntryBuilder::with_guard`][crate::EntryBuilder::with_guard]) allows the
+/// request. `req` is only called if `asset` has a guard.
+#[cfg(any(feature = "axum", feature = "tower", feature = "warp"))]
+pub(crate) fn is_allowed<'a>(asset: &Asset, req: impl FnOnce() -> RequestContext<'a>) -> bool {
+    asset.0.guard().is_none_or(|guard| guard.allows(&req()))
+}
+
+/// Evaluates the conditional request headers `req_headers` for `asset` (see
+/// [`crate::Conditional`]) and returns whether to respond with
+/// `304 Not Modified` and the headers to add to the response. Without the
+/// features `hash` and `http`, no `ETag` can be computed, so requests are
+/// never answered with 304.
+#[cfg(any(feature = "axum", feature = "warp"))]
+pub(crate) fn conditional<'h>(
+    asset: &Asset,
+    content: &[u8],
+    req_headers: impl IntoIterator<Item = (&'h str, &'h str)>,
+) -> (bool, Vec<(&'static str, String)>) {
+    #[cfg(any(feature = "hash", feature = "http"))]
+    {
+        let conditional = crate::Conditional::evaluate(asset, content, req_headers);
+        let headers = conditional.headers().map(|(name, value)| (name, value.to_owned())).collect();
+        (conditional.is_not_modified(), headers)
+    }
+
+    #[cfg(not(any(feature = "hash", feature = "http")))]
+    {
+        let _ = (asset, content, req_headers);
+        (false, Vec::new())
+    }
+}
+
+/// Decodes `%XX` escapes. Returns `None` if the result is not valid UTF-8.
+#[cfg(any(feature = "axum", feature = "tower", feature = "warp"))]
+pub(crate) fn percent_decode(s: &str) -> Option<Cow<'_, str>> {
+    if !s.contains('%') {
+        return Some(s.into());
+    }
+
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).filter(|hex| hex.iter().all(u8::is_ascii_hexdigit));
+        match (bytes[i], hex) {
+            (b'%', Some(hex)) => {
+                // Both are ASCII hex digits, so this cannot fail.
+                let hex = std::str::from_utf8(hex).unwrap();
+                out.push(u8::from_str_radix(hex, 16).unwrap());
+                i += 3;
+            }
+            (b, _) => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(out).ok().map(Into::into)
+}
+
+
+#[cfg(all(test, any(feature = "axum", feature = "tower", feature = "warp")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode() {
+        assert_eq!(percent_decode("foo/bar.js").as_deref(), Some("foo/bar.js"));
+        assert_eq!(percent_decode("sub%20dir/%C3%BC.txt").as_deref(), Some("sub dir/ü.txt"));
+        assert_eq!(percent_decode("100%").as_deref(), Some("100%"));
+        assert_eq!(percent_decode("%zz%4").as_deref(), Some("%zz%4"));
+        assert_eq!(percent_decode("%ff"), None);
+    }
+}
@@ -0,0 +1,38 @@
+// Service worker generated by reinda. The list of precached assets is
+// injected at build time. As all of them have hashed filenames, they never
+// change and can be served from the cache forever.
+const PRECACHE = __REINDA_PRECACHE__;
+const CACHE_NAME = 'reinda-' + PRECACHE
+    .reduce((hash, path) => [...path].reduce((h, c) => (h * 31 + c.charCodeAt(0)) >>> 0, hash), 7)
+    .toString(36);
+
+self.addEventListener('install', event => {
+    event.waitUntil(
+        caches.open(CACHE_NAME)
+            .then(cache => cache.addAll(PRECACHE))
+            .then(() => self.skipWaiting())
+    );
+});
+
+self.addEventListener('activate', event => {
+    event.waitUntil(
+        caches.keys()
+            .then(keys => Promise.all(
+                keys.filter(key => key.startsWith('reinda-') && key !== CACHE_NAME)
+                    .map(key => caches.delete(key))
+            ))
+            .then(() => self.clients.claim())
+    );
+});
+
+self.addEventListener('fetch', event => {
+    if (event.request.method !== 'GET') {
+        return;
+    }
+
+    event.respondWith(
+        caches.open(CACHE_NAME)
+            .then(cache => cache.match(event.request))
+            .then(cached => cached || fetch(event.request))
+    );
+});
@@ -0,0 +1,120 @@
+//! Serving assets as `tower::Service`, see [`AssetService`].
+
+use std::{
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use http::{header, HeaderValue, Method, Request, Response, StatusCode};
+use http_body_util::Full;
+
+use crate::{serve::{is_allowed, percent_decode}, Assets, RequestContext};
+
+
+/// A `tower::Service` serving all assets under their *hashed HTTP paths*,
+/// created via [`Assets::into_service`]. Cheap to clone.
+///
+/// The service handles `GET` and `HEAD` requests, responding as described
+/// in [`Asset::response`][crate::Asset::response]. In addition:
+/// - other methods are answered with `405 Method Not Allowed`,
+/// - unknown paths are answered with the fallback asset (see
+///   [`Builder::set_fallback`][crate::Builder::set_fallback]) or, if none is
+///   set, the error asset for 404 (see
+///   [`Builder::set_error_asset`][crate::Builder::set_error_asset]) or an
+///   empty 404 response,
+/// - requests denied by the guard of the asset (see
+///   [`EntryBuilder::with_guard`][crate::EntryBuilder::with_guard]) are
+///   answered with the error asset for 403 or an empty 403 response,
+/// - if loading an asset fails (only possible in dev mode), the response
+///   has status 500 and contains [`LoadError::to_html`][crate::LoadError::to_html].
+///
+/// As a plain `tower::Service`, it can be wrapped in any middleware like
+/// compression or tracing layers from `tower-http`.
+#[derive(Debug, Clone)]
+pub struct AssetService {
+    assets: Arc<Assets>,
+}
+
+impl Assets {
+    /// Turns these assets into a `tower::Service`, see [`AssetService`].
+    ///
+    /// Method is only available if the crate feature `tower` is enabled.
+    pub fn into_service(self) -> AssetService {
+        AssetService { assets: Arc::new(self) }
+    }
+}
+
+impl<B> tower_service::Service<Request<B>> for AssetService {
+    type Response = Response<Full<Bytes>>;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Send + Future<Output = Result<Self::Response, Infallible>>>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        let assets = self.assets.clone();
+        let (parts, _) = req.into_parts();
+        Box::pin(async move { Ok(serve(&assets, parts).await) })
+    }
+}
+
+async fn serve(assets: &Assets, req: http::request::Parts) -> Response<Full<Bytes>> {
+    if req.method != Method::GET && req.method != Method::HEAD {
+        let mut response = Response::new(Full::default());
+        *response.status_mut() = StatusCode::METHOD_NOT_ALLOWED;
+        response.headers_mut().insert(header::ALLOW, HeaderValue::from_static("GET, HEAD"));
+        return response;
+    }
+
+    let path = percent_decode(req.uri.path().trim_start_matches('/'));
+    let requested = path.as_deref().and_then(|path| Some((path, assets.get_or_fallback(path)?)));
+    let allowed = |(path, asset): &(&str, _)| is_allowed(asset, || {
+        let headers = req.headers.iter()
+            .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?)));
+        RequestContext::new(req.method.as_str(), path, headers)
+    });
+    let status = match &requested {
+        Some(requested) if allowed(requested) => StatusCode::OK,
+        Some(_) => StatusCode::FORBIDDEN,
+        None => StatusCode::NOT_FOUND,
+    };
+    let asset = match requested {
+        Some((_, asset)) if status == StatusCode::OK => asset,
+        _ => match assets.error_asset(status.as_u16()) {
+            Some(asset) => asset,
+            None => {
+                let mut response = Response::new(Full::default());
+                *response.status_mut() = status;
+                return response;
+            }
+        },
+    };
+
+    match asset.response(&req.headers).await {
+        Ok(response) => {
+            let (mut parts, body) = response.into_parts();
+            if parts.status == StatusCode::OK {
+                parts.status = status;
+            }
+            let body = if req.method == Method::HEAD { Bytes::new() } else { body };
+            #[cfg(feature = "metrics")]
+            crate::metrics::served(&asset, body.len());
+            Response::from_parts(parts, Full::new(body))
+        }
+        Err(e) => {
+            let mut response = Response::new(Full::new(e.to_html().into()));
+            *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+            response.headers_mut().insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("text/html; charset=utf-8"),
+            );
+            response
+        }
+    }
+}
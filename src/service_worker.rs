@@ -0,0 +1,122 @@
+//! Generating a service worker that precaches hashed assets, see
+//! [`Builder::add_service_worker`].
+
+use std::{borrow::Cow, io};
+
+use bytes::Bytes;
+
+use crate::{builder::apply_groups, AssetSource, Builder, EntryBuilder, PathHash};
+
+
+/// Placeholder in service worker templates that is replaced by a JSON array
+/// of the *hashed HTTP paths* to precache.
+pub const PRECACHE_PLACEHOLDER: &str = "__REINDA_PRECACHE__";
+
+/// The default service worker template: precaches all listed assets, serves
+/// them cache-first and deletes caches of older versions.
+pub const DEFAULT_TEMPLATE: &str = include_str!("service-worker.js");
+
+impl<'a> Builder<'a> {
+    /// Adds a service worker script that precaches all assets with hashed
+    /// filename that were added *before* calling this method, and mounts it
+    /// under `http_path`. The [`PRECACHE_PLACEHOLDER`] in `template` is
+    /// replaced by a JSON array of the *hashed HTTP paths* of those assets,
+    /// e.g. `["app.sbfNUtVcqxUK.js","style.Tk9IW7Wt4Ls-.css"]`. Use
+    /// [`DEFAULT_TEMPLATE`] or your own. As the paths are relative, serve
+    /// the service worker from the same prefix as your assets.
+    ///
+    /// The service worker is configured with [`EntryBuilder::stable_name`]
+    /// and a `Cache-Control` of `no-cache`, so that browsers notice updates.
+    /// It is regenerated whenever one of the precached assets changes. The
+    /// returned entry can be configured further, but calling
+    /// [`EntryBuilder::with_modifier`] on it removes the precache list.
+    ///
+    /// In dev mode, no filenames are hashed and the injected list is always
+    /// empty, so that the service worker does not serve stale files.
+    ///
+    /// ```
+    /// use reinda::service_worker::DEFAULT_TEMPLATE;
+    ///
+    /// let mut builder = reinda::Assets::builder();
+    /// # #[cfg(feature = "hash")]
+    /// builder.add_file("app.js", "assets/app.js").with_hash();
+    /// builder.add_service_worker("sw.js", DEFAULT_TEMPLATE);
+    /// ```
+    pub fn add_service_worker(
+        &mut self,
+        http_path: impl Into<Cow<'a, str>>,
+        template: impl Into<Cow<'static, str>>,
+    ) -> &mut EntryBuilder<'a> {
+        let groups = &self.groups;
+        let precached = self.assets.iter()
+            .flat_map(|entry| entry.http_paths().into_iter().map(move |path| (entry, path)))
+            .filter(|(entry, path)| {
+                let mut config = entry.config.clone();
+                apply_groups(groups, path, &mut config);
+                !matches!(config.path_hash, PathHash::None | PathHash::Stable)
+            })
+            .map(|(_, path)| Cow::Owned(path.into_owned()))
+            .collect::<Vec<_>>();
+
+        self.add_source(http_path, Template(template.into()))
+            .stable_name()
+            .with_cache_control("no-cache")
+            .with_modifier(precached, |content, ctx| {
+                let paths: Vec<_> = match cfg!(prod_mode) {
+                    true => ctx.dependencies().iter().map(|p| ctx.resolve_path(p)).collect(),
+                    false => vec![],
+                };
+                let list = json_array(&paths);
+                String::from_utf8_lossy(&content).replace(PRECACHE_PLACEHOLDER, &list).into()
+            })
+    }
+}
+
+struct Template(Cow<'static, str>);
+
+impl AssetSource for Template {
+    async fn load(&self) -> io::Result<Bytes> {
+        Ok(match &self.0 {
+            Cow::Borrowed(s) => Bytes::from_static(s.as_bytes()),
+            Cow::Owned(s) => Bytes::copy_from_slice(s.as_bytes()),
+        })
+    }
+}
+
+/// Formats `strings` as JSON array.
+fn json_array(strings: &[&str]) -> String {
+    let mut out = String::from("[");
+    for (i, s) in strings.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push('"');
+        for c in s.chars() {
+            match c {
+                '"' => out.push_str("\\\""),
+                '\\' => out.push_str("\\\\"),
+                // Also escaped to be safe inside `<script>`.
+                '<' => out.push_str("\\u003c"),
+                c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+                c => out.push(c),
+            }
+        }
+        out.push('"');
+    }
+    out.push(']');
+    out
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json() {
+        assert_eq!(json_array(&[]), "[]");
+        assert_eq!(json_array(&["a.js", "b c/\"d\"\\.css"]), r#"["a.js","b c/\"d\"\\.css"]"#);
+        assert_eq!(json_array(&["</script>\n"]), r#"["\u003c/script>\u000a"]"#);
+        assert!(DEFAULT_TEMPLATE.contains(PRECACHE_PLACEHOLDER));
+    }
+}
@@ -0,0 +1,104 @@
+//! Custom data sources for assets, see [`AssetSource`].
+
+use std::{fmt, future::Future, io, pin::Pin, sync::Arc, time::SystemTime};
+
+use bytes::Bytes;
+
+
+/// A source of asset data loaded at runtime, added via
+/// [`Builder::add_source`][crate::Builder::add_source]. Use this to load
+/// assets from places other than the file system, e.g. S3, an HTTP server or
+/// a database.
+///
+/// Like files added via `add_file`, sources are loaded in `Builder::build`
+/// in prod mode and whenever the asset is loaded in dev mode.
+///
+/// ```
+/// use std::io;
+/// use bytes::Bytes;
+///
+/// struct Motd;
+///
+/// impl reinda::AssetSource for Motd {
+///     async fn load(&self) -> io::Result<Bytes> {
+///         Ok(Bytes::from_static(b"Welcome!"))
+///     }
+/// }
+///
+/// let mut builder = reinda::Assets::builder();
+/// builder.add_source("motd.txt", Motd);
+/// ```
+pub trait AssetSource: 'static + Send + Sync {
+    /// Loads the full content.
+    fn load(&self) -> impl Future<Output = io::Result<Bytes>> + Send;
+
+    /// Returns the time of last modification, if known. The default
+    /// implementation returns `None`.
+    fn modified(&self) -> impl Future<Output = Option<SystemTime>> + Send {
+        async { None }
+    }
+
+    /// Returns the size of the content in bytes, if known without loading
+    /// it. The default implementation returns `None`.
+    fn size(&self) -> impl Future<Output = Option<u64>> + Send {
+        async { None }
+    }
+}
+
+type BoxFuture<'a, T> = Pin<Box<dyn 'a + Send + Future<Output = T>>>;
+
+/// Object safe version of `AssetSource`, only providing what is used
+/// internally.
+trait DynAssetSource: Send + Sync {
+    fn load(&self) -> BoxFuture<'_, io::Result<Bytes>>;
+    fn modified(&self) -> BoxFuture<'_, Option<SystemTime>>;
+}
+
+impl<T: AssetSource> DynAssetSource for T {
+    fn load(&self) -> BoxFuture<'_, io::Result<Bytes>> {
+        Box::pin(AssetSource::load(self))
+    }
+
+    fn modified(&self) -> BoxFuture<'_, Option<SystemTime>> {
+        Box::pin(AssetSource::modified(self))
+    }
+}
+
+/// Content provided directly, see
+/// [`Builder::add_bytes`][crate::Builder::add_bytes].
+pub(crate) struct InMemory(pub(crate) Bytes);
+
+impl AssetSource for InMemory {
+    async fn load(&self) -> io::Result<Bytes> {
+        Ok(self.0.clone())
+    }
+
+    async fn size(&self) -> Option<u64> {
+        Some(self.0.len() as u64)
+    }
+}
+
+/// Type-erased `AssetSource`, cheap to clone.
+#[derive(Clone)]
+pub(crate) struct CustomSource(Arc<dyn DynAssetSource>);
+
+impl CustomSource {
+    pub(crate) fn new(source: impl AssetSource) -> Self {
+        Self(Arc::new(source))
+    }
+
+    pub(crate) async fn load(&self) -> io::Result<Bytes> {
+        self.0.load().await
+    }
+
+    #[cfg_attr(dev_mode, allow(dead_code))]
+    pub(crate) async fn modified(&self) -> Option<SystemTime> {
+        self.0.modified().await
+    }
+}
+
+impl fmt::Debug for CustomSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CustomSource")
+    }
+}
@@ -0,0 +1,105 @@
+//! Subresource Integrity, see [`Asset::sri_hash`][crate::Asset::sri_hash].
+
+
+/// Returns the SRI string (`sha384-` followed by the base64 encoded digest)
+/// of `content`.
+pub(crate) fn sha384(content: &[u8]) -> String {
+    use base64::Engine;
+    use sha2::Digest;
+
+    let digest = sha2::Sha384::digest(content);
+    format!("sha384-{}", base64::engine::general_purpose::STANDARD.encode(digest))
+}
+
+/// Adds an `integrity` attribute to all `<script>` and `<link>` tags in
+/// `html` for which `lookup` returns an SRI string when called with their
+/// `src` or `href` value. Tags that already have an `integrity` attribute are
+/// left alone.
+pub(crate) fn inject(html: &[u8], lookup: impl Fn(&str) -> Option<String>) -> Vec<u8> {
+    let mut out = Vec::with_capacity(html.len());
+    let mut pos = 0;
+    while let Some(start) = find_tag(html, pos, &["script", "link"]) {
+        let Some(end) = html[start..].iter().position(|&b| b == b'>').map(|i| start + i) else {
+            break;
+        };
+
+        // Insert before a self-closing `/` and the whitespace before it.
+        let mut insert_at = end;
+        if html[..end].ends_with(b"/") {
+            insert_at -= 1;
+            while html[insert_at - 1].is_ascii_whitespace() {
+                insert_at -= 1;
+            }
+        }
+        let integrity = std::str::from_utf8(&html[start..insert_at]).ok()
+            .filter(|tag| attr_value(tag, "integrity").is_none())
+            .and_then(|tag| attr_value(tag, "src").or_else(|| attr_value(tag, "href")))
+            .and_then(&lookup);
+
+        out.extend_from_slice(&html[pos..insert_at]);
+        if let Some(integrity) = integrity {
+            out.extend_from_slice(format!(" integrity=\"{}\"", integrity).as_bytes());
+        }
+        pos = insert_at;
+    }
+
+    out.extend_from_slice(&html[pos..]);
+    out
+}
+
+/// Returns the index of the next start of a tag with one of the given `names`
+/// (and at least one attribute) at or after `pos`.
+pub(crate) fn find_tag(html: &[u8], pos: usize, names: &[&str]) -> Option<usize> {
+    (pos..html.len()).find(|&i| {
+        names.iter().any(|name| {
+            let tag = &html[i..];
+            tag.len() > name.len() + 1
+                && tag[0] == b'<'
+                && tag[1..][..name.len()].eq_ignore_ascii_case(name.as_bytes())
+                && tag[name.len() + 1].is_ascii_whitespace()
+        })
+    })
+}
+
+/// Returns the value of the quoted attribute `name` in `tag`, if present.
+pub(crate) fn attr_value<'t>(tag: &'t str, name: &str) -> Option<&'t str> {
+    let lower = tag.to_ascii_lowercase();
+    lower.match_indices(name)
+        .filter(|(i, _)| lower[..*i].ends_with(|c: char| c.is_ascii_whitespace()))
+        .find_map(|(i, _)| {
+            let rest = tag[i + name.len()..].trim_start().strip_prefix('=')?.trim_start();
+            let quote = rest.chars().next().filter(|&c| c == '"' || c == '\'')?;
+            let value = &rest[1..];
+            value.find(quote).map(|end| &value[..end])
+        })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inject_integrity() {
+        let lookup = |path: &str| match path {
+            "/app.js" | "style.css" => Some(format!("sha384-{}", path.len())),
+            _ => None,
+        };
+        let html = br#"<script src="/app.js"></script><link rel="stylesheet" href='style.css' />
+            <script src="other.js"></script><script integrity="x" src="/app.js"></script>
+            <scripts src="/app.js">"#;
+        let expected = br#"<script src="/app.js" integrity="sha384-7"></script><link rel="stylesheet" href='style.css' integrity="sha384-9" />
+            <script src="other.js"></script><script integrity="x" src="/app.js"></script>
+            <scripts src="/app.js">"#;
+        assert_eq!(String::from_utf8(inject(html, lookup)).unwrap(), std::str::from_utf8(expected).unwrap());
+    }
+
+    #[test]
+    fn sha384_format() {
+        // Known value from the SRI spec examples (`alert('Hello, world.');`).
+        assert_eq!(
+            sha384(b"alert('Hello, world.');"),
+            "sha384-H8BRh8j48O9oYatfu5AZzq6A9RINhZO5H16dQZngK7T62em8MUt1FLm52t+eX6xO",
+        );
+    }
+}
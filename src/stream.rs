@@ -0,0 +1,203 @@
+//! Streaming asset contents and answering `Range` requests, see
+//! [`Asset::content_stream`][crate::Asset::content_stream].
+
+use std::{
+    io::{self, Cursor, SeekFrom},
+    ops::Range,
+    pin::Pin,
+    task::{Context, Poll},
+    time::SystemTime,
+};
+
+use bytes::Bytes;
+use tokio::{
+    fs::File,
+    io::{AsyncRead, AsyncReadExt, AsyncSeekExt, ReadBuf, Take},
+};
+
+
+/// The content of an asset as [`AsyncRead`], returned by
+/// [`Asset::content_stream`][crate::Asset::content_stream]. Use
+/// [`Self::slice`] to only read a part of it, e.g. to answer a `Range`
+/// request. To get a `Stream` of `Bytes`, wrap it in `ReaderStream` from
+/// `tokio-util`.
+#[derive(Debug)]
+pub struct ContentStream {
+    inner: Inner,
+    len: u64,
+}
+
+#[derive(Debug)]
+enum Inner {
+    Memory(Cursor<Bytes>),
+    File {
+        file: Take<File>,
+        /// Position of the start of this stream in the file.
+        offset: u64,
+    },
+}
+
+impl ContentStream {
+    pub(crate) fn from_bytes(content: Bytes) -> Self {
+        Self { len: content.len() as u64, inner: Inner::Memory(Cursor::new(content)) }
+    }
+
+    #[cfg_attr(prod_mode, allow(dead_code))]
+    pub(crate) async fn from_file(path: &std::path::Path) -> io::Result<Self> {
+        let file = File::open(path).await?;
+        let len = file.metadata().await?.len();
+        Ok(Self { len, inner: Inner::File { file: file.take(len), offset: 0 } })
+    }
+
+    /// Like `from_file`, but returns `None` if the length or modification
+    /// time of the file differ from the given ones.
+    #[cfg_attr(dev_mode, allow(dead_code))]
+    pub(crate) async fn from_unchanged_file(
+        path: &std::path::Path,
+        len: u64,
+        modified: Option<SystemTime>,
+    ) -> io::Result<Option<Self>> {
+        let file = File::open(path).await?;
+        let metadata = file.metadata().await?;
+        if metadata.len() != len || metadata.modified().ok() != modified {
+            return Ok(None);
+        }
+        Ok(Some(Self { len, inner: Inner::File { file: file.take(len), offset: 0 } }))
+    }
+
+    /// Returns the content if it is in memory and nothing was read yet.
+    #[cfg_attr(not(feature = "http-body"), allow(dead_code))]
+    pub(crate) fn as_bytes(&self) -> Option<&Bytes> {
+        match &self.inner {
+            Inner::Memory(cursor) if cursor.position() == 0 => Some(cursor.get_ref()),
+            _ => None,
+        }
+    }
+
+    /// The number of bytes in this stream, e.g. for the `Content-Length`
+    /// header.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns `true` if this stream contains no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Restricts this stream to the given byte range, which is relative to
+    /// the start of this stream and clamped to its length. Must be called
+    /// before reading from the stream. Files are not read up to the start of
+    /// the range, but seeked.
+    pub async fn slice(self, range: Range<u64>) -> io::Result<Self> {
+        let end = range.end.min(self.len);
+        let start = range.start.min(end);
+        let len = end - start;
+        let inner = match self.inner {
+            Inner::Memory(cursor) => {
+                let content = cursor.into_inner().slice(start as usize..end as usize);
+                Inner::Memory(Cursor::new(content))
+            }
+            Inner::File { file, offset } => {
+                let mut file = file.into_inner();
+                file.seek(SeekFrom::Start(offset + start)).await?;
+                Inner::File { file: file.take(len), offset: offset + start }
+            }
+        };
+
+        Ok(Self { inner, len })
+    }
+}
+
+impl AsyncRead for ContentStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match &mut self.get_mut().inner {
+            Inner::Memory(cursor) => Pin::new(cursor).poll_read(cx, buf),
+            Inner::File { file, .. } => Pin::new(file).poll_read(cx, buf),
+        }
+    }
+}
+
+/// A parsed `Range` request header, see [`RangeRequest::parse`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RangeRequest {
+    /// The full content should be sent with status 200. Returned for invalid
+    /// or unsupported headers (like multiple ranges), which may be ignored
+    /// according to RFC 9110.
+    Full,
+
+    /// Only the given byte range should be sent with status 206.
+    Partial(Range<u64>),
+
+    /// None of the requested bytes exist, status 416 should be sent.
+    Unsatisfiable,
+}
+
+impl RangeRequest {
+    /// Parses the value of a `Range` header for content of length `len`.
+    /// Only single byte ranges (`bytes=0-99`, `bytes=100-` and `bytes=-100`)
+    /// are supported.
+    ///
+    /// ```
+    /// use reinda::RangeRequest;
+    ///
+    /// assert_eq!(RangeRequest::parse("bytes=0-99", 1000), RangeRequest::Partial(0..100));
+    /// assert_eq!(RangeRequest::parse("bytes=-100", 1000), RangeRequest::Partial(900..1000));
+    /// assert_eq!(RangeRequest::parse("bytes=1000-", 1000), RangeRequest::Unsatisfiable);
+    /// assert_eq!(RangeRequest::parse("bytes=0-1,5-9", 1000), RangeRequest::Full);
+    /// ```
+    pub fn parse(range: &str, len: u64) -> Self {
+        let Some(spec) = range.trim().strip_prefix("bytes=") else {
+            return Self::Full;
+        };
+        let Some((start, end)) = spec.trim().split_once('-') else {
+            return Self::Full;
+        };
+        let parse = |s: &str| s.trim().parse::<u64>().ok();
+
+        let range = match (start.trim().is_empty(), end.trim().is_empty()) {
+            // Suffix range: the last `end` bytes.
+            (true, false) => match parse(end) {
+                Some(0) => return Self::Unsatisfiable,
+                Some(n) => len.saturating_sub(n)..len,
+                None => return Self::Full,
+            },
+            (false, true) => match parse(start) {
+                Some(start) => start..len,
+                None => return Self::Full,
+            },
+            (false, false) => match (parse(start), parse(end)) {
+                (Some(start), Some(end)) if start <= end => start..end.saturating_add(1).min(len),
+                _ => return Self::Full,
+            },
+            (true, true) => return Self::Full,
+        };
+
+        if range.start >= len {
+            Self::Unsatisfiable
+        } else {
+            Self::Partial(range)
+        }
+    }
+
+    /// Returns the value for the `Content-Range` header of the response, or
+    /// `None` for [`RangeRequest::Full`].
+    ///
+    /// ```
+    /// use reinda::RangeRequest;
+    ///
+    /// assert_eq!(RangeRequest::Partial(0..100).content_range(1000).unwrap(), "bytes 0-99/1000");
+    /// assert_eq!(RangeRequest::Unsatisfiable.content_range(1000).unwrap(), "bytes */1000");
+    /// ```
+    pub fn content_range(&self, len: u64) -> Option<String> {
+        match self {
+            Self::Full => None,
+            Self::Partial(range) => Some(format!("bytes {}-{}/{}", range.start, range.end - 1, len)),
+            Self::Unsatisfiable => Some(format!("bytes */{}", len)),
+        }
+    }
+}
@@ -0,0 +1,438 @@
+//! Sanitizing and optimizing SVG files, see
+//! [`util::sanitize_svg`][crate::util::sanitize_svg] and
+//! [`util::optimize_svg`][crate::util::optimize_svg].
+
+use xmlparser::{ElementEnd, Token, Tokenizer};
+
+
+/// Elements that are removed including all their content.
+const FORBIDDEN_ELEMENTS: &[&str] = &[
+    "script", "foreignobject", "iframe", "embed", "object", "handler", "listener",
+];
+
+/// Image types that are allowed in `data:` URLs.
+const ALLOWED_DATA_IMAGES: &[&str] = &[
+    "data:image/png", "data:image/jpeg", "data:image/gif", "data:image/webp", "data:image/avif",
+];
+
+/// Elements in which whitespace is kept by `optimize`, as it might be
+/// rendered or significant.
+const TEXT_ELEMENTS: &[&str] = &["text", "tspan", "textpath", "style", "script", "title", "desc"];
+
+/// Namespace prefixes that editors use for their own data.
+const EDITOR_PREFIXES: &[&str] = &["inkscape", "sodipodi", "sketch", "serif"];
+
+pub(crate) fn sanitize(src: &[u8]) -> Result<Vec<u8>, String> {
+    let src = std::str::from_utf8(src).map_err(|e| format!("SVG is not valid UTF-8: {e}"))?;
+
+    let mut out = String::with_capacity(src.len());
+    // Nesting depth inside a forbidden element, `0` if not inside one.
+    let mut skip_depth = 0usize;
+    // Whether the last element start was a `<style>`.
+    let mut started_style = false;
+    let mut style = None::<Style>;
+    for token in Tokenizer::from(src) {
+        let token = token.map_err(|e| format!("invalid SVG: {e}"))?;
+
+        // The content of a stylesheet is collected and only checked once the
+        // element is closed, as it might be split into multiple tokens.
+        // Elements inside of it are dropped.
+        if let Some(style) = style.as_mut().filter(|_| skip_depth == 0) {
+            match token {
+                Token::Text { text } => {
+                    style.css.push_str(&decode_references(&text));
+                    style.raw.push_str(&text);
+                    continue;
+                }
+                Token::Cdata { text, span } => {
+                    style.css.push_str(&text);
+                    style.raw.push_str(&span);
+                    continue;
+                }
+                Token::Comment { span, .. } => {
+                    style.raw.push_str(&span);
+                    continue;
+                }
+                Token::ElementStart { .. } => {
+                    skip_depth += 1;
+                    continue;
+                }
+                _ => {}
+            }
+        }
+
+        match token {
+            Token::ElementStart { local, span, .. } => {
+                if skip_depth > 0 || is_forbidden(&local) {
+                    skip_depth += 1;
+                } else {
+                    started_style = local.eq_ignore_ascii_case("style");
+                    out.push_str(&span);
+                }
+            }
+            Token::Attribute { local, value, span, .. }
+                if skip_depth == 0 && is_safe_attribute(&local, &value) =>
+            {
+                out.push(' ');
+                out.push_str(&span);
+            }
+            Token::ElementEnd { end, span } => {
+                if skip_depth > 0 {
+                    skip_depth -= !matches!(end, ElementEnd::Open) as usize;
+                } else {
+                    if let Some(Style { raw, css }) = style.take() {
+                        // Stylesheets can load external resources via `url()`
+                        // and `@import`, so we drop those that do.
+                        let css = normalize_css(&css);
+                        if !css.contains("@import") && !has_external_url(&css) {
+                            out.push_str(&raw);
+                        }
+                    }
+                    if matches!(end, ElementEnd::Open) && started_style {
+                        style = Some(Style::default());
+                    }
+                    out.push_str(&span);
+                }
+            }
+            Token::Text { text } | Token::Cdata { span: text, .. } if skip_depth == 0 => {
+                out.push_str(&text);
+            }
+            Token::Declaration { span, .. } | Token::Comment { span, .. } if skip_depth == 0 => {
+                out.push_str(&span);
+            }
+
+            // Everything else is dropped: processing instructions and the
+            // whole DTD, including entity declarations.
+            _ => {}
+        }
+    }
+
+    Ok(out.into_bytes())
+}
+
+/// The content of a `<style>` element.
+#[derive(Default)]
+struct Style {
+    /// The content as it appears in the source.
+    raw: String,
+
+    /// The CSS, i.e. text and CDATA sections with character references
+    /// decoded and XML comments removed.
+    css: String,
+}
+
+pub(crate) fn optimize(src: &[u8]) -> Result<Vec<u8>, String> {
+    let src = std::str::from_utf8(src).map_err(|e| format!("SVG is not valid UTF-8: {e}"))?;
+
+    let mut out = String::with_capacity(src.len());
+    // Nesting depth inside a removed element, `0` if not inside one.
+    let mut skip_depth = 0usize;
+    // For each open element, whether whitespace is kept inside of it.
+    let mut keep_whitespace = Vec::new();
+    let mut is_text_element = false;
+    for token in Tokenizer::from(src) {
+        let token = token.map_err(|e| format!("invalid SVG: {e}"))?;
+        match token {
+            Token::ElementStart { prefix, local, span } => {
+                if skip_depth > 0 || is_editor_data(&prefix, &local) {
+                    skip_depth += 1;
+                } else {
+                    is_text_element = keep_whitespace.last().copied().unwrap_or(false)
+                        || TEXT_ELEMENTS.iter().any(|e| local.eq_ignore_ascii_case(e));
+                    out.push_str(&span);
+                }
+            }
+            Token::Attribute { prefix, local, span, .. }
+                if skip_depth == 0 && !is_editor_attribute(&prefix, &local) =>
+            {
+                out.push(' ');
+                out.push_str(&span);
+            }
+            Token::ElementEnd { end, span } => {
+                let closes = !matches!(end, ElementEnd::Open);
+                if skip_depth > 0 {
+                    skip_depth -= closes as usize;
+                    continue;
+                }
+                match end {
+                    ElementEnd::Open => keep_whitespace.push(is_text_element),
+                    ElementEnd::Close(..) => { keep_whitespace.pop(); }
+                    ElementEnd::Empty => {}
+                }
+                out.push_str(&span);
+            }
+            Token::Text { text } if skip_depth == 0 => {
+                if keep_whitespace.last().copied().unwrap_or(false) {
+                    out.push_str(&text);
+                } else if !text.trim().is_empty() {
+                    out.push_str(&text.split_whitespace().collect::<Vec<_>>().join(" "));
+                }
+            }
+            Token::Cdata { span, .. } if skip_depth == 0 => out.push_str(&span),
+
+            // The DTD is kept as entities declared in it might be used.
+            Token::DtdStart { span, .. }
+            | Token::EntityDeclaration { span, .. }
+            | Token::DtdEnd { span } => out.push_str(&span),
+
+            // Everything else is dropped: comments, the XML declaration,
+            // processing instructions and DTDs without declarations.
+            _ => {}
+        }
+    }
+
+    Ok(out.into_bytes())
+}
+
+/// Returns whether the element is metadata that is not rendered.
+fn is_editor_data(prefix: &str, local: &str) -> bool {
+    (prefix.is_empty() && local == "metadata") || EDITOR_PREFIXES.contains(&prefix)
+}
+
+fn is_editor_attribute(prefix: &str, local: &str) -> bool {
+    EDITOR_PREFIXES.contains(&prefix) || (prefix == "xmlns" && EDITOR_PREFIXES.contains(&local))
+}
+
+fn is_forbidden(element: &str) -> bool {
+    FORBIDDEN_ELEMENTS.iter().any(|f| element.eq_ignore_ascii_case(f))
+}
+
+fn is_safe_attribute(name: &str, value: &str) -> bool {
+    let css = normalize_css(&decode_references(value));
+    let value = normalize(value);
+    if name.len() > 2 && name.get(..2).is_some_and(|p| p.eq_ignore_ascii_case("on")) {
+        return false;
+    }
+    if name.eq_ignore_ascii_case("href") {
+        return value.starts_with('#') || (
+            ALLOWED_DATA_IMAGES.iter().any(|prefix| value.starts_with(prefix))
+        );
+    }
+
+    !value.contains("javascript:") && !has_external_url(&css)
+}
+
+/// Returns whether `css` contains a `url(...)` not referring to a fragment
+/// in the same document.
+fn has_external_url(css: &str) -> bool {
+    let css = css.to_ascii_lowercase();
+    css.match_indices("url(").any(|(pos, _)| {
+        let arg = css[pos + 4..].trim_start_matches(|c: char| c.is_whitespace() || c == '"' || c == '\'');
+        !arg.starts_with('#')
+    })
+}
+
+/// Decodes character references and removes whitespace and control
+/// characters (which browsers ignore in URLs), and lowercases the value.
+fn normalize(value: &str) -> String {
+    decode_references(value)
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Removes CSS comments, decodes CSS escapes (e.g. `\72` or `\r` for `r`)
+/// and lowercases the value, so that `url(` and `@import` can be found.
+fn normalize_css(css: &str) -> String {
+    let mut out = String::with_capacity(css.len());
+    let mut chars = css.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = ' ';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            '\\' => {
+                let mut code = 0;
+                let mut digits = 0;
+                while let Some(d) = chars.peek().and_then(|c| c.to_digit(16)).filter(|_| digits < 6) {
+                    code = code * 16 + d;
+                    digits += 1;
+                    chars.next();
+                }
+                if digits > 0 {
+                    chars.next_if(|c| c.is_whitespace());
+                    out.extend(char::from_u32(code).unwrap_or('\u{FFFD}').to_lowercase());
+                } else if let Some(c) = chars.next().filter(|c| *c != '\n') {
+                    out.extend(c.to_lowercase());
+                }
+            }
+            c => out.extend(c.to_lowercase()),
+        }
+    }
+    out
+}
+
+/// Decodes character references.
+fn decode_references(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(c) = rest.chars().next() {
+        let decoded = if c == '&' { decode_reference(rest) } else { None };
+        let (c, len) = decoded.unwrap_or((c, c.len_utf8()));
+        out.push(c);
+        rest = &rest[len..];
+    }
+    out
+}
+
+/// Decodes the character reference at the start of `s`, returning it and the
+/// length of the reference.
+fn decode_reference(s: &str) -> Option<(char, usize)> {
+    let end = s.find(';')?;
+    let c = match &s[1..end] {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "colon" => ':',
+        num => {
+            let num = num.strip_prefix('#')?;
+            let code = match num.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => num.parse().ok()?,
+            };
+            char::from_u32(code)?
+        }
+    };
+    Some((c, end + 1))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(input: &str, expected: &str) {
+        let out = sanitize(input.as_bytes()).unwrap();
+        assert_eq!(std::str::from_utf8(&out).unwrap(), expected);
+    }
+
+    #[test]
+    fn keeps_harmless() {
+        let svg = r##"<?xml version="1.0"?><!-- icon --><svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10"><defs><linearGradient id="g"/></defs><rect fill="url(#g)" width="10"/><use href="#g"/><style>rect { fill: red; }</style></svg>"##;
+        check(svg, svg);
+    }
+
+    #[test]
+    fn removes_scripts() {
+        check(
+            r#"<svg><script>alert(1)</script><g><script src="x.js"/></g></svg>"#,
+            "<svg><g></g></svg>",
+        );
+        check(
+            r#"<svg><foreignObject><div><script>alert(1)</script></div></foreignObject><circle/></svg>"#,
+            "<svg><circle/></svg>",
+        );
+    }
+
+    #[test]
+    fn removes_event_handlers() {
+        check(r#"<svg onload="alert(1)"><rect ONCLICK='x()' on="1"/></svg>"#, r#"<svg><rect on="1"/></svg>"#);
+    }
+
+    #[test]
+    fn removes_external_refs() {
+        check(
+            r#"<svg><a href="javascript:alert(1)"><image xlink:href="https://evil.com/x.png"/></a></svg>"#,
+            "<svg><a><image/></a></svg>",
+        );
+        check(
+            r#"<svg><a href="&#106;avascript:alert(1)"/><a href=" #x"/></svg>"#,
+            r#"<svg><a/><a href=" #x"/></svg>"#,
+        );
+        check(
+            r#"<svg><image href="data:image/png;base64,AAAA"/><image href="data:image/svg+xml,x"/></svg>"#,
+            r#"<svg><image href="data:image/png;base64,AAAA"/><image/></svg>"#,
+        );
+        check(
+            r#"<svg><rect style="fill: url( 'https://evil.com/x' )"/><set to="java&#x09;script:x"/></svg>"#,
+            "<svg><rect/><set/></svg>",
+        );
+        check(
+            r#"<svg><style>@import "https://evil.com/x.css";</style></svg>"#,
+            "<svg><style></style></svg>",
+        );
+    }
+
+    #[test]
+    fn removes_obfuscated_style_refs() {
+        let removed = [
+            r#"@im<![CDATA[port "https://evil.com/x.css"]]>"#,
+            "a { fill: u<!---->rl(https://evil.com/x) }",
+            r#"@\69mport "https://evil.com/x.css";"#,
+            r#"@\000069 mport "https://evil.com/x.css";"#,
+            "a { fill: u\\72l(https://evil.com/x) }",
+            "a { fill: u\\rl(https://evil.com/x) }",
+            "a { fill: u\\72 l(https://evil.com/x) }",
+            "&#64;import 'https://evil.com/x.css';",
+            "a { fill: url(/**/https://evil.com/x) }",
+            r#"<g/>@import "https://evil.com/x.css";"#,
+            r#"<?pi?>@import "https://evil.com/x.css";"#,
+        ];
+        for css in removed {
+            check(&format!("<svg><style>{css}</style></svg>"), "<svg><style></style></svg>");
+        }
+
+        check(
+            r#"<svg><rect style="fill: u\72l(https://evil.com/x)"/><rect fill="u\rl(//evil.com)"/></svg>"#,
+            "<svg><rect/><rect/></svg>",
+        );
+        check(
+            r#"<svg><style><![CDATA[a { fill: url(#g) } /* \75rl(x) */]]><!-- c --></style></svg>"#,
+            r#"<svg><style><![CDATA[a { fill: url(#g) } /* \75rl(x) */]]><!-- c --></style></svg>"#,
+        );
+    }
+
+    fn check_optimize(input: &str, expected: &str) {
+        let out = optimize(input.as_bytes()).unwrap();
+        assert_eq!(std::str::from_utf8(&out).unwrap(), expected);
+    }
+
+    #[test]
+    fn optimize_removes_metadata() {
+        check_optimize(
+            concat!(
+                r#"<?xml version="1.0"?><!-- Created with Inkscape -->"#,
+                r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape""#,
+                r#" inkscape:version="1.2"><metadata><rdf:RDF/></metadata>"#,
+                r#"<sodipodi:namedview id="v"/><rect inkscape:label="bg" width="10"/></svg>"#,
+            ),
+            r#"<svg xmlns="http://www.w3.org/2000/svg"><rect width="10"/></svg>"#,
+        );
+    }
+
+    #[test]
+    fn optimize_collapses_whitespace() {
+        check_optimize(
+            "<svg>\n  <g\n    id='a'>\n    <rect/>\n  </g>\n  <text> Hello  <tspan> World</tspan> </text>\n</svg>\n",
+            "<svg><g id='a'><rect/></g><text> Hello  <tspan> World</tspan> </text></svg>",
+        );
+        check_optimize(
+            r#"<!DOCTYPE svg [<!ENTITY x "y">]><svg><style>a { }</style>&x;</svg>"#,
+            r#"<!DOCTYPE svg [<!ENTITY x "y">]><svg><style>a { }</style>&x;</svg>"#,
+        );
+    }
+
+    #[test]
+    fn removes_dtd() {
+        check(
+            r#"<!DOCTYPE svg [<!ENTITY x "boom">]><?foo bar?><svg>&x;</svg>"#,
+            "<svg>&x;</svg>",
+        );
+    }
+
+    #[test]
+    fn rejects_invalid() {
+        assert!(sanitize(b"<svg><rect</svg>").is_err());
+        assert!(sanitize(b"<svg>\xff</svg>").is_err());
+    }
+}
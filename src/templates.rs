@@ -0,0 +1,108 @@
+//! Adapters for template engines, see [`Assets::minijinja_loader`] and
+//! [`Assets::to_tera`].
+//!
+//! `askama` is not supported as it compiles templates into the executable.
+
+use crate::Assets;
+#[cfg(feature = "minijinja")]
+use crate::{Asset, TextError};
+
+
+impl Assets {
+    /// Returns a template loader for `minijinja`, to be passed to
+    /// `Environment::set_loader`. Template names are *hashed HTTP paths*, so
+    /// templates are just assets registered like any other. In dev mode,
+    /// each template is loaded from the file system when first used (blocking
+    /// the current thread); as `minijinja` caches loaded templates, call
+    /// `Environment::clear_templates` to pick up changes. In prod mode, the
+    /// content already in memory is used.
+    ///
+    /// ```no_run
+    /// # async fn foo(assets: reinda::Assets) {
+    /// let assets = assets.into_static();
+    /// let mut env = minijinja::Environment::new();
+    /// env.set_loader(assets.minijinja_loader());
+    /// let html = env.get_template("index.html").unwrap().render(()).unwrap();
+    /// # }
+    /// ```
+    ///
+    /// Method is only available if the crate feature `minijinja` is enabled.
+    #[cfg(feature = "minijinja")]
+    pub fn minijinja_loader(
+        &'static self,
+    ) -> impl Fn(&str) -> Result<Option<String>, minijinja::Error> + Send + Sync + 'static {
+        move |name| {
+            let Some(asset) = self.get(name) else {
+                return Ok(None);
+            };
+            text_blocking(&asset).map(Some).map_err(|e| {
+                let msg = format!("failed to load template '{}'", name);
+                minijinja::Error::new(minijinja::ErrorKind::InvalidOperation, msg).with_source(e)
+            })
+        }
+    }
+
+    /// Creates a `tera::Tera` instance containing all assets whose *hashed
+    /// HTTP path* matches the glob pattern `glob` as templates, named by
+    /// their path. Templates are loaded once when calling this, so in dev
+    /// mode, call this again to pick up changes.
+    ///
+    /// ```no_run
+    /// # async fn foo(assets: reinda::Assets) -> tera::Result<()> {
+    /// let tera = assets.to_tera("templates/**/*.html").await?;
+    /// let html = tera.render("templates/index.html", &tera::Context::new())?;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// Method is only available if the crate feature `tera` is enabled.
+    ///
+    /// **Panics** if `glob` is not a valid glob pattern.
+    #[cfg(feature = "tera")]
+    pub async fn to_tera(&self, glob: &str) -> tera::Result<tera::Tera> {
+        let pattern = glob::Pattern::new(glob)
+            .unwrap_or_else(|e| panic!("invalid glob pattern '{}': {}", glob, e));
+
+        let mut templates = Vec::new();
+        for (path, asset) in self.iter().filter(|(path, _)| pattern.matches(path)) {
+            let content = asset.text().await.map_err(|e| {
+                tera::Error::chain(format!("failed to load template '{}'", path), e)
+            })?;
+            templates.push((path.to_owned(), content));
+        }
+
+        let mut tera = tera::Tera::default();
+        tera.add_raw_templates(templates)?;
+        Ok(tera)
+    }
+}
+
+/// Like [`Asset::text`], but blocking. In prod mode, this never blocks as the
+/// content is already in memory. In dev mode, the asset is loaded on a
+/// separate thread so that this also works inside async contexts.
+#[cfg(feature = "minijinja")]
+fn text_blocking(asset: &Asset) -> Result<String, TextError> {
+    #[cfg(prod_mode)]
+    {
+        use std::{future::Future, task::{Context, Poll, Waker}};
+
+        // `Asset::text` never yields in prod mode.
+        let mut fut = std::pin::pin!(asset.text());
+        match fut.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(result) => result,
+            Poll::Pending => unreachable!("`Asset::text` yielded in prod mode"),
+        }
+    }
+
+    #[cfg(dev_mode)]
+    {
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .expect("failed to create tokio runtime")
+                    .block_on(asset.text())
+            }).join().expect("loading template panicked")
+        })
+    }
+}
@@ -0,0 +1,145 @@
+//! Reference implementations of the path computations `reinda` performs,
+//! for tools that need to predict the paths under which assets are served,
+//! e.g. server-side rendering in another language or deploy scripts. These
+//! functions are used internally, so their output is exactly what `reinda`
+//! produces in prod mode. Their behavior only changes in semver-breaking
+//! releases; the test vectors in this module's tests pin it.
+//!
+//! Module is only available if the crate feature `test-util` is enabled.
+
+use crate::HashPlacement;
+
+
+/// Returns the hash of `content` as inserted into filenames: the first 9
+/// bytes of its SHA-256 digest, URL-safe base64 encoded without padding
+/// (always 12 characters).
+///
+/// ```
+/// assert_eq!(reinda::test_util::content_hash(b"foo"), "LCa0a2j_xo_5");
+/// ```
+pub fn content_hash(content: &[u8]) -> String {
+    crate::hash::encoded_hash(content)
+}
+
+/// Returns the *hashed HTTP path* for an asset with the given *unhashed HTTP
+/// path* and (final) content, as produced by
+/// [`EntryBuilder::with_hash`][crate::EntryBuilder::with_hash] with the
+/// default [`HashPlacement::AfterFirstDot`] and by
+/// [`minimal::Assets::with_hash`][crate::minimal::Assets::with_hash].
+///
+/// ```
+/// assert_eq!(reinda::test_util::hashed_path_of("js/app.min.js", b"foo"), "js/app.LCa0a2j_xo_5.min.js");
+/// ```
+pub fn hashed_path_of(unhashed_http_path: &str, content: &[u8]) -> String {
+    crate::hash::auto_hashed_path(unhashed_http_path, content)
+}
+
+/// Like [`hashed_path_of`], but with the given placement, as produced by
+/// [`EntryBuilder::with_hash_placement`][crate::EntryBuilder::with_hash_placement].
+/// For other settings like the hash length, see [`crate::hashed_path`].
+pub fn hashed_path_with(placement: &HashPlacement, unhashed_http_path: &str, content: &[u8]) -> String {
+    crate::hash::place_hash(placement, unhashed_http_path, &content_hash(content))
+}
+
+/// Splits a glob pattern into the leading path segments without glob meta
+/// characters and the rest. The first part is stripped from matched files
+/// before mounting them, see [`glob_http_path`].
+///
+/// ```
+/// use reinda::test_util::split_glob;
+///
+/// assert_eq!(split_glob("frontend/**/banana.txt"), ("frontend/", "**/banana.txt"));
+/// assert_eq!(split_glob("fonts/*.woff2"), ("fonts/", "*.woff2"));
+/// ```
+pub fn split_glob(glob: &str) -> (&str, &str) {
+    crate::split_glob(glob)
+}
+
+/// Returns the *unhashed HTTP path* of a file matched by `glob` when mounted
+/// via [`Builder::add_embedded_glob`][crate::Builder::add_embedded_glob] or
+/// [`Builder::add_glob`][crate::Builder::add_glob] under `http_prefix`.
+/// `file_path` is relative to the same base as `glob`. Returns `None` if
+/// `file_path` does not start with the static prefix of `glob`.
+///
+/// ```
+/// use reinda::test_util::glob_http_path;
+///
+/// let path = glob_http_path("animals/", "img/**/*.svg", "img/pets/cat.svg");
+/// assert_eq!(path.as_deref(), Some("animals/pets/cat.svg"));
+/// ```
+pub fn glob_http_path(http_prefix: &str, glob: &str, file_path: &str) -> Option<String> {
+    let (static_prefix, _) = split_glob(glob);
+    let suffix = file_path.strip_prefix(static_prefix)?;
+    Some(crate::builder::join_glob_path(http_prefix, suffix))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pinned outputs. If any of these change, it's a breaking change.
+    #[test]
+    fn vectors() {
+        assert_eq!(content_hash(b""), "47DEQpj8HBSa");
+        assert_eq!(content_hash(b"foo"), "LCa0a2j_xo_5");
+        assert_eq!(hashed_path_of("index.html", b""), "index.47DEQpj8HBSa.html");
+        assert_eq!(hashed_path_of("LICENSE", b""), "LICENSE-47DEQpj8HBSa");
+        assert_eq!(hashed_path_of(".well-known/x.txt", b""), ".well-known/x.47DEQpj8HBSa.txt");
+        assert_eq!(
+            hashed_path_with(&HashPlacement::BeforeExt, "a/jquery.min.js", b"foo"),
+            "a/jquery.min.LCa0a2j_xo_5.js",
+        );
+        assert_eq!(
+            hashed_path_with(&HashPlacement::Suffix, "a/jquery.min.js", b"foo"),
+            "a/jquery.min.js-LCa0a2j_xo_5",
+        );
+        assert_eq!(split_glob("a/b/c.txt"), ("a/b/c.txt", ""));
+        assert_eq!(split_glob("../x/y[ab]/*.css"), ("../x/", "y[ab]/*.css"));
+        assert_eq!(glob_http_path("", "a/*.js", "a/b.js").as_deref(), Some("b.js"));
+        assert_eq!(glob_http_path("", "a/*.js", "c/b.js"), None);
+    }
+
+    /// Properties that hold for all inputs, checked over generated paths.
+    #[test]
+    fn properties() {
+        let segments = ["", ".", "..", "a", ".env", "a.b", "a.b.c", "x-y", "ü.ß", "dir.d"];
+        let placements = [
+            HashPlacement::AfterFirstDot,
+            HashPlacement::BeforeExt,
+            HashPlacement::Suffix,
+        ];
+        for dir in &segments {
+            for file in &segments {
+                let path = if dir.is_empty() { file.to_string() } else { format!("{dir}/{file}") };
+                for (i, content) in [&b""[..], b"foo", &[0xff; 100]].iter().enumerate() {
+                    let hash = content_hash(content);
+                    assert_eq!(hash.len(), 12);
+                    assert!(hash.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_'));
+
+                    let hashed = hashed_path_of(&path, content);
+                    assert_eq!(hashed, hashed_path_with(&placements[0], &path, content));
+                    for placement in &placements {
+                        let hashed = hashed_path_with(placement, &path, content);
+
+                        // Exactly one occurence of the hash plus separator is
+                        // inserted into the last segment.
+                        assert_eq!(hashed.len(), path.len() + 13, "{path} #{i}");
+                        let pos = hashed.find(&hash).unwrap();
+                        let mut removed = hashed.clone();
+                        removed.replace_range(pos - 1..pos + 12, "");
+                        assert_eq!(removed, path);
+                        assert!(pos > path.rfind('/').unwrap_or(0));
+                    }
+                }
+            }
+        }
+
+        for glob in ["a/*.js", "a/b/**/c", "*", "a/[b]/c", "a/b/c", "../a/?"] {
+            let (prefix, suffix) = split_glob(glob);
+            assert_eq!(format!("{prefix}{suffix}"), glob);
+            assert!(!prefix.contains(['*', '?', '[', ']']));
+            assert!(prefix.is_empty() || prefix.ends_with('/') || suffix.is_empty());
+        }
+    }
+}
@@ -0,0 +1,89 @@
+//! Cheap heuristics to classify assets as text or binary.
+
+/// How many bytes at the start of a file are inspected for classification.
+pub(crate) const SNIFF_LEN: usize = 1024;
+
+/// Extensions of files that are always considered text.
+const TEXT_EXTENSIONS: &[&str] = &[
+    "css", "csv", "htm", "html", "js", "json", "map", "md", "mjs", "svg", "toml",
+    "ts", "tsv", "txt", "webmanifest", "xml", "yaml", "yml",
+];
+
+/// Extensions of files that are always considered binary.
+const BINARY_EXTENSIONS: &[&str] = &[
+    "avif", "bin", "br", "gif", "gz", "ico", "jpeg", "jpg", "mp3", "mp4", "ogg", "otf",
+    "pdf", "png", "ttf", "wasm", "webm", "webp", "woff", "woff2", "zip",
+];
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TextInfo {
+    pub(crate) is_text: bool,
+    pub(crate) charset: Option<&'static str>,
+}
+
+/// Classifies a file by the extension of its `path` and, if the extension is
+/// unknown, by inspecting its first bytes. Only the first [`SNIFF_LEN`] bytes
+/// of `content` are looked at.
+pub(crate) fn classify(path: &str, content: &[u8]) -> TextInfo {
+    let prefix = &content[..content.len().min(SNIFF_LEN)];
+    let is_utf8 = match std::str::from_utf8(prefix) {
+        Ok(_) => true,
+        // If the prefix just cuts a multi-byte char in half, it's still fine.
+        Err(e) => e.error_len().is_none() && prefix.len() == SNIFF_LEN,
+    };
+
+    let is_text = match extension(path) {
+        Some(ext) if TEXT_EXTENSIONS.contains(&&*ext) => true,
+        Some(ext) if BINARY_EXTENSIONS.contains(&&*ext) => false,
+        _ => is_utf8 && !prefix.contains(&0),
+    };
+
+    TextInfo {
+        is_text,
+        charset: if is_text && is_utf8 { Some("utf-8") } else { None },
+    }
+}
+
+/// Returns the lowercased extension of the last segment of `path`, if any.
+pub(crate) fn extension(path: &str) -> Option<String> {
+    let filename = path.rsplit('/').next().unwrap_or(path);
+    filename.rfind('.')
+        .filter(|&pos| pos > 0)
+        .map(|pos| filename[pos + 1..].to_ascii_lowercase())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_by_extension() {
+        let text = TextInfo { is_text: true, charset: Some("utf-8") };
+        let binary = TextInfo { is_text: false, charset: None };
+
+        assert_eq!(classify("index.html", b"<html>"), text);
+        assert_eq!(classify("static/Style.CSS", b"body {}"), text);
+        assert_eq!(classify("latin1.txt", b"gr\xfcn"), TextInfo { is_text: true, charset: None });
+        assert_eq!(classify("fonts/a.woff2", b"wOF2"), binary);
+        assert_eq!(classify("logo.png", b"plain ascii"), binary);
+    }
+
+    #[test]
+    fn classify_by_content() {
+        let text = TextInfo { is_text: true, charset: Some("utf-8") };
+        let binary = TextInfo { is_text: false, charset: None };
+
+        assert_eq!(classify("LICENSE", b"MIT license"), text);
+        assert_eq!(classify(".well-known/foo", "Grüße".as_bytes()), text);
+        assert_eq!(classify("data.unknown", b"foo\0bar"), binary);
+        assert_eq!(classify("blob", b"\xff\xfe\x00"), binary);
+        assert_eq!(classify(".htaccess", b"Deny from all"), text);
+
+        // Multi-byte char cut in half at the sniff boundary.
+        let mut long = vec![b'a'; SNIFF_LEN - 1];
+        long.extend_from_slice("ü".as_bytes());
+        assert_eq!(classify("long", &long), text);
+    }
+}
@@ -0,0 +1,95 @@
+//! Loading assets via HTTP, see [`UrlSource`].
+
+use std::{io, sync::OnceLock, time::Duration};
+
+use bytes::Bytes;
+
+use crate::AssetSource;
+
+
+/// An [`AssetSource`] downloading the asset via HTTP(S) GET. Usually used via
+/// [`Builder::add_url`][crate::Builder::add_url]; use this type directly with
+/// [`Builder::add_source`][crate::Builder::add_source] to configure timeout
+/// and retries.
+///
+/// Failed requests (network errors, timeouts and `5xx` responses) are
+/// retried with exponential backoff, starting at 100ms. Other non-success
+/// responses fail immediately.
+///
+/// ```
+/// use std::time::Duration;
+/// use reinda::UrlSource;
+///
+/// let mut builder = reinda::Assets::builder();
+/// let source = UrlSource::new("https://example.com/config.json")
+///     .timeout(Duration::from_secs(5))
+///     .retries(3);
+/// builder.add_source("config.json", source);
+/// ```
+#[derive(Debug, Clone)]
+pub struct UrlSource {
+    url: String,
+    timeout: Duration,
+    retries: u32,
+}
+
+impl UrlSource {
+    /// Creates a source for the given URL with a timeout of 30s and 2
+    /// retries.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            timeout: Duration::from_secs(30),
+            retries: 2,
+        }
+    }
+
+    /// Sets the timeout for each request.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets how often a failed request is retried.
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    async fn fetch(&self) -> Result<Bytes, (io::Error, bool)> {
+        static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+        let client = CLIENT.get_or_init(reqwest::Client::new);
+        let response = client.get(&self.url)
+            .timeout(self.timeout)
+            .send()
+            .await
+            .map_err(|e| (io::Error::other(e), true))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let err = io::Error::other(format!("GET '{}' returned status {}", self.url, status));
+            return Err((err, status.is_server_error()));
+        }
+
+        response.bytes().await.map_err(|e| (io::Error::other(e), true))
+    }
+}
+
+impl AssetSource for UrlSource {
+    async fn load(&self) -> io::Result<Bytes> {
+        let mut backoff = Duration::from_millis(100);
+        let mut attempt = 0;
+        loop {
+            match self.fetch().await {
+                Ok(bytes) => return Ok(bytes),
+                Err((err, retryable)) if !retryable || attempt >= self.retries => return Err(err),
+                Err(_) => {
+                    attempt += 1;
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+            }
+        }
+    }
+}
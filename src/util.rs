@@ -1,11 +1,18 @@
 //! Utility functions.
 
+use std::{
+    convert::TryFrom,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+#[cfg(feature = "modifiers")]
 use aho_corasick::AhoCorasick;
 
 
 /// Replaces multiple occurences in the given byte slice.
 ///
 /// This is more effient than calling `.replace` multiple times.
+#[cfg(feature = "modifiers")]
 pub fn replace_many<N, R>(src: &[u8], replacements: &[(N, R)]) -> Vec<u8>
 where
     N: AsRef<[u8]>,
@@ -26,6 +33,7 @@ where
 ///
 /// This is more effient than calling `.replace` multiple times. This function
 /// is the more powerful version of [`replace_many`].
+#[cfg(feature = "modifiers")]
 pub fn replace_many_with<N, T, R>(src: &[u8], needles: N, mut f: R) -> Vec<u8>
 where
     N: IntoIterator<Item = T>,
@@ -40,3 +48,289 @@ where
     });
     out.into()
 }
+
+/// Removes potentially dangerous content from an SVG file, as SVGs embedded
+/// into a website are a known XSS vector. This removes:
+///
+/// - `<script>`, `<foreignObject>` and other elements able to run code or
+///   embed documents, including their content,
+/// - event handler attributes (`onload`, `onclick`, ...),
+/// - `href`s not referring to a fragment (`#id`) or an embedded raster image,
+///   and all other attributes containing `javascript:` or an external
+///   `url(...)`,
+/// - stylesheets with `@import` or external `url(...)`,
+/// - the DTD (including entity declarations) and processing instructions.
+///
+/// Returns an error if `src` is not valid UTF-8 or not well-formed XML. Also
+/// see [`EntryBuilder::sanitize_svg`][crate::EntryBuilder::sanitize_svg].
+///
+/// ```
+/// let svg = br#"<svg onload="alert(1)"><script>alert(2)</script><rect/></svg>"#;
+/// assert_eq!(reinda::util::sanitize_svg(svg).unwrap(), b"<svg><rect/></svg>");
+/// ```
+#[cfg(feature = "sanitize-svg")]
+pub fn sanitize_svg(src: &[u8]) -> Result<Vec<u8>, String> {
+    crate::svg::sanitize(src)
+}
+
+/// Optimizes an SVG file for size without changing how it is rendered: removes
+/// comments, the XML declaration, `<metadata>` and editor specific elements
+/// and attributes (e.g. from Inkscape) and whitespace between elements.
+/// Whitespace inside text elements like `<text>` and `<style>` is kept.
+/// Returns an error if `src` is not valid UTF-8 or not well-formed XML. Also
+/// see [`EntryBuilder::optimize_svg`][crate::EntryBuilder::optimize_svg].
+///
+/// ```
+/// let svg = b"<?xml version=\"1.0\"?>\n<!-- icon -->\n<svg>\n  <metadata>x</metadata>\n  <rect/>\n</svg>\n";
+/// assert_eq!(reinda::util::optimize_svg(svg).unwrap(), b"<svg><rect/></svg>");
+/// ```
+#[cfg(feature = "optimize-svg")]
+pub fn optimize_svg(src: &[u8]) -> Result<Vec<u8>, String> {
+    crate::svg::optimize(src)
+}
+
+/// Minifies JavaScript code. The code is treated as classic script, so
+/// top-level names are kept, unless it can only be parsed as ES module (e.g.
+/// because it contains `import` statements). Returns an error if `src` is not
+/// valid JavaScript. Also see
+/// [`EntryBuilder::minify_js`][crate::EntryBuilder::minify_js].
+///
+/// ```
+/// let js = b"function greet(name) { let message = 'Hello ' + name; alert(message); }";
+/// let minified = reinda::util::minify_js(js).unwrap();
+/// assert_eq!(std::str::from_utf8(&minified).unwrap(), "var greet=(a=>{let b=`Hello `+ a;alert(b)})");
+/// ```
+#[cfg(feature = "minify-js")]
+pub fn minify_js(src: &[u8]) -> Result<Vec<u8>, String> {
+    crate::minify::js(src)
+}
+
+/// Escapes `&`, `<`, `>`, `"` and `'` so that the given string can be safely
+/// included in HTML text or attribute values.
+#[cfg(feature = "full")]
+pub(crate) fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Formats `time` as HTTP date (IMF-fixdate) as used by the `Last-Modified`
+/// header. Sub-second precision is truncated, times before 1970 are clamped.
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// let time = UNIX_EPOCH + Duration::from_secs(784111777);
+/// assert_eq!(reinda::util::format_http_date(time), "Sun, 06 Nov 1994 08:49:37 GMT");
+/// ```
+pub fn format_http_date(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let days = secs / 86400;
+    let (year, month, day) = civil_from_days(days as i64);
+    format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} GMT",
+        WEEKDAYS[(days % 7) as usize],
+        day,
+        MONTHS[month as usize - 1],
+        year,
+        secs % 86400 / 3600,
+        secs % 3600 / 60,
+        secs % 60,
+    )
+}
+
+/// Parses an HTTP date in the IMF-fixdate format, as sent in the
+/// `If-Modified-Since` header. The obsolete RFC 850 and asctime formats are
+/// not supported, `None` is returned for those and any invalid input.
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// let time = reinda::util::parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT");
+/// assert_eq!(time, Some(UNIX_EPOCH + Duration::from_secs(784111777)));
+/// assert_eq!(reinda::util::parse_http_date("yesterday"), None);
+/// ```
+pub fn parse_http_date(s: &str) -> Option<SystemTime> {
+    let (_weekday, rest) = s.trim().split_once(", ")?;
+    let mut parts = rest.split(' ');
+    let day = parts.next()?.parse::<u32>().ok()?;
+    let month = parts.next()?;
+    let month = MONTHS.iter().position(|m| *m == month)? as u32 + 1;
+    let year = parts.next()?.parse::<i64>().ok()?;
+    let time = parts.next()?;
+    if parts.next()? != "GMT" || parts.next().is_some() {
+        return None;
+    }
+
+    let mut hms = time.split(':').map(|p| p.parse::<u64>().ok());
+    let (h, m, s) = (hms.next()??, hms.next()??, hms.next()??);
+    if hms.next().is_some() || !(1..=31).contains(&day) || h > 23 || m > 59 || s > 60 {
+        return None;
+    }
+
+    let days = u64::try_from(days_from_civil(year, month, day)).ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(days * 86400 + h * 3600 + m * 60 + s))
+}
+
+/// Days since 1970-01-01 of the given date in the proleptic Gregorian
+/// calendar. See <http://howardhinnant.github.io/date_algorithms.html>.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// Inverse of [`days_from_civil`].
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Like [`replace_many`], but guarantees that the output has exactly the same
+/// length as `src`. This is important for binary formats in which offsets or
+/// lengths are stored, like WASM data sections or other compiled artifacts.
+///
+/// If `padding` is `Some(byte)`, replacements shorter than their needle are
+/// padded with that byte (e.g. `b'\0'` for NUL-terminated strings or `b' '`
+/// for whitespace-insensitive formats). If it's `None`, all replacements need
+/// to have the same length as their needle. A replacement that does not fit
+/// results in an error. Replacements that do not occur in `src` are never
+/// checked.
+#[cfg(feature = "modifiers")]
+pub fn patch_many<N, R>(
+    src: &[u8],
+    replacements: &[(N, R)],
+    padding: Option<u8>,
+) -> Result<Vec<u8>, PatchError>
+where
+    N: AsRef<[u8]>,
+    R: AsRef<[u8]>,
+{
+    let needles = replacements.iter().map(|(needle, _)| needle);
+    let replacer = AhoCorasick::new(needles).unwrap();
+    let mut out = Vec::with_capacity(src.len());
+    let mut err = None;
+    replacer.replace_all_with_bytes(src, &mut out, |m, needle, out| {
+        let replacement = replacements[m.pattern().as_usize()].1.as_ref();
+        let fits = match padding {
+            Some(_) => replacement.len() <= needle.len(),
+            None => replacement.len() == needle.len(),
+        };
+        if !fits {
+            err = Some(PatchError {
+                needle: needle.to_vec(),
+                replacement_len: replacement.len(),
+            });
+            return false;
+        }
+
+        out.extend_from_slice(replacement);
+        out.resize(out.len() + needle.len() - replacement.len(), padding.unwrap_or(0));
+        true
+    });
+
+    match err {
+        Some(err) => Err(err),
+        None => Ok(out),
+    }
+}
+
+/// Error returned by [`patch_many`] if a replacement does not fit in place of
+/// its needle.
+#[cfg(feature = "modifiers")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchError {
+    needle: Vec<u8>,
+    replacement_len: usize,
+}
+
+#[cfg(feature = "modifiers")]
+impl PatchError {
+    /// The needle that could not be replaced.
+    pub fn needle(&self) -> &[u8] {
+        &self.needle
+    }
+
+    /// The length of the replacement that did not fit.
+    pub fn replacement_len(&self) -> usize {
+        self.replacement_len
+    }
+}
+
+#[cfg(feature = "modifiers")]
+impl std::fmt::Display for PatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "replacement for '{}' has length {}, but needle has length {}",
+            String::from_utf8_lossy(&self.needle),
+            self.replacement_len,
+            self.needle.len(),
+        )
+    }
+}
+
+#[cfg(feature = "modifiers")]
+impl std::error::Error for PatchError {}
+
+
+#[cfg(all(test, feature = "modifiers"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patch_many_same_length() {
+        let src = b"\0foo.js\0bar.js\0";
+        assert_eq!(
+            patch_many(src, &[("foo.js", "baz.js"), ("bar.js", "qux.js")], None).unwrap(),
+            b"\0baz.js\0qux.js\0",
+        );
+
+        let err = patch_many(src, &[("foo.js", "foo.1234.js")], Some(0)).unwrap_err();
+        assert_eq!(err.needle(), b"foo.js");
+        assert_eq!(err.replacement_len(), 11);
+
+        // Not occuring replacements are not checked.
+        assert_eq!(patch_many(src, &[("nope", "longer")], None).unwrap(), src);
+    }
+
+    #[test]
+    fn patch_many_padding() {
+        let src = b"\0long-name.js\0";
+        assert_eq!(
+            patch_many(src, &[("long-name.js", "a.js")], Some(0)).unwrap(),
+            b"\0a.js\0\0\0\0\0\0\0\0\0",
+        );
+        assert_eq!(
+            patch_many(src, &[("long-name.js", "a.js")], Some(b' ')).unwrap(),
+            b"\0a.js        \0",
+        );
+        assert!(patch_many(src, &[("long-name.js", "a.js")], None).is_err());
+    }
+}
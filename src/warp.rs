@@ -0,0 +1,157 @@
+//! Integration with `warp`, see [`assets_filter`].
+//!
+//! Module is only available if the crate feature `warp` is enabled.
+
+use std::sync::Arc;
+
+use warp::{
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    hyper::Body,
+    path::Tail,
+    reply::Response,
+    Filter, Rejection,
+};
+
+use crate::{
+    serve::{conditional, content_type, is_allowed, load_encoded, percent_decode},
+    Asset, Assets, RequestContext,
+};
+
+
+/// Returns a filter serving all assets under their *hashed HTTP paths*,
+/// relative to the path not yet matched by previous filters. Combine it with
+/// `warp::path` to serve assets under a prefix.
+///
+/// The filter handles `GET` and `HEAD` requests and:
+/// - sets `Content-Type` (with `charset` for text assets) and, if
+///   configured, `Cache-Control`,
+/// - serves the content encoded according to the `Accept-Encoding` header
+///   if possible (see [`Asset::content_encoded`]),
+/// - serves the best variant according to the `Accept` header (see
+///   [`Asset::best_variant`]), unless the guard of that variant denies
+///   access,
+/// - sets `ETag` and `Last-Modified` and answers conditional requests with
+///   `304 Not Modified` (see [`Conditional`][crate::Conditional]; requires
+///   the crate feature `hash` or `http`),
+/// - responds with [`LoadError::to_html`][crate::LoadError::to_html] and
+///   status 500 if loading an asset fails (only possible in dev mode),
+/// - responds with an empty 403 response if the guard of the asset denies
+///   access (see [`EntryBuilder::with_guard`][crate::EntryBuilder::with_guard]).
+///
+/// Unknown paths are answered with the fallback asset (see
+/// [`Builder::set_fallback`][crate::Builder::set_fallback]) if one is set.
+/// Otherwise, they are rejected with `warp::reject::not_found()`, so that
+/// other filters can be tried. Use [`Assets::error_asset`] in a `recover`
+/// handler to serve a custom 404 page.
+///
+/// ```
+/// use warp::Filter;
+///
+/// # async fn build() -> Result<reinda::Assets, reinda::BuildError> {
+/// # reinda::Assets::builder().build().await }
+/// # async fn run() -> Result<(), reinda::BuildError> {
+/// let assets = build().await?;
+/// let routes = warp::path("assets").and(reinda::warp::assets_filter(assets));
+/// # Ok(())
+/// # }
+/// ```
+pub fn assets_filter(
+    assets: Assets,
+) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone {
+    let assets = Arc::new(assets);
+    let head = warp::get().map(|| false).or(warp::head().map(|| true)).unify();
+    head.and(warp::path::tail())
+        .and(warp::header::headers_cloned())
+        .and_then(move |head: bool, tail: Tail, headers: HeaderMap| {
+            let assets = assets.clone();
+            async move { serve(&assets, head, tail.as_str(), &headers).await }
+        })
+}
+
+async fn serve(
+    assets: &Assets,
+    head: bool,
+    path: &str,
+    headers: &HeaderMap,
+) -> Result<Response, Rejection> {
+    let path = percent_decode(path).ok_or_else(warp::reject::not_found)?;
+    let asset = assets.get_or_fallback(&path).ok_or_else(warp::reject::not_found)?;
+    let context = || {
+        let method = if head { "HEAD" } else { "GET" };
+        let headers = headers.iter()
+            .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?)));
+        RequestContext::new(method, &path, headers)
+    };
+    if !is_allowed(&asset, context) {
+        let mut response = Response::new(Body::empty());
+        *response.status_mut() = StatusCode::FORBIDDEN;
+        return Ok(response);
+    }
+
+    // Variants can have guards of their own. If the best one denies access,
+    // the requested asset is served instead.
+    let accept = headers.get(header::ACCEPT).and_then(|v| v.to_str().ok());
+    let variant = match accept {
+        Some(accept) if asset.has_variants() => Some(asset.best_variant(accept))
+            .filter(|variant| is_allowed(variant, context))
+            .unwrap_or_else(|| asset.clone()),
+        _ => asset.clone(),
+    };
+
+    let mut response = respond(&variant, head, headers).await;
+    if asset.has_variants() {
+        response.headers_mut().append(header::VARY, HeaderValue::from_static("accept"));
+    }
+    Ok(response)
+}
+
+async fn respond(asset: &Asset, head: bool, req_headers: &HeaderMap) -> Response {
+    let accept_encoding = req_headers.get(header::ACCEPT_ENCODING).and_then(|v| v.to_str().ok());
+    let (content, encoding_headers) = match load_encoded(asset, accept_encoding).await {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            let mut response = Response::new(e.to_html().into());
+            *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+            response.headers_mut().insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("text/html; charset=utf-8"),
+            );
+            return response;
+        }
+    };
+
+    let (not_modified, validators) = conditional(asset, &content, req_headers.iter()
+        .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?))));
+
+    let len = content.len();
+    let mut response = match head || not_modified {
+        true => Response::new(Body::empty()),
+        false => {
+            #[cfg(feature = "metrics")]
+            crate::metrics::served(asset, len);
+            Response::new(content.into())
+        }
+    };
+    if not_modified {
+        *response.status_mut() = StatusCode::NOT_MODIFIED;
+    }
+    let headers = response.headers_mut();
+    if !not_modified {
+        if let Ok(value) = HeaderValue::from_str(&content_type(asset)) {
+            headers.insert(header::CONTENT_TYPE, value);
+        }
+        headers.insert(header::CONTENT_LENGTH, len.into());
+    }
+    for (name, value) in encoding_headers {
+        headers.append(name, HeaderValue::from_static(value));
+    }
+    if let Some(value) = asset.cache_control().and_then(|v| HeaderValue::from_str(v).ok()) {
+        headers.insert(header::CACHE_CONTROL, value);
+    }
+    for (name, value) in validators {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            headers.insert(name, value);
+        }
+    }
+    response
+}
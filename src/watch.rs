@@ -0,0 +1,58 @@
+//! Support for live reloading in dev mode.
+
+use std::path::Path;
+
+use crate::Assets;
+
+
+/// Describes which assets are influenced by changed files, returned by
+/// [`Assets::changes`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ChangeEvent {
+    /// *HTTP paths* of all assets that are directly loaded from one of the
+    /// changed files. Sorted.
+    pub changed: Vec<String>,
+
+    /// *HTTP paths* of all assets that (transitively) depend on one of the
+    /// `changed` assets via modifier dependencies, but are not in `changed`
+    /// themselves. Sorted.
+    pub affected: Vec<String>,
+}
+
+impl ChangeEvent {
+    /// Returns `true` if no asset is influenced by the changes.
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.affected.is_empty()
+    }
+
+    /// Iterator over all influenced assets, i.e. `changed` and `affected`.
+    pub fn all(&self) -> impl Iterator<Item = &str> {
+        self.changed.iter().chain(&self.affected).map(|s| &**s)
+    }
+}
+
+impl Assets {
+    /// Given a list of changed files (*FS paths*), e.g. reported by a file
+    /// watcher, returns which assets are influenced by those changes. This
+    /// lets you scope live-reload notifications, e.g. only refreshing
+    /// stylesheets if only CSS assets changed.
+    ///
+    /// Files that are not known at build time but matched by a glob entry
+    /// are considered as well. In prod mode, assets never change after
+    /// [`Builder::build`][crate::Builder::build], so this always returns an
+    /// empty event.
+    pub fn changes<P: AsRef<Path>>(&self, changed_fs_paths: impl IntoIterator<Item = P>) -> ChangeEvent {
+        #[cfg(dev_mode)]
+        {
+            let paths = changed_fs_paths.into_iter().map(|p| p.as_ref().to_owned()).collect::<Vec<_>>();
+            self.0.changes(&paths)
+        }
+
+        #[cfg(prod_mode)]
+        {
+            let _ = changed_fs_paths;
+            ChangeEvent::default()
+        }
+    }
+}
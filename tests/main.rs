@@ -1,7 +1,8 @@
+#![cfg(feature = "full")]
+
 use reinda::Assets;
 
 
-#[cfg(feature = "hash")]
 macro_rules! assert_get {
     ($assets:expr, $http_path:expr, $hashed:expr, $expected:expr) => {
         let asset = $assets.get($http_path)
@@ -55,7 +56,7 @@ async fn minimal() -> Result<(), Box<dyn std::error::Error>> {
 
 /// This is almost the same setup as in `examples/main.rs`.
 #[tokio::test]
-#[cfg(feature = "hash")]
+#[cfg(all(feature = "hash", feature = "modifiers"))]
 async fn use_case_web() -> Result<(), Box<dyn std::error::Error>> {
     const EMBEDS: reinda::Embeds = reinda::embed! {
         base_path: "examples/assets",
@@ -101,6 +102,10 @@ async fn use_case_web() -> Result<(), Box<dyn std::error::Error>> {
     assert_eq!(assets.iter().count(), 10);
     assert!(assets.iter().all(|(path, _)| assets.get(path).is_some()));
 
+    assert!(assets.get("index.html").unwrap().is_text());
+    assert_eq!(assets.get("robots.txt").unwrap().charset(), Some("utf-8"));
+    assert!(assets.iter().filter(|(path, _)| path.ends_with(".woff2")).all(|(_, a)| !a.is_text()));
+
     assert_get!(assets, "robots.txt", false,
         include_str!("../examples/assets/robots.txt"));
     assert_get!(assets, "img/logo-foo.svg", false,
@@ -214,9 +219,3009 @@ async fn use_case_web() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[tokio::test]
+async fn groups() -> Result<(), Box<dyn std::error::Error>> {
+    const EMBEDS: reinda::Embeds = reinda::embed! {
+        base_path: "examples/assets",
+        files: ["robots.txt", "style.css", "logo.svg", "fonts/*.woff2"],
+    };
+
+    let mut builder = Assets::builder();
+    builder.group("static/**").with_cache_control("public, max-age=60");
+    builder.add_embedded("robots.txt", &EMBEDS["robots.txt"]);
+    builder.add_embedded("static/style.css", &EMBEDS["style.css"])
+        .with_cache_control("no-cache");
+    builder.add_embedded("static/img/logo.svg", &EMBEDS["logo.svg"]);
+    builder.add_embedded("static/fonts/", &EMBEDS["fonts/*.woff2"]);
+    builder.group("static/*.css").with_modifier::<_, _, &str>([], |_, _| "body {}".into());
+    let assets = builder.build().await?;
+
+    assert_eq!(assets.get("robots.txt").unwrap().cache_control(), None);
+    let css = assets.get("static/style.css").unwrap();
+    assert_eq!(css.cache_control(), Some("no-cache"));
+    assert_eq!(css.content().await?, "body {}");
+    assert_eq!(
+        assets.get("static/img/logo.svg").unwrap().cache_control(),
+        Some("public, max-age=60"),
+    );
+    assert_eq!(
+        assets.get("static/fonts/latin-400.woff2").unwrap().cache_control(),
+        Some("public, max-age=60"),
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn proxy_maps() -> Result<(), Box<dyn std::error::Error>> {
+    const EMBEDS: reinda::Embeds = reinda::embed! {
+        base_path: "tests/files",
+        files: ["peter.txt"],
+    };
+
+    let mut builder = Assets::builder();
+    builder.add_embedded("peter.txt", &EMBEDS["peter.txt"]);
+    builder.add_embedded("static/wolf.txt", &EMBEDS["peter.txt"])
+        .with_cache_control("public, max-age=60");
+    let assets = builder.build().await?;
+
+    assert_eq!(assets.nginx_map("/srv/assets"), concat!(
+        "location = \"/peter.txt\" {\n",
+        "    alias \"/srv/assets/peter.txt\";\n",
+        "    types {}\n",
+        "    default_type \"text/plain; charset=utf-8\";\n",
+        "}\n",
+        "location = \"/static/wolf.txt\" {\n",
+        "    alias \"/srv/assets/static/wolf.txt\";\n",
+        "    types {}\n",
+        "    default_type \"text/plain; charset=utf-8\";\n",
+        "    add_header Cache-Control \"public, max-age=60\";\n",
+        "}\n",
+    ));
+    assert_eq!(assets.caddy_map("/srv/assets"), concat!(
+        "root * \"/srv/assets\"\n",
+        "header \"/peter.txt\" Content-Type \"text/plain; charset=utf-8\"\n",
+        "header \"/static/wolf.txt\" Content-Type \"text/plain; charset=utf-8\"\n",
+        "header \"/static/wolf.txt\" Cache-Control \"public, max-age=60\"\n",
+        "file_server\n",
+    ));
+
+    let dir = std::env::temp_dir().join(format!("reinda-test-export-{}", std::process::id()));
+    assets.export(&dir).await?;
+    let expected = "Peter und der Wolf.\n";
+    assert_eq!(std::fs::read_to_string(dir.join("peter.txt"))?, expected);
+    assert_eq!(std::fs::read_to_string(dir.join("static/wolf.txt"))?, expected);
+    std::fs::remove_dir_all(&dir)?;
+
+    let mut builder = Assets::builder();
+    builder.add_embedded("../escape.txt", &EMBEDS["peter.txt"]);
+    let assets = builder.build().await?;
+    let err = assets.export(&dir).await.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(!dir.exists());
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "compress")]
+async fn proxy_precompressed() -> Result<(), Box<dyn std::error::Error>> {
+    const EMBEDS: reinda::Embeds = reinda::embed! {
+        base_path: "examples/assets",
+        files: ["index.html"],
+    };
+
+    let mut builder = Assets::builder();
+    builder.add_embedded("index.html", &EMBEDS["index.html"]);
+    let assets = builder.build().await?;
+
+    let nginx = assets.nginx_map("/srv/assets");
+    let caddy = assets.caddy_map("/srv/assets");
+    assert!(nginx.contains("    default_type \"text/html; charset=utf-8\";\n"));
+    assert!(caddy.contains("header \"/index.html\" Content-Type \"text/html; charset=utf-8\"\n"));
+
+    let dir = std::env::temp_dir().join(format!("reinda-test-precompressed-{}", std::process::id()));
+    assets.export(&dir).await?;
+    let brotli_path = dir.join("index.html.br");
+    if cfg!(prod_mode) {
+        assert!(nginx.contains("    brotli_static on;\n    add_header Vary Accept-Encoding;\n"));
+        assert!(caddy.ends_with("file_server {\n    precompressed br\n}\n"));
+
+        let mut decompressed = Vec::new();
+        brotli::BrotliDecompress(&mut &*std::fs::read(&brotli_path)?, &mut decompressed)?;
+        assert_eq!(decompressed, std::fs::read(dir.join("index.html"))?);
+    } else {
+        assert!(!nginx.contains("_static"));
+        assert!(caddy.ends_with("\nfile_server\n"));
+        assert!(!brotli_path.exists());
+    }
+    std::fs::remove_dir_all(&dir)?;
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "hash")]
+async fn cache_policy() -> Result<(), Box<dyn std::error::Error>> {
+    use reinda::CachePolicy;
+    use std::time::Duration;
+
+    const EMBEDS: reinda::Embeds = reinda::embed! {
+        base_path: "examples/assets",
+        files: ["index.html", "robots.txt", "style.css", "fonts/*.woff2"],
+    };
+
+    let mut builder = Assets::builder();
+    builder.set_cache_policy(|asset| {
+        if asset.is_filename_hashed() {
+            CachePolicy::Immutable
+        } else if asset.unhashed_http_path().ends_with(".html") {
+            CachePolicy::NoStore
+        } else {
+            CachePolicy::MaxAge(Duration::from_secs(300))
+        }
+    });
+    builder.add_embedded("index.html", &EMBEDS["index.html"]);
+    builder.add_embedded("robots.txt", &EMBEDS["robots.txt"])
+        .with_cache_control("no-cache");
+    builder.add_embedded("static/style.css", &EMBEDS["style.css"]).with_hash();
+    builder.add_embedded("static/fonts/", &EMBEDS["fonts/*.woff2"]);
+    let assets = builder.build().await?;
+
+    assert_eq!(assets.get("index.html").unwrap().cache_control(), Some("no-store"));
+    assert_eq!(assets.get("robots.txt").unwrap().cache_control(), Some("no-cache"));
+    assert_eq!(
+        assets.get("static/fonts/latin-400.woff2").unwrap().cache_control(),
+        Some("public, max-age=300"),
+    );
+    let (_, css) = assets.iter().find(|(path, _)| path.ends_with(".css")).unwrap();
+    let expected = if css.is_filename_hashed() {
+        "public, max-age=31536000, immutable"
+    } else {
+        "public, max-age=300"
+    };
+    assert_eq!(css.cache_control(), Some(expected));
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(dev_mode)]
+async fn dev_load_errors() -> Result<(), Box<dyn std::error::Error>> {
+    use reinda::LoadErrorKind;
+
+    let mut builder = Assets::builder();
+    builder.add_file("missing.txt", "tests/files/does-not-exist.txt");
+    builder.add_file("panic.txt", "tests/files/peter.txt")
+        .with_modifier::<_, _, &str>([], |_, _| panic!("oh no <script>"));
+    let assets = builder.build().await?;
+
+    let err = assets.get("missing.txt").unwrap().content().await.unwrap_err();
+    assert_eq!(err.http_path(), "missing.txt");
+    assert_eq!(err.fs_path(), Some(std::path::Path::new("tests/files/does-not-exist.txt")));
+    assert!(matches!(err.kind(), LoadErrorKind::Io(e) if e.kind() == std::io::ErrorKind::NotFound));
+    let err = std::io::Error::from(assets.get("missing.txt").unwrap().content().await.unwrap_err());
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    assert!(err.to_string().contains("'missing.txt' from 'tests/files/does-not-exist.txt'"));
+
+    let err = assets.get("panic.txt").unwrap().content().await.unwrap_err();
+    assert!(matches!(err.kind(), LoadErrorKind::ModifierPanic(msg) if msg == "oh no <script>"));
+    let html = err.to_html();
+    assert!(html.contains("<code>panic.txt</code>"));
+    assert!(html.contains("oh no &lt;script&gt;"));
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(all(dev_mode, feature = "modifiers"))]
+async fn dev_changes() -> Result<(), Box<dyn std::error::Error>> {
+    const EMBEDS: reinda::Embeds = reinda::embed! {
+        base_path: "examples/assets",
+        files: ["index.html", "style.css", "fonts/*.woff2"],
+    };
+
+    let mut builder = Assets::builder();
+    let fonts = builder.add_embedded("fonts/", &EMBEDS["fonts/*.woff2"]).http_paths();
+    builder.add_embedded("style.css", &EMBEDS["style.css"]).with_path_fixup(fonts);
+    builder.add_embedded("index.html", &EMBEDS["index.html"]).with_path_fixup(["style.css"]);
+    builder.add_file("robots.txt", "examples/assets/robots.txt");
+    let assets = builder.build().await?;
+
+    let event = assets.changes(["examples/assets/fonts/latin-400.woff2"]);
+    assert_eq!(event.changed, ["fonts/latin-400.woff2"]);
+    assert_eq!(event.affected, ["index.html", "style.css"]);
+
+    let event = assets.changes([
+        std::env::current_dir()?.join("examples/assets/style.css"),
+        "examples/assets/robots.txt".into(),
+    ]);
+    assert_eq!(event.changed, ["robots.txt", "style.css"]);
+    assert_eq!(event.affected, ["index.html"]);
+
+    assert!(assets.changes(["examples/assets/logo.svg"]).is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(prod_mode)]
+async fn after_constraints() -> Result<(), Box<dyn std::error::Error>> {
+    use std::sync::{Arc, Mutex};
+
+    let order = Arc::new(Mutex::new(Vec::new()));
+    let mut builder = Assets::builder();
+    for name in ["a", "b", "c", "d"] {
+        let order = order.clone();
+        let entry = builder.add_file(name, "tests/files/peter.txt")
+            .with_modifier::<_, _, &str>([], move |content, _| {
+                order.lock().unwrap().push(name);
+                content
+            });
+        match name {
+            "a" => { entry.after(["c"]); }
+            "b" => { entry.after(["a", "d"]); }
+            _ => {}
+        }
+    }
+    builder.build().await?;
+
+    let order = order.lock().unwrap().clone();
+    let pos = |name| order.iter().position(|n| *n == name).unwrap();
+    assert!(pos("c") < pos("a"));
+    assert!(pos("a") < pos("b"));
+    assert!(pos("d") < pos("b"));
+
+    let mut builder = Assets::builder();
+    builder.add_file("a", "tests/files/peter.txt").after(["b"]);
+    builder.add_file("b", "tests/files/peter.txt").after(["a"]);
+    assert!(matches!(builder.build().await, Err(reinda::BuildError::CyclicDependencies(_))));
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "modifiers")]
+async fn global_replacements() -> Result<(), Box<dyn std::error::Error>> {
+    const EMBEDS: reinda::Embeds = reinda::embed! {
+        base_path: "examples/assets",
+        files: ["index.html", "fonts/latin-400.woff2"],
+    };
+
+    let mut builder = Assets::builder();
+    builder.global_replace([("Reinda", String::from("Reinda v1.2.3"))]);
+    builder.global_replace([("foxes", "cats"), ("\0", "!")]);
+    builder.add_embedded("index.html", &EMBEDS["index.html"])
+        .with_modifier::<_, _, &str>([], |_, _| "<title>Reinda</title>{{ foo }}".into());
+    builder.add_embedded("font.woff2", &EMBEDS["fonts/latin-400.woff2"]);
+    let assets = builder.build().await?;
+
+    assert_get!(assets, "index.html", false, "<title>Reinda v1.2.3</title>{{ foo }}");
+    assert_get!(assets, "font.woff2", false,
+        include_bytes!("../examples/assets/fonts/latin-400.woff2"));
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(all(prod_mode, feature = "hash", feature = "modifiers"))]
+async fn binary_path_fixup() -> Result<(), Box<dyn std::error::Error>> {
+    use std::sync::{Arc, Mutex};
+
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let mut builder = Assets::builder();
+    builder.on_warning({
+        let warnings = warnings.clone();
+        move |w| warnings.lock().unwrap().push(w)
+    });
+    builder.add_file("peter.txt", "tests/files/peter.txt").with_hash();
+    builder.add_file("data.bin", "tests/files/data.bin").with_path_fixup(["peter.txt"]);
+    let assets = builder.build().await?;
+
+    assert_get!(assets, "data.bin", false, include_bytes!("files/data.bin"));
+    assert_eq!(*warnings.lock().unwrap(), [reinda::Warning::BinaryFixupSkipped {
+        asset: "data.bin".into(),
+        path: "peter.txt".into(),
+    }]);
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(all(prod_mode, feature = "hash", feature = "modifiers"))]
+async fn binary_policy() -> Result<(), Box<dyn std::error::Error>> {
+    use reinda::{BinaryPolicy, BuildError};
+
+    let mut builder = Assets::builder();
+    builder.add_file("peter.txt", "tests/files/peter.txt").with_hash();
+    builder.add_file("data.bin", "tests/files/data.bin")
+        .with_path_fixup(["peter.txt"])
+        .with_binary_policy(BinaryPolicy::Force);
+    let assets = builder.build().await?;
+    assert_get!(assets, "data.bin", false, b"\0asm\x01\0\0\0peter.xwmi7av4kIn7.txt\0\xff\xfe");
+
+    let mut builder = Assets::builder();
+    builder.add_file("peter.txt", "tests/files/peter.txt").with_hash();
+    builder.add_file("data.bin", "tests/files/data.bin")
+        .with_path_fixup(["peter.txt"])
+        .with_binary_policy(BinaryPolicy::Error);
+    let err = builder.build().await.unwrap_err();
+    assert!(matches!(
+        &err,
+        BuildError::BinaryFixup { http_path, path } if http_path == "data.bin" && path == "peter.txt",
+    ), "{:?}", err);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn limited_concurrent_reads() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = Assets::builder();
+    builder.set_max_concurrent_reads(2);
+    for i in 0..10 {
+        builder.add_file(format!("peter-{i}.txt"), "tests/files/peter.txt");
+    }
+    let assets = builder.build().await?;
+    assert_eq!(assets.len(), 10);
+    assert_get!(assets, "peter-7.txt", false, "Peter und der Wolf.\n");
+
+    if cfg!(prod_mode) {
+        let mut builder = Assets::builder();
+        builder.set_max_concurrent_reads(1);
+        builder.add_file("peter.txt", "tests/files/peter.txt");
+        builder.add_file("missing.txt", "tests/files/does-not-exist.txt");
+        let err = builder.build().await.unwrap_err();
+        assert!(matches!(err, reinda::BuildError::Io { path, .. }
+            if path == std::path::Path::new("tests/files/does-not-exist.txt")));
+    }
+
+    Ok(())
+}
+
 // TODO:
 // - cyclic dependencies
 // - missing dependencies (modifier asks for other path)
 // - `with_hash_between`
 // - dynamically load file
 // - escaped globs
+
+#[tokio::test]
+#[cfg(feature = "hash")]
+async fn expect_sha256() -> Result<(), Box<dyn std::error::Error>> {
+    const PETER: &str = "c709a2edabf89089fb3781ec3496d7efa2bf48fb610687315cf8126a9fa5838a";
+
+    // The modifier does not influence the check.
+    let mut builder = Assets::builder();
+    builder.add_file("peter.txt", "tests/files/peter.txt")
+        .expect_sha256(&PETER.to_uppercase())
+        .with_modifier::<_, _, &str>([], |_, _| "modified".into());
+    let assets = builder.build().await?;
+    assert_eq!(assets.get("peter.txt").unwrap().content().await?, "modified");
+
+    let mut builder = Assets::builder();
+    builder.add_file("data.bin", "tests/files/data.bin").expect_sha256(PETER);
+    let result = builder.build().await;
+
+    #[cfg(prod_mode)]
+    match result {
+        Err(reinda::BuildError::ChecksumMismatch { http_path, expected, actual }) => {
+            assert_eq!(http_path, "data.bin");
+            assert_eq!(expected, PETER);
+            assert_eq!(actual, "51f4ab47ed3b1a3d6d2725bb9478a1018a1f8d3b83cbccbb5db8f3769c44e5bb");
+        }
+        other => panic!("expected checksum mismatch, got {:?}", other),
+    }
+
+    #[cfg(dev_mode)]
+    {
+        let err = result?.get("data.bin").unwrap().content().await.unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            reinda::LoadErrorKind::ChecksumMismatch { expected, .. } if expected == PETER
+        ));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn error_assets() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = Assets::builder();
+    builder.add_file("peter.txt", "tests/files/peter.txt");
+    builder.add_file("errors/404.html", "tests/files/peter.txt")
+        .with_modifier::<_, _, &str>([], |_, _| "<h1>Not found</h1>".into());
+    builder.set_error_asset(404, "errors/404.html");
+    builder.set_error_asset(500, "peter.txt");
+    builder.set_error_asset(500, "errors/404.html");
+    let assets = builder.build().await?;
+
+    let not_found = assets.error_asset(404).unwrap();
+    assert_eq!(not_found.content().await?, "<h1>Not found</h1>");
+    assert_eq!(assets.error_asset(500).unwrap().content().await?, "<h1>Not found</h1>");
+    assert!(assets.error_asset(403).is_none());
+    assert_eq!(assets.len(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+#[should_panic(expected = "Error asset 'nope.html' for status 404 does not exist")]
+async fn error_asset_missing() {
+    let mut builder = Assets::builder();
+    builder.set_error_asset(404, "nope.html");
+    let _ = builder.build().await;
+}
+
+#[tokio::test]
+#[should_panic(expected = "Asset 'logo.svg' specified variant 'logo.webp' but that asset does not exist")]
+async fn variant_missing() {
+    let mut builder = Assets::builder();
+    builder.add_file("logo.svg", "examples/assets/logo.svg").with_variants(["logo.webp"]);
+    let _ = builder.build().await;
+}
+
+#[tokio::test]
+async fn spa_fallback() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = Assets::builder();
+    builder.add_file("index.html", "examples/assets/index.html");
+    builder.add_file("peter.txt", "tests/files/peter.txt");
+    builder.set_fallback("index.html");
+    let assets = builder.build().await?;
+
+    let index = assets.get("index.html").unwrap().content().await?;
+    assert_eq!(assets.fallback().unwrap().content().await?, index);
+    assert_eq!(assets.get_or_fallback("users/42").unwrap().content().await?, index);
+    assert_eq!(assets.get_or_fallback("peter.txt").unwrap().content().await?, "Peter und der Wolf.\n");
+    assert!(assets.get("users/42").is_none());
+
+    #[cfg(feature = "tower")]
+    {
+        use tower::ServiceExt;
+
+        let req = http::Request::get("/users/42").body(()).unwrap();
+        let res = assets.into_service().oneshot(req).await?;
+        assert_eq!(res.status(), http::StatusCode::OK);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[should_panic(expected = "Fallback asset 'nope.html' does not exist")]
+async fn fallback_missing() {
+    let mut builder = Assets::builder();
+    builder.set_fallback("nope.html");
+    let _ = builder.build().await;
+}
+
+#[tokio::test]
+async fn directory_index() -> Result<(), Box<dyn std::error::Error>> {
+    let builder = || {
+        let mut builder = Assets::builder();
+        builder.add_file("index.html", "examples/assets/index.html");
+        builder.add_file("docs/index.html", "tests/files/peter.txt");
+        #[cfg(feature = "hash")]
+        builder.add_file("hashed/index.html", "tests/files/peter.txt").with_hash();
+        builder.add_file("other/peter.txt", "tests/files/peter.txt");
+        builder
+    };
+
+    let without = builder().build().await?;
+    assert!(without.get("docs/").is_none());
+
+    let mut builder = builder();
+    builder.directory_index("index.html");
+    let assets = builder.build().await?;
+    let peter = "Peter und der Wolf.\n";
+    assert_eq!(assets.get("docs/").unwrap().content().await?, peter);
+    assert_eq!(assets.get("docs").unwrap().content().await?, peter);
+    assert_eq!(assets.get("").unwrap().content().await?, assets.get("index.html").unwrap().content().await?);
+    #[cfg(feature = "hash")]
+    assert_eq!(assets.get("hashed/").unwrap().content().await?, peter);
+    assert!(assets.get("other/").is_none());
+    assert!(assets.get("nope/").is_none());
+
+    // Directories matched by a glob are resolved to their index file as well.
+    let mut builder = Assets::builder();
+    builder.add_dir_glob("files/", "tests/files", "**");
+    builder.directory_index("base.json");
+    let assets = builder.build().await?;
+    let base = include_bytes!("files/config/base.json");
+    assert_eq!(assets.get("files/config").unwrap().content().await?, &base[..]);
+    assert_eq!(assets.get("files/config/").unwrap().content().await?, &base[..]);
+    assert!(assets.get("files/locales").is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn get_many() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = Assets::builder();
+    builder.add_file("index.html", "examples/assets/index.html");
+    builder.add_file("peter.txt", "tests/files/peter.txt");
+    let assets = builder.build().await?;
+
+    let lookup = assets.get_many(["peter.txt", "nope.js", "index.html", "nope.css"]);
+    let found = lookup.found.iter().map(|(path, _)| *path).collect::<Vec<_>>();
+    assert_eq!(found, ["peter.txt", "index.html"]);
+    assert_eq!(lookup.missing, ["nope.js", "nope.css"]);
+    assert_eq!(&*lookup.found[0].1.content().await?, b"Peter und der Wolf.\n");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn path_params() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = Assets::builder();
+    builder.add_file("v{version}/peter.txt", "tests/files/peter.txt");
+    builder.add_file("v{version}/index.html", "tests/files/peter.txt")
+        .with_modifier(["v{version}/peter.txt"], |_, ctx| {
+            assert_eq!(ctx.dependencies(), ["v1.2.3/peter.txt"]);
+            ctx.resolve_path("v1.2.3/peter.txt").to_owned().into()
+        });
+    builder.group("v{version}/*.html").with_cache_control("no-cache");
+    builder.set_error_asset(404, "v{version}/index.html");
+    builder.set_path_param("version", String::from("1.2.3"));
+    let assets = builder.build().await?;
+
+    assert_eq!(assets.len(), 2);
+    assert!(assets.get("v1.2.3/peter.txt").is_some());
+    let index = assets.get("v1.2.3/index.html").unwrap();
+    assert_eq!(index.content().await?, "v1.2.3/peter.txt");
+    assert_eq!(index.cache_control(), Some("no-cache"));
+    assert!(assets.error_asset(404).is_some());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn remove_and_replace() -> Result<(), Box<dyn std::error::Error>> {
+    const EMBEDS: reinda::Embeds = reinda::embed! {
+        base_path: "examples/assets",
+        files: ["robots.txt", "fonts/*.woff2"],
+    };
+
+    let mut builder = Assets::builder();
+    builder.add_embedded("fonts/", &EMBEDS["fonts/*.woff2"]);
+    builder.add_embedded("robots.txt", &EMBEDS["robots.txt"]);
+    builder.add_file("greeting.txt", "tests/files/peter.txt")
+        .with_modifier::<_, _, &str>([], |content, _| [&content[..], b"!"].concat().into())
+        .with_cache_control("no-cache");
+
+    assert!(builder.remove("fonts/"));
+    assert!(!builder.remove("fonts/"));
+    assert!(!builder.remove("fonts/latin-400.woff2"));
+    assert!(builder.replace("nope.txt", "tests/files/peter.txt").is_none());
+    builder.replace_embedded("greeting.txt", EMBEDS["robots.txt"].as_file().unwrap()).unwrap();
+    builder.replace("robots.txt", "tests/files/peter.txt").unwrap();
+    let assets = builder.build().await?;
+
+    assert_eq!(assets.len(), 2);
+    assert_get!(assets, "robots.txt", false, "Peter und der Wolf.\n");
+    assert_get!(assets, "greeting.txt", false, "User-agent: *\n\nDisallow: /banana\n!");
+    assert_eq!(assets.get("greeting.txt").unwrap().cache_control(), Some("no-cache"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn validate() -> Result<(), Box<dyn std::error::Error>> {
+    use reinda::{BuildError, LoadErrorKind};
+
+    let no_wolves = |bytes: &[u8]| match std::str::from_utf8(bytes) {
+        Ok(s) if s.contains("Wolf") => Err("contains a wolf".to_owned()),
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.to_string()),
+    };
+
+    let mut builder = Assets::builder();
+    builder.add_file("peter.txt", "tests/files/peter.txt")
+        .validate(|bytes| if bytes.is_empty() { Err("empty".into()) } else { Ok(()) })
+        .with_modifier::<_, _, &str>([], |bytes, _| bytes.to_vec().repeat(2).into());
+    builder.add_file("wolf.txt", "tests/files/peter.txt").validate(no_wolves);
+    let result = builder.build().await;
+
+    if cfg!(prod_mode) {
+        match result {
+            Err(BuildError::Validation { http_path, msg }) => {
+                assert_eq!(http_path, "wolf.txt");
+                assert_eq!(msg, "contains a wolf");
+            }
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+    } else {
+        let assets = result?;
+        assert_get!(assets, "peter.txt", false, "Peter und der Wolf.\nPeter und der Wolf.\n");
+        let err = assets.get("wolf.txt").unwrap().content().await.unwrap_err();
+        assert!(matches!(err.kind(), LoadErrorKind::Validation(msg) if msg == "contains a wolf"));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "sanitize-svg")]
+async fn sanitize_svg() -> Result<(), Box<dyn std::error::Error>> {
+    use bytes::Bytes;
+
+    struct Static(&'static str);
+    impl reinda::AssetSource for Static {
+        async fn load(&self) -> std::io::Result<Bytes> {
+            Ok(Bytes::from_static(self.0.as_bytes()))
+        }
+    }
+
+    let mut builder = Assets::builder();
+    builder.group("icons/*.svg").sanitize_svg();
+    builder.add_source("icons/bad.svg", Static(r#"<svg onload="alert(1)"><script>x()</script></svg>"#));
+    builder.add_source("bad.svg", Static(r#"<svg onload="alert(1)"/>"#));
+    let assets = builder.build().await?;
+    assert_get!(assets, "icons/bad.svg", false, "<svg></svg>");
+    assert_get!(assets, "bad.svg", false, r#"<svg onload="alert(1)"/>"#);
+
+    let mut builder = Assets::builder();
+    builder.add_source("broken.svg", Static("<svg><rect</svg>")).sanitize_svg();
+    let result = builder.build().await;
+    if cfg!(prod_mode) {
+        assert!(matches!(result, Err(reinda::BuildError::Validation { .. })));
+    } else {
+        assert!(result?.get("broken.svg").unwrap().content().await.is_err());
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn into_static() -> Result<(), Box<dyn std::error::Error>> {
+    const EMBEDS: reinda::Embeds = reinda::embed! {
+        base_path: "tests/files",
+        files: ["peter.txt"],
+    };
+
+    let mut builder = Assets::builder();
+    builder.add_embedded("peter.txt", &EMBEDS["peter.txt"]);
+    let assets: &'static Assets = builder.build().await?.into_static();
+
+    let content = tokio::spawn(async move {
+        assets.get("peter.txt").unwrap().content().await.unwrap()
+    }).await?;
+    assert_eq!(content, b"Peter und der Wolf.\n".as_slice());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn glob_mounts() -> Result<(), Box<dyn std::error::Error>> {
+    use reinda::GlobMount;
+
+    const EMBEDS: reinda::Embeds = reinda::embed! {
+        base_path: "examples/assets",
+        files: ["robots.txt", "fonts/*.woff2"],
+    };
+
+    let mut builder = Assets::builder();
+    let mount = builder.add_embedded("static/fonts/", &EMBEDS["fonts/*.woff2"]).glob_mount();
+    assert_eq!(mount, Some(GlobMount::new("static/fonts/", "fonts/*.woff2")));
+    assert_eq!(builder.add_embedded("robots.txt", &EMBEDS["robots.txt"]).glob_mount(), None);
+    let assets = builder.build().await?;
+
+    let mounts = assets.glob_mounts().collect::<Vec<_>>();
+    assert_eq!(mounts.len(), 1);
+    assert_eq!(mounts[0].http_prefix(), "static/fonts/");
+    assert_eq!(mounts[0].pattern(), "*.woff2");
+    assert!(mounts[0].matches("static/fonts/latin-400.woff2"));
+    assert!(!mounts[0].matches("fonts/latin-400.woff2"));
+    assert!(!mounts[0].matches("static/fonts/latin-400.woff"));
+    for (http_path, _) in assets.iter().filter(|(p, _)| p.starts_with("static/")) {
+        assert!(mounts[0].matches(http_path));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(all(feature = "report", feature = "modifiers"))]
+async fn build_report() -> Result<(), Box<dyn std::error::Error>> {
+    const EMBEDS: reinda::Embeds = reinda::embed! {
+        base_path: "examples/assets",
+        files: ["robots.txt"],
+    };
+
+    let mut builder = Assets::builder();
+    builder.add_embedded("robots.txt", &EMBEDS["robots.txt"]);
+    builder.add_file("peter.txt", "tests/files/peter.txt")
+        .with_append("Ende.\n")
+        .with_modifier::<_, _, &str>([], |content, _| content)
+        .with_cache_control("no-cache");
+    builder.global_replace([("Wolf", "Fuchs"), ("banana", "banana")]);
+    let assets = builder.build().await?;
+
+    #[cfg(dev_mode)]
+    assert!(assets.build_report().is_none());
+
+    #[cfg(prod_mode)]
+    {
+        use reinda::ReportSource;
+
+        let report = assets.build_report().unwrap();
+        assert_eq!(report.assets.len(), 2);
+
+        let peter = &report.assets[0];
+        assert_eq!(peter.unhashed_http_path, "peter.txt");
+        assert_eq!(peter.source, ReportSource::File { path: "tests/files/peter.txt".into() });
+        assert_eq!(
+            peter.source_sha256,
+            "c709a2edabf89089fb3781ec3496d7efa2bf48fb610687315cf8126a9fa5838a",
+        );
+        assert_eq!(peter.transformations, ["append", "modifier", "global_replace"]);
+        assert_eq!(peter.size, "Peter und der Fuchs.\nEnde.\n".len());
+        assert_eq!(peter.cache_control.as_deref(), Some("no-cache"));
+
+        let robots = &report.assets[1];
+        assert_eq!(robots.source, ReportSource::Embedded { path: "robots.txt".into() });
+        assert!(robots.transformations.is_empty());
+        assert_eq!(robots.sha256, robots.source_sha256);
+
+        let json = report.to_json();
+        assert!(json.contains(r#""kind": "embedded""#));
+        assert!(json.contains(r#""unhashed_http_path": "peter.txt""#));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn accept_variants() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = Assets::builder();
+    for (path, content) in [("logo.png", "png"), ("logo.avif", "avif"), ("logo.webp", "webp")] {
+        builder.add_file(path, "tests/files/peter.txt")
+            .with_modifier::<_, _, &str>([], move |_, _| content.into());
+    }
+    builder.add_file("logo.unknown", "tests/files/peter.txt");
+    builder.add_file("logo.jpg", "tests/files/peter.txt")
+        .with_variants(["logo.avif", "logo.unknown"])
+        .with_variants(["logo.webp"]);
+    let assets = builder.build().await?;
+
+    let logo = assets.get("logo.jpg").unwrap();
+    assert!(logo.has_variants());
+    assert!(!assets.get("logo.png").unwrap().has_variants());
+
+    let best = |accept: &str| {
+        let logo = logo.clone();
+        let accept = accept.to_owned();
+        async move { logo.best_variant(&accept).content().await.unwrap() }
+    };
+    let browser = "image/avif,image/webp,image/apng,image/svg+xml,image/*,*/*;q=0.8";
+    assert_eq!(best(browser).await, "avif");
+    assert_eq!(best("image/webp,image/*;q=0.8").await, "webp");
+    assert_eq!(best("image/jpeg,image/*;q=0.5").await, "Peter und der Wolf.\n");
+    assert_eq!(best("image/avif;q=0.1,image/webp;q=0.2,image/jpeg;q=0.2").await, "webp");
+    assert_eq!(best("text/html").await, "Peter und der Wolf.\n");
+    assert_eq!(best("").await, "Peter und der Wolf.\n");
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(dev_mode)]
+async fn dev_stale_while_revalidate() -> Result<(), Box<dyn std::error::Error>> {
+    let path = std::env::temp_dir().join(format!("reinda-swr-{}.txt", std::process::id()));
+    std::fs::write(&path, "a")?;
+
+    let mut builder = Assets::builder();
+    builder.add_file("file.txt", path.clone());
+    builder.dev_stale_while_revalidate(std::time::Duration::from_secs(60));
+    let assets = builder.build().await?;
+    let asset = assets.get("file.txt").unwrap();
+    assert_eq!(asset.content().await?, "a");
+
+    // The stale content is served while the file is reloaded in the background.
+    let eventually = |asset: reinda::Asset, f: fn(&Result<bytes::Bytes, reinda::LoadError>) -> bool| async move {
+        for _ in 0..200 {
+            if f(&asset.content().await) {
+                return true;
+            }
+            tokio::task::yield_now().await;
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        false
+    };
+    std::fs::write(&path, "b")?;
+    assert_eq!(asset.content().await?, "a");
+    assert!(eventually(asset.clone(), |r| matches!(r, Ok(c) if c == "b")).await);
+
+    // Reported changes are evicted from the cache.
+    std::fs::write(&path, "c")?;
+    assets.changes([&path]);
+    assert_eq!(asset.content().await?, "c");
+
+    // A failed refresh evicts the entry, so that the error is reported.
+    std::fs::remove_file(&path)?;
+    assert_eq!(asset.content().await?, "c");
+    assert!(eventually(asset, |r| r.is_err()).await);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn runtime_shutdown() -> Result<(), Box<dyn std::error::Error>> {
+    let path = std::env::temp_dir().join(format!("reinda-shutdown-{}.txt", std::process::id()));
+    std::fs::write(&path, "a")?;
+
+    let mut builder = Assets::builder();
+    builder.add_file("file.txt", path.clone());
+    builder.dev_stale_while_revalidate(std::time::Duration::from_secs(60));
+    let assets = builder.build().await?;
+    let asset = assets.get("file.txt").unwrap();
+    assert_eq!(asset.content().await?, "a");
+    std::fs::write(&path, "b")?;
+
+    let runtime = assets.runtime();
+    assert!(!runtime.is_shut_down());
+    #[cfg(dev_mode)]
+    assert_eq!(asset.content().await?, "a");
+    runtime.shutdown().await;
+    assert!(assets.runtime().is_shut_down());
+    runtime.shutdown().await;
+
+    // Without background tasks, the cache is bypassed in dev mode.
+    #[cfg(dev_mode)]
+    assert_eq!(asset.content().await?, "b");
+    #[cfg(prod_mode)]
+    assert_eq!(asset.content().await?, "a");
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn custom_sources() -> Result<(), Box<dyn std::error::Error>> {
+    use std::io;
+    use bytes::Bytes;
+
+    struct Static(&'static str);
+    impl reinda::AssetSource for Static {
+        async fn load(&self) -> io::Result<Bytes> {
+            Ok(Bytes::from_static(self.0.as_bytes()))
+        }
+    }
+
+    struct Failing;
+    impl reinda::AssetSource for Failing {
+        async fn load(&self) -> io::Result<Bytes> {
+            Err(io::Error::new(io::ErrorKind::ConnectionRefused, "bucket unreachable"))
+        }
+    }
+
+    let mut builder = Assets::builder();
+    builder.add_source("motd.txt", Static("Welcome!"))
+        .with_modifier::<_, _, &str>([], |content, _| [&content[..], b" :)"].concat().into());
+    let assets = builder.build().await?;
+    assert_get!(assets, "motd.txt", false, "Welcome! :)");
+
+    let mut builder = Assets::builder();
+    builder.add_source("broken.txt", Failing);
+    let result = builder.build().await;
+
+    #[cfg(prod_mode)]
+    assert!(matches!(
+        result,
+        Err(reinda::BuildError::Source { http_path, err })
+            if http_path == "broken.txt" && err.kind() == io::ErrorKind::ConnectionRefused
+    ));
+
+    #[cfg(dev_mode)]
+    {
+        let err = result?.get("broken.txt").unwrap().content().await.unwrap_err();
+        assert_eq!(err.fs_path(), None);
+        assert!(matches!(err.kind(), reinda::LoadErrorKind::Io(e) if e.to_string() == "bucket unreachable"));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "url")]
+async fn url_sources() -> Result<(), Box<dyn std::error::Error>> {
+    use std::sync::{Arc, atomic::{AtomicUsize, Ordering}};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // Tiny HTTP server: `/flaky` fails on the first request, `/missing` is
+    // always 404.
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let base = format!("http://{}", listener.local_addr()?);
+    let requests = Arc::new(AtomicUsize::new(0));
+    let counter = requests.clone();
+    tokio::spawn(async move {
+        let mut flaky_calls = 0;
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            counter.fetch_add(1, Ordering::SeqCst);
+            let mut buf = vec![0; 4096];
+            let n = stream.read(&mut buf).await.unwrap();
+            let request = String::from_utf8_lossy(&buf[..n]).into_owned();
+            let (status, body) = if request.starts_with("GET /flaky ") {
+                flaky_calls += 1;
+                if flaky_calls == 1 { ("503 Service Unavailable", "") } else { ("200 OK", "{}") }
+            } else if request.starts_with("GET /config.json ") {
+                ("200 OK", r#"{"motd": "hi"}"#)
+            } else {
+                ("404 Not Found", "")
+            };
+            let response = format!(
+                "HTTP/1.1 {}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                status, body.len(), body,
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+
+    let mut builder = Assets::builder();
+    builder.add_url("config.json", format!("{base}/config.json"));
+    builder.add_source("flaky.json", reinda::UrlSource::new(format!("{base}/flaky")).retries(1));
+    let assets = builder.build().await?;
+    assert_get!(assets, "config.json", false, r#"{"motd": "hi"}"#);
+    assert_get!(assets, "flaky.json", false, "{}");
+
+    requests.store(0, Ordering::SeqCst);
+    let mut builder = Assets::builder();
+    builder.add_source("missing.txt", reinda::UrlSource::new(format!("{base}/missing")).retries(5));
+    let result = builder.build().await;
+    #[cfg(prod_mode)]
+    let err = match result {
+        Err(reinda::BuildError::Source { err, .. }) => err,
+        other => panic!("unexpected result: {:?}", other),
+    };
+    #[cfg(dev_mode)]
+    let err = result?.get("missing.txt").unwrap().content().await.unwrap_err();
+    assert!(err.to_string().contains("404"));
+    assert_eq!(requests.load(Ordering::SeqCst), 1, "4xx responses are not retried");
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "axum")]
+async fn axum_router() -> Result<(), Box<dyn std::error::Error>> {
+    use axum::{body::Body, http::{header, Request, StatusCode}};
+    use tower::ServiceExt;
+
+    let mut builder = Assets::builder();
+    builder.add_file("peter.txt", "tests/files/peter.txt").with_cache_control("no-cache");
+    builder.add_file("sub dir/peter.txt", "tests/files/peter.txt");
+    builder.add_file("logo.webp", "tests/files/peter.txt")
+        .with_modifier::<_, _, &str>([], |_, _| "webp".into());
+    builder.add_file("logo.png", "tests/files/peter.txt").with_variants(["logo.webp"]);
+    builder.add_file("secret.webp", "tests/files/peter.txt")
+        .with_guard(|req| req.header("x-debug").is_some());
+    builder.add_file("secret.png", "tests/files/peter.txt").with_variants(["secret.webp"]);
+    let assets = builder.build().await?;
+    let router = assets.into_router::<()>();
+
+    let request = |method: &str, path: &str, accept: Option<&str>| {
+        let mut req = Request::builder().method(method).uri(path);
+        if let Some(accept) = accept {
+            req = req.header(header::ACCEPT, accept);
+        }
+        router.clone().oneshot(req.body(Body::empty()).unwrap())
+    };
+    let body = |res: axum::response::Response| async move {
+        axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap()
+    };
+
+    let res = request("GET", "/peter.txt", None).await?;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()[header::CONTENT_TYPE], "text/plain; charset=utf-8");
+    assert_eq!(res.headers()[header::CONTENT_LENGTH], "20");
+    assert_eq!(res.headers()[header::CACHE_CONTROL], "no-cache");
+    assert_eq!(body(res).await, "Peter und der Wolf.\n");
+
+    let res = request("HEAD", "/sub%20dir/peter.txt", None).await?;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()[header::CONTENT_LENGTH], "20");
+    assert!(body(res).await.is_empty());
+
+    let res = request("GET", "/logo.png", Some("image/webp,*/*")).await?;
+    assert_eq!(res.headers()[header::CONTENT_TYPE], "image/webp");
+    assert_eq!(res.headers()[header::VARY], "accept");
+    assert_eq!(body(res).await, "webp");
+
+    let res = request("GET", "/secret.png", Some("image/webp,*/*")).await?;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()[header::CONTENT_TYPE], "image/png");
+
+    #[cfg(feature = "hash")]
+    {
+        let etag = request("GET", "/peter.txt", None).await?.headers()[header::ETAG].clone();
+        let req = Request::get("/peter.txt").header(header::IF_NONE_MATCH, etag);
+        let res = router.clone().oneshot(req.body(Body::empty()).unwrap()).await?;
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(res.headers()[header::CACHE_CONTROL], "no-cache");
+        assert!(body(res).await.is_empty());
+    }
+
+    let res = request("POST", "/peter.txt", None).await?;
+    assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(res.headers()[header::ALLOW], "GET, HEAD");
+
+    let res = request("GET", "/nope.txt", None).await?;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert!(body(res).await.is_empty());
+
+    let mut builder = Assets::builder();
+    builder.add_file("404.html", "tests/files/peter.txt");
+    builder.set_error_asset(404, "404.html");
+    let res = builder.build().await?.into_router::<()>()
+        .oneshot(Request::get("/nope").body(Body::empty()).unwrap())
+        .await?;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert_eq!(res.headers()[header::CONTENT_TYPE], "text/html; charset=utf-8");
+    assert_eq!(body(res).await, "Peter und der Wolf.\n");
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "json")]
+async fn merged_json() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = Assets::builder();
+    builder.add_merged_json("config.json", [
+        "tests/files/config/base.json",
+        "tests/files/config/prod.json",
+    ]);
+    let assets = builder.build().await?;
+    assert_get!(
+        assets,
+        "config.json",
+        false,
+        r#"{"api":{"timeout":5,"url":"https://example.com"},"debug":false}"#
+    );
+
+    let mut builder = Assets::builder();
+    builder.add_merged_json("broken.json", ["tests/files/config/base.json", "tests/files/peter.txt"]);
+    let result = builder.build().await;
+    if cfg!(prod_mode) {
+        assert!(result.is_err());
+    } else {
+        assert!(result?.get("broken.json").unwrap().content().await.is_err());
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "hash")]
+async fn hash_placement() -> Result<(), Box<dyn std::error::Error>> {
+    use reinda::HashPlacement;
+
+    let mut builder = Assets::builder();
+    builder.hash_placement(HashPlacement::Suffix);
+    builder.add_file("jquery.min.js", "tests/files/peter.txt")
+        .with_hash_placement(HashPlacement::BeforeExt);
+    builder.add_file("app.min.js", "tests/files/peter.txt").with_hash();
+    builder.add_file("style.min.css", "tests/files/peter.txt");
+    builder.add_file("v/logo.png", "tests/files/peter.txt")
+        .with_hash_placement(HashPlacement::custom(|path, hash| path.replace("v/", &format!("{hash}/"))));
+    builder.group("*.css").with_hash();
+    let assets = builder.build().await?;
+
+    let mut paths = assets.iter().map(|(path, _)| path.to_owned()).collect::<Vec<_>>();
+    paths.sort();
+    if cfg!(prod_mode) {
+        let hash = "xwmi7av4kIn7";
+        assert_eq!(paths, [
+            format!("app.min.js-{hash}"),
+            format!("jquery.min.{hash}.js"),
+            format!("style.min.css-{hash}"),
+            format!("{hash}/logo.png"),
+        ]);
+    } else {
+        assert_eq!(paths, ["app.min.js", "jquery.min.js", "style.min.css", "v/logo.png"]);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "hash")]
+async fn hash_between() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = Assets::builder();
+    builder.add_file("peter.txt", "tests/files/peter.txt")
+        .with_hash_between("texts/peter-", ".txt");
+    builder.add_dir_glob("dir/", "tests/files", "*.txt")
+        .with_hash_between_fn(|path| match path.rsplit_once('.') {
+            Some((stem, ext)) => (format!("{stem}_"), format!(".{ext}")),
+            None => (path.to_owned(), String::new()),
+        });
+    let assets = builder.build().await?;
+
+    let mut paths = assets.iter().map(|(path, _)| path.to_owned()).collect::<Vec<_>>();
+    paths.sort();
+    if cfg!(prod_mode) {
+        let hash = "xwmi7av4kIn7";
+        assert_eq!(paths, [format!("dir/peter_{hash}.txt"), format!("texts/peter-{hash}.txt")]);
+    } else {
+        assert_eq!(paths, ["peter.txt"]);
+        assert!(assets.get("dir/peter.txt").is_some());
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "hash")]
+#[should_panic(expected = "`with_hash_between` cannot be used for glob or directory entries")]
+fn hash_between_glob() {
+    let mut builder = Assets::builder();
+    builder.add_dir("dir/", "tests/files").with_hash_between("a", "b");
+}
+
+#[tokio::test]
+#[cfg(feature = "hash")]
+async fn hash_length() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = Assets::builder();
+    builder.set_hash_length(6);
+    builder.add_file("short.txt", "tests/files/peter.txt").with_hash();
+    builder.add_file("long.txt", "tests/files/peter.txt").with_hash().with_hash_length(12);
+    let assets = builder.build().await?;
+
+    if cfg!(prod_mode) {
+        assert_get!(assets, "short.xwmi7av4.txt", true, "Peter und der Wolf.\n");
+        let long = assets.iter().map(|(path, _)| path).find(|p| p.starts_with("long.")).unwrap();
+        assert!(long.starts_with("long.xwmi7av4kIn7"));
+        assert_eq!(long.len(), "long.".len() + 16 + ".txt".len());
+    } else {
+        assert_get!(assets, "short.txt", false, "Peter und der Wolf.\n");
+        assert_get!(assets, "long.txt", false, "Peter und der Wolf.\n");
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "hash")]
+#[should_panic(expected = "hash length must be between 6 and 32 bytes, but is 4")]
+fn hash_length_too_short() {
+    Assets::builder().set_hash_length(4);
+}
+
+#[tokio::test]
+#[cfg(feature = "warp")]
+async fn warp_filter() -> Result<(), Box<dyn std::error::Error>> {
+    use warp::{http::StatusCode, Filter};
+
+    let mut builder = Assets::builder();
+    builder.add_file("peter.txt", "tests/files/peter.txt").with_cache_control("no-cache");
+    builder.add_file("sub dir/peter.txt", "tests/files/peter.txt");
+    builder.add_file("logo.webp", "tests/files/peter.txt")
+        .with_modifier::<_, _, &str>([], |_, _| "webp".into());
+    builder.add_file("logo.png", "tests/files/peter.txt").with_variants(["logo.webp"]);
+    builder.add_file("secret.webp", "tests/files/peter.txt")
+        .with_guard(|req| req.header("x-debug").is_some());
+    builder.add_file("secret.png", "tests/files/peter.txt").with_variants(["secret.webp"]);
+    let assets = builder.build().await?;
+    let filter = warp::path("assets").and(reinda::warp::assets_filter(assets));
+
+    let res = warp::test::request().path("/assets/peter.txt").reply(&filter).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()["content-type"], "text/plain; charset=utf-8");
+    assert_eq!(res.headers()["content-length"], "20");
+    assert_eq!(res.headers()["cache-control"], "no-cache");
+    assert_eq!(res.body(), "Peter und der Wolf.\n");
+
+    let res = warp::test::request().method("HEAD").path("/assets/sub%20dir/peter.txt")
+        .reply(&filter)
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()["content-length"], "20");
+    assert!(res.body().is_empty());
+
+    let res = warp::test::request().path("/assets/logo.png").header("accept", "image/webp")
+        .reply(&filter)
+        .await;
+    assert_eq!(res.headers()["content-type"], "image/webp");
+    assert_eq!(res.headers()["vary"], "accept");
+    assert_eq!(res.body(), "webp");
+
+    for (debug, content_type) in [(false, "image/png"), (true, "image/webp")] {
+        let mut req = warp::test::request().path("/assets/secret.png").header("accept", "image/webp");
+        if debug {
+            req = req.header("x-debug", "1");
+        }
+        let res = req.reply(&filter).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()["content-type"], content_type);
+    }
+
+    let res = warp::test::request().method("POST").path("/assets/peter.txt").reply(&filter).await;
+    assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+
+    for path in ["/assets/nope.txt", "/peter.txt"] {
+        let res = warp::test::request().path(path).reply(&filter).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    let fallback = warp::any().map(|| "fallback");
+    let res = warp::test::request().path("/assets/nope.txt").reply(&filter.or(fallback)).await;
+    assert_eq!(res.body(), "fallback");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn processing_order() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = Assets::builder();
+    builder.add_file("c.txt", "tests/files/peter.txt")
+        .with_modifier(["b.txt"], |content, _| content);
+    builder.add_file("b.txt", "tests/files/peter.txt").after(["a.txt"]);
+    builder.add_file("a.txt", "tests/files/peter.txt");
+    builder.add_file("other.txt", "tests/files/peter.txt");
+    let assets = builder.build().await?;
+
+    if cfg!(prod_mode) {
+        let order = assets.processing_order().unwrap();
+        assert_eq!(order.len(), 4);
+        let pos = |path| order.iter().position(|p| p == path).unwrap();
+        assert!(pos("a.txt") < pos("b.txt"));
+        assert!(pos("b.txt") < pos("c.txt"));
+        assert!(order.iter().any(|p| p == "other.txt"));
+    } else {
+        assert!(assets.processing_order().is_none());
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "http")]
+async fn http_response() -> Result<(), Box<dyn std::error::Error>> {
+    use http::{header, HeaderMap, StatusCode};
+
+    let mut builder = Assets::builder();
+    builder.add_file("peter.txt", "tests/files/peter.txt").with_cache_control("no-cache");
+    builder.add_file("logo.webp", "tests/files/peter.txt")
+        .with_modifier::<_, _, &str>([], |_, _| "webp".into());
+    builder.add_file("logo.png", "tests/files/peter.txt").with_variants(["logo.webp"]);
+    let assets = builder.build().await?;
+
+    let peter = assets.get("peter.txt").unwrap();
+    let res = peter.response(&HeaderMap::new()).await?;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()[header::CONTENT_TYPE], "text/plain; charset=utf-8");
+    assert_eq!(res.headers()[header::CONTENT_LENGTH], "20");
+    assert_eq!(res.headers()[header::CACHE_CONTROL], "no-cache");
+    assert!(!res.headers().contains_key(header::VARY));
+    assert_eq!(res.body(), "Peter und der Wolf.\n");
+
+    let etag = res.headers()[header::ETAG].clone();
+    let mut headers = HeaderMap::new();
+    headers.insert(header::IF_NONE_MATCH, etag.clone());
+    let res = peter.response(&headers).await?;
+    assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(res.headers()[header::ETAG], etag);
+    assert!(!res.headers().contains_key(header::CONTENT_TYPE));
+    assert!(res.body().is_empty());
+
+    let mut headers = HeaderMap::new();
+    headers.insert(header::ACCEPT, "image/webp".parse()?);
+    headers.insert(header::IF_NONE_MATCH, etag);
+    let res = assets.get("logo.png").unwrap().response(&headers).await?;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()[header::CONTENT_TYPE], "image/webp");
+    assert_eq!(res.headers()[header::VARY], "accept");
+    assert_eq!(res.body(), "webp");
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "hash")]
+async fn stable_name() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = Assets::builder();
+    builder.group("*.js").with_hash();
+    builder.add_file("app.js", "tests/files/peter.txt");
+    builder.add_file("sw.js", "tests/files/peter.txt")
+        .stable_name()
+        .with_modifier(["app.js"], |_, ctx| format!("cache('{}')", ctx.resolve_path("app.js")).into());
+    builder.add_file("other.js", "tests/files/peter.txt").with_hash().stable_name();
+    let assets = builder.build().await?;
+
+    let app_path = assets.iter()
+        .map(|(path, _)| path.to_owned())
+        .find(|path| path.starts_with("app."))
+        .unwrap();
+    assert_eq!(app_path != "app.js", cfg!(prod_mode));
+    let expected = format!("cache('{}')", app_path);
+    assert_get!(assets, "sw.js", false, &expected);
+    assert_get!(assets, "other.js", false, "Peter und der Wolf.\n");
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "tower")]
+async fn tower_service() -> Result<(), Box<dyn std::error::Error>> {
+    use http::{header, Request, StatusCode};
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    let mut builder = Assets::builder();
+    builder.add_file("peter.txt", "tests/files/peter.txt");
+    builder.add_file("errors/404.html", "tests/files/peter.txt")
+        .with_modifier::<_, _, &str>([], |_, _| "<h1>Not found</h1>".into());
+    builder.set_error_asset(404, "errors/404.html");
+    let service = builder.build().await?.into_service();
+
+    let call = |method: &str, path: &str| {
+        let req = Request::builder().method(method).uri(path).body(()).unwrap();
+        service.clone().oneshot(req)
+    };
+
+    let res = call("GET", "/peter.txt").await?;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()[header::CONTENT_TYPE], "text/plain; charset=utf-8");
+    assert!(res.headers().contains_key(header::ETAG));
+    assert_eq!(res.into_body().collect().await?.to_bytes(), "Peter und der Wolf.\n");
+
+    let res = call("HEAD", "/peter.txt").await?;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()[header::CONTENT_LENGTH], "20");
+    assert!(res.into_body().collect().await?.to_bytes().is_empty());
+
+    let res = call("GET", "/nope").await?;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert_eq!(res.headers()[header::CONTENT_TYPE], "text/html; charset=utf-8");
+    assert_eq!(res.into_body().collect().await?.to_bytes(), "<h1>Not found</h1>");
+
+    let res = call("DELETE", "/peter.txt").await?;
+    assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(res.headers()[header::ALLOW], "GET, HEAD");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn mime() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = Assets::builder();
+    builder.add_mime_type(".GLB", "model/gltf-binary");
+    builder.add_file("models/cube.glb", "tests/files/data.bin");
+    builder.add_file("style.css", "tests/files/peter.txt");
+    builder.add_file("LICENSE", "tests/files/peter.txt");
+    builder.add_str("healthz", "ok").with_mime("text/plain");
+    builder.add_file("app.css", "tests/files/data.bin").with_mime("application/wasm");
+    let assets = builder.build().await?;
+
+    assert_eq!(assets.get("models/cube.glb").unwrap().mime(), Some("model/gltf-binary"));
+    assert_eq!(assets.get("style.css").unwrap().mime(), Some("text/css"));
+    assert_eq!(assets.get("LICENSE").unwrap().mime(), None);
+    assert_eq!(assets.get("healthz").unwrap().mime(), Some("text/plain"));
+    assert_eq!(assets.get("app.css").unwrap().mime(), Some("application/wasm"));
+
+    let mut mimes = assets.iter_with_mime()
+        .map(|(path, _, mime)| (path.to_owned(), mime))
+        .collect::<Vec<_>>();
+    mimes.sort();
+    assert_eq!(mimes, [
+        ("LICENSE".to_owned(), None),
+        ("app.css".to_owned(), Some("application/wasm")),
+        ("healthz".to_owned(), Some("text/plain")),
+        ("models/cube.glb".to_owned(), Some("model/gltf-binary")),
+        ("style.css".to_owned(), Some("text/css")),
+    ]);
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "hash")]
+async fn service_worker() -> Result<(), Box<dyn std::error::Error>> {
+    use reinda::service_worker::{DEFAULT_TEMPLATE, PRECACHE_PLACEHOLDER};
+
+    let mut builder = Assets::builder();
+    builder.group("static/**").with_hash();
+    builder.add_file("app.js", "tests/files/peter.txt").with_hash();
+    builder.add_file("static/style.css", "tests/files/peter.txt");
+    builder.add_file("index.html", "tests/files/peter.txt");
+    builder.add_service_worker("sw.js", "const FILES = __REINDA_PRECACHE__;");
+    builder.add_service_worker("default-sw.js", DEFAULT_TEMPLATE);
+    let assets = builder.build().await?;
+
+    let sw = assets.get("sw.js").unwrap();
+    assert_eq!(sw.cache_control(), Some("no-cache"));
+    let expected = if cfg!(prod_mode) {
+        let mut hashed = assets.iter()
+            .map(|(path, _)| path.to_owned())
+            .filter(|path| path.starts_with("app.") || path.starts_with("static/"))
+            .map(|path| format!("\"{}\"", path))
+            .collect::<Vec<_>>();
+        hashed.sort();
+        assert_eq!(hashed.len(), 2);
+        format!("const FILES = [{}];", hashed.join(","))
+    } else {
+        "const FILES = [];".to_owned()
+    };
+    assert_get!(assets, "sw.js", false, &expected);
+
+    let default = assets.get("default-sw.js").unwrap().content().await?;
+    assert!(!std::str::from_utf8(&default)?.contains(PRECACHE_PLACEHOLDER));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn asset_modules() -> Result<(), Box<dyn std::error::Error>> {
+    const EMBEDS: reinda::Embeds = reinda::embed! {
+        base_path: "examples/assets",
+        files: ["robots.txt", "fonts/*.woff2"],
+    };
+
+    struct Panel;
+    impl reinda::AssetModule for Panel {
+        fn register(&self, builder: &mut reinda::Builder<'_>) {
+            builder.add_embedded("fonts/", &EMBEDS["fonts/*.woff2"]);
+            builder.add_file("404.txt", "tests/files/peter.txt")
+                .with_modifier(["robots.txt"], |_, ctx| ctx.resolve_path("robots.txt").to_owned().into());
+            builder.add_embedded("robots.txt", &EMBEDS["robots.txt"]);
+            builder.group("*.txt").with_cache_control("no-cache");
+            builder.set_error_asset(404, "404.txt");
+        }
+    }
+
+    let mut builder = Assets::builder();
+    builder.add_file("robots.txt", "tests/files/peter.txt");
+    builder.set_error_asset(500, "robots.txt");
+    builder.add_module("admin/", Panel);
+    builder.add_module("other/", |builder: &mut reinda::Builder<'_>| {
+        builder.add_file("peter.txt", "tests/files/peter.txt");
+    });
+    let assets = builder.build().await?;
+
+    assert_get!(assets, "robots.txt", false, "Peter und der Wolf.\n");
+    assert_get!(assets, "admin/robots.txt", false, "User-agent: *\n\nDisallow: /banana\n");
+    assert_get!(assets, "admin/404.txt", false, "admin/robots.txt");
+    assert_get!(assets, "other/peter.txt", false, "Peter und der Wolf.\n");
+    assert!(assets.get("admin/fonts/latin-400.woff2").is_some());
+    assert_eq!(assets.get("admin/404.txt").unwrap().cache_control(), Some("no-cache"));
+    assert_eq!(assets.get("robots.txt").unwrap().cache_control(), None);
+    assert_eq!(assets.error_asset(404).unwrap().content().await?, "admin/robots.txt");
+    assert_eq!(assets.error_asset(500).unwrap().content().await?, "Peter und der Wolf.\n");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn merge_builders() -> Result<(), Box<dyn std::error::Error>> {
+    let library = || {
+        let mut builder = Assets::builder();
+        builder.add_file("index.txt", "tests/files/peter.txt")
+            .with_modifier(["data.bin"], |_, ctx| ctx.resolve_path("data.bin").to_owned().into());
+        builder.add_file("data.bin", "tests/files/data.bin");
+        builder.group("*.txt").with_cache_control("no-cache");
+        builder.set_error_asset(404, "index.txt");
+        builder.set_error_asset(500, "index.txt");
+        builder
+    };
+
+    let mut builder = Assets::builder();
+    builder.add_file("peter.txt", "tests/files/peter.txt");
+    builder.set_error_asset(500, "peter.txt");
+    builder.merge_prefixed("lib/", library());
+    let assets = builder.build().await?;
+
+    assert_get!(assets, "peter.txt", false, "Peter und der Wolf.\n");
+    assert_get!(assets, "lib/index.txt", false, "lib/data.bin");
+    assert!(assets.get("lib/data.bin").is_some());
+    assert_eq!(assets.get("lib/index.txt").unwrap().cache_control(), Some("no-cache"));
+    assert_eq!(assets.get("peter.txt").unwrap().cache_control(), None);
+    assert_eq!(assets.error_asset(404).unwrap().content().await?, "lib/data.bin");
+    assert_eq!(assets.error_asset(500).unwrap().content().await?, "Peter und der Wolf.\n");
+
+    let mut builder = Assets::builder();
+    builder.merge(library());
+    let assets = builder.build().await?;
+    assert_get!(assets, "index.txt", false, "data.bin");
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "hash")]
+async fn etag() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = Assets::builder();
+    builder.add_file("peter.txt", "tests/files/peter.txt");
+    builder.add_file("other.txt", "tests/files/peter.txt")
+        .with_modifier::<_, _, &str>([], |_, _| "other".into());
+    let assets = builder.build().await?;
+
+    let peter = assets.get("peter.txt").unwrap();
+    let other = assets.get("other.txt").unwrap();
+    if cfg!(prod_mode) {
+        let etag = peter.etag().unwrap();
+        assert_eq!(etag, r#""c709a2edabf89089fb3781ec3496d7ef""#);
+        assert_ne!(other.etag(), Some(etag));
+        assert!(peter.is_not_modified(etag));
+        assert!(peter.is_not_modified(&format!(r#"W/{etag}, "foo""#)));
+        assert!(!other.is_not_modified(etag));
+    } else {
+        assert_eq!(peter.etag(), None);
+        assert!(!peter.is_not_modified("*"));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "hash")]
+async fn recommended_cache_policy() -> Result<(), Box<dyn std::error::Error>> {
+    use reinda::CachePolicy;
+
+    let mut builder = Assets::builder();
+    builder.add_file("peter.txt", "tests/files/peter.txt");
+    builder.add_file("robots.txt", "tests/files/peter.txt").with_cache_control("max-age=60");
+    builder.add_file("app.js", "tests/files/peter.txt").with_hash();
+    let assets = builder.build().await?;
+
+    assert_eq!(assets.get("peter.txt").unwrap().cache_policy(), CachePolicy::NoCache);
+    assert_eq!(
+        assets.get("robots.txt").unwrap().cache_policy(),
+        CachePolicy::Custom("max-age=60".into()),
+    );
+    let (_, js) = assets.iter().find(|(path, _)| path.ends_with(".js")).unwrap();
+    let expected = if cfg!(prod_mode) { CachePolicy::Immutable } else { CachePolicy::NoCache };
+    assert_eq!(js.cache_policy(), expected);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn extensions() -> Result<(), Box<dyn std::error::Error>> {
+    #[derive(Debug, PartialEq)]
+    struct RequiresLogin;
+    #[derive(Debug, PartialEq)]
+    struct Priority(u8);
+
+    const EMBEDS: reinda::Embeds = reinda::embed! {
+        base_path: "examples/assets",
+        files: ["fonts/*.woff2"],
+    };
+
+    let mut builder = Assets::builder();
+    builder.add_file("admin.txt", "tests/files/peter.txt")
+        .with_extension(RequiresLogin)
+        .with_extension(Priority(1))
+        .with_extension(Priority(2));
+    builder.add_embedded("fonts/", &EMBEDS["fonts/*.woff2"]).with_extension(Priority(3));
+    builder.add_file("peter.txt", "tests/files/peter.txt");
+    let assets = builder.build().await?;
+
+    let admin = assets.get("admin.txt").unwrap();
+    assert_eq!(admin.extension::<RequiresLogin>(), Some(&RequiresLogin));
+    assert_eq!(admin.extension::<Priority>(), Some(&Priority(2)));
+    assert_eq!(admin.extension::<String>(), None);
+    let font = assets.get("fonts/latin-i400.woff2").unwrap();
+    assert_eq!(font.extension::<Priority>(), Some(&Priority(3)));
+    assert_eq!(font.extension::<RequiresLogin>(), None);
+    assert_eq!(assets.get("peter.txt").unwrap().extension::<Priority>(), None);
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "tower")]
+async fn guards() -> Result<(), Box<dyn std::error::Error>> {
+    use http::{header, Request, StatusCode};
+    use http_body_util::BodyExt;
+    use reinda::RequestContext;
+    use tower::ServiceExt;
+
+    let mut builder = Assets::builder();
+    builder.group("*.map").with_guard(|req| req.header("x-debug").is_some());
+    builder.add_file("admin.txt", "tests/files/peter.txt")
+        .with_guard(|req| req.header("Authorization") == Some("secret") && req.path() == "admin.txt");
+    builder.add_file("app.js.map", "tests/files/peter.txt");
+    builder.add_file("peter.txt", "tests/files/peter.txt");
+    builder.add_file("403.txt", "tests/files/peter.txt")
+        .with_modifier::<_, _, &str>([], |_, _| "forbidden".into());
+    builder.set_error_asset(403, "403.txt");
+    let assets = builder.build().await?;
+
+    let admin = assets.get("admin.txt").unwrap();
+    assert!(admin.is_allowed(&RequestContext::new("GET", "admin.txt", [("authorization", "secret")])));
+    assert!(!admin.is_allowed(&RequestContext::new("GET", "admin.txt", [])));
+    assert!(assets.get("peter.txt").unwrap().is_allowed(&RequestContext::new("GET", "peter.txt", [])));
+
+    let service = assets.into_service();
+    let call = |path: &str, headers: &[(&str, &str)]| {
+        let mut req = Request::builder().uri(path);
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        service.clone().oneshot(req.body(()).unwrap())
+    };
+
+    let res = call("/admin.txt", &[]).await?;
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    assert_eq!(res.into_body().collect().await?.to_bytes(), "forbidden");
+    let res = call("/admin.txt", &[("authorization", "secret")]).await?;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()[header::CONTENT_LENGTH], "20");
+    assert_eq!(call("/app.js.map", &[]).await?.status(), StatusCode::FORBIDDEN);
+    assert_eq!(call("/app.js.map", &[("x-debug", "1")]).await?.status(), StatusCode::OK);
+    assert_eq!(call("/peter.txt", &[]).await?.status(), StatusCode::OK);
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(all(feature = "compress", feature = "axum", feature = "tower", feature = "warp"))]
+async fn serve_encoded() -> Result<(), Box<dyn std::error::Error>> {
+    use http::{header, HeaderMap, HeaderValue, Request};
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    const EMBEDS: reinda::Embeds = reinda::embed! {
+        base_path: "examples/assets",
+        files: ["index.html"],
+    };
+
+    let mut builder = Assets::builder();
+    builder.add_embedded("index.html", &EMBEDS["index.html"]);
+    let assets = builder.build().await?;
+    let original = assets.get("index.html").unwrap().content().await?;
+
+    let check = |headers: &HeaderMap, body: &[u8]| {
+        if cfg!(prod_mode) {
+            assert_eq!(headers[header::CONTENT_ENCODING], "br");
+            assert_eq!(headers[header::VARY], "accept-encoding");
+            assert_eq!(headers[header::CONTENT_LENGTH], body.len().to_string().as_str());
+            let mut decompressed = Vec::new();
+            brotli::BrotliDecompress(&mut &*body, &mut decompressed).unwrap();
+            assert_eq!(decompressed, original);
+        } else {
+            assert!(!headers.contains_key(header::CONTENT_ENCODING));
+            assert!(!headers.contains_key(header::VARY));
+            assert_eq!(body, original);
+        }
+    };
+    let request = || Request::get("/index.html").header(header::ACCEPT_ENCODING, "gzip, br");
+
+    let res = assets.clone().into_router::<()>()
+        .oneshot(request().body(axum::body::Body::empty())?)
+        .await?;
+    let headers = res.headers().clone();
+    check(&headers, &axum::body::to_bytes(res.into_body(), usize::MAX).await?);
+
+    let res = assets.clone().into_service().oneshot(request().body(())?).await?;
+    let headers = res.headers().clone();
+    check(&headers, &res.into_body().collect().await?.to_bytes());
+
+    let res = warp::test::request().path("/index.html").header("accept-encoding", "gzip, br")
+        .reply(&reinda::warp::assets_filter(assets.clone()))
+        .await;
+    let headers = res.headers().iter()
+        .map(|(name, value)| {
+            (name.as_str().parse().unwrap(), HeaderValue::from_bytes(value.as_bytes()).unwrap())
+        })
+        .collect::<HeaderMap>();
+    check(&headers, res.body());
+
+    let res = assets.get("index.html").unwrap().response(&HeaderMap::new()).await?;
+    assert!(!res.headers().contains_key(header::CONTENT_ENCODING));
+    assert_eq!(res.body(), &original);
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "compress")]
+async fn content_encoded() -> Result<(), Box<dyn std::error::Error>> {
+    use reinda::{AcceptedEncodings, Encoding};
+
+    const EMBEDS: reinda::Embeds = reinda::embed! {
+        base_path: "examples/assets",
+        files: ["index.html"],
+    };
+
+    let mut builder = Assets::builder();
+    builder.add_embedded("index.html", &EMBEDS["index.html"]);
+    builder.add_embedded("modified.html", &EMBEDS["index.html"])
+        .with_modifier::<_, _, &str>([], |content, _| [&content[..], b"\n"].concat().into());
+    let assets = builder.build().await?;
+
+    let index = assets.get("index.html").unwrap();
+    let original = index.content().await?;
+    let (content, encoding) = index.content_encoded(AcceptedEncodings::default()).await?;
+    assert_eq!((content, encoding), (original.clone(), None));
+
+    let (content, encoding) = index.content_encoded(AcceptedEncodings::from_header("gzip, br")).await?;
+    if cfg!(prod_mode) {
+        assert_eq!(index.encodings().collect::<Vec<_>>(), [Encoding::Brotli]);
+        assert_eq!(encoding, Some(Encoding::Brotli));
+        let mut decompressed = Vec::new();
+        brotli::BrotliDecompress(&mut &*content, &mut decompressed)?;
+        assert_eq!(decompressed, original);
+    } else {
+        assert_eq!(index.encodings().count(), 0);
+        assert_eq!((content, encoding), (original, None));
+    }
+
+    let modified = assets.get("modified.html").unwrap();
+    assert_eq!(modified.encodings().count(), 0);
+    let (_, encoding) = modified.content_encoded(AcceptedEncodings::all()).await?;
+    assert_eq!(encoding, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "gzip")]
+async fn gzip() -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::Read;
+    use reinda::{AcceptedEncodings, Encoding};
+
+    let mut builder = Assets::builder();
+    builder.enable_gzip();
+    builder.add_file("style.css", "examples/assets/style.css");
+    builder.add_file("font.woff2", "examples/assets/fonts/latin-400.woff2");
+    let assets = builder.build().await?;
+
+    let css = assets.get("style.css").unwrap();
+    let original = css.content().await?;
+    let (content, encoding) = css.content_encoded(AcceptedEncodings::from_header("gzip")).await?;
+    if cfg!(prod_mode) {
+        assert_eq!(css.encodings().collect::<Vec<_>>(), [Encoding::Gzip]);
+        assert_eq!(encoding, Some(Encoding::Gzip));
+        let mut decompressed = Vec::new();
+        flate2::read::GzDecoder::new(&*content).read_to_end(&mut decompressed)?;
+        assert_eq!(decompressed, original);
+    } else {
+        assert_eq!((content, encoding), (original, None));
+    }
+
+    let font = assets.get("font.woff2").unwrap();
+    assert_eq!(font.encodings().count(), 0);
+    let (_, encoding) = font.content_encoded(AcceptedEncodings::all()).await?;
+    assert_eq!(encoding, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn restrict_source_maps() -> Result<(), Box<dyn std::error::Error>> {
+    use reinda::{RequestContext, Restriction};
+
+    let mut builder = Assets::builder();
+    builder.restrict_source_maps(Restriction::HeaderToken("X-Debug-Token".into()));
+    builder.add_file("app.js", "examples/assets/bundle.8f29ad31.js");
+    builder.add_file("app.js.map", "examples/assets/bundle.8f29ad31.js.map");
+    builder.add_file("static/vendor.js.map", "examples/assets/bundle.8f29ad31.js.map");
+    builder.add_file("public.js.map", "examples/assets/bundle.8f29ad31.js.map")
+        .with_guard(|_| true);
+    let assets = builder.build().await?;
+
+    let anonymous = RequestContext::new("GET", "", []);
+    let debug = RequestContext::new("GET", "", [("x-debug-token", "")]);
+    for path in ["app.js.map", "static/vendor.js.map"] {
+        let map = assets.get(path).unwrap();
+        assert!(!map.is_allowed(&anonymous));
+        assert!(map.is_allowed(&debug));
+    }
+    assert!(assets.get("app.js").unwrap().is_allowed(&anonymous));
+    assert!(assets.get("public.js.map").unwrap().is_allowed(&anonymous));
+
+    let mut builder = Assets::builder();
+    builder.restrict_source_maps(Restriction::Deny);
+    builder.add_file("app.js.map", "examples/assets/bundle.8f29ad31.js.map");
+    let assets = builder.build().await?;
+    assert!(!assets.get("app.js.map").unwrap().is_allowed(&debug));
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "compress")]
+async fn compress_runtime_files() -> Result<(), Box<dyn std::error::Error>> {
+    use reinda::{AcceptedEncodings, Encoding};
+
+    let mut builder = Assets::builder();
+    builder.compress_runtime_files();
+    builder.add_file("style.css", "examples/assets/style.css");
+    builder.add_file("font.woff2", "examples/assets/fonts/latin-400.woff2");
+    let assets = builder.build().await?;
+
+    let css = assets.get("style.css").unwrap();
+    let original = css.content().await?;
+    let (content, encoding) = css.content_encoded(AcceptedEncodings::from_header("br")).await?;
+    if cfg!(prod_mode) {
+        assert_eq!(encoding, Some(Encoding::Brotli));
+        let mut decompressed = Vec::new();
+        brotli::BrotliDecompress(&mut &*content, &mut decompressed)?;
+        assert_eq!(decompressed, original);
+    } else {
+        assert_eq!((content, encoding), (original, None));
+    }
+    assert_eq!(assets.get("font.woff2").unwrap().encodings().count(), 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "compress-zstd")]
+async fn zstd() -> Result<(), Box<dyn std::error::Error>> {
+    use reinda::{AcceptedEncodings, Encoding};
+
+    const EMBEDS: reinda::Embeds = reinda::embed! {
+        base_path: "examples/assets",
+        files: ["style.css"],
+        compression: "zstd",
+    };
+
+    let mut builder = Assets::builder();
+    builder.add_embedded("style.css", &EMBEDS["style.css"]);
+    let assets = builder.build().await?;
+
+    let css = assets.get("style.css").unwrap();
+    let original = css.content().await?;
+    assert_eq!(original, include_bytes!("../examples/assets/style.css")[..]);
+    let (content, encoding) = css.content_encoded(AcceptedEncodings::from_header("zstd")).await?;
+    if cfg!(prod_mode) {
+        assert_eq!(encoding, Some(Encoding::Zstd));
+        assert_eq!(zstd::stream::decode_all(&*content)?, original);
+        let (_, encoding) = css.content_encoded(AcceptedEncodings::from_header("br")).await?;
+        assert_eq!(encoding, None);
+    } else {
+        assert_eq!((content, encoding), (original, None));
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "gzip")]
+#[tokio::test]
+async fn compress_on_demand() {
+    use reinda::{AcceptedEncodings, Encoding};
+
+    let mut builder = Assets::builder();
+    builder.add_file("style.css", "examples/assets/style.css");
+    builder.add_file("copy.css", "examples/assets/style.css");
+    builder.compress_on_demand(1024 * 1024);
+    let assets = builder.build().await.unwrap();
+    let asset = assets.get("style.css").unwrap();
+    let gzip = AcceptedEncodings::default().with(Encoding::Gzip);
+
+    let (first, encoding) = asset.content_encoded(gzip).await.unwrap();
+    let (second, _) = asset.content_encoded(gzip).await.unwrap();
+    if cfg!(prod_mode) {
+        assert_eq!(encoding, Some(Encoding::Gzip));
+        assert!(first.len() < asset.content().await.unwrap().len());
+        assert_eq!(first.as_ptr(), second.as_ptr());
+    } else {
+        assert_eq!(encoding, None);
+    }
+
+    // Concurrent requests share one compression.
+    let copy = assets.get("copy.css").unwrap();
+    let (a, b, c) = tokio::join!(
+        copy.content_encoded(gzip),
+        copy.content_encoded(gzip),
+        copy.content_encoded(gzip),
+    );
+    let (a, b, c) = (a.unwrap().0, b.unwrap().0, c.unwrap().0);
+    if cfg!(prod_mode) {
+        assert!(a.as_ptr() == b.as_ptr() && b.as_ptr() == c.as_ptr());
+    }
+
+    let (_, encoding) = asset.content_encoded(AcceptedEncodings::default()).await.unwrap();
+    assert_eq!(encoding, None);
+}
+
+#[tokio::test]
+async fn content_stream_range() {
+    use reinda::RangeRequest;
+    use tokio::io::AsyncReadExt;
+
+    let mut builder = Assets::builder();
+    builder.add_file("peter.txt", "tests/files/peter.txt");
+    let assets = builder.build().await.unwrap();
+    let asset = assets.get("peter.txt").unwrap();
+
+    let mut full = String::new();
+    let mut stream = asset.content_stream().await.unwrap();
+    assert_eq!(stream.len(), 20);
+    stream.read_to_string(&mut full).await.unwrap();
+    assert_eq!(full, "Peter und der Wolf.\n");
+
+    let stream = asset.content_stream().await.unwrap();
+    let range = RangeRequest::parse("bytes=6-8", stream.len());
+    assert_eq!(range.content_range(stream.len()).unwrap(), "bytes 6-8/20");
+    let RangeRequest::Partial(range) = range else { panic!("expected partial range") };
+    let mut part = String::new();
+    let mut stream = stream.slice(range).await.unwrap();
+    assert_eq!(stream.len(), 3);
+    stream.read_to_string(&mut part).await.unwrap();
+    assert_eq!(part, "und");
+
+    assert_eq!(RangeRequest::parse("bytes=20-", 20), RangeRequest::Unsatisfiable);
+    assert_eq!(RangeRequest::parse("items=0-1", 20), RangeRequest::Full);
+}
+
+#[tokio::test]
+async fn content_stream_from_file() -> Result<(), Box<dyn std::error::Error>> {
+    use tokio::io::AsyncReadExt;
+
+    let dir = std::env::temp_dir().join(format!("reinda-test-stream-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join("peter.txt");
+    std::fs::write(&path, "Peter und der Wolf.\n")?;
+
+    let mut builder = Assets::builder();
+    builder.add_file("peter.txt", path.clone());
+    let assets = builder.build().await?;
+    let asset = assets.get("peter.txt").unwrap();
+    let read = || async {
+        let mut out = String::new();
+        asset.content_stream().await.unwrap().read_to_string(&mut out).await.unwrap();
+        out
+    };
+
+    // Same length and modification time: streamed from the file in both
+    // modes, even though prod mode has the old content in memory.
+    let mtime = std::fs::metadata(&path)?.modified()?;
+    std::fs::write(&path, "Peter und der Fuchs\n")?;
+    std::fs::File::options().write(true).open(&path)?.set_modified(mtime)?;
+    assert_eq!(read().await, "Peter und der Fuchs\n");
+
+    // Otherwise, prod mode serves the content from building.
+    std::fs::write(&path, "Rotkäppchen\n")?;
+    let expected = if cfg!(prod_mode) { "Peter und der Wolf.\n" } else { "Rotkäppchen\n" };
+    assert_eq!(read().await, expected);
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn last_modified() {
+    const EMBEDS: reinda::Embeds = reinda::embed! {
+        base_path: "tests/files",
+        files: ["peter.txt"],
+    };
+
+    let file = EMBEDS["peter.txt"].as_file().unwrap();
+    let mtime = file.modified().unwrap();
+
+    let mut builder = Assets::builder();
+    builder.add_embedded("peter.txt", &EMBEDS["peter.txt"]);
+    let assets = builder.build().await.unwrap();
+    let asset = assets.get("peter.txt").unwrap();
+
+    if cfg!(prod_mode) {
+        let modified = asset.last_modified().unwrap();
+        let header = reinda::util::format_http_date(modified);
+        assert_eq!(reinda::util::parse_http_date(&header), Some(modified));
+        assert!(!asset.is_modified_since(&header));
+        assert!(asset.is_modified_since("Thu, 01 Jan 1970 00:00:00 GMT"));
+    } else {
+        assert!(mtime > std::time::UNIX_EPOCH);
+        assert_eq!(asset.last_modified(), None);
+        assert!(asset.is_modified_since("Sun, 06 Nov 1994 08:49:37 GMT"));
+    }
+}
+
+#[cfg(feature = "hash")]
+#[tokio::test]
+async fn subresource_integrity() {
+    let mut builder = Assets::builder();
+    builder.add_file("static/style.css", "examples/assets/style.css").with_hash();
+    builder.add_file("index.html", "examples/assets/index.html")
+        .with_modifier(["static/style.css"], |html, ctx| ctx.inject_integrity(&html).into());
+    let assets = builder.build().await.unwrap();
+
+    let index = assets.get("index.html").unwrap().content().await.unwrap();
+    let index = std::str::from_utf8(&index).unwrap();
+    let (_, style) = assets.iter().find(|(path, _)| path.ends_with(".css")).unwrap();
+    let sri = style.sri_hash().await.unwrap();
+    assert!(sri.starts_with("sha384-"));
+
+    let expected = format!(r#"href="/static/style.css" integrity="{}">"#, sri);
+    assert_eq!(index.contains(&expected), cfg!(prod_mode));
+    assert!(!index.contains("<script src=\"/{{ bundle_path }}\" integrity"));
+}
+
+#[cfg(all(feature = "manifest", feature = "hash"))]
+#[tokio::test]
+async fn manifest() {
+    use reinda::Manifest;
+
+    let mut builder = Assets::builder();
+    builder.add_file("index.html", "examples/assets/index.html");
+    builder.add_file("static/style.css", "examples/assets/style.css").with_hash();
+    let assets = builder.build().await.unwrap();
+
+    let manifest = assets.manifest();
+    assert_eq!(manifest.version, Manifest::VERSION);
+    assert_eq!(manifest.assets.len(), 2);
+    assert_eq!(manifest.resolve("index.html"), Some("index.html"));
+    let style = manifest.resolve("static/style.css").unwrap();
+    assert!(assets.get(style).is_some());
+    assert_eq!(style != "static/style.css", cfg!(prod_mode));
+
+    let json = manifest.to_json();
+    assert!(json.contains(r#""version": 1"#));
+    assert_eq!(Manifest::from_json(&json).unwrap(), manifest);
+    assert!(Manifest::from_json(r#"{ "version": 2, "assets": {} }"#).is_err());
+}
+
+#[cfg(all(feature = "manifest", feature = "hash"))]
+#[tokio::test]
+async fn dry_run_prod() {
+    use std::sync::{Arc, Mutex};
+
+    let resolved = Arc::new(Mutex::new(None));
+    let mut builder = Assets::builder();
+    builder.add_file("static/style.css", "examples/assets/style.css").with_hash();
+    builder.add_file("index.html", "examples/assets/index.html").with_modifier(["static/style.css"], {
+        let resolved = resolved.clone();
+        move |content, ctx| {
+            *resolved.lock().unwrap() = Some(ctx.resolve_path("static/style.css").to_owned());
+            content
+        }
+    });
+    let assets = builder.build().await.unwrap();
+
+    let manifest = assets.dry_run_prod().await.unwrap();
+    let style = manifest.resolve("static/style.css").unwrap();
+    assert_ne!(style, "static/style.css");
+    assert_eq!(resolved.lock().unwrap().as_deref(), Some(style));
+    assert_eq!(manifest.resolve("index.html"), Some("index.html"));
+    if cfg!(prod_mode) {
+        assert_eq!(manifest, assets.manifest());
+    }
+
+    // Cycles are only detected when building in prod mode.
+    let mut builder = Assets::builder();
+    builder.add_file("a", "tests/files/peter.txt").after(["b"]);
+    builder.add_file("b", "tests/files/peter.txt").after(["a"]);
+    let result = match builder.build().await {
+        Ok(assets) => assets.dry_run_prod().await.map(drop),
+        Err(e) => Err(e),
+    };
+    assert!(matches!(result, Err(reinda::BuildError::CyclicDependencies(_))));
+}
+
+#[cfg(feature = "hash")]
+#[tokio::test]
+async fn conditional_requests() {
+    use reinda::Conditional;
+
+    let mut builder = Assets::builder();
+    builder.add_file("peter.txt", "tests/files/peter.txt").with_cache_control("no-cache");
+    let assets = builder.build().await.unwrap();
+    let asset = assets.get("peter.txt").unwrap();
+    let content = asset.content().await.unwrap();
+
+    let unconditional = Conditional::evaluate(&asset, &content, []);
+    assert_eq!(unconditional.status(), 200);
+    let etag = unconditional.etag().to_owned();
+    let headers = unconditional.headers().collect::<Vec<_>>();
+    assert!(headers.contains(&("etag", &*etag)));
+    assert!(headers.contains(&("cache-control", "no-cache")));
+
+    let evaluate = |headers: &[(&str, &str)]| {
+        Conditional::evaluate(&asset, &content, headers.iter().copied()).is_not_modified()
+    };
+    assert!(evaluate(&[("If-None-Match", &etag)]));
+    assert!(evaluate(&[("if-none-match", "\"foo\""), ("if-none-match", &format!("W/{etag}"))]));
+    assert!(!evaluate(&[("If-None-Match", "\"foo\"")]));
+
+    // `If-Modified-Since` is ignored in the presence of `If-None-Match`.
+    let future = "Fri, 01 Jan 2100 00:00:00 GMT";
+    assert!(!evaluate(&[("If-None-Match", "\"foo\""), ("If-Modified-Since", future)]));
+    assert_eq!(evaluate(&[("If-Modified-Since", future)]), cfg!(prod_mode));
+}
+
+#[tokio::test]
+async fn text() {
+    const EMBEDS: reinda::Embeds = reinda::embed! {
+        base_path: "tests/files",
+        files: ["peter.txt"],
+    };
+
+    let mut builder = Assets::builder();
+    builder.add_embedded("peter.txt", &EMBEDS["peter.txt"]);
+    builder.add_file("font.woff2", "examples/assets/fonts/latin-400.woff2");
+    let assets = builder.build().await.unwrap();
+
+    let peter = assets.get("peter.txt").unwrap();
+    assert_eq!(peter.text().await.unwrap(), "Peter und der Wolf.\n");
+    assert_eq!(peter.text().await.unwrap(), "Peter und der Wolf.\n");
+    let err = assets.get("font.woff2").unwrap().text().await.unwrap_err();
+    assert!(matches!(err, reinda::TextError::InvalidUtf8(_)));
+
+    #[cfg(prod_mode)]
+    assert_eq!(EMBEDS["peter.txt"].as_file().unwrap().as_str().unwrap(), "Peter und der Wolf.\n");
+}
+
+#[tokio::test]
+async fn locale_variants() {
+    let mut builder = Assets::builder();
+    builder.add_file("index.html", "examples/assets/index.html")
+        .with_locale_variants([("de", "index.de.html"), ("en-GB", "index.en-gb.html")]);
+    builder.add_file("index.de.html", "tests/files/peter.txt");
+    builder.add_file("index.en-gb.html", "tests/files/peter.txt")
+        .with_modifier::<_, _, &str>([], |_, _| "colour".into());
+    let assets = builder.build().await.unwrap();
+
+    let text = |asset: Option<reinda::Asset>| async move {
+        String::from_utf8(asset.unwrap().content().await.unwrap().to_vec()).unwrap()
+    };
+    let index = text(assets.get("index.html")).await;
+    assert!(assets.get("index.html").unwrap().has_locale_variants());
+    assert_eq!(text(assets.get_localized("index.html", "de-AT, en;q=0.5")).await, "Peter und der Wolf.\n");
+    assert_eq!(text(assets.get_localized("index.html", "fr, en;q=0.8")).await, "colour");
+    assert_eq!(text(assets.get_localized("index.html", "fr")).await, index);
+    assert_eq!(text(assets.get_localized("index.html", "")).await, index);
+    assert!(assets.get_localized("nope.html", "de").is_none());
+}
+
+#[tokio::test]
+async fn preload_links() {
+    let mut builder = Assets::builder();
+    builder.add_file("static/style.css", "examples/assets/style.css");
+    builder.add_file("fonts/a.woff2", "examples/assets/fonts/latin-400.woff2");
+    builder.add_file("other.html", "tests/files/peter.txt");
+    builder.add_file("index.html", "examples/assets/index.html")
+        .with_modifier(["static/style.css", "fonts/a.woff2", "other.html"], |html, _| html);
+    let assets = builder.build().await.unwrap();
+
+    let links = assets.get("index.html").unwrap().preload_links("/assets/");
+    assert_eq!(links, [
+        "</assets/static/style.css>; rel=preload; as=style",
+        "</assets/fonts/a.woff2>; rel=preload; as=font; crossorigin",
+    ]);
+    assert!(assets.get("other.html").unwrap().preload_links("/").is_empty());
+}
+
+#[cfg(any(feature = "minijinja", feature = "tera"))]
+fn template_assets() -> reinda::Builder<'static> {
+    let mut builder = Assets::builder();
+    builder.add_file("templates/hello.html", "tests/files/peter.txt")
+        .with_modifier::<_, _, &str>([], |_, _| "Hello {{ name }}!".into());
+    builder.add_file("peter.txt", "tests/files/peter.txt");
+    builder
+}
+
+#[cfg(feature = "minijinja")]
+#[tokio::test]
+async fn minijinja_loader() {
+    let assets = template_assets().build().await.unwrap().into_static();
+    let mut env = minijinja::Environment::new();
+    env.set_loader(assets.minijinja_loader());
+
+    let hello = env.get_template("templates/hello.html").unwrap();
+    assert_eq!(hello.render(minijinja::context! { name => "Peter" }).unwrap(), "Hello Peter!");
+    assert!(env.get_template("nope.html").is_err());
+}
+
+#[cfg(feature = "tera")]
+#[tokio::test]
+async fn tera_templates() {
+    let assets = template_assets().build().await.unwrap();
+    let tera = assets.to_tera("templates/*.html").await.unwrap();
+
+    let mut context = tera::Context::new();
+    context.insert("name", "Peter");
+    assert_eq!(tera.render("templates/hello.html", &context).unwrap(), "Hello Peter!");
+    assert_eq!(tera.get_template_names().collect::<Vec<_>>(), ["templates/hello.html"]);
+}
+
+#[cfg(feature = "hash")]
+#[tokio::test]
+async fn csp_hashes() {
+    let mut builder = Assets::builder();
+    builder.add_file("index.html", "examples/assets/index.html")
+        .with_modifier::<_, _, &str>([], |_, _| "<script>alert('Hello, world.');</script>".into());
+    builder.add_file("app.js", "tests/files/peter.txt")
+        .with_modifier::<_, _, &str>([], |_, _| "<script>alert(1)</script>".into());
+    let assets = builder.build().await.unwrap();
+
+    let index = assets.get("index.html").unwrap();
+    assert_eq!(index.csp_hashes().await.unwrap(), ["'sha256-qznLcsROx4GACP2dm0UCKCzCG+HiZ1guq6ZZDob/Tng='"]);
+    assert!(assets.get("app.js").unwrap().csp_hashes().await.unwrap().is_empty());
+}
+
+#[cfg(feature = "hash")]
+#[tokio::test]
+async fn render_inlined() {
+    let mut builder = Assets::builder();
+    builder.add_file("mail/welcome.html", "tests/files/peter.txt")
+        .with_modifier::<_, _, &str>([], |_, _| concat!(
+            r#"<link rel="stylesheet" href="/assets/static/mail.css" media="screen">"#,
+            r#"<img alt="Peter" src="../img/peter.txt"><img src="https://example.com/x.png">"#,
+            r#"<a href="/assets/static/mail.css">link</a>"#,
+        ).into());
+    builder.add_file("static/mail.css", "tests/files/peter.txt")
+        .with_modifier::<_, _, &str>([], |_, _| "body { background: url('../img/peter.txt') }".into());
+    builder.add_file("img/peter.txt", "tests/files/peter.txt");
+    let assets = builder.build().await.unwrap();
+
+    let html = assets.render_inlined("mail/welcome.html", "assets/").await.unwrap();
+    let data_uri = "data:text/plain;base64,UGV0ZXIgdW5kIGRlciBXb2xmLgo=";
+    assert_eq!(html, format!(
+        r#"<style media="screen">body {{ background: url("{data_uri}") }}</style><img alt="Peter" src="{data_uri}"><img src="https://example.com/x.png"><a href="/assets/static/mail.css">link</a>"#,
+    ));
+    assert!(assets.render_inlined("nope.html", "").await.is_err());
+}
+
+#[tokio::test]
+#[cfg(feature = "compress")]
+async fn brotli_dictionary() -> Result<(), Box<dyn std::error::Error>> {
+    const EMBEDS: reinda::Embeds = reinda::embed! {
+        base_path: "tests/files",
+        files: ["locales/*.json"],
+        brotli_dictionary: true,
+    };
+
+    let mut builder = Assets::builder();
+    builder.add_embedded("locales/", &EMBEDS["locales/*.json"]);
+    let assets = builder.build().await?;
+
+    for lang in ["de", "en", "es", "fr"] {
+        let path = format!("locales/{}.json", lang);
+        let original = std::fs::read(format!("tests/files/{}", path))?;
+        let asset = assets.get(&path).unwrap();
+        assert_eq!(asset.content().await?, original);
+
+        // Clients don't have the dictionary, so the stored form can't be
+        // served.
+        if cfg!(prod_mode) {
+            assert_eq!(asset.encodings().count(), 0);
+        }
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(all(feature = "hash", feature = "modifiers"))]
+async fn in_memory() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = Assets::builder();
+    builder.add_bytes("config.json", r#"{ "debug": false }"#).with_hash();
+    builder.add_str("index.html", String::from(r#"<script src="config.json"></script>"#))
+        .with_path_fixup(["config.json"]);
+    let assets = builder.build().await?;
+
+    let (path, asset) = assets.iter().find(|(path, _)| path.ends_with(".json")).unwrap();
+    assert_eq!(asset.content().await?, r#"{ "debug": false }"#);
+    if cfg!(prod_mode) {
+        assert_ne!(path, "config.json");
+    }
+
+    let expected = format!(r#"<script src="{}"></script>"#, path);
+    assert_get!(assets, "index.html", false, &expected);
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "hash")]
+async fn hash_salt() -> Result<(), Box<dyn std::error::Error>> {
+    let build = |salt: Option<&str>| {
+        let mut builder = Assets::builder();
+        if let Some(salt) = salt {
+            builder.hash_salt(salt);
+        }
+        builder.add_file("peter.txt", "tests/files/peter.txt").with_hash();
+        builder.build()
+    };
+    let path = |assets: Assets| assets.iter().next().unwrap().0.to_owned();
+
+    let unsalted = path(build(None).await?);
+    let salted = path(build(Some("incident-1")).await?);
+    let salted2 = path(build(Some("incident-2")).await?);
+    if cfg!(prod_mode) {
+        assert_eq!(path(build(Some("")).await?), unsalted);
+        assert_ne!(salted, unsalted);
+        assert_ne!(salted, salted2);
+        assert!(salted.starts_with("peter.") && salted.ends_with(".txt"));
+    } else {
+        assert_eq!([unsalted, salted, salted2], ["peter.txt"; 3]);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_dir() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = Assets::builder();
+    builder.add_dir("static/", "examples/assets");
+    builder.add_dir_glob("locales/", "tests/files", "**/*.json");
+    let assets = builder.build().await?;
+
+    assert_get!(assets, "static/style.css", false, include_bytes!("../examples/assets/style.css"));
+    assert_get!(assets, "static/fonts/latin-400.woff2", false,
+        include_bytes!("../examples/assets/fonts/latin-400.woff2"));
+    assert_get!(assets, "locales/locales/de.json", false, include_bytes!("files/locales/de.json"));
+    assert_get!(assets, "locales/config/base.json", false, include_bytes!("files/config/base.json"));
+    assert!(assets.get("locales/peter.txt").is_none());
+    assert!(assets.get("static/missing.txt").is_none());
+
+    if cfg!(prod_mode) {
+        let json_files = std::fs::read_dir("tests/files/locales")?.count() + 2;
+        assert_eq!(assets.iter().filter(|(p, _)| p.starts_with("locales/")).count(), json_files);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn duplicate_paths() -> Result<(), Box<dyn std::error::Error>> {
+    let builder = || {
+        let mut builder = Assets::builder();
+        builder.add_file("peter.txt", "tests/files/peter.txt");
+        builder.add_file("peter.txt", "tests/files/data.bin");
+        builder
+    };
+
+    match builder().build().await {
+        Err(reinda::BuildError::DuplicatePath { http_path, first, second }) => {
+            assert_eq!(http_path, "peter.txt");
+            assert_eq!(first, "file 'tests/files/peter.txt'");
+            assert_eq!(second, "file 'tests/files/data.bin'");
+        }
+        other => panic!("unexpected result: {:?}", other.map(|_| ())),
+    }
+
+    let mut builder = builder();
+    builder.allow_duplicate_paths();
+    let assets = builder.build().await?;
+    assert_get!(assets, "peter.txt", false, include_bytes!("files/data.bin"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn was_modified() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = Assets::builder();
+    builder.add_file("peter.txt", "tests/files/peter.txt");
+    builder.add_file("shout.txt", "tests/files/peter.txt")
+        .with_modifier(Vec::<&str>::new(), |content, _| content.to_ascii_uppercase().into());
+    let assets = builder.build().await?;
+
+    let peter = assets.get("peter.txt").unwrap();
+    let shout = assets.get("shout.txt").unwrap();
+    assert!(!peter.was_modified().await?);
+    assert!(shout.was_modified().await?);
+
+    // SHA-256 of "Peter und der Wolf.\n".
+    let expected = "c709a2edabf89089fb3781ec3496d7efa2bf48fb610687315cf8126a9fa5838a";
+    assert_eq!(peter.source_sha256().await?, expected);
+    assert_eq!(shout.source_sha256().await?, expected);
+
+    Ok(())
+}
+
+#[cfg(feature = "metrics")]
+#[tokio::test]
+async fn metrics() -> Result<(), Box<dyn std::error::Error>> {
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    let _guard = metrics::set_default_local_recorder(&recorder);
+
+    let mut builder = Assets::builder();
+    builder.add_file("peter.txt", "tests/files/peter.txt").with_metrics_tag("text");
+    let assets = builder.build().await?;
+    assert_eq!(assets.get("peter.txt").unwrap().metrics_tag(), Some("text"));
+    assert!(assets.get("nope.txt").is_none());
+    assets.get("peter.txt").unwrap().content().await?;
+
+    #[cfg(feature = "tower")]
+    {
+        use tower::ServiceExt;
+
+        let req = http::Request::get("/peter.txt").body(()).unwrap();
+        assets.clone().into_service().oneshot(req).await?;
+    }
+
+    let snapshot = snapshotter.snapshot().into_vec();
+    let value = |name: &str| snapshot.iter()
+        .find(|(key, ..)| key.key().name() == name)
+        .map(|(key, _, _, value)| (key.key().labels().cloned().collect::<Vec<_>>(), value));
+    let lookups = if cfg!(feature = "tower") { 4 } else { 3 };
+    assert_eq!(value("reinda_lookups_total").unwrap().1, &DebugValue::Counter(lookups));
+    assert_eq!(value("reinda_lookup_misses_total").unwrap().1, &DebugValue::Counter(1));
+    match value("reinda_dev_load_duration_seconds") {
+        Some((_, DebugValue::Histogram(values))) => assert!(cfg!(dev_mode) && !values.is_empty()),
+        other => assert!(cfg!(prod_mode) && other.is_none()),
+    }
+    if cfg!(feature = "tower") {
+        let (labels, value) = value("reinda_served_bytes_total").unwrap();
+        assert_eq!(labels, [metrics::Label::new("tag", "text")]);
+        assert_eq!(value, &DebugValue::Counter(20));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn warm_up() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = Assets::builder();
+    builder.add_file("peter.txt", "tests/files/peter.txt");
+    builder.add_dir("locales/", "tests/files/locales");
+    builder.dev_stale_while_revalidate(std::time::Duration::from_secs(60));
+    let assets = builder.build().await?;
+    assert!(assets.warm_up().await.is_empty());
+
+    #[cfg(dev_mode)]
+    {
+        let mut builder = Assets::builder();
+        builder.add_file("peter.txt", "tests/files/peter.txt");
+        builder.add_file("missing.txt", "tests/files/missing.txt");
+        builder.add_dir("nope/", "tests/files/nope");
+        let assets = builder.build().await?;
+        let errors = assets.warm_up().await;
+        let paths = errors.iter().map(|e| e.http_path()).collect::<Vec<_>>();
+        assert_eq!(paths, ["missing.txt", "nope/"]);
+        assert!(errors.iter().all(|e| matches!(e.kind(), reinda::LoadErrorKind::Io(_))));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "hash")]
+async fn content_hasher() -> Result<(), Box<dyn std::error::Error>> {
+    struct Hex;
+    impl reinda::ContentHasher for Hex {
+        fn digest(&self, _salt: &[u8], content: &[u8]) -> Vec<u8> {
+            content.len().to_be_bytes().to_vec()
+        }
+        fn encode(&self, digest: &[u8]) -> String {
+            digest.iter().map(|b| format!("{:02x}", b)).collect()
+        }
+    }
+
+    let mut builder = Assets::builder();
+    builder.content_hasher(Hex);
+    builder.add_file("peter.txt", "tests/files/peter.txt").with_hash();
+    let assets = builder.build().await?;
+
+    if cfg!(prod_mode) {
+        assert_get!(assets, "peter.0000000000000014.txt", true, "Peter und der Wolf.\n");
+    } else {
+        assert_get!(assets, "peter.txt", false, "Peter und der Wolf.\n");
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(all(prod_mode, feature = "blake3", feature = "xxhash"))]
+async fn builtin_content_hashers() -> Result<(), Box<dyn std::error::Error>> {
+    let mut hashes = Vec::new();
+    for hasher in 0..2 {
+        let mut builder = Assets::builder();
+        match hasher {
+            0 => builder.content_hasher(reinda::Blake3Hasher),
+            _ => builder.content_hasher(reinda::Xxh3Hasher),
+        };
+        builder.add_file("peter.txt", "tests/files/peter.txt").with_hash();
+        let assets = builder.build().await?;
+        let (path, _) = assets.iter().next().unwrap();
+        hashes.push(path.to_owned());
+    }
+
+    assert_eq!(hashes[0].len(), "peter..txt".len() + 12);
+    assert_eq!(hashes[1].len(), "peter..txt".len() + 12);
+    assert_ne!(hashes[0], "peter.xwmi7av4kIn7.txt");
+    assert_ne!(hashes[0], hashes[1]);
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(all(feature = "cli", feature = "modifiers"))]
+async fn cli_report() -> Result<(), Box<dyn std::error::Error>> {
+    use reinda::cli::{AssetPipeline, Section};
+
+    struct Pipeline;
+    impl AssetPipeline for Pipeline {
+        fn configure<'a>(&'a self, builder: &mut reinda::Builder<'a>) {
+            builder.add_file("peter.txt", "tests/files/peter.txt").with_hash();
+            builder.add_file("index.html", "tests/files/peter.txt")
+                .with_path_fixup(["peter.txt"])
+                .after(["data.bin"]);
+            builder.add_file("data.bin", "tests/files/data.bin");
+        }
+    }
+
+    let mut out = Vec::new();
+    reinda::cli::write_report(&Pipeline, &[Section::Sizes, Section::Deps], &mut out).await?;
+    assert_eq!(String::from_utf8(out)?, [
+        "# sizes",
+        "        20  data.bin",
+        "        20  index.html",
+        "        20  peter.txt",
+        "# deps",
+        "index.html -> data.bin, peter.txt",
+        "",
+    ].join("\n"));
+
+    let mut out = Vec::new();
+    reinda::cli::write_report(&Pipeline, &[Section::Manifest], &mut out).await?;
+    let out = String::from_utf8(out)?;
+    assert!(out.starts_with("# manifest\n{"));
+    assert!(out.contains(r#""path": "peter.xwmi7av4kIn7.txt""#));
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "hash")]
+async fn hashed_path_matches_builder() -> Result<(), Box<dyn std::error::Error>> {
+    use reinda::{hashed_path, HashOptions, HashPlacement};
+
+    let mut builder = Assets::builder();
+    builder.hash_salt("v2");
+    builder.set_hash_length(6);
+    builder.add_file("texts/peter.txt", "tests/files/peter.txt")
+        .with_hash_placement(HashPlacement::Suffix);
+    let assets = builder.build().await?;
+
+    let options = HashOptions::new().placement(HashPlacement::Suffix).length(6).salt("v2");
+    let expected = hashed_path("texts/peter.txt", include_bytes!("files/peter.txt"), &options);
+    assert!(expected.starts_with("texts/peter.txt-"));
+    assert_eq!(expected.len(), "texts/peter.txt-".len() + 8);
+    if cfg!(prod_mode) {
+        assert!(assets.get(&expected).is_some_and(|asset| asset.is_filename_hashed()));
+    }
+    assert_eq!(
+        hashed_path("peter.txt", include_bytes!("files/peter.txt"), &HashOptions::default()),
+        "peter.xwmi7av4kIn7.txt",
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(dev_mode)]
+async fn remap_prefix() -> Result<(), Box<dyn std::error::Error>> {
+    let files = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+
+    let mut builder = Assets::builder();
+    builder.remap_prefix("/home/ci/checkout/tests", &files);
+    builder.remap_prefix("/home/ci/checkout", "/nope");
+    builder.add_file("peter.txt", "/home/ci/checkout/tests/peter.txt");
+    builder.add_dir("locales/", "/home/ci/checkout/tests/locales");
+    builder.add_file("unchanged.txt", "tests/files/peter.txt");
+    let assets = builder.build().await?;
+
+    assert_get!(assets, "peter.txt", false, "Peter und der Wolf.\n");
+    assert_get!(assets, "unchanged.txt", false, "Peter und der Wolf.\n");
+    assert!(assets.get("locales/de.json").is_some());
+    assert!(assets.warm_up().await.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn embed_shared_file() {
+    const EMBEDS: reinda::Embeds = reinda::embed! {
+        base_path: "tests/files",
+        files: ["peter.txt", "*.txt"],
+    };
+
+    let single = EMBEDS["peter.txt"].files().next().unwrap();
+    let globbed = EMBEDS["*.txt"].files().next().unwrap();
+    assert_eq!(single.path(), "peter.txt");
+    assert_eq!(globbed.path(), "peter.txt");
+
+    #[cfg(prod_mode)]
+    {
+        assert_eq!(single.content(), globbed.content());
+        assert_eq!(single.content.as_ptr(), globbed.content.as_ptr());
+    }
+}
+
+#[tokio::test]
+async fn modifier_context_path() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = Assets::builder();
+    builder.add_file("peter.txt", "tests/files/peter.txt")
+        .with_modifier::<_, _, &str>([], |_, ctx| {
+            format!("{} {:?}", ctx.unhashed_http_path(), ctx.glob_suffix()).into()
+        });
+    builder.add_dir("locales/", "tests/files/locales")
+        .with_modifier::<_, _, &str>([], |_, ctx| {
+            format!("{} {:?}", ctx.unhashed_http_path(), ctx.glob_suffix()).into()
+        });
+    let assets = builder.build().await?;
+
+    assert_get!(assets, "peter.txt", false, "peter.txt None");
+    assert_get!(assets, "locales/de.json", false, "locales/de.json Some(\"de.json\")");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn filter_glob_files() -> Result<(), Box<dyn std::error::Error>> {
+    const EMBEDS: reinda::Embeds = reinda::embed! {
+        base_path: "tests/files",
+        files: ["locales/*.json"],
+    };
+
+    let mut builder = Assets::builder();
+    let entry = builder.add_embedded("embedded/", &EMBEDS["locales/*.json"])
+        .filter(|path| path != "de.json")
+        .filter(|path| !path.starts_with("f"));
+    assert_eq!(entry.http_paths(), ["embedded/en.json", "embedded/es.json"]);
+    builder.add_dir("dir/", "tests/files/locales").filter(|path| path.starts_with("e"));
+    let assets = builder.build().await?;
+
+    assert!(assets.get("embedded/en.json").is_some());
+    assert!(assets.get("embedded/es.json").is_some());
+    assert!(assets.get("embedded/de.json").is_none());
+    assert!(assets.get("embedded/fr.json").is_none());
+    assert!(assets.get("dir/en.json").is_some());
+    assert!(assets.get("dir/de.json").is_none());
+
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "single file entry")]
+fn filter_single_file() {
+    Assets::builder().add_file("peter.txt", "tests/files/peter.txt").filter(|_| true);
+}
+
+#[tokio::test]
+async fn map_http_paths() -> Result<(), Box<dyn std::error::Error>> {
+    const EMBEDS: reinda::Embeds = reinda::embed! {
+        base_path: "tests/files",
+        files: ["locales/*.json"],
+    };
+
+    let mut builder = Assets::builder();
+    let entry = builder.add_embedded("embedded/", &EMBEDS["locales/*.json"])
+        .filter(|path| path != "fr.json")
+        .map_http_paths(|path| path.to_uppercase())
+        .map_http_paths(|path| format!("lang-{}", path));
+    assert_eq!(entry.http_paths(), ["embedded/lang-DE.JSON", "embedded/lang-EN.JSON", "embedded/lang-ES.JSON"]);
+    builder.add_dir("dir/", "tests/files/locales")
+        .map_http_paths(|path| path.trim_end_matches(".json").to_owned())
+        .with_modifier::<_, _, &str>([], |_, ctx| ctx.glob_suffix().unwrap().to_owned().into());
+    let assets = builder.build().await?;
+
+    assert!(assets.get("embedded/lang-DE.JSON").is_some());
+    assert!(assets.get("embedded/de.json").is_none());
+    assert!(assets.get("embedded/lang-FR.JSON").is_none());
+    assert_get!(assets, "dir/en", false, "en");
+    assert!(assets.get("dir/en.json").is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(unix)]
+async fn add_dir_symlinks() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = std::env::temp_dir().join(format!("reinda-test-symlinks-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("sub"))?;
+    std::fs::write(dir.join("sub/a.txt"), "a")?;
+    std::os::unix::fs::symlink(&dir, dir.join("sub/loop"))?;
+    std::os::unix::fs::symlink(dir.join("missing.txt"), dir.join("dangling.txt"))?;
+
+    let mut builder = Assets::builder();
+    builder.add_dir("files/", &dir).map_http_paths(|path| path.to_uppercase());
+    let assets = builder.build().await?;
+    assert_get!(assets, "files/SUB/A.TXT", false, "a");
+    assert!(assets.get("files/DANGLING.TXT").is_none());
+
+    // Files added later are found after being reported as changed.
+    std::fs::write(dir.join("b.txt"), "b")?;
+    assets.changes([dir.join("b.txt")]);
+    if cfg!(dev_mode) {
+        assert_get!(assets, "files/B.TXT", false, "b");
+    }
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(all(feature = "hash", feature = "modifiers"))]
+async fn auto_path_fixup() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = Assets::builder();
+    builder.add_str("index.html", "<a href=\"peter.txt\">peter.txt.bak</a> locales/de.json")
+        .with_auto_path_fixup();
+    builder.add_file("peter.txt", "tests/files/peter.txt").with_hash();
+    builder.add_str("peter.txt.bak", "old").with_hash();
+    builder.add_dir("locales/", "tests/files/locales");
+    let assets = builder.build().await?;
+
+    if cfg!(prod_mode) {
+        let bak = reinda::hashed_path("peter.txt.bak", b"old", &reinda::HashOptions::new());
+        let expected = format!("<a href=\"peter.xwmi7av4kIn7.txt\">{}</a> locales/de.json", bak);
+        assert_eq!(assets.get("index.html").unwrap().content().await?, expected.as_bytes());
+        let order = assets.processing_order().unwrap();
+        let pos = |p: &str| order.iter().position(|o| o == p).unwrap();
+        assert!(pos("peter.txt") < pos("index.html"));
+    } else {
+        assert_get!(assets, "index.html", false, "<a href=\"peter.txt\">peter.txt.bak</a> locales/de.json");
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn global_modifier() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = Assets::builder();
+    builder.with_global_modifier(|path| path.ends_with(".txt"), |content, ctx| {
+        format!("{}: {}", ctx.unhashed_http_path(), String::from_utf8_lossy(&content)).into()
+    });
+    builder.with_global_modifier(|path| path.starts_with("b"), |content, _| {
+        [&content[..], b"!"].concat().into()
+    });
+    builder.add_str("a.txt", "foo");
+    builder.add_str("b.txt", "bar").with_modifier::<_, _, &str>([], |content, _| {
+        content.to_ascii_uppercase().into()
+    });
+    builder.add_str("c.json", "{}");
+    let assets = builder.build().await?;
+
+    assert_get!(assets, "a.txt", false, "a.txt: foo");
+    assert_get!(assets, "b.txt", false, "b.txt: BAR!");
+    assert_get!(assets, "c.json", false, "{}");
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "minify-js")]
+async fn minify_js() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = Assets::builder();
+    builder.add_str("app.js", "import { x } from './lib.js';\nconsole.log( x );\n").minify_js();
+    builder.group("*.min.js").minify_js();
+    builder.add_str("b.min.js", "const value = 1 + 2;");
+    builder.add_str("plain.js", "const value = 1 + 2;");
+    let assets = builder.build().await?;
+
+    if cfg!(prod_mode) {
+        assert_get!(assets, "app.js", false, "import{x as a}from\"./lib.js\";console.log(a)");
+        assert_get!(assets, "b.min.js", false, "const value=1+ 2");
+    } else {
+        assert_get!(assets, "app.js", false, "import { x } from './lib.js';\nconsole.log( x );\n");
+        assert_get!(assets, "b.min.js", false, "const value = 1 + 2;");
+    }
+    assert_get!(assets, "plain.js", false, "const value = 1 + 2;");
+
+    let mut builder = Assets::builder();
+    builder.add_str("broken.js", "let = = ;").minify_js();
+    let result = builder.build().await;
+    assert_eq!(cfg!(prod_mode), matches!(result, Err(reinda::BuildError::Minify { .. })));
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "optimize-svg")]
+async fn optimize_svg() -> Result<(), Box<dyn std::error::Error>> {
+    const SVG: &str = "<?xml version=\"1.0\"?>\n<svg>\n  <!-- dot -->\n  <circle r=\"1\"/>\n</svg>\n";
+
+    let mut builder = Assets::builder();
+    builder.group("icons/*.svg").optimize_svg();
+    builder.add_str("icons/dot.svg", SVG);
+    builder.add_str("raw.svg", SVG);
+    let assets = builder.build().await?;
+
+    if cfg!(prod_mode) {
+        assert_get!(assets, "icons/dot.svg", false, "<svg><circle r=\"1\"/></svg>");
+    } else {
+        assert_get!(assets, "icons/dot.svg", false, SVG);
+    }
+    assert_get!(assets, "raw.svg", false, SVG);
+
+    let mut builder = Assets::builder();
+    builder.add_str("broken.svg", "<svg><rect width=1/></svg>").optimize_svg();
+    let result = builder.build().await;
+    assert_eq!(cfg!(prod_mode), matches!(result, Err(reinda::BuildError::Minify { .. })));
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(all(feature = "hash", feature = "modifiers"))]
+async fn template_path() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = Assets::builder();
+    builder.add_str("index.html", "<a href=\"/{{: path: peter.txt :}}\">{{:path:foo.txt:}}</a>")
+        .with_template();
+    builder.add_file("peter.txt", "tests/files/peter.txt").with_hash();
+    builder.add_str("foo.txt", "foo");
+    let assets = builder.build().await?;
+
+    if cfg!(prod_mode) {
+        assert_get!(assets, "index.html", false, "<a href=\"/peter.xwmi7av4kIn7.txt\">foo.txt</a>");
+        let order = assets.processing_order().unwrap();
+        let pos = |p: &str| order.iter().position(|o| o == p).unwrap();
+        assert!(pos("peter.txt") < pos("index.html"));
+    } else {
+        assert_get!(assets, "index.html", false, "<a href=\"/peter.txt\">foo.txt</a>");
+    }
+
+    let mut builder = Assets::builder();
+    builder.add_str("index.html", "{{: path:missing.txt :}}").with_template();
+    let result = builder.build().await;
+    if cfg!(prod_mode) {
+        assert!(matches!(result, Err(reinda::BuildError::Template { .. })));
+    } else {
+        let err = result?.get("index.html").unwrap().content().await.unwrap_err();
+        assert!(matches!(err.kind(), reinda::LoadErrorKind::Template(_)));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "modifiers")]
+async fn variables() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = Assets::builder();
+    builder.add_variable("api_url", "https://api.example.com");
+    builder.add_variable("version", "1.0");
+    builder.add_str("config.js", "const API = '{{: var:api_url :}}/v{{:var:version:}}';")
+        .with_template();
+    builder.add_str("foo.txt", "foo").with_modifier::<_, _, &str>([], |content, ctx| {
+        let version = ctx.variable("version").unwrap();
+        assert_eq!(ctx.variable("nope"), None);
+        format!("{} {}", String::from_utf8_lossy(&content), version).into()
+    });
+    let assets = builder.build().await?;
+
+    assert_get!(assets, "config.js", false, "const API = 'https://api.example.com/v1.0';");
+    assert_get!(assets, "foo.txt", false, "foo 1.0");
+
+    let mut builder = Assets::builder();
+    builder.add_str("config.js", "{{: var:nope :}}").with_template();
+    let result = builder.build().await;
+    if cfg!(prod_mode) {
+        assert!(matches!(result, Err(reinda::BuildError::Template { .. })));
+    } else {
+        let err = result?.get("config.js").unwrap().content().await.unwrap_err();
+        assert!(matches!(err.kind(), reinda::LoadErrorKind::Template(_)));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "modifiers")]
+async fn template_include() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = Assets::builder();
+    builder.add_str("index.html", "<body>{{: include:header.html :}}<main></main></body>")
+        .with_template();
+    builder.add_str("header.html", "<header>{{: include:logo.svg :}}</header>").with_template();
+    builder.add_str("logo.svg", "<svg/>").with_modifier::<_, _, &str>([], |content, _| {
+        content.to_ascii_uppercase().into()
+    });
+    let assets = builder.build().await?;
+
+    assert_get!(assets, "index.html", false, "<body><header><SVG/></header><main></main></body>");
+
+    let mut builder = Assets::builder();
+    builder.add_str("a.html", "a {{: include:b.html :}}").with_template();
+    builder.add_str("b.html", "b {{: include:a.html :}}").with_template();
+    let result = builder.build().await;
+    if cfg!(prod_mode) {
+        assert!(matches!(result, Err(reinda::BuildError::CyclicDependencies(_))));
+    } else {
+        let err = result?.get("a.html").unwrap().content().await.unwrap_err();
+        assert_eq!(err.http_path(), "b.html");
+        assert!(err.to_string().contains("cyclic includes: a.html -> b.html -> a.html"));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "modifiers")]
+async fn template_conditionals() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = Assets::builder();
+    builder.add_str(
+        "index.html",
+        "<head>{{: if dev :}}<script src=\"/{{: path:reload.js :}}\"></script>{{: endif :}}</head>\
+            {{: if prod :}}prod{{: else :}}dev{{: endif :}}",
+    ).with_template();
+    builder.add_str("reload.js", "// reload");
+    let assets = builder.build().await?;
+
+    if cfg!(prod_mode) {
+        assert_get!(assets, "index.html", false, "<head></head>prod");
+    } else {
+        assert_get!(assets, "index.html", false, "<head><script src=\"/reload.js\"></script></head>dev");
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "hash")]
+async fn prepend_append() -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = Assets::builder();
+    builder.add_file("peter.txt", "tests/files/peter.txt")
+        .with_prepend("/* header */\n")
+        .with_append(&b"\n// footer"[..])
+        .with_modifier::<_, _, &str>([], |content, _| content.to_ascii_uppercase().into())
+        .with_hash();
+    builder.add_str("foo.txt", "foo").with_append("!");
+    let assets = builder.build().await?;
+
+    let peter = std::fs::read_to_string("tests/files/peter.txt")?;
+    let expected = format!("/* header */\n{}\n// footer", peter).to_ascii_uppercase();
+    if cfg!(prod_mode) {
+        let hashed = reinda::hashed_path("peter.txt", expected.as_bytes(), &reinda::HashOptions::new());
+        assert_ne!(hashed, "peter.xwmi7av4kIn7.txt");
+        assert_eq!(assets.get(&hashed).unwrap().content().await?, expected.as_bytes());
+    } else {
+        assert_get!(assets, "peter.txt", false, &expected);
+    }
+    assert_get!(assets, "foo.txt", false, "foo!");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn dev_path() -> Result<(), Box<dyn std::error::Error>> {
+    const EMBEDS: reinda::Embeds = reinda::embed! {
+        base_path: "tests/files",
+        files: ["peter.txt", "config/*.json"],
+    };
+
+    let dev_dir = std::env::temp_dir().join(format!("reinda-dev-path-{}", std::process::id()));
+    std::fs::create_dir_all(&dev_dir)?;
+    std::fs::write(dev_dir.join("base.json"), "{ \"dev\": true }")?;
+    std::fs::write(dev_dir.join("new.json"), "{}")?;
+
+    let mut builder = Assets::builder();
+    builder.add_embedded("peter.txt", &EMBEDS["peter.txt"]).with_dev_path(dev_dir.join("new.json"));
+    builder.add_embedded("config/", &EMBEDS["config/*.json"]).with_dev_path(&dev_dir);
+    let assets = builder.build().await?;
+
+    let peter = std::fs::read("tests/files/peter.txt")?;
+    let base = std::fs::read("tests/files/config/base.json")?;
+    if cfg!(prod_mode) {
+        assert_get!(assets, "peter.txt", false, &peter);
+        assert_get!(assets, "config/base.json", false, &base);
+        assert!(assets.get("config/new.json").is_none());
+    } else {
+        assert_get!(assets, "peter.txt", false, "{}");
+        assert_get!(assets, "config/base.json", false, "{ \"dev\": true }");
+        assert_get!(assets, "config/new.json", false, "{}");
+    }
+
+    std::fs::remove_dir_all(&dev_dir)?;
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "hash")]
+async fn modifier_context_mode() -> Result<(), Box<dyn std::error::Error>> {
+    let describe = |content: bytes::Bytes, ctx: reinda::ModifierContext| -> bytes::Bytes {
+        let mode = if ctx.is_prod() { "prod" } else { "dev" };
+        format!("{} {} {}", String::from_utf8_lossy(&content), mode, ctx.is_hash_configured()).into()
+    };
+    let mut builder = Assets::builder();
+    builder.add_str("a.txt", "a").with_modifier::<_, _, &str>([], describe);
+    builder.add_str("b.txt", "b").with_modifier::<_, _, &str>([], describe).with_hash();
+    let assets = builder.build().await?;
+
+    if cfg!(prod_mode) {
+        assert_get!(assets, "a.txt", false, "a prod false");
+        let b = reinda::hashed_path("b.txt", b"b prod true", &reinda::HashOptions::new());
+        assert_eq!(assets.get(&b).unwrap().content().await?, "b prod true");
+    } else {
+        assert_get!(assets, "a.txt", false, "a dev false");
+        assert_get!(assets, "b.txt", false, "b dev true");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use reinda::minimal::Assets;
+
+
+const EMBEDS: reinda::Embeds = reinda::embed! {
+    base_path: "tests/files",
+    files: ["peter.txt", "*.bin"],
+};
+
+#[test]
+fn unhashed() -> Result<(), Box<dyn std::error::Error>> {
+    let assets = Assets::new(&EMBEDS);
+    assert_eq!(assets.len(), 2);
+    assert_eq!(
+        assets.iter().map(|a| a.http_path()).collect::<Vec<_>>(),
+        ["data.bin", "peter.txt"],
+    );
+
+    let peter = assets.get("peter.txt").unwrap();
+    assert!(!peter.is_filename_hashed());
+    assert_eq!(peter.unhashed_http_path(), "peter.txt");
+    assert_eq!(&*peter.content()?, b"Peter und der Wolf.\n");
+    assert_eq!(assets.resolve_path("peter.txt"), Some("peter.txt"));
+
+    assert!(assets.get("peter.tx").is_none());
+    assert!(assets.get("zzz").is_none());
+    assert!(assets.resolve_path("nope.txt").is_none());
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "hash")]
+fn hashed() -> Result<(), Box<dyn std::error::Error>> {
+    let assets = Assets::with_hash(&EMBEDS, |path| path == "peter.txt");
+
+    let http_path = assets.resolve_path("peter.txt").unwrap();
+    let peter = assets.get(http_path).unwrap();
+    assert_eq!(&*peter.content()?, b"Peter und der Wolf.\n");
+    assert_eq!(peter.unhashed_http_path(), "peter.txt");
+    assert_eq!(assets.resolve_path("data.bin"), Some("data.bin"));
+
+    if cfg!(prod_mode) {
+        assert!(http_path.starts_with("peter.") && http_path.ends_with(".txt"));
+        assert_ne!(http_path, "peter.txt");
+        assert!(peter.is_filename_hashed());
+        assert!(assets.get("peter.txt").is_none());
+    } else {
+        assert_eq!(http_path, "peter.txt");
+        assert!(!peter.is_filename_hashed());
+    }
+
+    Ok(())
+}